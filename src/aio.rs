@@ -0,0 +1,443 @@
+use std::time::Instant;
+
+use futures::stream::{self, StreamExt, TryStreamExt};
+use serde::de::DeserializeOwned;
+
+use crate::client::{
+    adjusted_series_params, assemble_intraday, content_encoding, crypto_daily_params,
+    daily_series_params, decode_body, decode_csv, decode_json, decompress, error_context,
+    exchange_rate_params, fundamentals_params, fx_daily_params, intraday_month_params,
+    intraday_months, intraday_series_params, log_request, news_params, overview_params, param,
+    quote_params, symbol_search_params, ClientOptions, Decoder, Progress, ACCEPT_ENCODING,
+    EARNINGS_CALENDAR_PARAMS, IPO_CALENDAR_PARAMS, LISTING_STATUS_PARAMS, SECTOR_PARAMS,
+};
+use crate::economy::EconomicResponse;
+use crate::fx::ExchangeRateResponse;
+use crate::indicators::{self, Indicator, IndicatorOutput, Source};
+use crate::news::NewsResponse;
+use crate::overview::overview_from_value;
+use crate::paginate::Paginator;
+use crate::quote::GlobalQuoteResponse;
+use crate::search::SymbolSearchResponse;
+use crate::series::{AdjustedSeriesResponse, FromCsv};
+use crate::{
+    calendar, summarise, ApiError, BalanceSheet, CashFlow, CompanyOverview, CryptoDailySeries,
+    DailySeries, Earnings, EarningsEvent, EconomicIndicator, EconomicSeries, EquitySummary,
+    ExchangeRate, Frequency, FxDailySeries, IncomeStatement, Interval, IntradaySeries, IpoEvent,
+    Listing, NewsArticle, OutputSize, PortfolioClientBuilder, Quote, SectorPerformance, Series,
+    Statements, Symbol, SymbolMatch, TimePeriod,
+};
+
+pub struct PortfolioClient {
+    client: reqwest::Client,
+    options: ClientOptions,
+}
+
+impl PortfolioClient {
+    pub fn new<S: Into<String>>(api_key: S) -> PortfolioClient {
+        PortfolioClient::builder(api_key)
+            .build_async()
+            .expect("default client configuration is valid")
+    }
+
+    pub fn from_env() -> PortfolioClient {
+        PortfolioClient::builder_from_env()
+            .build_async()
+            .expect("default client configuration is valid")
+    }
+
+    pub fn builder<S: Into<String>>(api_key: S) -> PortfolioClientBuilder {
+        crate::PortfolioClient::builder(api_key)
+    }
+
+    pub fn builder_from_env() -> PortfolioClientBuilder {
+        crate::PortfolioClient::builder_from_env()
+    }
+
+    pub(crate) fn from_parts(client: reqwest::Client, options: ClientOptions) -> PortfolioClient {
+        PortfolioClient { client, options }
+    }
+
+    async fn query<T: DeserializeOwned>(&self, params: &[(&str, &str)]) -> Result<T, ApiError> {
+        self.request(params, decode_json).await
+    }
+
+    async fn query_csv<T: DeserializeOwned>(
+        &self,
+        params: &[(&str, &str)],
+    ) -> Result<Vec<T>, ApiError> {
+        self.request(params, decode_csv).await
+    }
+
+    async fn query_series<T: DeserializeOwned + FromCsv>(
+        &self,
+        params: &[(&str, &str)],
+    ) -> Result<T, ApiError> {
+        self.request(params, self.options.series_decoder()).await
+    }
+
+    async fn request<T>(&self, params: &[(&str, &str)], decode: Decoder<T>) -> Result<T, ApiError> {
+        if let Some(cached) = self.options.cached(params, decode) {
+            return cached.map_err(|err| err.with_context(error_context(params)));
+        }
+        self.download(params, decode).await
+    }
+
+    async fn download<T>(
+        &self,
+        params: &[(&str, &str)],
+        decode: Decoder<T>,
+    ) -> Result<T, ApiError> {
+        let mut attempt = 1;
+        loop {
+            match self.fetch(params, decode).await {
+                Err(err) if self.options.retry.should_retry(attempt, &err) => {
+                    tokio::time::sleep(self.options.retrying(params, attempt, &err)).await;
+                    attempt += 1;
+                }
+                result => return result.map_err(|err| err.with_context(error_context(params))),
+            }
+        }
+    }
+
+    async fn fetch<T>(&self, params: &[(&str, &str)], decode: Decoder<T>) -> Result<T, ApiError> {
+        if let Some(body) = self.options.replayed(params) {
+            return self.options.parse(params, &body?, decode);
+        }
+        self.options.online()?;
+        let api_key = self.options.api_key()?;
+        tokio::time::sleep(self.options.reserve(params)?).await;
+
+        let started = Instant::now();
+        let body = match &self.options.transport {
+            Some(transport) => transport.get(params),
+            None => {
+                async {
+                    let resp = self
+                        .client
+                        .get(&self.options.base_url)
+                        .header(reqwest::header::ACCEPT_ENCODING, ACCEPT_ENCODING)
+                        .query(params)
+                        .query(&[("apikey", &api_key)])
+                        .send()
+                        .await?
+                        .error_for_status()?;
+                    let encoding = content_encoding(resp.headers());
+                    Ok((encoding, resp.bytes().await?))
+                }
+                .await
+                // the URL holds the API key
+                .map_err(|err: reqwest::Error| ApiError::from(err.without_url()))
+                .and_then(|(encoding, bytes)| decompress(encoding.as_deref(), &bytes))
+            }
+        };
+        log_request(
+            "Alpha Vantage",
+            param(params, "function"),
+            params,
+            started,
+            &body,
+        );
+        let body = body?;
+
+        self.options.record(params, &body)?;
+        self.options.parse(params, &body, decode)
+    }
+
+    pub async fn daily_series(
+        &self,
+        symbol: Symbol,
+        output_size: OutputSize,
+    ) -> Result<DailySeries, ApiError> {
+        let params = self
+            .options
+            .time_series(&daily_series_params(&symbol, &output_size));
+        if let OutputSize::Compact = output_size {
+            return self.query_series(&params).await;
+        }
+        let decode = self.options.series_decoder();
+        if let Some(cached) = self.options.cached(&params, decode) {
+            return cached.map_err(|err| err.with_context(error_context(&params)));
+        }
+        if let Some(previous) = self.options.previous(&params) {
+            let compact = self
+                .request(
+                    &self
+                        .options
+                        .time_series(&daily_series_params(&symbol, &OutputSize::Compact)),
+                    decode_body,
+                )
+                .await?;
+            if let Some(merged) = self.options.merge_daily_series(&previous, &compact) {
+                return self
+                    .options
+                    .parse(&params, &merged, decode)
+                    .map_err(|err| err.with_context(error_context(&params)));
+            }
+        }
+        self.download(&params, decode).await
+    }
+
+    pub async fn series(&self, symbol: Symbol, frequency: Frequency) -> Result<Series, ApiError> {
+        match frequency {
+            Frequency::Daily => self
+                .daily_series(symbol, OutputSize::Full)
+                .await
+                .map(Series::from),
+            Frequency::Weekly | Frequency::Monthly => self
+                .query_series::<AdjustedSeriesResponse>(
+                    &self
+                        .options
+                        .time_series(&adjusted_series_params(&symbol, frequency)),
+                )
+                .await
+                .map(|response| response.into_series(frequency)),
+        }
+    }
+
+    pub async fn intraday_series(
+        &self,
+        symbol: Symbol,
+        interval: Interval,
+    ) -> Result<IntradaySeries, ApiError> {
+        self.query_series(
+            &self
+                .options
+                .time_series(&intraday_series_params(&symbol, &interval)),
+        )
+        .await
+    }
+
+    pub async fn intraday_history(
+        &self,
+        symbol: Symbol,
+        interval: Interval,
+        from: chrono::NaiveDate,
+        to: chrono::NaiveDate,
+    ) -> Result<IntradaySeries, ApiError> {
+        self.options.premium("extended intraday history")?;
+        let pages = intraday_months(from, to);
+        let mut months = Paginator::new(pages.len(), &self.options.rate_limiter)?;
+        for month in pages {
+            let params = self
+                .options
+                .time_series(&intraday_month_params(&symbol, &interval, &month));
+            let series = match self.options.finished_month(&params, &month) {
+                Some(series) => series.map_err(|err| err.with_context(error_context(&params)))?,
+                None => self.query_series(&params).await?,
+            };
+            months.push(series);
+        }
+        assemble_intraday(months, from, to)
+    }
+
+    pub async fn quote(&self, symbol: Symbol) -> Result<Quote, ApiError> {
+        let params = self.options.entitled(&quote_params(&symbol));
+        let mut quote = self
+            .query::<GlobalQuoteResponse>(&params)
+            .await
+            .and_then(GlobalQuoteResponse::into_quote)?;
+        quote.retrieved_at = Some(self.options.retrieved_at(&params));
+        Ok(quote)
+    }
+
+    pub async fn quotes(&self, symbols: &[Symbol]) -> Result<Vec<Quote>, ApiError> {
+        stream::iter(symbols.iter().cloned())
+            .map(|symbol| self.quote(symbol))
+            .buffered(self.options.max_concurrency)
+            .try_collect()
+            .await
+    }
+
+    pub async fn refresh_all<F>(
+        &self,
+        symbols: &[Symbol],
+        mut progress: F,
+    ) -> Vec<Result<DailySeries, ApiError>>
+    where
+        F: FnMut(Progress),
+    {
+        let mut results: Vec<Option<Result<DailySeries, ApiError>>> =
+            symbols.iter().map(|_symbol| None).collect();
+        let mut refreshes = stream::iter(symbols.iter().enumerate())
+            .map(|(idx, symbol)| async move {
+                (
+                    idx,
+                    self.daily_series(symbol.clone(), OutputSize::Full).await,
+                )
+            })
+            .buffer_unordered(self.options.max_concurrency);
+
+        let mut completed = 0;
+        while let Some((idx, result)) = refreshes.next().await {
+            completed += 1;
+            progress(Progress {
+                symbol: &symbols[idx],
+                completed,
+                total: symbols.len(),
+                error: result.as_ref().err(),
+            });
+            results[idx] = Some(result);
+        }
+
+        results
+            .into_iter()
+            .map(|result| result.expect("every symbol is refreshed"))
+            .collect()
+    }
+
+    pub async fn company_overview(&self, symbol: Symbol) -> Result<CompanyOverview, ApiError> {
+        self.query(&overview_params(&symbol))
+            .await
+            .and_then(overview_from_value)
+    }
+
+    pub async fn earnings(&self, symbol: Symbol) -> Result<Earnings, ApiError> {
+        self.query(&fundamentals_params("EARNINGS", &symbol)).await
+    }
+
+    pub async fn income_statement(
+        &self,
+        symbol: Symbol,
+    ) -> Result<Statements<IncomeStatement>, ApiError> {
+        self.query(&fundamentals_params("INCOME_STATEMENT", &symbol))
+            .await
+    }
+
+    pub async fn balance_sheet(
+        &self,
+        symbol: Symbol,
+    ) -> Result<Statements<BalanceSheet>, ApiError> {
+        self.query(&fundamentals_params("BALANCE_SHEET", &symbol))
+            .await
+    }
+
+    pub async fn cash_flow(&self, symbol: Symbol) -> Result<Statements<CashFlow>, ApiError> {
+        self.query(&fundamentals_params("CASH_FLOW", &symbol)).await
+    }
+
+    pub async fn exchange_rate(&self, from: &str, to: &str) -> Result<ExchangeRate, ApiError> {
+        self.query::<ExchangeRateResponse>(&exchange_rate_params(from, to))
+            .await
+            .map(|response| response.rate)
+    }
+
+    pub async fn fx_daily_series(
+        &self,
+        from: &str,
+        to: &str,
+        output_size: OutputSize,
+    ) -> Result<FxDailySeries, ApiError> {
+        self.query(&fx_daily_params(from, to, &output_size)).await
+    }
+
+    pub async fn crypto_daily_series(
+        &self,
+        symbol: Symbol,
+        market: &str,
+    ) -> Result<CryptoDailySeries, ApiError> {
+        self.query(&crypto_daily_params(&symbol, market)).await
+    }
+
+    pub async fn crypto_quote(&self, symbol: Symbol, market: &str) -> Result<Quote, ApiError> {
+        let mut quote = self
+            .crypto_daily_series(symbol.clone(), market)
+            .await?
+            .to_quote()?;
+        quote.retrieved_at = Some(
+            self.options
+                .retrieved_at(&crypto_daily_params(&symbol, market)),
+        );
+        Ok(quote)
+    }
+
+    pub async fn indicator(
+        &self,
+        symbol: Symbol,
+        indicator: Indicator,
+        source: Source,
+    ) -> Result<IndicatorOutput, ApiError> {
+        match source {
+            Source::Local => {
+                let series = self.daily_series(symbol, OutputSize::Full).await?;
+                Ok(indicators::compute(&series, indicator))
+            }
+            Source::Remote => {
+                let params = indicators::remote_params(&symbol, indicator);
+                let params: Vec<(&str, &str)> = params
+                    .iter()
+                    .map(|(key, value)| (*key, value.as_str()))
+                    .collect();
+                self.query(&params)
+                    .await
+                    .and_then(|value| indicators::remote_output(indicator, value))
+            }
+        }
+    }
+
+    pub async fn search_symbols(&self, keywords: &str) -> Result<Vec<SymbolMatch>, ApiError> {
+        self.query::<SymbolSearchResponse>(&symbol_search_params(keywords))
+            .await
+            .map(|response| response.best_matches)
+    }
+
+    pub async fn earnings_calendar(&self) -> Result<Vec<EarningsEvent>, ApiError> {
+        self.query_csv(&EARNINGS_CALENDAR_PARAMS).await
+    }
+
+    pub async fn upcoming_ipos(&self) -> Result<Vec<IpoEvent>, ApiError> {
+        self.query_csv(&IPO_CALENDAR_PARAMS).await
+    }
+
+    pub async fn listed_symbols(&self) -> Result<Vec<Listing>, ApiError> {
+        self.query_csv(&LISTING_STATUS_PARAMS).await
+    }
+
+    pub async fn economic_indicator(
+        &self,
+        indicator: EconomicIndicator,
+    ) -> Result<EconomicSeries, ApiError> {
+        self.query::<EconomicResponse>(&indicator.params())
+            .await
+            .map(EconomicResponse::into_series)
+    }
+
+    pub async fn sector_performance(&self) -> Result<SectorPerformance, ApiError> {
+        self.query(&SECTOR_PARAMS).await
+    }
+
+    pub async fn news(&self, symbol: Symbol, limit: usize) -> Result<Vec<NewsArticle>, ApiError> {
+        let mut feed = self
+            .query::<NewsResponse>(&news_params(&symbol))
+            .await?
+            .feed;
+        feed.truncate(limit);
+        Ok(feed)
+    }
+
+    pub async fn latest_price(&self, symbol: Symbol) -> Result<f64, ApiError> {
+        Ok(self.quote(symbol).await?.price)
+    }
+
+    pub async fn summary(
+        &self,
+        symbol: Symbol,
+        time_period: TimePeriod,
+    ) -> Result<EquitySummary, ApiError> {
+        let today = calendar::today();
+        let series = match time_period {
+            TimePeriod::AllTime => self.series(symbol.clone(), Frequency::Monthly).await?,
+            _ if time_period.fits_compact(today) => self
+                .daily_series(symbol.clone(), OutputSize::Compact)
+                .await?
+                .into(),
+            _ => {
+                let series = self.series(symbol, Frequency::Daily).await?;
+                return summarise(series, None, time_period);
+            }
+        };
+        let daily = self.series(symbol, Frequency::Daily).await.ok();
+
+        summarise(series, daily.as_ref(), time_period)
+    }
+}
@@ -0,0 +1,145 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{ApiError, FiftyTwoWeekRange, Frequency, Notifier, Provider, Quote, Symbol};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Condition {
+    Above { price: f64 },
+    Below { price: f64 },
+    DailyMove { percent: f64 },
+    FiftyTwoWeekHigh,
+    FiftyTwoWeekLow,
+}
+impl Condition {
+    pub fn describe(&self) -> String {
+        match self {
+            Condition::Above { price } => format!("price above {}", price),
+            Condition::Below { price } => format!("price below {}", price),
+            Condition::DailyMove { percent } => format!("moves {}% in a day", percent),
+            Condition::FiftyTwoWeekHigh => "touches its 52-week high".to_string(),
+            Condition::FiftyTwoWeekLow => "touches its 52-week low".to_string(),
+        }
+    }
+
+    fn needs_range(&self) -> bool {
+        matches!(
+            self,
+            Condition::FiftyTwoWeekHigh | Condition::FiftyTwoWeekLow
+        )
+    }
+
+    fn is_met(&self, quote: &Quote, range: Option<FiftyTwoWeekRange>) -> bool {
+        match self {
+            Condition::Above { price } => quote.price > *price,
+            Condition::Below { price } => quote.price < *price,
+            Condition::DailyMove { percent } => quote.change_percent.abs() >= *percent,
+            Condition::FiftyTwoWeekHigh => range.is_some_and(|range| quote.high >= range.high),
+            Condition::FiftyTwoWeekLow => range.is_some_and(|range| quote.low <= range.low),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertRule {
+    pub id: u32,
+    pub symbol: Symbol,
+    pub condition: Condition,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notify: Vec<Notifier>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TriggeredAlert {
+    pub rule: AlertRule,
+    pub price: f64,
+    pub change_percent: f64,
+}
+
+#[derive(Debug, Default)]
+pub struct Alerts {
+    rules: Vec<AlertRule>,
+}
+impl Alerts {
+    pub fn new() -> Alerts {
+        Alerts::default()
+    }
+
+    pub fn add<S: Into<Symbol>>(
+        &mut self,
+        symbol: S,
+        condition: Condition,
+        notify: Vec<Notifier>,
+    ) -> u32 {
+        let id = self.rules.iter().map(|rule| rule.id).max().unwrap_or(0) + 1;
+        self.rules.push(AlertRule {
+            id,
+            symbol: symbol.into(),
+            condition,
+            notify,
+        });
+        id
+    }
+
+    pub(crate) fn insert(&mut self, rule: AlertRule) {
+        self.rules.push(rule);
+    }
+
+    pub fn remove(&mut self, id: u32) -> bool {
+        let count = self.rules.len();
+        self.rules.retain(|rule| rule.id != id);
+        self.rules.len() != count
+    }
+
+    pub fn rules(&self) -> &[AlertRule] {
+        &self.rules
+    }
+
+    pub fn check<P: Provider + ?Sized>(
+        &self,
+        provider: &P,
+    ) -> Result<Vec<TriggeredAlert>, ApiError> {
+        let mut symbols: Vec<Symbol> = self.rules.iter().map(|rule| rule.symbol.clone()).collect();
+        symbols.sort();
+        symbols.dedup();
+
+        let mut triggered = vec![];
+        for (symbol, quote) in symbols.iter().zip(provider.quotes(&symbols)?) {
+            let rules: Vec<&AlertRule> = self
+                .rules
+                .iter()
+                .filter(|rule| &rule.symbol == symbol)
+                .collect();
+            let range = if rules.iter().any(|rule| rule.condition.needs_range()) {
+                fifty_two_week_range(provider, symbol, &quote)?
+            } else {
+                None
+            };
+
+            triggered.extend(
+                rules
+                    .into_iter()
+                    .filter(|rule| rule.condition.is_met(&quote, range))
+                    .map(|rule| TriggeredAlert {
+                        rule: rule.clone(),
+                        price: quote.price,
+                        change_percent: quote.change_percent,
+                    }),
+            );
+        }
+        Ok(triggered)
+    }
+}
+
+// over the year to the quote's trading day, from weekly bars
+fn fifty_two_week_range<P: Provider + ?Sized>(
+    provider: &P,
+    symbol: &Symbol,
+    quote: &Quote,
+) -> Result<Option<FiftyTwoWeekRange>, ApiError> {
+    let series = provider.series(symbol.clone(), Frequency::Weekly)?;
+    Ok(FiftyTwoWeekRange::of(
+        &series.bars,
+        quote.latest_trading_day,
+    ))
+}
@@ -0,0 +1,205 @@
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use crate::{
+    ApiError, AssetType, CompanyOverview, PortfolioClient, PositionValuation, Symbol, Targets,
+    Valuation,
+};
+
+const UNKNOWN: &str = "Unknown";
+const UNTAGGED: &str = "Untagged";
+const UNASSIGNED: &str = "Unassigned";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Dimension {
+    Sector,
+    AssetClass,
+    Geography,
+    Currency,
+    Tag,
+    Account,
+}
+impl Dimension {
+    pub const ALL: [Dimension; 4] = [
+        Dimension::AssetClass,
+        Dimension::Sector,
+        Dimension::Geography,
+        Dimension::Currency,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Dimension::Sector => "Sector",
+            Dimension::AssetClass => "Asset class",
+            Dimension::Geography => "Geography",
+            Dimension::Currency => "Currency",
+            Dimension::Tag => "Tag",
+            Dimension::Account => "Account",
+        }
+    }
+
+    pub fn targets(self, targets: &Targets) -> &BTreeMap<String, f64> {
+        match self {
+            Dimension::Sector => &targets.sector,
+            Dimension::AssetClass => &targets.asset_class,
+            Dimension::Geography => &targets.geography,
+            Dimension::Currency => &targets.currency,
+            Dimension::Tag => &targets.tag,
+            Dimension::Account => &targets.account,
+        }
+    }
+
+    pub fn targets_mut(self, targets: &mut Targets) -> &mut BTreeMap<String, f64> {
+        match self {
+            Dimension::Sector => &mut targets.sector,
+            Dimension::AssetClass => &mut targets.asset_class,
+            Dimension::Geography => &mut targets.geography,
+            Dimension::Currency => &mut targets.currency,
+            Dimension::Tag => &mut targets.tag,
+            Dimension::Account => &mut targets.account,
+        }
+    }
+
+    // explicit position metadata wins over the company overview. A position with
+    // several tags is grouped under all of them together, so weights still add up.
+    fn classify(
+        self,
+        position: &PositionValuation,
+        overviews: &HashMap<Symbol, CompanyOverview>,
+    ) -> String {
+        let position = &position.position;
+        let overview = overviews.get(&position.symbol);
+        let known = |value: &str| Some(value.to_string()).filter(|value| !value.is_empty());
+        match self {
+            Dimension::AssetClass => position.asset_type.label().to_string(),
+            Dimension::Currency => position.currency.clone(),
+            Dimension::Sector => match position.asset_type {
+                AssetType::Equity | AssetType::Etf => position
+                    .sector
+                    .clone()
+                    .or_else(|| overview.and_then(|overview| known(&overview.sector))),
+                asset_type => Some(asset_type.label().to_string()),
+            }
+            .unwrap_or_else(|| UNKNOWN.to_string()),
+            Dimension::Geography => position
+                .country
+                .clone()
+                .or_else(|| overview.and_then(|overview| known(&overview.country)))
+                .unwrap_or_else(|| UNKNOWN.to_string()),
+            Dimension::Tag if position.tags.is_empty() => UNTAGGED.to_string(),
+            Dimension::Tag => {
+                let mut tags = position.tags.clone();
+                tags.sort();
+                tags.join(", ")
+            }
+            Dimension::Account => position
+                .account
+                .clone()
+                .unwrap_or_else(|| UNASSIGNED.to_string()),
+        }
+    }
+}
+impl FromStr for Dimension {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<Dimension, ApiError> {
+        match s.to_lowercase().as_str() {
+            "sector" => Ok(Dimension::Sector),
+            "asset-class" | "asset_class" => Ok(Dimension::AssetClass),
+            "geography" | "country" => Ok(Dimension::Geography),
+            "currency" => Ok(Dimension::Currency),
+            "tag" => Ok(Dimension::Tag),
+            "account" => Ok(Dimension::Account),
+            _ => Err(ApiError::Api(format!(
+                "unknown allocation dimension `{}`",
+                s
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct AllocationWeight {
+    pub name: String,
+    pub market_value: f64,
+    pub weight: f64,
+    pub target: Option<f64>,
+}
+impl AllocationWeight {
+    pub fn delta(&self) -> Option<f64> {
+        self.target.map(|target| self.weight - target)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Allocation {
+    pub dimension: Dimension,
+    pub market_value: f64,
+    pub weights: Vec<AllocationWeight>,
+}
+impl Allocation {
+    // weights are percentages of the valuation's base-currency market value
+    pub fn new(
+        valuation: &Valuation,
+        overviews: &HashMap<Symbol, CompanyOverview>,
+        dimension: Dimension,
+        targets: &Targets,
+    ) -> Allocation {
+        let mut values: BTreeMap<String, f64> = BTreeMap::new();
+        for position in &valuation.positions {
+            *values
+                .entry(dimension.classify(position, overviews))
+                .or_default() += position.base_market_value();
+        }
+
+        let targets = dimension.targets(targets);
+        for name in targets.keys() {
+            values.entry(name.clone()).or_default();
+        }
+
+        let market_value = valuation.market_value();
+        let mut weights: Vec<AllocationWeight> = values
+            .into_iter()
+            .map(|(name, value)| AllocationWeight {
+                target: targets.get(&name).copied(),
+                weight: if market_value > 0.0 {
+                    value / market_value * 100.0
+                } else {
+                    0.0
+                },
+                market_value: value,
+                name,
+            })
+            .collect();
+        weights.sort_by(|a, b| b.market_value.total_cmp(&a.market_value));
+
+        Allocation {
+            dimension,
+            market_value,
+            weights,
+        }
+    }
+}
+
+// ETFs and funds have no overview, so failed lookups are left for "Unknown"
+pub fn fetch_overviews(
+    client: &PortfolioClient,
+    valuation: &Valuation,
+) -> HashMap<Symbol, CompanyOverview> {
+    let mut overviews = HashMap::new();
+    for position in &valuation.positions {
+        let position = &position.position;
+        let needs_overview = matches!(position.asset_type, AssetType::Equity | AssetType::Etf)
+            && (position.sector.is_none() || position.country.is_none());
+        if !needs_overview || overviews.contains_key(&position.symbol) {
+            continue;
+        }
+        if let Ok(overview) = client.company_overview(position.symbol.clone()) {
+            overviews.insert(position.symbol.clone(), overview);
+        }
+    }
+    overviews
+}
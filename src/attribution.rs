@@ -0,0 +1,125 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::Symbol;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HoldingAttribution {
+    pub symbol: Symbol,
+    // share of the portfolio at the start of the period
+    pub weight: f64,
+    // in percent, or none without prices for the period
+    #[serde(rename = "return")]
+    pub holding_return: Option<f64>,
+    // weight × return, in percentage points; these sum to the portfolio's return
+    pub contribution: Option<f64>,
+    // weight × the return over the benchmark's
+    pub relative_contribution: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Attribution {
+    pub start_date: chrono::NaiveDate,
+    pub end_date: chrono::NaiveDate,
+    pub holdings: Vec<HoldingAttribution>,
+    pub portfolio_return: f64,
+    pub benchmark: Option<Symbol>,
+    pub benchmark_return: Option<f64>,
+    pub relative_return: Option<f64>,
+}
+
+// the first and last prices from `start`, if there are two
+fn period_return(
+    prices: &BTreeMap<chrono::NaiveDate, f64>,
+    start: Option<chrono::NaiveDate>,
+) -> Option<(chrono::NaiveDate, chrono::NaiveDate, f64)> {
+    let mut prices = prices
+        .range(start.unwrap_or(chrono::NaiveDate::MIN)..)
+        .filter(|(_date, price)| **price > 0.0);
+    let (first_date, first) = prices.next()?;
+    let (last_date, last) = prices.next_back()?;
+    Some((*first_date, *last_date, (last / first - 1.0) * 100.0))
+}
+
+// `weights` are today's fractions of the portfolio, which are wound back to
+// the start of the period with each holding's return, so this is the return
+// of today's holdings over the period rather than of the trades made in it.
+// Holdings without prices, such as cash, keep their value throughout.
+pub fn attribution(
+    prices: &BTreeMap<Symbol, BTreeMap<chrono::NaiveDate, f64>>,
+    weights: &BTreeMap<Symbol, f64>,
+    start: Option<chrono::NaiveDate>,
+    benchmark: Option<(Symbol, &BTreeMap<chrono::NaiveDate, f64>)>,
+) -> Option<Attribution> {
+    let returns: BTreeMap<&Symbol, (chrono::NaiveDate, chrono::NaiveDate, f64)> = weights
+        .keys()
+        .filter_map(|symbol| Some((symbol, period_return(prices.get(symbol)?, start)?)))
+        .collect();
+    let start_date = returns
+        .values()
+        .map(|(first, _last, _return)| *first)
+        .min()?;
+    let end_date = returns
+        .values()
+        .map(|(_first, last, _return)| *last)
+        .max()?;
+
+    let start_values: BTreeMap<&Symbol, f64> = weights
+        .iter()
+        .map(|(symbol, weight)| {
+            let growth = returns
+                .get(symbol)
+                .map_or(1.0, |(_first, _last, holding_return)| {
+                    1.0 + holding_return / 100.0
+                });
+            (symbol, weight / growth)
+        })
+        .collect();
+    let total: f64 = start_values.values().sum();
+    if total <= 0.0 {
+        return None;
+    }
+
+    let benchmark_return = benchmark
+        .as_ref()
+        .and_then(|(_symbol, prices)| period_return(prices, Some(start_date)))
+        .map(|(_first, _last, benchmark_return)| benchmark_return);
+    let mut holdings: Vec<HoldingAttribution> = start_values
+        .iter()
+        .map(|(symbol, value)| {
+            let weight = value / total;
+            let holding_return = returns
+                .get(symbol)
+                .map(|(_first, _last, holding_return)| *holding_return);
+            HoldingAttribution {
+                symbol: (*symbol).clone(),
+                weight,
+                holding_return,
+                contribution: holding_return.map(|holding_return| weight * holding_return),
+                relative_contribution: holding_return
+                    .and_then(|holding_return| Some(weight * (holding_return - benchmark_return?))),
+            }
+        })
+        .collect();
+    // the biggest contributors first
+    holdings.sort_by(|a, b| {
+        let contribution = |holding: &HoldingAttribution| holding.contribution.unwrap_or(f64::MIN);
+        contribution(b).total_cmp(&contribution(a))
+    });
+
+    let portfolio_return = holdings
+        .iter()
+        .filter_map(|holding| holding.contribution)
+        .sum();
+    Some(Attribution {
+        start_date,
+        end_date,
+        holdings,
+        portfolio_return,
+        benchmark: benchmark.map(|(symbol, _prices)| symbol),
+        benchmark_return,
+        relative_return: benchmark_return
+            .map(|benchmark_return| portfolio_return - benchmark_return),
+    })
+}
@@ -0,0 +1,395 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::Datelike;
+use serde::Serialize;
+
+use crate::metrics::{self, Metrics};
+use crate::{Frequency, Symbol};
+
+pub type Prices = BTreeMap<chrono::NaiveDate, f64>;
+pub type Weights = BTreeMap<Symbol, f64>;
+
+// `commission` is charged per trade and `slippage` is a fraction of the price
+// paid on buys and given up on sells
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Costs {
+    pub commission: f64,
+    pub slippage: f64,
+}
+
+pub struct Context<'a> {
+    pub date: chrono::NaiveDate,
+    pub cash: f64,
+    pub holdings: &'a BTreeMap<Symbol, f64>,
+    prices: &'a BTreeMap<Symbol, Prices>,
+}
+impl<'a> Context<'a> {
+    pub fn symbols(&self) -> impl Iterator<Item = &'a Symbol> {
+        self.prices.keys()
+    }
+
+    // the latest close on or before the current date
+    pub fn price(&self, symbol: &Symbol) -> Option<f64> {
+        self.history(symbol)
+            .next_back()
+            .map(|(_date, price)| *price)
+    }
+
+    pub fn history(
+        &self,
+        symbol: &Symbol,
+    ) -> impl DoubleEndedIterator<Item = (&'a chrono::NaiveDate, &'a f64)> {
+        let date = self.date;
+        self.prices
+            .get(symbol)
+            .into_iter()
+            .flat_map(move |prices| prices.range(..=date))
+    }
+
+    pub fn sma(&self, symbol: &Symbol, period: usize) -> Option<f64> {
+        let closes: Vec<f64> = self
+            .history(symbol)
+            .rev()
+            .take(period)
+            .map(|(_date, price)| *price)
+            .collect();
+        if period == 0 || closes.len() < period {
+            return None;
+        }
+        Some(closes.iter().sum::<f64>() / period as f64)
+    }
+
+    pub fn value(&self) -> f64 {
+        self.cash
+            + self
+                .holdings
+                .iter()
+                .map(|(symbol, units)| units * self.price(symbol).unwrap_or(0.0))
+                .sum::<f64>()
+    }
+}
+
+// returns the target weights (fractions of the portfolio's value) to trade to,
+// or `None` to leave the portfolio as it is
+pub trait Strategy {
+    fn rebalance(&mut self, context: &Context) -> Option<Weights>;
+}
+impl<F: FnMut(&Context) -> Option<Weights>> Strategy for F {
+    fn rebalance(&mut self, context: &Context) -> Option<Weights> {
+        self(context)
+    }
+}
+
+pub struct BuyAndHold {
+    weights: Weights,
+    bought: bool,
+}
+impl BuyAndHold {
+    pub fn new(weights: Weights) -> BuyAndHold {
+        BuyAndHold {
+            weights,
+            bought: false,
+        }
+    }
+}
+impl Strategy for BuyAndHold {
+    fn rebalance(&mut self, _context: &Context) -> Option<Weights> {
+        if self.bought {
+            return None;
+        }
+        self.bought = true;
+        Some(self.weights.clone())
+    }
+}
+
+// trades back to the weights on the first trading day of each week or month
+pub struct PeriodicRebalance {
+    weights: Weights,
+    frequency: Frequency,
+    period: Option<(i32, u32)>,
+}
+impl PeriodicRebalance {
+    pub fn new(weights: Weights, frequency: Frequency) -> PeriodicRebalance {
+        PeriodicRebalance {
+            weights,
+            frequency,
+            period: None,
+        }
+    }
+}
+impl Strategy for PeriodicRebalance {
+    fn rebalance(&mut self, context: &Context) -> Option<Weights> {
+        let date = context.date;
+        let period = match self.frequency {
+            Frequency::Daily => (date.year(), date.ordinal()),
+            Frequency::Weekly => (date.iso_week().year(), date.iso_week().week()),
+            Frequency::Monthly => (date.year(), date.month()),
+        };
+        if self.period == Some(period) {
+            return None;
+        }
+        self.period = Some(period);
+        Some(self.weights.clone())
+    }
+}
+
+// holds an equal weight of every symbol whose fast moving average is above its
+// slow one, and cash otherwise
+pub struct MovingAverageCrossover {
+    fast: usize,
+    slow: usize,
+    held: Option<BTreeSet<Symbol>>,
+}
+impl MovingAverageCrossover {
+    pub fn new(fast: usize, slow: usize) -> MovingAverageCrossover {
+        MovingAverageCrossover {
+            fast,
+            slow,
+            held: None,
+        }
+    }
+}
+impl Strategy for MovingAverageCrossover {
+    fn rebalance(&mut self, context: &Context) -> Option<Weights> {
+        let held: BTreeSet<Symbol> = context
+            .symbols()
+            .filter(|symbol| {
+                match (
+                    context.sma(symbol, self.fast),
+                    context.sma(symbol, self.slow),
+                ) {
+                    (Some(fast), Some(slow)) => fast > slow,
+                    _ => false,
+                }
+            })
+            .cloned()
+            .collect();
+        if self.held.as_ref() == Some(&held) {
+            return None;
+        }
+
+        let weight = 1.0 / held.len().max(1) as f64;
+        let weights = held.iter().map(|symbol| (symbol.clone(), weight)).collect();
+        self.held = Some(held);
+        Some(weights)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct BacktestTrade {
+    pub date: chrono::NaiveDate,
+    pub symbol: Symbol,
+    pub quantity: f64,
+    pub price: f64,
+    pub commission: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Backtest {
+    pub equity: Prices,
+    pub trades: Vec<BacktestTrade>,
+    pub metrics: Option<Metrics>,
+}
+impl Backtest {
+    pub fn commission(&self) -> f64 {
+        self.trades.iter().map(|trade| trade.commission).sum()
+    }
+}
+
+struct Account {
+    cash: f64,
+    holdings: BTreeMap<Symbol, f64>,
+    trades: Vec<BacktestTrade>,
+}
+impl Account {
+    fn trade(
+        &mut self,
+        date: chrono::NaiveDate,
+        symbol: &Symbol,
+        quantity: f64,
+        price: f64,
+        costs: Costs,
+    ) {
+        let price = if quantity > 0.0 {
+            price * (1.0 + costs.slippage)
+        } else {
+            price * (1.0 - costs.slippage)
+        };
+        self.cash -= quantity * price + costs.commission;
+        *self.holdings.entry(symbol.clone()).or_default() += quantity;
+        self.holdings.retain(|_symbol, units| units.abs() > 1e-9);
+        self.trades.push(BacktestTrade {
+            date,
+            symbol: symbol.clone(),
+            quantity,
+            price,
+            commission: costs.commission,
+        });
+    }
+}
+
+// strategies see each day's closes and trade at them, so a backtest is only as
+// honest as the strategy's use of `Context`
+pub fn run<S: Strategy + ?Sized>(
+    prices: &BTreeMap<Symbol, Prices>,
+    start: chrono::NaiveDate,
+    end: chrono::NaiveDate,
+    cash: f64,
+    costs: Costs,
+    risk_free_rate: f64,
+    strategy: &mut S,
+) -> Backtest {
+    let dates: BTreeSet<chrono::NaiveDate> = prices
+        .values()
+        .flat_map(|prices| prices.range(start..=end).map(|(date, _price)| *date))
+        .collect();
+
+    let mut account = Account {
+        cash,
+        holdings: BTreeMap::new(),
+        trades: vec![],
+    };
+    let mut equity = Prices::new();
+    for date in dates {
+        let context = Context {
+            date,
+            cash: account.cash,
+            holdings: &account.holdings,
+            prices,
+        };
+        let value = context.value();
+        if let Some(weights) = strategy.rebalance(&context) {
+            let mut orders: Vec<(Symbol, f64, f64)> = vec![];
+            let symbols: BTreeSet<&Symbol> =
+                weights.keys().chain(context.holdings.keys()).collect();
+            for symbol in symbols {
+                let price = match context.price(symbol) {
+                    Some(price) if price > 0.0 => price,
+                    _ => continue,
+                };
+                let held = context.holdings.get(symbol).copied().unwrap_or(0.0);
+                let target = value * weights.get(symbol).copied().unwrap_or(0.0) / price;
+                if ((target - held) * price).abs() > 0.005 {
+                    orders.push((symbol.clone(), target - held, price));
+                }
+            }
+
+            // sells first, then buys scaled down to the cash they leave
+            orders.sort_by(|a, b| a.1.total_cmp(&b.1));
+            for (symbol, quantity, price) in &orders {
+                if *quantity < 0.0 {
+                    account.trade(date, symbol, *quantity, *price, costs);
+                }
+            }
+            let buys: Vec<&(Symbol, f64, f64)> = orders
+                .iter()
+                .filter(|(_symbol, quantity, _price)| *quantity > 0.0)
+                .collect();
+            let cost: f64 = buys
+                .iter()
+                .map(|(_symbol, quantity, price)| quantity * price * (1.0 + costs.slippage))
+                .sum();
+            let available = account.cash - costs.commission * buys.len() as f64;
+            let scale = if cost > available {
+                (available / cost).max(0.0)
+            } else {
+                1.0
+            };
+            for (symbol, quantity, price) in buys {
+                if scale > 0.0 {
+                    account.trade(date, symbol, quantity * scale, *price, costs);
+                }
+            }
+        }
+
+        let context = Context {
+            date,
+            cash: account.cash,
+            holdings: &account.holdings,
+            prices,
+        };
+        equity.insert(date, context.value());
+    }
+
+    Backtest {
+        metrics: metrics::compute(&equity, risk_free_rate),
+        equity,
+        trades: account.trades,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(date: &str) -> chrono::NaiveDate {
+        date.parse().unwrap()
+    }
+
+    fn prices(closes: &[(&str, f64)]) -> Prices {
+        closes
+            .iter()
+            .map(|(day, close)| (date(day), *close))
+            .collect()
+    }
+
+    #[test]
+    fn buys_are_scaled_to_the_cash_left_after_commission() {
+        let ibm = Symbol::new("IBM");
+        let prices = BTreeMap::from([(
+            ibm.clone(),
+            prices(&[("2024-01-02", 100.0), ("2024-01-03", 200.0)]),
+        )]);
+        let costs = Costs {
+            commission: 1.0,
+            slippage: 0.0,
+        };
+        let mut strategy = BuyAndHold::new(Weights::from([(ibm, 1.0)]));
+        let backtest = run(
+            &prices,
+            date("2024-01-01"),
+            date("2024-12-31"),
+            1000.0,
+            costs,
+            0.0,
+            &mut strategy,
+        );
+        assert_eq!(backtest.trades.len(), 1);
+        assert!((backtest.trades[0].quantity - 9.99).abs() < 1e-9);
+        assert!((backtest.equity[&date("2024-01-03")] - 1998.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn rebalances_sell_before_buying() {
+        let (a, b) = (Symbol::new("A"), Symbol::new("B"));
+        let prices = BTreeMap::from([
+            (
+                a.clone(),
+                prices(&[("2024-01-31", 100.0), ("2024-02-01", 200.0)]),
+            ),
+            (
+                b.clone(),
+                prices(&[("2024-01-31", 100.0), ("2024-02-01", 100.0)]),
+            ),
+        ]);
+        let weights = Weights::from([(a.clone(), 0.5), (b.clone(), 0.5)]);
+        let mut strategy = PeriodicRebalance::new(weights, Frequency::Monthly);
+        let backtest = run(
+            &prices,
+            date("2024-01-01"),
+            date("2024-12-31"),
+            1000.0,
+            Costs::default(),
+            0.0,
+            &mut strategy,
+        );
+
+        // back to 750 of each once A has doubled
+        let rebalance: Vec<(&Symbol, f64)> = backtest.trades[2..]
+            .iter()
+            .map(|trade| (&trade.symbol, trade.quantity))
+            .collect();
+        assert_eq!(rebalance, [(&a, -1.25), (&b, 2.5)]);
+        assert_eq!(backtest.equity[&date("2024-02-01")], 1500.0);
+    }
+}
@@ -0,0 +1,168 @@
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::{ApiError, DividendPayment};
+
+fn default_frequency() -> u32 {
+    2
+}
+
+fn default_face_value() -> f64 {
+    100.0
+}
+
+fn valid_frequency(frequency: u32) -> Result<u32, ApiError> {
+    match frequency {
+        1 | 2 | 3 | 4 | 6 | 12 => Ok(frequency),
+        _ => Err(ApiError::Api(format!(
+            "{} coupons a year don't divide it into whole months",
+            frequency
+        ))),
+    }
+}
+
+fn frequency<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+    valid_frequency(u32::deserialize(deserializer)?).map_err(serde::de::Error::custom)
+}
+
+// a fixed coupon bond; a position's quantity is a number of bonds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bond {
+    // annual rate, in percent of the face value
+    pub coupon: f64,
+    // coupons a year, counted back from maturity
+    #[serde(default = "default_frequency", deserialize_with = "frequency")]
+    pub frequency: u32,
+    pub maturity: chrono::NaiveDate,
+    #[serde(default = "default_face_value")]
+    pub face_value: f64,
+    // the clean price in percent of the face value, par unless given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price: Option<f64>,
+}
+impl Bond {
+    pub fn new(
+        coupon: f64,
+        frequency: u32,
+        maturity: chrono::NaiveDate,
+        face_value: f64,
+        price: Option<f64>,
+    ) -> Result<Bond, ApiError> {
+        Ok(Bond {
+            coupon,
+            frequency: valid_frequency(frequency)?,
+            maturity,
+            face_value,
+            price,
+        })
+    }
+
+    // the coupon date `periods` back from maturity, always counted from the
+    // maturity so that month ends don't drift
+    fn coupon_date(&self, periods: u32) -> Option<chrono::NaiveDate> {
+        let months = periods.checked_mul(12 / self.frequency.clamp(1, 12))?;
+        self.maturity
+            .checked_sub_months(chrono::Months::new(months))
+    }
+
+    // the coupon paid on each date, per bond
+    pub fn coupon_payment(&self) -> f64 {
+        self.face_value * self.coupon / 100.0 / f64::from(self.frequency.clamp(1, 12))
+    }
+
+    // the latest coupon date on or before `date`, and the next one after it
+    fn coupon_period(
+        &self,
+        date: chrono::NaiveDate,
+    ) -> Option<(chrono::NaiveDate, chrono::NaiveDate)> {
+        if date >= self.maturity {
+            return None;
+        }
+        let mut next = self.maturity;
+        for periods in 1.. {
+            let previous = self.coupon_date(periods)?;
+            if previous <= date {
+                return Some((previous, next));
+            }
+            next = previous;
+        }
+        None
+    }
+
+    // interest earned since the last coupon, per bond, counting actual days
+    pub fn accrued_interest(&self, date: chrono::NaiveDate) -> f64 {
+        match self.coupon_period(date) {
+            Some((previous, next)) => {
+                let elapsed = (date - previous).num_days() as f64;
+                let days = (next - previous).num_days() as f64;
+                self.coupon_payment() * elapsed / days
+            }
+            None => 0.0,
+        }
+    }
+
+    pub fn clean_price(&self) -> f64 {
+        self.face_value * self.price.unwrap_or(100.0) / 100.0
+    }
+
+    // what a bond is worth on `date`, with the interest accrued so far
+    pub fn dirty_price(&self, date: chrono::NaiveDate) -> f64 {
+        self.clean_price() + self.accrued_interest(date)
+    }
+
+    // the coupons paid from `from` and before `to`, per bond
+    pub fn coupons(&self, from: chrono::NaiveDate, to: chrono::NaiveDate) -> Vec<DividendPayment> {
+        let mut coupons = vec![];
+        for date in (0..)
+            .map_while(|periods| self.coupon_date(periods))
+            .take_while(|date| *date >= from)
+        {
+            if date < to {
+                coupons.push(DividendPayment {
+                    date,
+                    amount: self.coupon_payment(),
+                });
+            }
+        }
+        coupons.reverse();
+        coupons
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(date: &str) -> chrono::NaiveDate {
+        date.parse().unwrap()
+    }
+
+    #[test]
+    fn interest_accrues_between_coupons() {
+        let bond = Bond::new(4.0, 4, date("2026-03-31"), 1000.0, Some(98.0)).unwrap();
+        assert_eq!(bond.coupon_payment(), 10.0);
+        assert_eq!(bond.dirty_price(date("2025-12-31")), 980.0);
+        // halfway through the 90 days to maturity
+        assert!((bond.dirty_price(date("2026-02-14")) - 985.0).abs() < 1e-9);
+        assert_eq!(bond.accrued_interest(date("2026-04-01")), 0.0);
+    }
+
+    #[test]
+    fn month_ends_do_not_drift() {
+        let bond = Bond::new(4.0, 4, date("2026-03-31"), 1000.0, None).unwrap();
+        let dates: Vec<String> = bond
+            .coupons(date("2025-03-01"), date("2026-04-01"))
+            .iter()
+            .map(|coupon| coupon.date.to_string())
+            .collect();
+        assert_eq!(
+            dates,
+            [
+                "2025-03-31",
+                "2025-06-30",
+                "2025-09-30",
+                "2025-12-31",
+                "2026-03-31"
+            ]
+        );
+    }
+}
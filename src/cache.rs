@@ -0,0 +1,199 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use crate::StorageError;
+
+static WRITES: AtomicU64 = AtomicU64::new(0);
+
+// compressed entries first, with plain JSON ones from before still read
+#[cfg(feature = "compression")]
+const EXTENSIONS: &[&str] = &["json.zst", "json"];
+#[cfg(not(feature = "compression"))]
+const EXTENSIONS: &[&str] = &["json"];
+
+#[derive(Debug, Default)]
+pub struct CacheStats {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+impl CacheStats {
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+}
+
+pub struct Cache {
+    dir: PathBuf,
+    ttl: Duration,
+    write_only: bool,
+    stats: Arc<CacheStats>,
+}
+impl Cache {
+    pub fn new<P: Into<PathBuf>>(dir: P, ttl: Duration) -> Cache {
+        Cache {
+            dir: dir.into(),
+            ttl,
+            write_only: false,
+            stats: Arc::default(),
+        }
+    }
+
+    // shared, so they can still be read once the cache is given to a client
+    pub fn stats(&self) -> Arc<CacheStats> {
+        self.stats.clone()
+    }
+
+    // always fetches, but still stores responses for other clients to read
+    pub fn write_only(mut self) -> Cache {
+        self.write_only = true;
+        self
+    }
+
+    pub fn default_dir() -> Result<PathBuf, StorageError> {
+        dirs::cache_dir()
+            .map(|dir| dir.join("portfolio"))
+            .ok_or(StorageError::NoConfigDir)
+    }
+
+    fn path(&self, params: &[(&str, &str)], extension: &str) -> PathBuf {
+        let today = chrono::Utc::now().date_naive().to_string();
+        self.dir
+            .join(format!("{}.{}", file_name(params, &today), extension))
+    }
+
+    pub(crate) fn get(&self, params: &[(&str, &str)]) -> Option<String> {
+        let body = self.read(params);
+        let counter = match body {
+            Some(_) => &self.stats.hits,
+            None => &self.stats.misses,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        body
+    }
+
+    fn read(&self, params: &[(&str, &str)]) -> Option<String> {
+        if self.write_only {
+            return None;
+        }
+        let (path, modified) = EXTENSIONS.iter().find_map(|extension| {
+            let path = self.path(params, extension);
+            let modified = fs::metadata(&path).and_then(|meta| meta.modified()).ok()?;
+            Some((path, modified))
+        })?;
+        let age = SystemTime::now().duration_since(modified).ok()?;
+        if age > self.ttl {
+            return None;
+        }
+        self.read_file(&path)
+    }
+
+    fn read_file(&self, path: &Path) -> Option<String> {
+        let bytes = fs::read(path).ok()?;
+        #[cfg(feature = "compression")]
+        {
+            if path.extension().is_some_and(|extension| extension == "zst") {
+                return String::from_utf8(zstd::decode_all(&bytes[..]).ok()?).ok();
+            }
+            // plain entries are compressed as they're read, keeping their age
+            let body = String::from_utf8(bytes).ok()?;
+            let modified = fs::metadata(path).and_then(|meta| meta.modified()).ok();
+            let migrated = zstd::encode_all(body.as_bytes(), 0).and_then(|compressed| {
+                self.write(&path.with_extension("json.zst"), &compressed, modified)
+            });
+            if migrated.is_ok() {
+                let _ = fs::remove_file(path);
+            }
+            Some(body)
+        }
+        #[cfg(not(feature = "compression"))]
+        String::from_utf8(bytes).ok()
+    }
+
+    // the most recent response from any day, however old, for bringing up to date
+    pub(crate) fn latest(&self, params: &[(&str, &str)]) -> Option<String> {
+        self.read_file(&self.latest_path(params)?)
+    }
+
+    pub(crate) fn modified(&self, params: &[(&str, &str)]) -> Option<SystemTime> {
+        fs::metadata(self.latest_path(params)?)
+            .and_then(|meta| meta.modified())
+            .ok()
+    }
+
+    fn latest_path(&self, params: &[(&str, &str)]) -> Option<PathBuf> {
+        let prefix = file_name(params, "");
+        let newest = fs::read_dir(&self.dir)
+            .ok()?
+            .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+            .filter(|name| {
+                name.strip_prefix(&prefix)
+                    .and_then(|rest| rest.split_once('.'))
+                    .is_some_and(|(date, extension)| {
+                        date.parse::<chrono::NaiveDate>().is_ok() && EXTENSIONS.contains(&extension)
+                    })
+            })
+            // dates sort as strings, and a compressed entry after a plain one
+            .max()?;
+        Some(self.dir.join(newest))
+    }
+
+    pub(crate) fn age(&self) -> Option<Duration> {
+        let updated = fs::read_dir(&self.dir)
+            .ok()?
+            .filter_map(|entry| entry.ok()?.metadata().ok()?.modified().ok())
+            .max()?;
+        SystemTime::now().duration_since(updated).ok()
+    }
+
+    pub(crate) fn put(&self, params: &[(&str, &str)], body: &str) {
+        tracing::trace!(dir = %self.dir.display(), bytes = body.len(), "caching");
+        #[cfg(feature = "compression")]
+        let _ = zstd::encode_all(body.as_bytes(), 0)
+            .and_then(|compressed| self.write(&self.path(params, "json.zst"), &compressed, None));
+        #[cfg(not(feature = "compression"))]
+        let _ = self.write(&self.path(params, "json"), body.as_bytes(), None);
+    }
+
+    fn write(
+        &self,
+        path: &Path,
+        contents: &[u8],
+        modified: Option<SystemTime>,
+    ) -> std::io::Result<()> {
+        // unique to the write, so concurrent writers never share a temporary file
+        let tmp_path = path.with_extension(format!(
+            "{}-{}.tmp",
+            std::process::id(),
+            WRITES.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&self.dir)?;
+        fs::write(&tmp_path, contents)?;
+        if let Some(modified) = modified {
+            fs::File::options()
+                .write(true)
+                .open(&tmp_path)?
+                .set_modified(modified)?;
+        }
+        fs::rename(&tmp_path, path)
+    }
+}
+
+pub(crate) fn file_name(params: &[(&str, &str)], date: &str) -> String {
+    let mut parts: Vec<&str> = params.iter().map(|(_key, value)| *value).collect();
+    parts.push(date);
+    parts
+        .join("-")
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '.' | '-' => c,
+            _ => '_',
+        })
+        .collect()
+}
@@ -0,0 +1,283 @@
+use chrono::{Datelike, TimeZone, Weekday};
+use chrono_tz::Tz;
+use serde::{de, Deserialize, Deserializer, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EarningsEvent {
+    pub symbol: String,
+    pub name: String,
+    #[serde(rename(deserialize = "reportDate"))]
+    pub report_date: chrono::NaiveDate,
+    #[serde(rename(deserialize = "fiscalDateEnding"))]
+    pub fiscal_date_ending: chrono::NaiveDate,
+    // the consensus EPS estimate, when there is one
+    pub estimate: Option<f64>,
+    pub currency: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IpoEvent {
+    pub symbol: String,
+    pub name: String,
+    #[serde(rename(deserialize = "ipoDate"))]
+    pub ipo_date: chrono::NaiveDate,
+    #[serde(rename(deserialize = "priceRangeLow"))]
+    pub price_range_low: Option<f64>,
+    #[serde(rename(deserialize = "priceRangeHigh"))]
+    pub price_range_high: Option<f64>,
+    pub currency: String,
+    pub exchange: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Listing {
+    pub symbol: String,
+    pub name: String,
+    pub exchange: String,
+    #[serde(rename(deserialize = "assetType"))]
+    pub asset_type: String,
+    #[serde(
+        rename(deserialize = "ipoDate"),
+        deserialize_with = "deserialize_optional_date"
+    )]
+    pub ipo_date: Option<chrono::NaiveDate>,
+    #[serde(
+        rename(deserialize = "delistingDate"),
+        deserialize_with = "deserialize_optional_date"
+    )]
+    pub delisting_date: Option<chrono::NaiveDate>,
+    pub status: String,
+}
+
+// missing dates are written as `null`
+fn deserialize_optional_date<'de, D>(deserializer: D) -> Result<Option<chrono::NaiveDate>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    match s.as_str() {
+        "" | "null" | "None" => Ok(None),
+        s => s.parse().map(Some).map_err(de::Error::custom),
+    }
+}
+
+// NYSE and NASDAQ share a calendar: weekdays apart from these holidays, from 09:30
+// to 16:00 New York time, closing at 13:00 on some days next to holidays
+pub const EXCHANGE_TIME_ZONE: Tz = chrono_tz::America::New_York;
+
+// the date in New York, which the exchanges' daily bars are dated by
+pub fn today() -> chrono::NaiveDate {
+    chrono::Utc::now()
+        .with_timezone(&EXCHANGE_TIME_ZONE)
+        .date_naive()
+}
+
+pub fn holidays(year: i32) -> Vec<(chrono::NaiveDate, &'static str)> {
+    let date = |month, day| chrono::NaiveDate::from_ymd_opt(year, month, day).unwrap();
+    let nth = |month, weekday, n| {
+        chrono::NaiveDate::from_weekday_of_month_opt(year, month, weekday, n).unwrap()
+    };
+
+    let mut holidays = vec![];
+    // a new year's day on a Saturday isn't made up on the Friday before
+    match date(1, 1).weekday() {
+        Weekday::Sat => {}
+        Weekday::Sun => holidays.push((date(1, 2), "New Year's Day")),
+        _ => holidays.push((date(1, 1), "New Year's Day")),
+    }
+    if year >= 1998 {
+        holidays.push((nth(1, Weekday::Mon, 3), "Martin Luther King Jr. Day"));
+    }
+    holidays.push((nth(2, Weekday::Mon, 3), "Washington's Birthday"));
+    holidays.push((easter(year) - chrono::Duration::days(2), "Good Friday"));
+    let memorial_day = chrono::NaiveDate::from_weekday_of_month_opt(year, 5, Weekday::Mon, 5)
+        .unwrap_or_else(|| nth(5, Weekday::Mon, 4));
+    holidays.push((memorial_day, "Memorial Day"));
+    if year >= 2022 {
+        holidays.push((observed(date(6, 19)), "Juneteenth"));
+    }
+    holidays.push((observed(date(7, 4)), "Independence Day"));
+    holidays.push((nth(9, Weekday::Mon, 1), "Labor Day"));
+    holidays.push((nth(11, Weekday::Thu, 4), "Thanksgiving Day"));
+    holidays.push((observed(date(12, 25)), "Christmas Day"));
+    holidays
+}
+
+pub fn holiday(date: chrono::NaiveDate) -> Option<&'static str> {
+    holidays(date.year())
+        .into_iter()
+        .find(|(holiday, _name)| *holiday == date)
+        .map(|(_holiday, name)| name)
+}
+
+pub fn is_trading_day(date: chrono::NaiveDate) -> bool {
+    !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) && holiday(date).is_none()
+}
+
+pub fn trading_day_on_or_before(date: chrono::NaiveDate) -> chrono::NaiveDate {
+    let mut day = date;
+    while !is_trading_day(day) {
+        day = day.pred_opt().unwrap();
+    }
+    day
+}
+
+pub fn previous_trading_day(date: chrono::NaiveDate) -> chrono::NaiveDate {
+    trading_day_on_or_before(date.pred_opt().unwrap())
+}
+
+pub fn next_trading_day(date: chrono::NaiveDate) -> chrono::NaiveDate {
+    let mut day = date.succ_opt().unwrap();
+    while !is_trading_day(day) {
+        day = day.succ_opt().unwrap();
+    }
+    day
+}
+
+// the trading days after `from`, up to and including `to`
+pub fn trading_days_between(from: chrono::NaiveDate, to: chrono::NaiveDate) -> usize {
+    from.iter_days()
+        .skip(1)
+        .take_while(|day| *day <= to)
+        .filter(|day| is_trading_day(*day))
+        .count()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Session {
+    pub open: chrono::DateTime<Tz>,
+    pub close: chrono::DateTime<Tz>,
+}
+
+pub fn session(date: chrono::NaiveDate) -> Option<Session> {
+    if !is_trading_day(date) {
+        return None;
+    }
+    let at = |hour, minute| {
+        EXCHANGE_TIME_ZONE
+            .from_local_datetime(&date.and_hms_opt(hour, minute, 0).unwrap())
+            .single()
+    };
+    let close = if closes_early(date) { (13, 0) } else { (16, 0) };
+    Some(Session {
+        open: at(9, 30)?,
+        close: at(close.0, close.1)?,
+    })
+}
+
+// the day before Independence Day, the day after Thanksgiving and Christmas Eve,
+// when they are trading days
+fn closes_early(date: chrono::NaiveDate) -> bool {
+    let year = date.year();
+    let independence_eve = chrono::NaiveDate::from_ymd_opt(year, 7, 3).unwrap();
+    let thanksgiving = chrono::NaiveDate::from_weekday_of_month_opt(year, 11, Weekday::Thu, 4);
+    let christmas_eve = chrono::NaiveDate::from_ymd_opt(year, 12, 24).unwrap();
+    date == independence_eve
+        || Some(date) == thanksgiving.and_then(|day| day.succ_opt())
+        || date == christmas_eve
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MarketStatus {
+    Open { closes: chrono::DateTime<Tz> },
+    Closed { opens: chrono::DateTime<Tz> },
+}
+
+pub fn market_status(now: chrono::DateTime<chrono::Utc>) -> MarketStatus {
+    let now = now.with_timezone(&EXCHANGE_TIME_ZONE);
+    let today = now.date_naive();
+    if let Some(session) = session(today) {
+        if now < session.open {
+            return MarketStatus::Closed {
+                opens: session.open,
+            };
+        }
+        if now < session.close {
+            return MarketStatus::Open {
+                closes: session.close,
+            };
+        }
+    }
+    // every trading day has a session, as New York changes its clocks at 02:00
+    let next = session(next_trading_day(today)).unwrap();
+    MarketStatus::Closed { opens: next.open }
+}
+
+// holidays falling on a Saturday are taken on the Friday before, and on a Sunday
+// on the Monday after
+fn observed(date: chrono::NaiveDate) -> chrono::NaiveDate {
+    match date.weekday() {
+        Weekday::Sat => date.pred_opt().unwrap(),
+        Weekday::Sun => date.succ_opt().unwrap(),
+        _ => date,
+    }
+}
+
+// the anonymous Gregorian algorithm
+fn easter(year: i32) -> chrono::NaiveDate {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    chrono::NaiveDate::from_ymd_opt(year, month as u32, day as u32).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(date: &str) -> chrono::NaiveDate {
+        date.parse().unwrap()
+    }
+
+    #[test]
+    fn holidays_are_observed() {
+        assert_eq!(holiday(date("2024-03-29")), Some("Good Friday"));
+        assert_eq!(holiday(date("2025-04-18")), Some("Good Friday"));
+        assert_eq!(holiday(date("2024-11-28")), Some("Thanksgiving Day"));
+        // Juneteenth 2022 fell on a Sunday
+        assert_eq!(holiday(date("2022-06-20")), Some("Juneteenth"));
+        assert_eq!(holiday(date("2023-01-02")), Some("New Year's Day"));
+        // but a Saturday new year's day isn't taken on the Friday
+        assert!(is_trading_day(date("2021-12-31")));
+        assert_eq!(holiday(date("2021-06-18")), None);
+    }
+
+    #[test]
+    fn trading_days_skip_weekends_and_holidays() {
+        assert_eq!(next_trading_day(date("2024-03-28")), date("2024-04-01"));
+        assert_eq!(previous_trading_day(date("2024-04-01")), date("2024-03-28"));
+        assert_eq!(
+            trading_day_on_or_before(date("2024-12-25")),
+            date("2024-12-24")
+        );
+        assert_eq!(
+            trading_days_between(date("2024-12-20"), date("2024-12-31")),
+            6
+        );
+    }
+
+    #[test]
+    fn sessions_close_early_next_to_holidays() {
+        let close = |day| session(date(day)).unwrap().close.time().to_string();
+        assert_eq!(close("2024-11-29"), "13:00:00");
+        assert_eq!(close("2024-12-24"), "13:00:00");
+        assert_eq!(close("2024-12-26"), "16:00:00");
+        assert!(session(date("2024-12-25")).is_none());
+
+        // 13:00 in New York on the day before Independence Day
+        let now = chrono::Utc.with_ymd_and_hms(2024, 7, 3, 17, 0, 0).unwrap();
+        let opens = session(date("2024-07-05")).unwrap().open;
+        assert_eq!(market_status(now), MarketStatus::Closed { opens });
+    }
+}
@@ -0,0 +1,179 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
+
+use plotters::coord::Shift;
+use plotters::prelude::*;
+
+use crate::{Allocation, EquityCurve};
+
+const SIZE: (u32, u32) = (1024, 600);
+const PALETTE: [RGBColor; 8] = [
+    RGBColor(31, 119, 180),
+    RGBColor(255, 127, 14),
+    RGBColor(44, 160, 44),
+    RGBColor(214, 39, 40),
+    RGBColor(148, 103, 189),
+    RGBColor(140, 86, 75),
+    RGBColor(227, 119, 194),
+    RGBColor(127, 127, 127),
+];
+
+#[derive(Debug)]
+pub enum ChartError {
+    UnsupportedFormat(String),
+    NoData,
+    Plot(String),
+}
+impl fmt::Display for ChartError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ChartError::UnsupportedFormat(path) => {
+                write!(f, "{}: charts are drawn as .svg or .png files", path)
+            }
+            ChartError::NoData => write!(f, "nothing to chart"),
+            ChartError::Plot(message) => write!(f, "couldn't draw the chart: {}", message),
+        }
+    }
+}
+impl Error for ChartError {}
+impl<E: Error + Send + Sync> From<DrawingAreaErrorKind<E>> for ChartError {
+    fn from(error: DrawingAreaErrorKind<E>) -> ChartError {
+        ChartError::Plot(error.to_string())
+    }
+}
+
+// picks the backend from the file extension
+macro_rules! with_backend {
+    ($path:expr, |$root:ident| $body:expr) => {{
+        let path: &Path = $path.as_ref();
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("svg") => {
+                let $root = SVGBackend::new(path, SIZE).into_drawing_area();
+                $body
+            }
+            Some("png") => {
+                let $root = BitMapBackend::new(path, SIZE).into_drawing_area();
+                $body
+            }
+            _ => Err(ChartError::UnsupportedFormat(path.display().to_string())),
+        }
+    }};
+}
+
+pub fn price_chart<P: AsRef<Path>>(
+    path: P,
+    title: &str,
+    values: &BTreeMap<chrono::NaiveDate, f64>,
+) -> Result<(), ChartError> {
+    with_backend!(path, |root| draw_lines(root, title, &[("Close", values)]))
+}
+
+pub fn equity_curve_chart<P: AsRef<Path>>(path: P, curve: &EquityCurve) -> Result<(), ChartError> {
+    let market_value = curve.values();
+    let cost_basis: BTreeMap<chrono::NaiveDate, f64> = curve
+        .days
+        .iter()
+        .map(|(date, point)| (*date, point.cost_basis))
+        .collect();
+    with_backend!(path, |root| draw_lines(
+        root,
+        "Portfolio value",
+        &[("Market value", &market_value), ("Cost basis", &cost_basis)]
+    ))
+}
+
+pub fn allocation_chart<P: AsRef<Path>>(
+    path: P,
+    allocation: &Allocation,
+) -> Result<(), ChartError> {
+    with_backend!(path, |root| draw_pie(root, allocation))
+}
+
+fn draw_lines<DB: DrawingBackend>(
+    root: DrawingArea<DB, Shift>,
+    title: &str,
+    lines: &[(&str, &BTreeMap<chrono::NaiveDate, f64>)],
+) -> Result<(), ChartError>
+where
+    DB::ErrorType: 'static,
+{
+    let dates = lines.iter().flat_map(|(_label, values)| values.keys());
+    let (start, end) = match (dates.clone().min(), dates.max()) {
+        (Some(start), Some(end)) => (*start, *end),
+        _ => return Err(ChartError::NoData),
+    };
+    let (min, max) = lines
+        .iter()
+        .flat_map(|(_label, values)| values.values())
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| {
+            (min.min(*value), max.max(*value))
+        });
+    let margin = ((max - min) * 0.05).max(f64::EPSILON);
+
+    root.fill(&WHITE)?;
+    let mut chart = ChartBuilder::on(&root)
+        .caption(title, ("sans-serif", 24))
+        .margin(16)
+        .x_label_area_size(40)
+        .y_label_area_size(70)
+        .build_cartesian_2d(start..end, (min - margin)..(max + margin))?;
+    chart.configure_mesh().light_line_style(WHITE).draw()?;
+
+    for (idx, (label, values)) in lines.iter().enumerate() {
+        let colour = PALETTE[idx % PALETTE.len()];
+        chart
+            .draw_series(LineSeries::new(
+                values.iter().map(|(date, value)| (*date, *value)),
+                colour.stroke_width(2),
+            ))?
+            .label(*label)
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], colour));
+    }
+    if lines.len() > 1 {
+        chart
+            .configure_series_labels()
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()?;
+    }
+    root.present()?;
+    Ok(())
+}
+
+fn draw_pie<DB: DrawingBackend>(
+    root: DrawingArea<DB, Shift>,
+    allocation: &Allocation,
+) -> Result<(), ChartError>
+where
+    DB::ErrorType: 'static,
+{
+    let weights: Vec<_> = allocation
+        .weights
+        .iter()
+        .filter(|weight| weight.market_value > 0.0)
+        .collect();
+    if weights.is_empty() {
+        return Err(ChartError::NoData);
+    }
+    let sizes: Vec<f64> = weights.iter().map(|weight| weight.market_value).collect();
+    let labels: Vec<&str> = weights.iter().map(|weight| weight.name.as_str()).collect();
+    let colours: Vec<RGBColor> = (0..weights.len())
+        .map(|idx| PALETTE[idx % PALETTE.len()])
+        .collect();
+
+    root.fill(&WHITE)?;
+    let root = root.titled(allocation.dimension.label(), ("sans-serif", 24))?;
+    let (width, height) = root.dim_in_pixel();
+    let centre = (width as i32 / 2, height as i32 / 2);
+    let radius = f64::from(width.min(height)) * 0.35;
+
+    let mut pie = Pie::new(&centre, &radius, &sizes, &colours, &labels);
+    pie.start_angle(-90.0);
+    pie.label_style(("sans-serif", 16).into_font());
+    pie.percentages(("sans-serif", 14).into_font().color(&WHITE));
+    root.draw(&pie)?;
+    root.present()?;
+    Ok(())
+}
@@ -0,0 +1,159 @@
+use clap::ArgMatches;
+use portfolio::render::{self, Align, Cell, Table};
+use serde::Serialize;
+
+use crate::{
+    alerts_path, exit, output, print_csv, print_json, provider, OrExit, Output, EXIT_ALERTS,
+    EXIT_USAGE,
+};
+
+pub fn alert(matches: &ArgMatches) {
+    let path = alerts_path(matches);
+    let mut alerts = portfolio::Alerts::load(&path).or_exit();
+
+    match matches.subcommand() {
+        ("add", Some(matches)) => {
+            let value = |name| {
+                matches
+                    .value_of(name)
+                    .map(|value: &str| value.parse().or_exit())
+            };
+            let condition = if let Some(price) = value("above") {
+                portfolio::Condition::Above { price }
+            } else if let Some(price) = value("below") {
+                portfolio::Condition::Below { price }
+            } else if let Some(percent) = value("move") {
+                portfolio::Condition::DailyMove { percent }
+            } else if matches.is_present("52w-high") {
+                portfolio::Condition::FiftyTwoWeekHigh
+            } else {
+                portfolio::Condition::FiftyTwoWeekLow
+            };
+            let mut notify: Vec<portfolio::Notifier> = matches
+                .values_of("webhook")
+                .into_iter()
+                .flatten()
+                .map(|url| portfolio::Notifier::Webhook {
+                    url: url.to_string(),
+                })
+                .collect();
+            if matches.is_present("desktop") {
+                notify.push(portfolio::Notifier::Desktop);
+            }
+            let id = alerts.add(
+                matches.value_of("symbol").unwrap().to_uppercase(),
+                condition,
+                notify,
+            );
+            alerts.save(&path).or_exit();
+            match output(matches) {
+                Output::Json => {
+                    print_json(alerts.rules().iter().find(|rule| rule.id == id).unwrap())
+                }
+                _ => println!("Added alert {}", id),
+            }
+        }
+        ("remove", Some(matches)) => {
+            let id = matches.value_of("id").unwrap().parse().or_exit();
+            if !alerts.remove(id) {
+                exit(EXIT_USAGE, &format!("No alert with id {}", id));
+            }
+            alerts.save(&path).or_exit();
+        }
+        ("list", Some(matches)) => match output(matches) {
+            Output::Table => {
+                let mut table = Table::new(&[
+                    ("ID", Align::Right),
+                    ("SYMBOL", Align::Left),
+                    ("CONDITION", Align::Left),
+                ]);
+                for rule in alerts.rules() {
+                    table.row(vec![
+                        Cell::new(rule.id.to_string()),
+                        Cell::new(&*rule.symbol),
+                        Cell::new(rule.condition.describe()),
+                    ]);
+                }
+                print!("{}", table);
+            }
+            Output::Csv => {
+                #[derive(Serialize)]
+                struct Row<'a> {
+                    id: u32,
+                    symbol: &'a str,
+                    condition: String,
+                }
+                print_csv(alerts.rules().iter().map(|rule| Row {
+                    id: rule.id,
+                    symbol: &rule.symbol,
+                    condition: rule.condition.describe(),
+                }))
+            }
+            Output::Json => print_json(alerts.rules()),
+        },
+        _ => unreachable!(),
+    }
+}
+
+pub fn check_alerts(matches: &ArgMatches) {
+    let alerts = portfolio::Alerts::load(alerts_path(matches)).or_exit();
+    let triggered = alerts.check(&*provider(matches)).or_exit();
+
+    match output(matches) {
+        Output::Table => {
+            if !triggered.is_empty() {
+                let mut table = Table::new(&[
+                    ("ID", Align::Right),
+                    ("SYMBOL", Align::Left),
+                    ("CONDITION", Align::Left),
+                    ("PRICE", Align::Right),
+                    ("CHANGE %", Align::Right),
+                ]);
+                for alert in &triggered {
+                    table.row(vec![
+                        Cell::new(alert.rule.id.to_string()),
+                        Cell::new(&*alert.rule.symbol),
+                        Cell::new(alert.rule.condition.describe()),
+                        Cell::new(render::money(
+                            alert.price,
+                            render::currency_symbol(alert.rule.symbol.currency()),
+                        )),
+                        Cell::change(alert.change_percent, render::percent(alert.change_percent)),
+                    ]);
+                }
+                print!("{}", table);
+            }
+        }
+        Output::Csv => {
+            #[derive(Serialize)]
+            struct Row<'a> {
+                id: u32,
+                symbol: &'a str,
+                condition: String,
+                price: f64,
+                change_percent: f64,
+            }
+            print_csv(triggered.iter().map(|alert| Row {
+                id: alert.rule.id,
+                symbol: &alert.rule.symbol,
+                condition: alert.rule.condition.describe(),
+                price: alert.price,
+                change_percent: alert.change_percent,
+            }))
+        }
+        Output::Json => print_json(&triggered),
+    }
+
+    for alert in &triggered {
+        for notifier in &alert.rule.notify {
+            if let Err(err) = notifier.notify(alert) {
+                eprintln!("Failed to notify for alert {}: {:?}", alert.rule.id, err);
+            }
+        }
+    }
+
+    // a non-zero exit lets cron jobs act on triggered alerts
+    if !triggered.is_empty() {
+        std::process::exit(EXIT_ALERTS);
+    }
+}
@@ -0,0 +1,398 @@
+use std::collections::BTreeMap;
+
+use clap::ArgMatches;
+use portfolio::render::{self, Align, Cell, Table};
+use portfolio::{indicators, metrics};
+use serde::Serialize;
+
+use crate::{
+    common_currency, exit, held_prices, load_portfolio, metrics_table, output, print_csv,
+    print_json, progress_bar, provider, report_progress, stored_symbols, symbol_currency, OrExit,
+    Output, EXIT_NO_DATA,
+};
+
+pub fn backtest(matches: &ArgMatches) {
+    let symbols: Vec<portfolio::Symbol> = matches
+        .values_of("symbol")
+        .unwrap()
+        .map(|symbol| symbol.to_uppercase().into())
+        .collect();
+    let start = matches.value_of("from").unwrap().parse().or_exit();
+    let end = match matches.value_of("to") {
+        Some(to) => to.parse().or_exit(),
+        None => portfolio::calendar::today(),
+    };
+    let cash = matches.value_of("cash").unwrap().parse().or_exit();
+    let costs = portfolio::backtest::Costs {
+        commission: matches.value_of("commission").unwrap().parse().or_exit(),
+        slippage: matches
+            .value_of("slippage")
+            .unwrap()
+            .parse::<f64>()
+            .or_exit()
+            / 100.0,
+    };
+    let risk_free_rate = matches.value_of("risk-free").unwrap().parse().or_exit();
+
+    // adjusted closes, so that dividends count towards returns
+    let provider = provider(matches);
+    let mut prices = BTreeMap::new();
+    let bar = progress_bar();
+    for (idx, symbol) in symbols.iter().enumerate() {
+        let series = provider.series(symbol.clone(), portfolio::Frequency::Daily);
+        report_progress(
+            &bar,
+            portfolio::Progress {
+                symbol,
+                completed: idx + 1,
+                total: symbols.len(),
+                error: None,
+            },
+        );
+        if series.is_err() {
+            bar.finish_and_clear();
+        }
+        let series = series.or_exit();
+        let closes: portfolio::backtest::Prices = series
+            .bars
+            .iter()
+            .map(|(date, bar)| (*date, bar.adjusted_close))
+            .collect();
+        prices.insert(symbol.clone(), closes);
+    }
+    bar.finish_and_clear();
+
+    let weights: portfolio::backtest::Weights = symbols
+        .iter()
+        .map(|symbol| (symbol.clone(), 1.0 / symbols.len() as f64))
+        .collect();
+    let mut strategy: Box<dyn portfolio::backtest::Strategy> =
+        match matches.value_of("strategy").unwrap() {
+            "buy-and-hold" => Box::new(portfolio::backtest::BuyAndHold::new(weights)),
+            "sma-cross" => Box::new(portfolio::backtest::MovingAverageCrossover::new(
+                matches.value_of("fast").unwrap().parse().or_exit(),
+                matches.value_of("slow").unwrap().parse().or_exit(),
+            )),
+            _ => Box::new(portfolio::backtest::PeriodicRebalance::new(
+                weights,
+                match matches.value_of("every").unwrap() {
+                    "week" => portfolio::Frequency::Weekly,
+                    _ => portfolio::Frequency::Monthly,
+                },
+            )),
+        };
+    let backtest = portfolio::backtest::run(
+        &prices,
+        start,
+        end,
+        cash,
+        costs,
+        risk_free_rate,
+        &mut *strategy,
+    );
+
+    match output(matches) {
+        Output::Table => {
+            let metrics = backtest
+                .metrics
+                .as_ref()
+                .unwrap_or_else(|| exit(EXIT_NO_DATA, "Not enough history"));
+            let mut table = metrics_table("Backtest", metrics);
+            let currency = common_currency(symbols.iter().map(|symbol| symbol.currency()));
+            if let Some((_date, value)) = backtest.equity.iter().next_back() {
+                table.row(vec![
+                    "Final value".into(),
+                    render::money(*value, currency).into(),
+                ]);
+            }
+            table.row(vec![
+                "Trades".into(),
+                backtest.trades.len().to_string().into(),
+            ]);
+            table.row(vec![
+                "Commission".into(),
+                render::money(backtest.commission(), currency).into(),
+            ]);
+            print!("{}", table);
+        }
+        Output::Csv => print_csv(&backtest.trades),
+        Output::Json => print_json(&backtest),
+    }
+}
+
+pub fn correlations(matches: &ArgMatches) {
+    let provider = provider(matches);
+    let valuation = load_portfolio(matches).valuation(&*provider).or_exit();
+    let prices = held_prices(&*provider, &valuation);
+    let benchmark = matches.value_of("benchmark").map(|benchmark| {
+        let series = provider
+            .daily_series(benchmark.to_uppercase().into(), portfolio::OutputSize::Full)
+            .or_exit();
+        (
+            portfolio::Symbol::from(benchmark.to_uppercase()),
+            indicators::adjusted_closes(&series),
+        )
+    });
+    let correlations = metrics::correlations(
+        &prices,
+        &valuation.weights(),
+        benchmark
+            .as_ref()
+            .map(|(symbol, prices)| (symbol.clone(), prices)),
+    );
+
+    match output(matches) {
+        Output::Table => {
+            let benchmark_column = correlations
+                .benchmark
+                .as_ref()
+                .map(|benchmark| format!("BETA ({})", &**benchmark));
+            let mut columns = vec![("", Align::Left)];
+            for symbol in &correlations.symbols {
+                columns.push((&**symbol, Align::Right));
+            }
+            if let Some(column) = &benchmark_column {
+                columns.push((column, Align::Right));
+            }
+            let mut table = Table::new(&columns);
+            for (symbol, row) in correlations.symbols.iter().zip(&correlations.matrix) {
+                let mut cells = vec![Cell::new(&**symbol)];
+                for correlation in row {
+                    cells.push(match correlation {
+                        Some(correlation) => render::number(*correlation, 2).into(),
+                        None => Cell::empty(),
+                    });
+                }
+                if benchmark_column.is_some() {
+                    cells.push(match correlations.betas.get(symbol) {
+                        Some(beta) => render::number(*beta, 2).into(),
+                        None => Cell::empty(),
+                    });
+                }
+                table.row(cells);
+            }
+            if let Some(beta) = correlations.portfolio_beta {
+                let mut cells = vec![Cell::new("Portfolio")];
+                cells.extend(correlations.symbols.iter().map(|_symbol| Cell::empty()));
+                cells.push(render::number(beta, 2).into());
+                table.row(cells);
+            }
+            print!("{}", table);
+        }
+        Output::Csv => {
+            #[derive(Serialize)]
+            struct Row<'a> {
+                symbol: &'a str,
+                other: &'a str,
+                correlation: Option<f64>,
+            }
+            let mut rows = vec![];
+            for (symbol, row) in correlations.symbols.iter().zip(&correlations.matrix) {
+                for (other, correlation) in correlations.symbols.iter().zip(row) {
+                    rows.push(Row {
+                        symbol,
+                        other,
+                        correlation: *correlation,
+                    });
+                }
+            }
+            print_csv(&rows);
+        }
+        Output::Json => print_json(&correlations),
+    }
+}
+
+pub fn risk(matches: &ArgMatches) {
+    let limit: f64 = matches.value_of("limit").unwrap().parse().or_exit();
+    let provider = provider(matches);
+    let valuation = load_portfolio(matches).valuation(&*provider).or_exit();
+    let prices = held_prices(&*provider, &valuation);
+    let report = portfolio::risk_report(&prices, &valuation.weights(), limit / 100.0);
+
+    let percent = |value: Option<f64>| match value {
+        Some(value) => Cell::new(format!("{}%", render::number(value, 2))),
+        None => Cell::empty(),
+    };
+    match output(matches) {
+        Output::Table => {
+            let mut table = Table::new(&[
+                ("SYMBOL", Align::Left),
+                ("WEIGHT", Align::Right),
+                ("VOLATILITY", Align::Right),
+                ("RISK SHARE", Align::Right),
+                ("", Align::Left),
+            ]);
+            for holding in &report.holdings {
+                table.row(vec![
+                    Cell::new(&*holding.symbol),
+                    percent(Some(holding.weight * 100.0)),
+                    percent(holding.volatility),
+                    percent(holding.contribution),
+                    if holding.concentrated {
+                        Cell::new(format!("over {}%", render::number(limit, 0)))
+                    } else {
+                        Cell::empty()
+                    },
+                ]);
+            }
+            table.row(vec![
+                Cell::new("Portfolio"),
+                Cell::empty(),
+                percent(Some(report.volatility)),
+                Cell::empty(),
+                Cell::empty(),
+            ]);
+            print!("{}", table);
+            println!(
+                "Effective holdings: {}",
+                render::number(report.effective_holdings, 1)
+            );
+        }
+        Output::Csv => print_csv(&report.holdings),
+        Output::Json => print_json(&report),
+    }
+}
+
+pub fn screen(matches: &ArgMatches) {
+    let value = |name: &str| matches.value_of(name).unwrap();
+    let fast = value("fast").parse().or_exit();
+    let slow = value("slow").parse().or_exit();
+    let within = value("within").parse().or_exit();
+    let mut screens = vec![];
+    if matches.is_present("golden-cross") {
+        screens.push(portfolio::Screen::GoldenCross { fast, slow, within });
+    }
+    if matches.is_present("death-cross") {
+        screens.push(portfolio::Screen::DeathCross { fast, slow, within });
+    }
+    if matches.is_present("rsi-oversold") {
+        screens.push(portfolio::Screen::RsiOversold {
+            period: value("rsi").parse().or_exit(),
+            threshold: value("threshold").parse().or_exit(),
+        });
+    }
+
+    // a symbol without prices is skipped rather than ending the scan
+    let provider = provider(matches);
+    let mut found = vec![];
+    for symbol in stored_symbols(matches) {
+        let series = match provider.daily_series(symbol.clone(), portfolio::OutputSize::Full) {
+            Ok(series) => series,
+            Err(err) => {
+                eprintln!("Skipping {}: {}", &*symbol, err);
+                continue;
+            }
+        };
+        found.extend(
+            screens
+                .iter()
+                .filter_map(|screen| portfolio::screen(&symbol, &series, *screen)),
+        );
+    }
+
+    match output(matches) {
+        Output::Table => {
+            if found.is_empty() {
+                println!("No symbols match");
+                return;
+            }
+            let optional = |value: Option<f64>| match value {
+                Some(value) => Cell::new(render::number(value, 2)),
+                None => Cell::empty(),
+            };
+            let mut table = Table::new(&[
+                ("SYMBOL", Align::Left),
+                ("SIGNAL", Align::Left),
+                ("DATE", Align::Left),
+                ("CLOSE", Align::Right),
+                ("FAST", Align::Right),
+                ("SLOW", Align::Right),
+                ("RSI", Align::Right),
+            ]);
+            for screened in &found {
+                table.row(vec![
+                    Cell::new(&*screened.symbol),
+                    Cell::new(&screened.signal),
+                    Cell::new(screened.date.to_string()),
+                    Cell::new(render::number(screened.close, 2)),
+                    optional(screened.fast),
+                    optional(screened.slow),
+                    optional(screened.rsi),
+                ]);
+            }
+            print!("{}", table);
+        }
+        Output::Csv => print_csv(&found),
+        Output::Json => print_json(&found),
+    }
+}
+
+pub fn simulate_dca(matches: &ArgMatches) {
+    let symbol = matches.value_of("symbol").unwrap();
+    let amount = matches.value_of("amount").unwrap().parse().or_exit();
+    let schedule = matches.value_of("every").unwrap().parse().or_exit();
+    let since = matches.value_of("since").unwrap().parse().or_exit();
+
+    let series = provider(matches)
+        .series(symbol.into(), portfolio::Frequency::Daily)
+        .or_exit();
+    let simulation = match portfolio::simulate_dca(&series, amount, schedule, since) {
+        Some(simulation) => simulation,
+        None => {
+            exit(
+                EXIT_NO_DATA,
+                &format!("No prices for {} since {}", symbol, since),
+            );
+        }
+    };
+
+    match output(matches) {
+        Output::Table => {
+            let gain = simulation.gain();
+            let currency = symbol_currency(symbol);
+            let mut table = Table::new(&[(symbol, Align::Left), ("", Align::Right)]);
+            table.row(vec![
+                "Period".into(),
+                format!("{} to {}", simulation.start_date, simulation.end_date).into(),
+            ]);
+            table.row(vec![
+                "Purchases".into(),
+                simulation.purchases.len().to_string().into(),
+            ]);
+            table.row(vec![
+                "Units".into(),
+                render::number(simulation.units, 4).into(),
+            ]);
+            table.row(vec![
+                "Average price".into(),
+                render::money(simulation.average_price(), currency).into(),
+            ]);
+            table.row(vec![
+                "Invested".into(),
+                render::money(simulation.invested(), currency).into(),
+            ]);
+            table.row(vec![
+                "Value".into(),
+                render::money(simulation.market_value(), currency).into(),
+            ]);
+            table.row(vec![
+                "Dividends".into(),
+                render::money(simulation.dividends, currency).into(),
+            ]);
+            table.row(vec![
+                "Gain".into(),
+                Cell::change(gain, render::signed_money(gain, currency)),
+            ]);
+            table.row(vec![
+                "Annualised".into(),
+                match simulation.annualised_return() {
+                    Some(annualised) => Cell::change(annualised, render::percent(annualised)),
+                    None => Cell::empty(),
+                },
+            ]);
+            print!("{}", table);
+        }
+        Output::Csv => print_csv(&simulation.purchases),
+        Output::Json => print_json(&simulation),
+    }
+}
@@ -0,0 +1,889 @@
+use clap::{App, AppSettings, Arg, ArgGroup, ArgMatches, SubCommand};
+
+use crate::{chart_extension, stored_symbols, OrExit, DIMENSIONS, PROVIDERS};
+
+fn valid_symbol(symbol: String) -> Result<(), String> {
+    portfolio::Symbol::parse(&symbol)
+        .map(|_symbol| ())
+        .map_err(|err| err.to_string())
+}
+
+pub fn app() -> App<'static, 'static> {
+    let symbol_arg = Arg::with_name("symbol")
+        .validator(valid_symbol)
+        .required(true);
+    // any number of `--tag`s, matching positions with any of them
+    let tag_arg = Arg::with_name("tag")
+        .long("tag")
+        .takes_value(true)
+        .multiple(true)
+        .number_of_values(1);
+    let latest_price = SubCommand::with_name("latest-price")
+        .arg(
+            Arg::with_name("symbol")
+                .validator(valid_symbol)
+                .required(true)
+                .multiple(true),
+        )
+        .arg(Arg::with_name("watch").long("watch").takes_value(true));
+    #[cfg(feature = "streaming")]
+    let latest_price = latest_price.arg(
+        Arg::with_name("stream")
+            .long("stream")
+            .conflicts_with("watch"),
+    );
+
+    App::new("Portfolio")
+        .version("0.1")
+        .author("Jacob Haslehurst <jacob@haslehurst.net>")
+        .arg(
+            Arg::with_name("config")
+                .long("config")
+                .takes_value(true)
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("portfolio")
+                .long("portfolio")
+                .takes_value(true)
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("ledger")
+                .long("ledger")
+                .takes_value(true)
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("watchlist")
+                .long("watchlist")
+                .takes_value(true)
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("alerts")
+                .long("alerts")
+                .takes_value(true)
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("provider")
+                .long("provider")
+                .takes_value(true)
+                .possible_values(PROVIDERS)
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("tier")
+                .long("tier")
+                .takes_value(true)
+                .validator(|value| match value.parse::<portfolio::ApiTier>() {
+                    Ok(_tier) => Ok(()),
+                    Err(err) => Err(err.to_string()),
+                })
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("output")
+                .long("output")
+                .takes_value(true)
+                .validator(|value| match value.as_str() {
+                    "table" | "csv" | "json" => Ok(()),
+                    _ if chart_extension(&value) => Ok(()),
+                    _ => Err("must be table, csv, json or an .svg or .png file".to_string()),
+                })
+                .global(true),
+        )
+        .arg(Arg::with_name("json").long("json").global(true))
+        .arg(
+            Arg::with_name("quiet")
+                .long("quiet")
+                .short("q")
+                .global(true),
+        )
+        .arg(Arg::with_name("offline").long("offline").global(true))
+        .arg(
+            Arg::with_name("account")
+                .long("account")
+                .takes_value(true)
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("verbose")
+                .long("verbose")
+                .short("v")
+                .multiple(true)
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("record")
+                .long("record")
+                .takes_value(true)
+                .value_name("dir")
+                .conflicts_with("replay")
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("replay")
+                .long("replay")
+                .takes_value(true)
+                .value_name("dir")
+                .global(true),
+        )
+        .subcommand(latest_price)
+        .subcommand(
+            SubCommand::with_name("price-on")
+                .arg(&symbol_arg)
+                .arg(Arg::with_name("date").required(true))
+                .arg(Arg::with_name("ohlc").long("ohlc")),
+        )
+        .subcommand(
+            SubCommand::with_name("summary")
+                .arg(&symbol_arg)
+                .arg(Arg::with_name("from").long("from").takes_value(true))
+                .arg(
+                    Arg::with_name("to")
+                        .long("to")
+                        .takes_value(true)
+                        .requires("from"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("series").arg(&symbol_arg).arg(
+                Arg::with_name("frequency")
+                    .long("frequency")
+                    .takes_value(true)
+                    .possible_values(&["daily", "weekly", "monthly"])
+                    .default_value("daily"),
+            ),
+        )
+        .subcommand(
+            SubCommand::with_name("indicator")
+                .arg(&symbol_arg)
+                .arg(Arg::with_name("sma").long("sma").takes_value(true))
+                .arg(Arg::with_name("ema").long("ema").takes_value(true))
+                .arg(Arg::with_name("rsi").long("rsi").takes_value(true))
+                .arg(Arg::with_name("macd").long("macd"))
+                .arg(
+                    Arg::with_name("bollinger")
+                        .long("bollinger")
+                        .takes_value(true),
+                )
+                .arg(Arg::with_name("remote").long("remote")),
+        )
+        .subcommand(
+            SubCommand::with_name("chart")
+                .arg(&symbol_arg)
+                .arg(
+                    Arg::with_name("period")
+                        .long("period")
+                        .takes_value(true)
+                        .default_value("6m"),
+                )
+                .arg(
+                    Arg::with_name("style")
+                        .long("style")
+                        .takes_value(true)
+                        .possible_values(&["line", "candle"])
+                        .default_value("line"),
+                )
+                .arg(Arg::with_name("compact").long("compact"))
+                .arg(
+                    Arg::with_name("width")
+                        .long("width")
+                        .takes_value(true)
+                        .default_value("80"),
+                )
+                .arg(
+                    Arg::with_name("height")
+                        .long("height")
+                        .takes_value(true)
+                        .default_value("20"),
+                ),
+        )
+        .subcommand(SubCommand::with_name("dividends").arg(&symbol_arg))
+        .subcommand(SubCommand::with_name("income-forecast").arg(&tag_arg))
+        .subcommand(SubCommand::with_name("info").arg(&symbol_arg))
+        .subcommand(
+            SubCommand::with_name("earnings-calendar").arg(
+                Arg::with_name("soon")
+                    .long("soon")
+                    .takes_value(true)
+                    .default_value("7"),
+            ),
+        )
+        .subcommand(
+            SubCommand::with_name("economy").arg(
+                Arg::with_name("maturity")
+                    .long("maturity")
+                    .takes_value(true)
+                    .possible_values(&["3m", "2y", "5y", "7y", "10y", "30y"])
+                    .default_value("10y"),
+            ),
+        )
+        .subcommand(SubCommand::with_name("sectors"))
+        .subcommand(
+            SubCommand::with_name("news").arg(&symbol_arg).arg(
+                Arg::with_name("limit")
+                    .long("limit")
+                    .takes_value(true)
+                    .default_value("10"),
+            ),
+        )
+        .subcommand(SubCommand::with_name("fx").arg(Arg::with_name("pair").required(true)))
+        .subcommand(SubCommand::with_name("search").arg(Arg::with_name("query").required(true)))
+        .subcommand(
+            SubCommand::with_name("add-position")
+                .arg(&symbol_arg)
+                .arg(Arg::with_name("quantity").required(true))
+                .arg(Arg::with_name("cost-basis").required(true))
+                .arg(Arg::with_name("date").long("date").takes_value(true))
+                .arg(
+                    Arg::with_name("asset-type")
+                        .long("asset-type")
+                        .takes_value(true)
+                        .possible_values(&["equity", "etf", "crypto", "cash", "bond", "option"])
+                        .default_value("equity"),
+                )
+                .arg(
+                    Arg::with_name("currency")
+                        .long("currency")
+                        .takes_value(true),
+                )
+                .arg(Arg::with_name("sector").long("sector").takes_value(true))
+                .arg(Arg::with_name("country").long("country").takes_value(true))
+                .arg(&tag_arg)
+                .arg(Arg::with_name("note").long("note").takes_value(true))
+                .arg(
+                    Arg::with_name("coupon")
+                        .long("coupon")
+                        .takes_value(true)
+                        .required_if("asset-type", "bond"),
+                )
+                .arg(
+                    Arg::with_name("maturity")
+                        .long("maturity")
+                        .takes_value(true)
+                        .required_if("asset-type", "bond"),
+                )
+                .arg(
+                    Arg::with_name("frequency")
+                        .long("frequency")
+                        .takes_value(true)
+                        .possible_values(&["1", "2", "3", "4", "6", "12"])
+                        .default_value("2"),
+                )
+                .arg(
+                    Arg::with_name("face-value")
+                        .long("face-value")
+                        .takes_value(true)
+                        .default_value("100"),
+                )
+                .arg(Arg::with_name("price").long("price").takes_value(true))
+                .arg(
+                    Arg::with_name("underlying")
+                        .long("underlying")
+                        .validator(valid_symbol)
+                        .takes_value(true)
+                        .required_if("asset-type", "option"),
+                )
+                .arg(
+                    Arg::with_name("kind")
+                        .long("kind")
+                        .takes_value(true)
+                        .possible_values(&["call", "put"])
+                        .required_if("asset-type", "option"),
+                )
+                .arg(
+                    Arg::with_name("strike")
+                        .long("strike")
+                        .takes_value(true)
+                        .required_if("asset-type", "option"),
+                )
+                .arg(
+                    Arg::with_name("expiry")
+                        .long("expiry")
+                        .takes_value(true)
+                        .required_if("asset-type", "option"),
+                )
+                .arg(
+                    Arg::with_name("multiplier")
+                        .long("multiplier")
+                        .takes_value(true)
+                        .default_value("100"),
+                )
+                .arg(
+                    Arg::with_name("volatility")
+                        .long("volatility")
+                        .takes_value(true)
+                        .default_value("30"),
+                )
+                .arg(
+                    Arg::with_name("risk-free")
+                        .long("risk-free")
+                        .takes_value(true)
+                        .default_value("0"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("base-currency").arg(Arg::with_name("currency").required(true)),
+        )
+        .subcommand(SubCommand::with_name("positions").arg(&tag_arg))
+        .subcommand(
+            SubCommand::with_name("value").arg(&tag_arg).arg(
+                Arg::with_name("expiry-warning")
+                    .long("expiry-warning")
+                    .takes_value(true)
+                    .default_value("7"),
+            ),
+        )
+        .subcommand(SubCommand::with_name("history").arg(&tag_arg))
+        .subcommand(SubCommand::with_name("cash"))
+        .subcommand(
+            SubCommand::with_name("rebalance")
+                .arg(
+                    Arg::with_name("cash")
+                        .long("cash")
+                        .takes_value(true)
+                        .default_value("0"),
+                )
+                .arg(
+                    Arg::with_name("min-trade")
+                        .long("min-trade")
+                        .takes_value(true)
+                        .default_value("0"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("allocation")
+                .arg(
+                    Arg::with_name("by")
+                        .long("by")
+                        .takes_value(true)
+                        .possible_values(DIMENSIONS),
+                )
+                .arg(&tag_arg),
+        )
+        .subcommand(
+            SubCommand::with_name("target")
+                .arg(
+                    Arg::with_name("dimension")
+                        .required(true)
+                        .possible_values(&[
+                            "symbol",
+                            "asset-class",
+                            "sector",
+                            "geography",
+                            "currency",
+                            "tag",
+                            "account",
+                        ]),
+                )
+                .arg(Arg::with_name("name").required(true))
+                .arg(Arg::with_name("weight")),
+        )
+        .subcommand(
+            SubCommand::with_name("compare")
+                .arg(&tag_arg)
+                .arg(
+                    Arg::with_name("benchmark")
+                        .long("benchmark")
+                        .validator(valid_symbol)
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("risk-free")
+                        .long("risk-free")
+                        .takes_value(true)
+                        .default_value("0"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("correlations").arg(
+                Arg::with_name("benchmark")
+                    .long("benchmark")
+                    .validator(valid_symbol)
+                    .takes_value(true),
+            ),
+        )
+        .subcommand(
+            SubCommand::with_name("attribution")
+                .arg(&tag_arg)
+                .arg(
+                    Arg::with_name("period")
+                        .long("period")
+                        .takes_value(true)
+                        .default_value("1y"),
+                )
+                .arg(
+                    Arg::with_name("benchmark")
+                        .long("benchmark")
+                        .validator(valid_symbol)
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("risk").arg(
+                Arg::with_name("limit")
+                    .long("limit")
+                    .takes_value(true)
+                    .default_value("20"),
+            ),
+        )
+        .subcommand(
+            SubCommand::with_name("movers")
+                .arg(
+                    Arg::with_name("window")
+                        .long("window")
+                        .takes_value(true)
+                        .possible_values(&["day", "week", "month"])
+                        .default_value("day"),
+                )
+                .arg(
+                    Arg::with_name("limit")
+                        .long("limit")
+                        .takes_value(true)
+                        .default_value("5"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("screen")
+                .arg(Arg::with_name("golden-cross").long("golden-cross"))
+                .arg(Arg::with_name("death-cross").long("death-cross"))
+                .arg(Arg::with_name("rsi-oversold").long("rsi-oversold"))
+                .group(
+                    ArgGroup::with_name("condition")
+                        .args(&["golden-cross", "death-cross", "rsi-oversold"])
+                        .multiple(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("fast")
+                        .long("fast")
+                        .takes_value(true)
+                        .default_value("50"),
+                )
+                .arg(
+                    Arg::with_name("slow")
+                        .long("slow")
+                        .takes_value(true)
+                        .default_value("200"),
+                )
+                .arg(
+                    Arg::with_name("within")
+                        .long("within")
+                        .takes_value(true)
+                        .default_value("5"),
+                )
+                .arg(
+                    Arg::with_name("rsi")
+                        .long("rsi")
+                        .takes_value(true)
+                        .default_value("14"),
+                )
+                .arg(
+                    Arg::with_name("threshold")
+                        .long("threshold")
+                        .takes_value(true)
+                        .default_value("30"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("simulate-dca")
+                .arg(&symbol_arg)
+                .arg(
+                    Arg::with_name("amount")
+                        .long("amount")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("every")
+                        .long("every")
+                        .takes_value(true)
+                        .possible_values(&["week", "fortnight", "month", "quarter"])
+                        .default_value("month"),
+                )
+                .arg(
+                    Arg::with_name("since")
+                        .long("since")
+                        .takes_value(true)
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("backtest")
+                .arg(
+                    Arg::with_name("symbol")
+                        .validator(valid_symbol)
+                        .required(true)
+                        .multiple(true),
+                )
+                .arg(
+                    Arg::with_name("strategy")
+                        .long("strategy")
+                        .takes_value(true)
+                        .possible_values(&["buy-and-hold", "rebalance", "sma-cross"])
+                        .default_value("rebalance"),
+                )
+                .arg(
+                    Arg::with_name("every")
+                        .long("every")
+                        .takes_value(true)
+                        .possible_values(&["week", "month"])
+                        .default_value("month"),
+                )
+                .arg(
+                    Arg::with_name("fast")
+                        .long("fast")
+                        .takes_value(true)
+                        .default_value("50"),
+                )
+                .arg(
+                    Arg::with_name("slow")
+                        .long("slow")
+                        .takes_value(true)
+                        .default_value("200"),
+                )
+                .arg(
+                    Arg::with_name("from")
+                        .long("from")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(Arg::with_name("to").long("to").takes_value(true))
+                .arg(
+                    Arg::with_name("cash")
+                        .long("cash")
+                        .takes_value(true)
+                        .default_value("10000"),
+                )
+                .arg(
+                    Arg::with_name("commission")
+                        .long("commission")
+                        .takes_value(true)
+                        .default_value("0"),
+                )
+                .arg(
+                    Arg::with_name("slippage")
+                        .long("slippage")
+                        .takes_value(true)
+                        .default_value("0"),
+                )
+                .arg(
+                    Arg::with_name("risk-free")
+                        .long("risk-free")
+                        .takes_value(true)
+                        .default_value("0"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("performance")
+                .arg(Arg::with_name("symbol").validator(valid_symbol))
+                .arg(&tag_arg)
+                .arg(
+                    Arg::with_name("risk-free")
+                        .long("risk-free")
+                        .takes_value(true)
+                        .default_value("0"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("gains")
+                .arg(&tag_arg)
+                .arg(
+                    Arg::with_name("year")
+                        .long("year")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("method")
+                        .long("method")
+                        .takes_value(true)
+                        .possible_values(&["fifo", "lifo", "average", "specific"])
+                        .default_value("fifo"),
+                )
+                .arg(
+                    Arg::with_name("year-start")
+                        .long("year-start")
+                        .takes_value(true)
+                        .validator(|value| match value.parse::<u32>() {
+                            Ok(1..=12) => Ok(()),
+                            _ => Err("must be a month from 1 to 12".to_string()),
+                        }),
+                )
+                .arg(
+                    Arg::with_name("tax")
+                        .long("tax")
+                        .takes_value(true)
+                        .possible_values(&["us", "uk"]),
+                )
+                .arg(
+                    Arg::with_name("short-rate")
+                        .long("short-rate")
+                        .takes_value(true)
+                        .requires("tax"),
+                )
+                .arg(
+                    Arg::with_name("long-rate")
+                        .long("long-rate")
+                        .takes_value(true)
+                        .requires("tax"),
+                )
+                .arg(
+                    Arg::with_name("allowance")
+                        .long("allowance")
+                        .takes_value(true)
+                        .requires("tax"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("watch")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("add").arg(
+                        Arg::with_name("symbol")
+                            .validator(valid_symbol)
+                            .required(true)
+                            .multiple(true),
+                    ),
+                )
+                .subcommand(
+                    SubCommand::with_name("remove").arg(
+                        Arg::with_name("symbol")
+                            .validator(valid_symbol)
+                            .required(true)
+                            .multiple(true),
+                    ),
+                )
+                .subcommand(SubCommand::with_name("list"))
+                .subcommand(SubCommand::with_name("quotes")),
+        )
+        .subcommand(
+            SubCommand::with_name("alert")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("add")
+                        .arg(&symbol_arg)
+                        .arg(Arg::with_name("above").long("above").takes_value(true))
+                        .arg(Arg::with_name("below").long("below").takes_value(true))
+                        .arg(Arg::with_name("move").long("move").takes_value(true))
+                        .arg(Arg::with_name("52w-high").long("52w-high"))
+                        .arg(Arg::with_name("52w-low").long("52w-low"))
+                        .arg(
+                            Arg::with_name("webhook")
+                                .long("webhook")
+                                .takes_value(true)
+                                .multiple(true)
+                                .number_of_values(1),
+                        )
+                        .arg(Arg::with_name("desktop").long("desktop"))
+                        .group(
+                            ArgGroup::with_name("condition")
+                                .args(&["above", "below", "move", "52w-high", "52w-low"])
+                                .required(true),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("remove").arg(Arg::with_name("id").required(true)),
+                )
+                .subcommand(SubCommand::with_name("list")),
+        )
+        .subcommand(SubCommand::with_name("check-alerts"))
+        .subcommand(
+            SubCommand::with_name("serve")
+                .arg(
+                    Arg::with_name("port")
+                        .long("port")
+                        .takes_value(true)
+                        .default_value("8080"),
+                )
+                .arg(
+                    Arg::with_name("bind")
+                        .long("bind")
+                        .takes_value(true)
+                        .default_value("127.0.0.1"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("daemon").arg(
+                Arg::with_name("interval")
+                    .long("interval")
+                    .takes_value(true)
+                    .default_value("15m"),
+            ),
+        )
+        .subcommand(
+            SubCommand::with_name("refresh").arg(
+                Arg::with_name("concurrency")
+                    .long("concurrency")
+                    .takes_value(true)
+                    .default_value("4"),
+            ),
+        )
+        .subcommand(SubCommand::with_name("sync-splits"))
+        .subcommand(
+            SubCommand::with_name("drip")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("add").arg(
+                        Arg::with_name("symbol")
+                            .validator(valid_symbol)
+                            .required(true)
+                            .multiple(true),
+                    ),
+                )
+                .subcommand(
+                    SubCommand::with_name("remove").arg(
+                        Arg::with_name("symbol")
+                            .validator(valid_symbol)
+                            .required(true)
+                            .multiple(true),
+                    ),
+                )
+                .subcommand(SubCommand::with_name("list"))
+                .subcommand(SubCommand::with_name("sync")),
+        )
+        .subcommand(
+            SubCommand::with_name("import")
+                .arg(
+                    Arg::with_name("format")
+                        .long("format")
+                        .takes_value(true)
+                        .possible_values(&["generic", "commsec", "ibkr"])
+                        .default_value("generic"),
+                )
+                .arg(Arg::with_name("file").required(true))
+                .arg(&tag_arg)
+                .arg(
+                    Arg::with_name("identifiers")
+                        .long("identifiers")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("map")
+                        .long("map")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("completions")
+                .arg(
+                    Arg::with_name("shell")
+                        .possible_values(&clap::Shell::variants())
+                        .required_unless("symbols"),
+                )
+                .arg(
+                    Arg::with_name("bin-name")
+                        .long("bin-name")
+                        .takes_value(true)
+                        .default_value("portfolio"),
+                )
+                .arg(Arg::with_name("symbols").long("symbols")),
+        )
+}
+
+// subcommands whose positional arguments are symbols
+const SYMBOL_COMMANDS: &[&str] = &[
+    "latest-price",
+    "summary",
+    "series",
+    "indicator",
+    "chart",
+    "dividends",
+    "info",
+    "news",
+    "add-position",
+    "simulate-dca",
+    "backtest",
+    "performance",
+    "watch",
+    "alert",
+];
+
+// clap's scripts only know the static arguments, so symbols are completed by
+// calling back into `completions --symbols` where the shell makes that easy
+fn completions(shell: clap::Shell, bin_name: &str) -> String {
+    let mut script = vec![];
+    app().gen_completions_to(bin_name, shell, &mut script);
+    let mut script = String::from_utf8(script).unwrap();
+    let function = format!("_{}_symbols", bin_name.replace('-', "_"));
+    let commands = SYMBOL_COMMANDS.join(" ");
+
+    match shell {
+        clap::Shell::Bash => {
+            script.push_str(&format!(
+                r#"
+{function}() {{
+    _{name} "$@"
+    local cur="${{COMP_WORDS[COMP_CWORD]}}"
+    case " {commands} " in
+        *" ${{COMP_WORDS[1]}} "*) ;;
+        *) return 0 ;;
+    esac
+    if [[ ${{cur}} == -* ]]; then
+        return 0
+    fi
+    local replies=()
+    for reply in "${{COMPREPLY[@]}}"; do
+        [[ ${{reply}} == \<*\> ]] || replies+=("${{reply}}")
+    done
+    if [[ ${{#replies[@]}} -eq 0 ]]; then
+        COMPREPLY=( $(compgen -W "$({bin} completions --symbols 2>/dev/null)" -- "${{cur}}") )
+    else
+        COMPREPLY=("${{replies[@]}}")
+    fi
+}}
+
+complete -F {function} -o bashdefault -o default {bin}
+"#,
+                function = function,
+                name = bin_name,
+                commands = commands,
+                bin = bin_name,
+            ));
+        }
+        clap::Shell::Zsh => {
+            script = script.replace("symbol:_files'", &format!("symbol:{}'", function));
+            script = script.replacen(
+                "autoload -U is-at-least\n",
+                &format!(
+                    r#"autoload -U is-at-least
+
+{function}() {{
+    local -a symbols
+    symbols=(${{(f)"$({bin} completions --symbols 2>/dev/null)"}})
+    _describe 'symbol' symbols
+}}
+"#,
+                    function = function,
+                    bin = bin_name,
+                ),
+                1,
+            );
+        }
+        clap::Shell::Fish => script.push_str(&format!(
+            "complete -c {bin} -n \"__fish_seen_subcommand_from {commands}\" -f -a \"({bin} completions --symbols 2>/dev/null)\"\n",
+            bin = bin_name,
+            commands = commands,
+        )),
+        _ => {}
+    }
+    script
+}
+
+pub fn print_completions(matches: &ArgMatches) {
+    if matches.is_present("symbols") {
+        for symbol in stored_symbols(matches) {
+            std::println!("{}", &*symbol);
+        }
+        return;
+    }
+    let shell = matches.value_of("shell").unwrap().parse().or_exit();
+    let bin_name = matches.value_of("bin-name").unwrap();
+    std::print!("{}", completions(shell, bin_name));
+}
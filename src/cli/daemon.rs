@@ -0,0 +1,132 @@
+use std::collections::HashSet;
+use std::thread;
+use std::time::Instant;
+
+use clap::ArgMatches;
+use serde::Serialize;
+
+use crate::{
+    alerts_path, alpha_vantage_builder, cache, load_portfolio, output, parse_interval,
+    progress_bar, rate_limiter, report_progress, stored_symbols, watchlist_path, OrExit, Output,
+};
+
+// refreshes through a write-only cache so that other commands read fresh
+// responses, then checks alerts against the cache
+fn refresh_and_check(
+    matches: &ArgMatches,
+    refresh_client: &portfolio::PortfolioClient,
+    client: &portfolio::PortfolioClient,
+    notified: &mut HashSet<(u32, chrono::NaiveDate)>,
+) -> Result<(), portfolio::ApiError> {
+    let portfolio = load_portfolio(matches);
+    let valuation = portfolio.valuation(refresh_client)?;
+
+    let watchlist = portfolio::Watchlist::load(watchlist_path(matches)).or_exit();
+    let watched: Vec<portfolio::Symbol> = watchlist
+        .symbols()
+        .filter(|symbol| {
+            !portfolio
+                .positions()
+                .iter()
+                .any(|position| &position.symbol == *symbol)
+        })
+        .cloned()
+        .collect();
+    refresh_client.quotes(&watched)?;
+
+    let alerts = portfolio::Alerts::load(alerts_path(matches)).or_exit();
+    let triggered = alerts.check(client)?;
+    let now = chrono::Local::now();
+    let json = output(matches) == Output::Json;
+    if json {
+        // one line per refresh, so the output can be streamed
+        #[derive(Serialize)]
+        struct Refresh<'a> {
+            time: chrono::DateTime<chrono::Local>,
+            positions: usize,
+            watched: usize,
+            triggered: &'a [portfolio::TriggeredAlert],
+        }
+        std::println!(
+            "{}",
+            serde_json::to_string(&Refresh {
+                time: now,
+                positions: valuation.positions.len(),
+                watched: watched.len(),
+                triggered: &triggered,
+            })
+            .unwrap()
+        );
+    } else {
+        println!(
+            "{} refreshed {} positions and {} watched symbols, {} alerts triggered",
+            now.format("%Y-%m-%d %H:%M:%S"),
+            valuation.positions.len(),
+            watched.len(),
+            triggered.len()
+        );
+    }
+
+    // each rule notifies at most once a day
+    for alert in &triggered {
+        if !notified.insert((alert.rule.id, now.date_naive())) {
+            continue;
+        }
+        if !json {
+            println!("  {}", alert.message());
+        }
+        for notifier in &alert.rule.notify {
+            if let Err(err) = notifier.notify(alert) {
+                eprintln!("Failed to notify for alert {}: {:?}", alert.rule.id, err);
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn refresh(matches: &ArgMatches) {
+    let concurrency: usize = matches.value_of("concurrency").unwrap().parse().or_exit();
+    let client = alpha_vantage_builder()
+        .cache(cache().write_only())
+        .max_concurrency(concurrency)
+        .build()
+        .or_exit();
+
+    let symbols: Vec<portfolio::Symbol> = stored_symbols(matches).into_iter().collect();
+    let bar = progress_bar();
+    let results = client.refresh_all(&symbols, |progress| {
+        if let Some(err) = progress.error {
+            bar.suspend(|| eprintln!("Skipping {}: {}", &**progress.symbol, err));
+        }
+        report_progress(&bar, progress);
+    });
+    bar.finish_and_clear();
+    let refreshed = results.iter().filter(|result| result.is_ok()).count();
+    println!("Refreshed {} of {} symbols", refreshed, symbols.len());
+}
+
+pub fn daemon(matches: &ArgMatches) {
+    let interval = parse_interval(matches.value_of("interval").unwrap());
+
+    // both clients share one rate limiter so together they stay within the tier
+    let rate_limiter = rate_limiter();
+    let refresh_client = alpha_vantage_builder()
+        .cache(cache().write_only())
+        .rate_limiter(rate_limiter.clone())
+        .build()
+        .or_exit();
+    let client = alpha_vantage_builder()
+        .cache(cache())
+        .rate_limiter(rate_limiter)
+        .build()
+        .or_exit();
+
+    let mut notified = HashSet::new();
+    loop {
+        let started = Instant::now();
+        if let Err(err) = refresh_and_check(matches, &refresh_client, &client, &mut notified) {
+            eprintln!("Refresh failed: {}", err);
+        }
+        thread::sleep(interval.saturating_sub(started.elapsed()));
+    }
+}
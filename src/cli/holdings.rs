@@ -0,0 +1,675 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::Ordering;
+
+use chrono::Datelike;
+use clap::ArgMatches;
+use portfolio::money::to_f64;
+use portfolio::render::{self, Align, Cell, Table};
+use serde::Serialize;
+
+use crate::{
+    alpha_vantage_client, chart_file, load_ledger, load_portfolio, output, portfolio_path,
+    print_csv, print_freshness, print_json, provider, tags, OrExit, Output, QUIET,
+};
+
+#[derive(Serialize)]
+struct ValuationRow<'a> {
+    symbol: &'a str,
+    currency: &'a str,
+    quantity: portfolio::Quantity,
+    cost_basis: portfolio::Money,
+    price: f64,
+    market_value: f64,
+    fx_rate: f64,
+    base_market_value: f64,
+    base_day_change: f64,
+    base_unrealised_pnl: f64,
+}
+
+// each option's value split into intrinsic and extrinsic, per contract, and its
+// Greeks per contract
+fn options_table(
+    options: &[(&portfolio::PositionValuation, &portfolio::OptionContract)],
+    today: chrono::NaiveDate,
+) -> Table {
+    let mut table = Table::new(&[
+        ("OPTION", Align::Left),
+        ("UNDERLYING", Align::Left),
+        ("STRIKE", Align::Right),
+        ("EXPIRY", Align::Left),
+        ("DAYS", Align::Right),
+        ("INTRINSIC", Align::Right),
+        ("EXTRINSIC", Align::Right),
+        ("DELTA", Align::Right),
+        ("GAMMA", Align::Right),
+        ("THETA", Align::Right),
+        ("VEGA", Align::Right),
+    ]);
+    for (position, option) in options {
+        let local = render::currency_symbol(&position.position.currency);
+        let money = |value: Option<f64>| match value {
+            Some(value) => Cell::new(render::money(value, local)),
+            None => Cell::empty(),
+        };
+        let greeks = position
+            .underlying_price
+            .map(|spot| option.greeks(spot, today));
+        let greek = |value: fn(&portfolio::Greeks) -> f64, decimals| match &greeks {
+            Some(greeks) => Cell::new(render::number(value(greeks) * option.multiplier, decimals)),
+            None => Cell::empty(),
+        };
+        table.row(vec![
+            Cell::new(&*position.position.symbol),
+            Cell::new(format!(
+                "{} {}",
+                &*option.underlying,
+                match option.kind {
+                    portfolio::OptionKind::Call => "call",
+                    portfolio::OptionKind::Put => "put",
+                }
+            )),
+            Cell::new(render::money(option.strike, local)),
+            Cell::new(option.expiry.to_string()),
+            Cell::new(option.days_to_expiry(today).max(0).to_string()),
+            money(position.intrinsic_value()),
+            money(position.extrinsic_value()),
+            greek(|greeks| greeks.delta, 2),
+            greek(|greeks| greeks.gamma, 4),
+            greek(|greeks| greeks.theta, 2),
+            greek(|greeks| greeks.vega, 2),
+        ]);
+    }
+    table
+}
+
+pub fn add_position(matches: &ArgMatches) {
+    let path = portfolio_path(matches);
+    let mut portfolio = portfolio::Portfolio::load(&path).or_exit();
+
+    let purchase_date = match matches.value_of("date") {
+        Some(date) => date.parse().or_exit(),
+        None => chrono::Local::now().date_naive(),
+    };
+    let mut position = portfolio::Position::new(
+        matches.value_of("symbol").unwrap(),
+        matches.value_of("quantity").unwrap().parse().or_exit(),
+        matches.value_of("cost-basis").unwrap().parse().or_exit(),
+        purchase_date,
+    );
+    position.asset_type = matches.value_of("asset-type").unwrap().parse().or_exit();
+    if let Some(currency) = matches.value_of("currency") {
+        position.currency = currency.to_uppercase();
+    }
+    position.sector = matches.value_of("sector").map(str::to_string);
+    position.country = matches.value_of("country").map(str::to_string);
+    position.tags = tags(matches).unwrap_or_default();
+    position.account = matches.value_of("account").map(str::to_string);
+    position.note = matches.value_of("note").map(str::to_string);
+    if position.asset_type == portfolio::AssetType::Bond {
+        position.bond = Some(
+            portfolio::Bond::new(
+                matches.value_of("coupon").unwrap().parse().or_exit(),
+                matches.value_of("frequency").unwrap().parse().or_exit(),
+                matches.value_of("maturity").unwrap().parse().or_exit(),
+                matches.value_of("face-value").unwrap().parse().or_exit(),
+                matches
+                    .value_of("price")
+                    .map(|price| price.parse().or_exit()),
+            )
+            .or_exit(),
+        );
+    }
+    if position.asset_type == portfolio::AssetType::Option {
+        position.option = Some(portfolio::OptionContract {
+            underlying: matches
+                .value_of("underlying")
+                .unwrap()
+                .to_uppercase()
+                .into(),
+            kind: matches.value_of("kind").unwrap().parse().or_exit(),
+            strike: matches.value_of("strike").unwrap().parse().or_exit(),
+            expiry: matches.value_of("expiry").unwrap().parse().or_exit(),
+            multiplier: matches.value_of("multiplier").unwrap().parse().or_exit(),
+            volatility: matches.value_of("volatility").unwrap().parse().or_exit(),
+            risk_free_rate: matches.value_of("risk-free").unwrap().parse().or_exit(),
+        });
+    }
+    if output(matches) == Output::Json {
+        print_json(&position);
+    }
+    portfolio.add_position(position);
+
+    portfolio.save(&path).or_exit();
+}
+
+pub fn base_currency(matches: &ArgMatches) {
+    let path = portfolio_path(matches);
+    let mut portfolio = portfolio::Portfolio::load(&path).or_exit();
+
+    let base_currency = matches.value_of("currency").unwrap().to_uppercase();
+    if output(matches) == Output::Json {
+        #[derive(Serialize)]
+        struct BaseCurrency<'a> {
+            base_currency: &'a str,
+        }
+        print_json(&BaseCurrency {
+            base_currency: &base_currency,
+        });
+    }
+    portfolio.set_base_currency(base_currency);
+
+    portfolio.save(&path).or_exit();
+}
+
+pub fn positions(matches: &ArgMatches) {
+    let portfolio = load_portfolio(matches);
+
+    match output(matches) {
+        Output::Table => {
+            let mut table = Table::new(&[
+                ("SYMBOL", Align::Left),
+                ("QUANTITY", Align::Right),
+                ("COST", Align::Right),
+                ("PURCHASED", Align::Left),
+                ("ACCOUNT", Align::Left),
+                ("TAGS", Align::Left),
+            ]);
+            for position in portfolio.positions() {
+                table.row(vec![
+                    Cell::new(&*position.symbol),
+                    Cell::new(render::number(to_f64(position.quantity), 2)),
+                    Cell::new(render::money(
+                        to_f64(position.cost_basis),
+                        render::currency_symbol(&position.currency),
+                    )),
+                    Cell::new(position.purchase_date.to_string()),
+                    Cell::new(position.account.as_deref().unwrap_or("")),
+                    Cell::new(position.tags.join(", ")),
+                ]);
+            }
+            print!("{}", table);
+        }
+        Output::Csv => {
+            // csv has no lists, so tags are separated by semicolons as on import
+            #[derive(Serialize)]
+            struct Row<'a> {
+                symbol: &'a str,
+                quantity: portfolio::Quantity,
+                cost_basis: portfolio::Money,
+                purchase_date: chrono::NaiveDate,
+                asset_type: portfolio::AssetType,
+                currency: &'a str,
+                sector: Option<&'a str>,
+                country: Option<&'a str>,
+                account: Option<&'a str>,
+                tags: String,
+                note: Option<&'a str>,
+            }
+            print_csv(portfolio.positions().iter().map(|position| Row {
+                symbol: &position.symbol,
+                quantity: position.quantity,
+                cost_basis: position.cost_basis,
+                purchase_date: position.purchase_date,
+                asset_type: position.asset_type,
+                currency: &position.currency,
+                sector: position.sector.as_deref(),
+                country: position.country.as_deref(),
+                account: position.account.as_deref(),
+                tags: position.tags.join(";"),
+                note: position.note.as_deref(),
+            }))
+        }
+        Output::Json => print_json(portfolio.positions()),
+    }
+}
+
+pub fn value(matches: &ArgMatches) {
+    let portfolio = load_portfolio(matches);
+    let valuation = portfolio.valuation(&*provider(matches)).or_exit();
+    let warning_days: i64 = matches
+        .value_of("expiry-warning")
+        .unwrap()
+        .parse()
+        .or_exit();
+    let today = portfolio::calendar::today();
+    let options: Vec<(&portfolio::PositionValuation, &portfolio::OptionContract)> = valuation
+        .positions
+        .iter()
+        .filter_map(|position| Some((position, position.position.option.as_ref()?)))
+        .collect();
+
+    match output(matches) {
+        Output::Table => {
+            let base = render::currency_symbol(&valuation.base_currency);
+            let base_value = format!("VALUE ({})", valuation.base_currency);
+            let mut table = Table::new(&[
+                ("SYMBOL", Align::Left),
+                ("QUANTITY", Align::Right),
+                ("PRICE", Align::Right),
+                ("VALUE", Align::Right),
+                (&base_value, Align::Right),
+                ("COST", Align::Right),
+                ("DAY CHANGE", Align::Right),
+                ("P&L", Align::Right),
+                ("P&L %", Align::Right),
+            ]);
+            for position in &valuation.positions {
+                let local = render::currency_symbol(&position.position.currency);
+                let pnl = position.base_unrealised_pnl();
+                table.row(vec![
+                    Cell::new(&*position.position.symbol),
+                    Cell::new(render::number(to_f64(position.position.quantity), 2)),
+                    Cell::new(render::money(position.price, local)),
+                    Cell::new(render::money(position.market_value(), local)),
+                    Cell::new(render::money(position.base_market_value(), base)),
+                    Cell::new(render::money(position.base_cost_basis(), base)),
+                    Cell::change(
+                        position.base_day_change(),
+                        render::signed_money(position.base_day_change(), base),
+                    ),
+                    Cell::change(pnl, render::signed_money(pnl, base)),
+                    Cell::change(pnl, render::percent(position.unrealised_pnl_percent())),
+                ]);
+            }
+            let pnl = valuation.unrealised_pnl();
+            table.row(vec![
+                Cell::new("TOTAL"),
+                Cell::empty(),
+                Cell::empty(),
+                Cell::empty(),
+                Cell::new(render::money(valuation.market_value(), base)),
+                Cell::new(render::money(valuation.cost_basis(), base)),
+                Cell::change(
+                    valuation.day_change(),
+                    render::signed_money(valuation.day_change(), base),
+                ),
+                Cell::change(pnl, render::signed_money(pnl, base)),
+                Cell::change(pnl, render::percent(valuation.unrealised_pnl_percent())),
+            ]);
+            print!("{}", table);
+            if !options.is_empty() {
+                println!();
+                print!("{}", options_table(&options, today));
+            }
+            print_freshness(valuation.prices_as_of, valuation.retrieved_at);
+        }
+        Output::Csv => print_csv(valuation.positions.iter().map(|position| ValuationRow {
+            symbol: &position.position.symbol,
+            currency: &position.position.currency,
+            quantity: position.position.quantity,
+            cost_basis: position.position.cost_basis,
+            price: position.price,
+            market_value: position.market_value(),
+            fx_rate: position.fx_rate,
+            base_market_value: position.base_market_value(),
+            base_day_change: position.base_day_change(),
+            base_unrealised_pnl: position.base_unrealised_pnl(),
+        })),
+        Output::Json => print_json(&valuation),
+    }
+    if !QUIET.load(Ordering::Relaxed) {
+        for (position, option) in &options {
+            let days = option.days_to_expiry(today);
+            if days < 0 {
+                eprintln!(
+                    "Warning: {} expired on {}",
+                    &*position.position.symbol, option.expiry
+                );
+            } else if days <= warning_days {
+                eprintln!(
+                    "Warning: {} expires in {} days, on {}",
+                    &*position.position.symbol, days, option.expiry
+                );
+            }
+        }
+    }
+}
+
+pub fn allocation(matches: &ArgMatches) {
+    let portfolio = load_portfolio(matches);
+    let valuation = portfolio.valuation(&*provider(matches)).or_exit();
+    // a chart file shows a single dimension, asset class unless `--by` says otherwise
+    let dimensions = match matches.value_of("by") {
+        Some(dimension) => vec![dimension.parse().or_exit()],
+        None if chart_file(matches).is_some() => vec![portfolio::Dimension::AssetClass],
+        None => portfolio::Dimension::ALL.to_vec(),
+    };
+    let overviews = if dimensions.iter().any(|dimension| {
+        matches!(
+            dimension,
+            portfolio::Dimension::Sector | portfolio::Dimension::Geography
+        )
+    }) {
+        portfolio::fetch_overviews(&alpha_vantage_client(), &valuation)
+    } else {
+        Default::default()
+    };
+    let allocations: Vec<portfolio::Allocation> = dimensions
+        .into_iter()
+        .map(|dimension| {
+            portfolio::Allocation::new(&valuation, &overviews, dimension, portfolio.targets())
+        })
+        .collect();
+
+    if let Some(path) = chart_file(matches) {
+        #[cfg(feature = "charts")]
+        portfolio::charts::allocation_chart(path, &allocations[0]).or_exit();
+        #[cfg(not(feature = "charts"))]
+        crate::charts_disabled(path);
+        return;
+    }
+
+    match output(matches) {
+        Output::Table => {
+            let base = render::currency_symbol(&valuation.base_currency);
+            for (idx, allocation) in allocations.iter().enumerate() {
+                if idx > 0 {
+                    println!();
+                }
+                let mut table = Table::new(&[
+                    (allocation.dimension.label(), Align::Left),
+                    ("VALUE", Align::Right),
+                    ("WEIGHT", Align::Right),
+                    ("TARGET", Align::Right),
+                    ("DELTA", Align::Right),
+                ]);
+                for weight in &allocation.weights {
+                    table.row(vec![
+                        Cell::new(weight.name.as_str()),
+                        Cell::new(render::money(weight.market_value, base)),
+                        Cell::new(format!("{}%", render::number(weight.weight, 2))),
+                        match weight.target {
+                            Some(target) => Cell::new(format!("{}%", render::number(target, 2))),
+                            None => Cell::empty(),
+                        },
+                        match weight.delta() {
+                            Some(delta) => Cell::new(render::percent(delta)),
+                            None => Cell::empty(),
+                        },
+                    ]);
+                }
+                print!("{}", table);
+            }
+        }
+        Output::Csv => {
+            #[derive(Serialize)]
+            struct Row<'a> {
+                dimension: portfolio::Dimension,
+                name: &'a str,
+                market_value: f64,
+                weight: f64,
+                target: Option<f64>,
+                delta: Option<f64>,
+            }
+            print_csv(allocations.iter().flat_map(|allocation| {
+                allocation.weights.iter().map(move |weight| Row {
+                    dimension: allocation.dimension,
+                    name: &weight.name,
+                    market_value: weight.market_value,
+                    weight: weight.weight,
+                    target: weight.target,
+                    delta: weight.delta(),
+                })
+            }))
+        }
+        Output::Json => print_json(&allocations),
+    }
+}
+
+pub fn rebalance(matches: &ArgMatches) {
+    let cash = matches.value_of("cash").unwrap().parse().or_exit();
+    let min_trade_size = matches.value_of("min-trade").unwrap().parse().or_exit();
+
+    let portfolio = load_portfolio(matches);
+    let provider = provider(matches);
+    let valuation = portfolio.valuation(&*provider).or_exit();
+
+    let mut prices = HashMap::new();
+    for symbol in portfolio.targets().symbol.keys() {
+        let symbol = portfolio::Symbol::new(symbol.as_str());
+        let held = valuation
+            .positions
+            .iter()
+            .any(|position| position.position.symbol == symbol);
+        if !held {
+            let quote = provider.quote(symbol.clone()).or_exit();
+            prices.insert(symbol, quote.price);
+        }
+    }
+    let rebalance = portfolio::rebalance(
+        &valuation,
+        portfolio.targets(),
+        &prices,
+        cash,
+        min_trade_size,
+    );
+
+    match output(matches) {
+        Output::Table => {
+            let base = render::currency_symbol(&valuation.base_currency);
+            let mut table = Table::new(&[
+                ("SYMBOL", Align::Left),
+                ("ACTION", Align::Left),
+                ("QUANTITY", Align::Right),
+                ("PRICE", Align::Right),
+                ("VALUE", Align::Right),
+                ("CURRENT", Align::Right),
+                ("TARGET", Align::Right),
+            ]);
+            for trade in &rebalance.trades {
+                table.row(vec![
+                    Cell::new(&*trade.symbol),
+                    Cell::change(trade.quantity, if trade.is_buy() { "BUY" } else { "SELL" }),
+                    Cell::new(render::number(trade.quantity.abs(), 2)),
+                    Cell::new(render::money(trade.price * trade.fx_rate, base)),
+                    Cell::new(render::money(trade.value().abs(), base)),
+                    Cell::new(render::money(trade.current_value, base)),
+                    Cell::new(render::money(trade.target_value, base)),
+                ]);
+            }
+            print!("{}", table);
+            println!(
+                "Remaining cash: {}",
+                render::money(rebalance.remaining_cash(), base)
+            );
+        }
+        Output::Csv => {
+            #[derive(Serialize)]
+            struct Row<'a> {
+                symbol: &'a str,
+                quantity: f64,
+                price: f64,
+                fx_rate: f64,
+                value: f64,
+                current_value: f64,
+                target_value: f64,
+            }
+            print_csv(rebalance.trades.iter().map(|trade| Row {
+                symbol: &trade.symbol,
+                quantity: trade.quantity,
+                price: trade.price,
+                fx_rate: trade.fx_rate,
+                value: trade.value(),
+                current_value: trade.current_value,
+                target_value: trade.target_value,
+            }))
+        }
+        Output::Json => print_json(&rebalance),
+    }
+}
+
+pub fn target(matches: &ArgMatches) {
+    let path = portfolio_path(matches);
+    let mut portfolio = portfolio::Portfolio::load(&path).or_exit();
+
+    let name = matches.value_of("name").unwrap().to_string();
+    let targets = match matches.value_of("dimension").unwrap() {
+        "symbol" => &mut portfolio.targets_mut().symbol,
+        dimension => dimension
+            .parse::<portfolio::Dimension>()
+            .or_exit()
+            .targets_mut(portfolio.targets_mut()),
+    };
+    match matches.value_of("weight") {
+        Some(weight) => {
+            targets.insert(name, weight.trim_end_matches('%').parse().or_exit());
+        }
+        None => {
+            targets.remove(&name);
+        }
+    }
+    if output(matches) == Output::Json {
+        print_json(portfolio.targets());
+    }
+
+    portfolio.save(&path).or_exit();
+}
+
+pub fn cash(matches: &ArgMatches) {
+    let ledger = load_ledger(matches);
+    let balances = ledger.cash_balances();
+
+    match output(matches) {
+        Output::Table => {
+            let mut table = Table::new(&[("CURRENCY", Align::Left), ("BALANCE", Align::Right)]);
+            for (currency, balance) in &balances {
+                table.row(vec![
+                    Cell::new(currency.as_str()),
+                    Cell::new(render::money(
+                        to_f64(*balance),
+                        render::currency_symbol(currency),
+                    )),
+                ]);
+            }
+            print!("{}", table);
+        }
+        Output::Csv => {
+            #[derive(Serialize)]
+            struct Row<'a> {
+                currency: &'a str,
+                balance: portfolio::Money,
+            }
+            let rows: Vec<Row> = balances
+                .iter()
+                .map(|(currency, balance)| Row {
+                    currency,
+                    balance: *balance,
+                })
+                .collect();
+            print_csv(&rows);
+        }
+        Output::Json => print_json(&balances),
+    }
+}
+
+pub fn income_forecast(matches: &ArgMatches) {
+    let today = portfolio::calendar::today();
+    let start = today.with_day(1).unwrap() + chrono::Months::new(1);
+    let provider = provider(matches);
+    let valuation = load_portfolio(matches).valuation(&*provider).or_exit();
+    let client = alpha_vantage_client();
+
+    let mut held: BTreeMap<portfolio::Symbol, (f64, f64, Option<&portfolio::Bond>)> =
+        BTreeMap::new();
+    for position in &valuation.positions {
+        if matches!(
+            position.position.asset_type,
+            portfolio::AssetType::Equity | portfolio::AssetType::Etf | portfolio::AssetType::Bond
+        ) {
+            let (quantity, fx_rate, bond) = held
+                .entry(position.position.symbol.clone())
+                .or_insert((0.0, position.fx_rate, None));
+            *quantity += to_f64(position.position.quantity);
+            *fx_rate = position.fx_rate;
+            *bond = position.position.bond.as_ref();
+        }
+    }
+    let holdings: Vec<_> = held
+        .into_iter()
+        .map(|(symbol, (quantity, fx_rate, bond))| {
+            let in_base = |payment: portfolio::DividendPayment| portfolio::DividendPayment {
+                amount: payment.amount * fx_rate,
+                ..payment
+            };
+            if let Some(bond) = bond {
+                let end = start + chrono::Months::new(12);
+                let coupons = bond.coupons(start, end).into_iter().map(in_base).collect();
+                return (symbol, quantity, coupons);
+            }
+            let series = provider
+                .daily_series(symbol.clone(), portfolio::OutputSize::Full)
+                .or_exit();
+            // ETFs have no overview, so their history alone is projected
+            let forward_rate = client
+                .company_overview(symbol.clone())
+                .ok()
+                .and_then(|overview| overview.dividend_per_share);
+            let payments = portfolio::projected_dividends(&series.dividends(), forward_rate, start)
+                .into_iter()
+                .map(in_base)
+                .collect();
+            (symbol, quantity, payments)
+        })
+        .collect();
+    let forecast = portfolio::IncomeForecast::new(start, holdings);
+    let currency = render::currency_symbol(&valuation.base_currency);
+
+    match output(matches) {
+        Output::Table => {
+            let labels: Vec<String> = forecast
+                .months
+                .iter()
+                .map(|month| month.format("%b").to_string())
+                .collect();
+            let mut columns = vec![("SYMBOL", Align::Left)];
+            columns.extend(labels.iter().map(|label| (label.as_str(), Align::Right)));
+            columns.push(("TOTAL", Align::Right));
+            let mut table = Table::new(&columns);
+            let income = |amount: f64| {
+                if amount > 0.0 {
+                    Cell::new(render::money(amount, currency))
+                } else {
+                    Cell::empty()
+                }
+            };
+            for holding in &forecast.holdings {
+                let mut cells = vec![Cell::new(&*holding.symbol)];
+                cells.extend(holding.monthly.iter().map(|amount| income(*amount)));
+                cells.push(Cell::new(render::money(holding.total, currency)));
+                table.row(cells);
+            }
+            let mut cells = vec![Cell::new("Total")];
+            cells.extend(forecast.monthly.iter().map(|amount| income(*amount)));
+            cells.push(Cell::new(render::money(forecast.total, currency)));
+            table.row(cells);
+            print!("{}", table);
+            if let (Some(first), Some(last)) = (forecast.months.first(), forecast.months.last()) {
+                println!(
+                    "Period: {} to {}",
+                    first.format("%B %Y"),
+                    last.format("%B %Y")
+                );
+            }
+        }
+        Output::Csv => {
+            #[derive(Serialize)]
+            struct Row<'a> {
+                symbol: &'a str,
+                month: chrono::NaiveDate,
+                income: f64,
+            }
+            print_csv(forecast.holdings.iter().flat_map(|holding| {
+                forecast
+                    .months
+                    .iter()
+                    .zip(&holding.monthly)
+                    .map(move |(month, income)| Row {
+                        symbol: &holding.symbol,
+                        month: *month,
+                        income: *income,
+                    })
+            }))
+        }
+        Output::Json => print_json(&forecast),
+    }
+}
@@ -0,0 +1,576 @@
+use std::path::PathBuf;
+use std::sync::atomic::Ordering;
+
+use clap::ArgMatches;
+use portfolio::money::to_f64;
+use portfolio::render::{self, Align, Cell, Table};
+use portfolio::{indicators, metrics};
+use serde::Serialize;
+
+use crate::{
+    chart_file, common_currency, config, exit, fetch_equity_curve, held_prices, ledger_path,
+    load_ledger, load_portfolio, metrics_table, output, period_start, print_csv, print_json,
+    provider, tags, OrExit, Output, EXIT_NO_DATA, EXIT_USAGE, OFFLINE,
+};
+
+// replaces ISINs and CUSIPs with tickers, from `--map`, the identifiers
+// file and then OpenFIGI, saving any found, and exits if some can't be
+fn resolve_identifiers(matches: &ArgMatches, transactions: &mut [portfolio::Transaction]) {
+    let path = match matches.value_of("identifiers") {
+        Some(path) => PathBuf::from(path),
+        None => portfolio::default_identifiers_path().or_exit(),
+    };
+    let mut identifiers = portfolio::IdentifierMap::load(&path).or_exit();
+    let known = identifiers.iter().count();
+    let mut changed = false;
+    for mapping in matches.values_of("map").into_iter().flatten() {
+        let (identifier, symbol) = mapping
+            .split_once('=')
+            .unwrap_or_else(|| exit(EXIT_USAGE, "--map must look like <isin>=<symbol>"));
+        let identifier = portfolio::Identifier::parse(identifier).unwrap_or_else(|| {
+            exit(
+                EXIT_USAGE,
+                &format!("`{}` isn't an ISIN or CUSIP", identifier),
+            )
+        });
+        identifiers.insert(&identifier, symbol.parse().or_exit());
+        changed = true;
+    }
+
+    let openfigi = match &config().api_keys.openfigi {
+        Some(api_key) => portfolio::OpenFigiClient::with_api_key(api_key),
+        None => portfolio::OpenFigiClient::new(),
+    };
+    let resolver: Option<&dyn portfolio::SymbolResolver> = match OFFLINE.load(Ordering::Relaxed) {
+        true => None,
+        false => Some(&openfigi),
+    };
+    let unresolved = identifiers.resolve(transactions, resolver).or_exit();
+    if changed || identifiers.iter().count() != known {
+        identifiers.save(&path).or_exit();
+    }
+    if !unresolved.is_empty() {
+        let unresolved: Vec<String> = unresolved
+            .iter()
+            .map(|identifier| identifier.to_string())
+            .collect();
+        exit(
+            EXIT_NO_DATA,
+            &format!(
+                "Couldn't find the ticker of {}; give it with --map <identifier>=<symbol>",
+                unresolved.join(", ")
+            ),
+        );
+    }
+}
+
+pub fn history(matches: &ArgMatches) {
+    let ledger = load_ledger(matches);
+    let curve = fetch_equity_curve(&ledger, &*provider(matches));
+
+    if let Some(path) = chart_file(matches) {
+        #[cfg(feature = "charts")]
+        portfolio::charts::equity_curve_chart(path, &curve).or_exit();
+        #[cfg(not(feature = "charts"))]
+        crate::charts_disabled(path);
+        return;
+    }
+
+    match output(matches) {
+        Output::Table => {
+            let mut table = Table::new(&[
+                ("DATE", Align::Left),
+                ("VALUE", Align::Right),
+                ("COST", Align::Right),
+                ("P&L", Align::Right),
+                ("NET DEPOSITS", Align::Right),
+            ]);
+            let currency = common_currency(
+                ledger
+                    .transactions()
+                    .iter()
+                    .filter_map(|transaction| transaction.currency()),
+            );
+            for (date, point) in &curve.days {
+                let pnl = point.market_value - point.cost_basis;
+                table.row(vec![
+                    Cell::new(date.to_string()),
+                    Cell::new(render::money(point.market_value, currency)),
+                    Cell::new(render::money(point.cost_basis, currency)),
+                    Cell::change(pnl, render::signed_money(pnl, currency)),
+                    Cell::new(render::money(point.net_deposits, currency)),
+                ]);
+            }
+            print!("{}", table);
+        }
+        Output::Csv => {
+            #[derive(Serialize)]
+            struct Row {
+                date: chrono::NaiveDate,
+                market_value: f64,
+                cost_basis: f64,
+                net_deposits: f64,
+                realised_gains: f64,
+                dividends: f64,
+                net_flow: f64,
+            }
+            print_csv(curve.days.iter().map(|(date, point)| Row {
+                date: *date,
+                market_value: point.market_value,
+                cost_basis: point.cost_basis,
+                net_deposits: point.net_deposits,
+                realised_gains: point.realised_gains,
+                dividends: point.dividends,
+                net_flow: point.net_flow,
+            }))
+        }
+        Output::Json => print_json(&curve),
+    }
+}
+
+pub fn gains(matches: &ArgMatches) {
+    let year = matches.value_of("year").unwrap().parse().or_exit();
+    let method = matches.value_of("method").unwrap().parse().or_exit();
+    let start_month: Option<u32> = matches
+        .value_of("year-start")
+        .map(|month| month.parse().or_exit());
+
+    // a preset's rates, allowance and tax year can each be overridden
+    let rules = matches.value_of("tax").map(|tax| {
+        let mut rules: portfolio::Jurisdiction = tax.parse().or_exit();
+        if let Some(rate) = matches.value_of("short-rate") {
+            rules.short_term_rate = rate.parse().or_exit();
+        }
+        if let Some(rate) = matches.value_of("long-rate") {
+            rules.long_term_rate = rate.parse().or_exit();
+        }
+        if let Some(allowance) = matches.value_of("allowance") {
+            rules.allowance = allowance.parse().or_exit();
+        }
+        if let Some(month) = start_month {
+            rules.year_start = (month, 1);
+        }
+        rules
+    });
+    let year_start = match &rules {
+        Some(rules) => portfolio::TaxRules::year_start(rules),
+        None => (start_month.unwrap_or(1), 1),
+    };
+
+    let ledger = load_ledger(matches);
+    let report =
+        portfolio::GainsReport::for_year(ledger.realised_lots(method).or_exit(), year, year_start)
+            .unwrap_or_else(|err| exit(EXIT_USAGE, &err.to_string()));
+    let long_term = |gain: &portfolio::RealisedGain| match &rules {
+        Some(rules) => portfolio::TaxRules::long_term(rules, gain),
+        None => gain.long_term(),
+    };
+    let estimate = rules
+        .as_ref()
+        .map(|rules| portfolio::estimate_tax(&report, rules).or_exit());
+
+    match output(matches) {
+        Output::Table => {
+            let mut table = Table::new(&[
+                ("SYMBOL", Align::Left),
+                ("ACQUIRED", Align::Left),
+                ("SOLD", Align::Left),
+                ("QUANTITY", Align::Right),
+                ("PROCEEDS", Align::Right),
+                ("COST", Align::Right),
+                ("GAIN", Align::Right),
+                ("TERM", Align::Left),
+                ("", Align::Left),
+            ]);
+            let currency = common_currency(report.gains.iter().map(|gain| gain.currency.as_str()));
+            for gain in &report.gains {
+                let row_currency = render::currency_symbol(&gain.currency);
+                table.row(vec![
+                    Cell::new(&*gain.symbol),
+                    Cell::new(gain.acquired.to_string()),
+                    Cell::new(gain.sold.to_string()),
+                    Cell::new(render::number(to_f64(gain.quantity), 2)),
+                    Cell::new(render::money(to_f64(gain.proceeds), row_currency)),
+                    Cell::new(render::money(to_f64(gain.cost_basis), row_currency)),
+                    Cell::change(
+                        to_f64(gain.gain()),
+                        render::signed_money(to_f64(gain.gain()), row_currency),
+                    ),
+                    Cell::new(if long_term(gain) { "long" } else { "short" }),
+                    if gain.wash_sale {
+                        Cell::new("wash sale")
+                    } else {
+                        Cell::empty()
+                    },
+                ]);
+            }
+            table.row(vec![
+                Cell::new("TOTAL"),
+                Cell::new(report.start_date.to_string()),
+                Cell::new(report.end_date.to_string()),
+                Cell::empty(),
+                Cell::new(render::money(to_f64(report.proceeds()), currency)),
+                Cell::new(render::money(to_f64(report.cost_basis()), currency)),
+                Cell::change(
+                    to_f64(report.total()),
+                    render::signed_money(to_f64(report.total()), currency),
+                ),
+                Cell::empty(),
+                Cell::empty(),
+            ]);
+            print!("{}", table);
+            let wash_sales: Vec<&portfolio::RealisedGain> = report.wash_sales().collect();
+            if !wash_sales.is_empty() {
+                let losses: portfolio::Money = wash_sales.iter().map(|gain| -gain.gain()).sum();
+                println!(
+                    "{} wash sales: {} of losses repurchased within 30 days",
+                    wash_sales.len(),
+                    render::money(to_f64(losses), currency)
+                );
+            }
+            match &estimate {
+                Some(estimate) => {
+                    println!(
+                        "Short term: {}  Long term: {}",
+                        render::signed_money(to_f64(estimate.short_term_gains), currency),
+                        render::signed_money(to_f64(estimate.long_term_gains), currency)
+                    );
+                    println!(
+                        "Estimated {} tax: {} (allowance used {}, taxable {} short term and {} long term)",
+                        estimate.jurisdiction,
+                        render::money(to_f64(estimate.tax), currency),
+                        render::money(to_f64(estimate.allowance_used), currency),
+                        render::money(to_f64(estimate.taxable_short_term), currency),
+                        render::money(to_f64(estimate.taxable_long_term), currency)
+                    );
+                    if !estimate.disallowed_losses.is_zero() {
+                        println!(
+                            "Disallowed wash sale losses: {}",
+                            render::money(to_f64(estimate.disallowed_losses), currency)
+                        );
+                    }
+                }
+                None => println!(
+                    "Short term: {}  Long term: {}",
+                    render::signed_money(to_f64(report.short_term()), currency),
+                    render::signed_money(to_f64(report.long_term()), currency)
+                ),
+            }
+        }
+        Output::Csv => {
+            #[derive(Serialize)]
+            struct Row<'a> {
+                symbol: &'a str,
+                acquired: chrono::NaiveDate,
+                sold: chrono::NaiveDate,
+                quantity: portfolio::Quantity,
+                currency: &'a str,
+                proceeds: portfolio::Money,
+                cost_basis: portfolio::Money,
+                gain: portfolio::Money,
+                long_term: bool,
+                wash_sale: bool,
+            }
+            print_csv(report.gains.iter().map(|gain| Row {
+                symbol: &gain.symbol,
+                acquired: gain.acquired,
+                sold: gain.sold,
+                quantity: gain.quantity,
+                currency: &gain.currency,
+                proceeds: gain.proceeds,
+                cost_basis: gain.cost_basis,
+                gain: gain.gain(),
+                long_term: long_term(gain),
+                wash_sale: gain.wash_sale,
+            }))
+        }
+        Output::Json => {
+            #[derive(Serialize)]
+            struct Report<'a> {
+                #[serde(flatten)]
+                report: &'a portfolio::GainsReport,
+                #[serde(skip_serializing_if = "Option::is_none")]
+                tax: Option<portfolio::TaxEstimate>,
+            }
+            print_json(&Report {
+                report: &report,
+                tax: estimate,
+            })
+        }
+    }
+}
+
+pub fn performance(matches: &ArgMatches) {
+    let risk_free_rate = matches.value_of("risk-free").unwrap().parse().or_exit();
+
+    let (name, metrics) = match matches.value_of("symbol") {
+        Some(symbol) => {
+            let series = provider(matches)
+                .daily_series(symbol.into(), portfolio::OutputSize::Full)
+                .or_exit();
+            (
+                symbol.to_string(),
+                metrics::compute(&indicators::adjusted_closes(&series), risk_free_rate),
+            )
+        }
+        None => {
+            let ledger = load_ledger(matches);
+            let curve = fetch_equity_curve(&ledger, &*provider(matches));
+            (
+                "Portfolio".to_string(),
+                metrics::for_equity_curve(&curve, risk_free_rate),
+            )
+        }
+    };
+    let metrics = metrics.unwrap_or_else(|| exit(EXIT_NO_DATA, "Not enough history"));
+
+    match output(matches) {
+        Output::Table => print!("{}", metrics_table(&name, &metrics)),
+        Output::Csv => print_csv(&[metrics]),
+        Output::Json => print_json(&metrics),
+    }
+}
+
+pub fn compare(matches: &ArgMatches) {
+    let benchmark = matches.value_of("benchmark").unwrap();
+    let risk_free_rate = matches.value_of("risk-free").unwrap().parse().or_exit();
+    let provider = provider(matches);
+
+    let ledger = load_ledger(matches);
+    let curve = fetch_equity_curve(&ledger, &*provider);
+    let benchmark_series = provider
+        .daily_series(benchmark.into(), portfolio::OutputSize::Full)
+        .or_exit();
+
+    let comparison = metrics::compare(
+        &curve.time_weighted_index(),
+        &indicators::adjusted_closes(&benchmark_series),
+        risk_free_rate,
+    )
+    .unwrap_or_else(|| exit(EXIT_NO_DATA, "Not enough overlapping history"));
+
+    match output(matches) {
+        Output::Table => {
+            let mut table = Table::new(&[("", Align::Left), ("", Align::Right)]);
+            table.row(vec![
+                "Period".into(),
+                format!("{} to {}", comparison.start_date, comparison.end_date).into(),
+            ]);
+            table.row(vec![
+                "Portfolio".into(),
+                Cell::change(
+                    comparison.portfolio_return,
+                    render::percent(comparison.portfolio_return),
+                ),
+            ]);
+            table.row(vec![
+                benchmark.into(),
+                Cell::change(
+                    comparison.benchmark_return,
+                    render::percent(comparison.benchmark_return),
+                ),
+            ]);
+            table.row(vec![
+                "Relative".into(),
+                Cell::change(
+                    comparison.relative_return,
+                    render::percent(comparison.relative_return),
+                ),
+            ]);
+            table.row(vec![
+                "Alpha".into(),
+                Cell::change(comparison.alpha, render::percent(comparison.alpha)),
+            ]);
+            table.row(vec![
+                "Beta".into(),
+                render::number(comparison.beta, 2).into(),
+            ]);
+            print!("{}", table);
+        }
+        Output::Csv => print_csv(&[comparison]),
+        Output::Json => print_json(&comparison),
+    }
+}
+
+pub fn attribution(matches: &ArgMatches) {
+    let start = period_start(
+        matches.value_of("period").unwrap(),
+        portfolio::calendar::today(),
+    );
+    let provider = provider(matches);
+    let valuation = load_portfolio(matches).valuation(&*provider).or_exit();
+    let prices = held_prices(&*provider, &valuation);
+    let benchmark = matches.value_of("benchmark").map(|benchmark| {
+        let series = provider
+            .daily_series(benchmark.to_uppercase().into(), portfolio::OutputSize::Full)
+            .or_exit();
+        (
+            portfolio::Symbol::from(benchmark.to_uppercase()),
+            indicators::adjusted_closes(&series),
+        )
+    });
+    let attribution = portfolio::attribution(
+        &prices,
+        &valuation.weights(),
+        start,
+        benchmark
+            .as_ref()
+            .map(|(symbol, prices)| (symbol.clone(), prices)),
+    )
+    .unwrap_or_else(|| exit(EXIT_NO_DATA, "No prices for that period"));
+
+    let percent = |value: Option<f64>| match value {
+        Some(value) => Cell::change(value, render::percent(value)),
+        None => Cell::empty(),
+    };
+    match output(matches) {
+        Output::Table => {
+            let mut columns = vec![
+                ("SYMBOL", Align::Left),
+                ("WEIGHT", Align::Right),
+                ("RETURN", Align::Right),
+                ("CONTRIBUTION", Align::Right),
+            ];
+            if attribution.benchmark_return.is_some() {
+                columns.push(("RELATIVE", Align::Right));
+            }
+            let mut table = Table::new(&columns);
+            let row =
+                |cells: Vec<Cell>| -> Vec<Cell> { cells.into_iter().take(columns.len()).collect() };
+            for holding in &attribution.holdings {
+                table.row(row(vec![
+                    Cell::new(&*holding.symbol),
+                    Cell::new(format!("{}%", render::number(holding.weight * 100.0, 2))),
+                    percent(holding.holding_return),
+                    percent(holding.contribution),
+                    percent(holding.relative_contribution),
+                ]));
+            }
+            table.row(row(vec![
+                Cell::new("Portfolio"),
+                Cell::empty(),
+                Cell::empty(),
+                percent(Some(attribution.portfolio_return)),
+                percent(attribution.relative_return),
+            ]));
+            print!("{}", table);
+            println!(
+                "Period: {} to {}",
+                attribution.start_date, attribution.end_date
+            );
+            if let (Some(benchmark), Some(benchmark_return)) =
+                (&attribution.benchmark, attribution.benchmark_return)
+            {
+                println!("{}: {}", &**benchmark, render::percent(benchmark_return));
+            }
+        }
+        Output::Csv => print_csv(&attribution.holdings),
+        Output::Json => print_json(&attribution),
+    }
+}
+
+pub fn import(matches: &ArgMatches) {
+    let format = matches.value_of("format").unwrap().parse().or_exit();
+    let mut transactions =
+        portfolio::import::csv::import_file(matches.value_of("file").unwrap(), format).or_exit();
+    resolve_identifiers(matches, &mut transactions);
+    for transaction in &mut transactions {
+        for tag in tags(matches).unwrap_or_default() {
+            transaction.add_tag(&tag);
+        }
+        if let Some(account) = matches.value_of("account") {
+            transaction.set_account(account);
+        }
+    }
+
+    let path = ledger_path(matches);
+    let mut ledger = portfolio::Ledger::load(&path).or_exit();
+    let count = transactions.len();
+    if output(matches) == Output::Json {
+        print_json(&transactions);
+    }
+    for transaction in transactions {
+        ledger.record(transaction);
+    }
+    ledger.save(&path).or_exit();
+
+    if output(matches) != Output::Json {
+        println!("Imported {} transactions", count);
+    }
+}
+
+pub fn sync_splits(matches: &ArgMatches) {
+    let path = ledger_path(matches);
+    let mut ledger = portfolio::Ledger::load(&path).or_exit();
+
+    let count = ledger.sync_splits(&*provider(matches)).or_exit();
+    ledger.save(&path).or_exit();
+
+    match output(matches) {
+        Output::Json => {
+            #[derive(Serialize)]
+            struct Synced {
+                splits: usize,
+            }
+            print_json(&Synced { splits: count });
+        }
+        _ => println!("Recorded {} splits", count),
+    }
+}
+
+pub fn drip(matches: &ArgMatches) {
+    let path = ledger_path(matches);
+    let mut ledger = portfolio::Ledger::load(&path).or_exit();
+
+    let symbols = |matches: &ArgMatches| -> Vec<portfolio::Symbol> {
+        matches
+            .values_of("symbol")
+            .unwrap()
+            .map(|symbol| symbol.to_uppercase().into())
+            .collect()
+    };
+    match matches.subcommand() {
+        (command @ ("add" | "remove"), Some(matches)) => {
+            for symbol in symbols(matches) {
+                ledger.set_reinvested(symbol, command == "add");
+            }
+            ledger.save(&path).or_exit();
+            if output(matches) == Output::Json {
+                print_json(ledger.reinvested());
+            }
+        }
+        ("list", Some(matches)) => match output(matches) {
+            Output::Table => {
+                for symbol in ledger.reinvested() {
+                    println!("{}", &**symbol);
+                }
+            }
+            Output::Csv => {
+                #[derive(Serialize)]
+                struct Row<'a> {
+                    symbol: &'a str,
+                }
+                print_csv(ledger.reinvested().iter().map(|symbol| Row { symbol }))
+            }
+            Output::Json => print_json(ledger.reinvested()),
+        },
+        ("sync", Some(matches)) => {
+            let count = ledger.sync_reinvestment(&*provider(matches)).or_exit();
+            ledger.save(&path).or_exit();
+
+            match output(matches) {
+                Output::Json => {
+                    #[derive(Serialize)]
+                    struct Synced {
+                        reinvestments: usize,
+                    }
+                    print_json(&Synced {
+                        reinvestments: count,
+                    });
+                }
+                _ => println!("Recorded {} reinvestments", count),
+            }
+        }
+        _ => unreachable!(),
+    }
+}
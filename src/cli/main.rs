@@ -0,0 +1,674 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::io::{self, IsTerminal};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use clap::ArgMatches;
+use portfolio::render::{self, Align, Cell, Table};
+use portfolio::{indicators, metrics};
+use serde::Serialize;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Output {
+    Table,
+    Csv,
+    Json,
+}
+
+static CONFIG: OnceLock<portfolio::Config> = OnceLock::new();
+static QUIET: AtomicBool = AtomicBool::new(false);
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+static RECORDING: OnceLock<portfolio::Recording> = OnceLock::new();
+static TIER: OnceLock<portfolio::ApiTier> = OnceLock::new();
+
+// `--quiet` silences tables and messages but not requested csv or json output,
+// which is printed with `std::println!`
+macro_rules! println {
+    ($($arg:tt)*) => {
+        if !$crate::QUIET.load(std::sync::atomic::Ordering::Relaxed) {
+            std::println!($($arg)*)
+        }
+    };
+}
+macro_rules! print {
+    ($($arg:tt)*) => {
+        if !$crate::QUIET.load(std::sync::atomic::Ordering::Relaxed) {
+            std::print!($($arg)*)
+        }
+    };
+}
+
+// after the macros, which are only in scope below their definitions
+mod alerts;
+mod analysis;
+mod app;
+mod daemon;
+mod holdings;
+mod ledger;
+mod market;
+mod prometheus;
+mod serve;
+#[cfg(feature = "streaming")]
+mod stream;
+
+// the config file, overridden by environment variables and then by flags
+fn config() -> &'static portfolio::Config {
+    CONFIG.get().expect("config is loaded before commands run")
+}
+
+// a rate limiter for the Alpha Vantage plan in `--tier` or the config
+fn rate_limiter() -> Arc<portfolio::RateLimiter> {
+    let tier = TIER.get().copied().unwrap_or(portfolio::ApiTier::Free);
+    Arc::new(portfolio::RateLimiter::new(tier))
+}
+
+fn load_config(matches: &ArgMatches) -> portfolio::Config {
+    let path = match matches.value_of("config") {
+        Some(path) => PathBuf::from(path),
+        None => portfolio::default_config_path().or_exit(),
+    };
+    portfolio::Config::load(path).or_exit().with_env()
+}
+
+fn output(matches: &ArgMatches) -> Output {
+    if matches.is_present("json") {
+        return Output::Json;
+    }
+    match matches.value_of("output").or(config().output.as_deref()) {
+        Some("csv") => Output::Csv,
+        Some("json") => Output::Json,
+        _ => Output::Table,
+    }
+}
+
+fn chart_extension(path: &str) -> bool {
+    path.ends_with(".svg") || path.ends_with(".png")
+}
+
+// `--output chart.svg` writes a chart instead of printing
+fn chart_file(matches: &ArgMatches) -> Option<PathBuf> {
+    matches
+        .value_of("output")
+        .filter(|value| chart_extension(value))
+        .map(PathBuf::from)
+}
+
+// exit statuses, so that scripts and monitoring checks can tell failures apart
+const EXIT_ALERTS: i32 = 1;
+const EXIT_USAGE: i32 = 2;
+const EXIT_API: i32 = 3;
+const EXIT_NO_DATA: i32 = 4;
+const EXIT_FILE: i32 = 5;
+
+fn exit(code: i32, message: &str) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(code);
+}
+
+trait Failure: fmt::Display {
+    fn exit_code(&self) -> i32;
+}
+impl Failure for portfolio::ApiError {
+    fn exit_code(&self) -> i32 {
+        match self.kind() {
+            portfolio::ApiError::NoData
+            | portfolio::ApiError::InvalidSymbol(_)
+            | portfolio::ApiError::Offline(_) => EXIT_NO_DATA,
+            portfolio::ApiError::Recording(..) => EXIT_FILE,
+            _ => EXIT_API,
+        }
+    }
+}
+impl Failure for portfolio::StorageError {
+    fn exit_code(&self) -> i32 {
+        EXIT_FILE
+    }
+}
+impl Failure for portfolio::import::ImportError {
+    fn exit_code(&self) -> i32 {
+        EXIT_FILE
+    }
+}
+#[cfg(feature = "charts")]
+impl Failure for portfolio::charts::ChartError {
+    fn exit_code(&self) -> i32 {
+        match self {
+            portfolio::charts::ChartError::UnsupportedFormat(_) => EXIT_USAGE,
+            portfolio::charts::ChartError::NoData => EXIT_NO_DATA,
+            portfolio::charts::ChartError::Plot(_) => EXIT_FILE,
+        }
+    }
+}
+impl Failure for io::Error {
+    fn exit_code(&self) -> i32 {
+        EXIT_FILE
+    }
+}
+
+// arguments clap can't validate
+macro_rules! usage_failure {
+    ($($error:ty),*) => {
+        $(impl Failure for $error {
+            fn exit_code(&self) -> i32 {
+                EXIT_USAGE
+            }
+        })*
+    };
+}
+usage_failure!(
+    String,
+    std::num::ParseIntError,
+    std::num::ParseFloatError,
+    chrono::ParseError,
+    rust_decimal::Error,
+    portfolio::SymbolError
+);
+
+trait OrExit<T> {
+    fn or_exit(self) -> T;
+}
+impl<T, E: Failure> OrExit<T> for Result<T, E> {
+    fn or_exit(self) -> T {
+        self.unwrap_or_else(|err| exit(err.exit_code(), &err.to_string()))
+    }
+}
+
+#[cfg(not(feature = "charts"))]
+fn charts_disabled(_path: PathBuf) {
+    exit(EXIT_USAGE, "Chart files need the `charts` feature");
+}
+
+fn print_json<T: Serialize + ?Sized>(value: &T) {
+    std::println!("{}", serde_json::to_string_pretty(value).unwrap());
+}
+
+fn print_csv<T: Serialize, I: IntoIterator<Item = T>>(rows: I) {
+    let mut writer = csv::Writer::from_writer(io::stdout());
+    for row in rows {
+        writer.serialize(row).unwrap();
+    }
+    writer.flush().or_exit();
+}
+
+// under a table, e.g. "Prices as of 2024-03-08, retrieved 2h ago. The market is
+// open until 16:00 EST."
+fn print_freshness(
+    as_of: Option<chrono::NaiveDate>,
+    retrieved_at: Option<chrono::DateTime<chrono::Utc>>,
+) {
+    let as_of = match as_of {
+        Some(as_of) => as_of,
+        None => return,
+    };
+    let retrieved = retrieved_at
+        .and_then(|time| (chrono::Utc::now() - time).to_std().ok())
+        .map(|age| format!(", retrieved {} ago", render::age(age)))
+        .unwrap_or_default();
+    let market = match portfolio::calendar::market_status(chrono::Utc::now()) {
+        portfolio::calendar::MarketStatus::Open { closes } => {
+            format!("open until {}", closes.format("%H:%M %Z"))
+        }
+        portfolio::calendar::MarketStatus::Closed { opens } => {
+            format!("closed, opening {}", opens.format("%a %Y-%m-%d %H:%M %Z"))
+        }
+    };
+    println!(
+        "\nPrices as of {}{}. The market is {}.",
+        as_of, retrieved, market
+    );
+}
+
+fn print_quotes_freshness(quotes: &[portfolio::Quote]) {
+    print_freshness(
+        quotes.iter().map(|quote| quote.latest_trading_day).min(),
+        quotes.iter().filter_map(|quote| quote.retrieved_at).min(),
+    );
+}
+
+/// The currency symbol prices of `symbol` are quoted in, going by its exchange.
+fn symbol_currency(symbol: &str) -> &'static str {
+    render::currency_symbol(portfolio::Symbol::new(symbol).currency())
+}
+
+/// The symbol of the one currency the given codes share, or none when they
+/// are mixed and a total would add pence to dollars.
+fn common_currency<'a>(mut codes: impl Iterator<Item = &'a str>) -> &'a str {
+    match codes.next() {
+        Some(first) if codes.all(|code| code == first) => render::currency_symbol(first),
+        _ => "",
+    }
+}
+
+// adjusted daily closes of the valued equities and ETFs
+fn held_prices(
+    provider: &dyn portfolio::Provider,
+    valuation: &portfolio::Valuation,
+) -> BTreeMap<portfolio::Symbol, portfolio::backtest::Prices> {
+    let mut prices = BTreeMap::new();
+    for position in &valuation.positions {
+        let position = &position.position;
+        if !matches!(
+            position.asset_type,
+            portfolio::AssetType::Equity | portfolio::AssetType::Etf
+        ) || prices.contains_key(&position.symbol)
+        {
+            continue;
+        }
+        let series = provider
+            .daily_series(position.symbol.clone(), portfolio::OutputSize::Full)
+            .or_exit();
+        prices.insert(
+            position.symbol.clone(),
+            indicators::adjusted_closes(&series),
+        );
+    }
+    prices
+}
+
+fn metrics_table(name: &str, metrics: &metrics::Metrics) -> Table {
+    let mut table = Table::new(&[(name, Align::Left), ("", Align::Right)]);
+    table.row(vec![
+        "Period".into(),
+        format!("{} to {}", metrics.start_date, metrics.end_date).into(),
+    ]);
+    table.row(vec![
+        "Total return".into(),
+        Cell::change(metrics.total_return, render::percent(metrics.total_return)),
+    ]);
+    table.row(vec![
+        "CAGR".into(),
+        Cell::change(
+            metrics.annualised_return,
+            render::percent(metrics.annualised_return),
+        ),
+    ]);
+    if let Some(money_weighted_return) = metrics.money_weighted_return {
+        table.row(vec![
+            "Money-weighted".into(),
+            Cell::change(
+                money_weighted_return,
+                render::percent(money_weighted_return),
+            ),
+        ]);
+    }
+    table.row(vec![
+        "Max drawdown".into(),
+        format!("{}%", render::number(metrics.max_drawdown, 2)).into(),
+    ]);
+    table.row(vec![
+        "Volatility".into(),
+        format!("{}%", render::number(metrics.volatility, 2)).into(),
+    ]);
+    table.row(vec![
+        "Sharpe".into(),
+        render::number(metrics.sharpe_ratio, 2).into(),
+    ]);
+    table.row(vec![
+        "Sortino".into(),
+        render::number(metrics.sortino_ratio, 2).into(),
+    ]);
+    table
+}
+
+fn portfolio_path(matches: &ArgMatches) -> PathBuf {
+    match matches.value_of("portfolio") {
+        Some(path) => PathBuf::from(path),
+        None => portfolio::default_portfolio_path().or_exit(),
+    }
+}
+
+fn ledger_path(matches: &ArgMatches) -> PathBuf {
+    match matches.value_of("ledger") {
+        Some(path) => PathBuf::from(path),
+        None => portfolio::default_ledger_path().or_exit(),
+    }
+}
+
+fn watchlist_path(matches: &ArgMatches) -> PathBuf {
+    match matches.value_of("watchlist") {
+        Some(path) => PathBuf::from(path),
+        None => portfolio::default_watchlist_path().or_exit(),
+    }
+}
+
+fn alerts_path(matches: &ArgMatches) -> PathBuf {
+    match matches.value_of("alerts") {
+        Some(path) => PathBuf::from(path),
+        None => portfolio::default_alerts_path().or_exit(),
+    }
+}
+
+fn load_portfolio(matches: &ArgMatches) -> portfolio::Portfolio {
+    try_load_portfolio(matches).or_exit()
+}
+
+fn try_load_portfolio(
+    matches: &ArgMatches,
+) -> Result<portfolio::Portfolio, portfolio::StorageError> {
+    let mut portfolio = portfolio::Portfolio::load(portfolio_path(matches))?;
+    if let Some(base_currency) = &config().base_currency {
+        portfolio.set_base_currency(base_currency.to_uppercase());
+    }
+    let ledger = portfolio::Ledger::load(ledger_path(matches))?;
+    for position in ledger.positions() {
+        portfolio.add_position(position);
+    }
+    if let Some(account) = matches.value_of("account") {
+        portfolio.retain_account(account);
+    }
+    if let Some(tags) = tags(matches) {
+        portfolio.retain_tagged(&tags);
+    }
+    Ok(portfolio)
+}
+
+fn tags(matches: &ArgMatches) -> Option<Vec<String>> {
+    matches
+        .values_of("tag")
+        .map(|tags| tags.map(str::to_string).collect())
+}
+
+fn load_ledger(matches: &ArgMatches) -> portfolio::Ledger {
+    filter_ledger(
+        matches,
+        portfolio::Ledger::load(ledger_path(matches)).or_exit(),
+    )
+}
+
+// only the `--account`, and the holdings with the `--tag`s, if they're given
+fn filter_ledger(matches: &ArgMatches, ledger: portfolio::Ledger) -> portfolio::Ledger {
+    let ledger = match matches.value_of("account") {
+        Some(account) => ledger.for_account(account),
+        None => ledger,
+    };
+    match tags(matches) {
+        Some(tags) => ledger.tagged(&tags),
+        None => ledger,
+    }
+}
+
+// symbols in the portfolio, ledger and watchlist, for shell completion
+fn stored_symbols(matches: &ArgMatches) -> BTreeSet<portfolio::Symbol> {
+    let portfolio = load_portfolio(matches);
+    let watchlist = portfolio::Watchlist::load(watchlist_path(matches)).unwrap_or_default();
+    portfolio
+        .positions()
+        .iter()
+        .filter(|position| {
+            !matches!(
+                position.asset_type,
+                portfolio::AssetType::Cash | portfolio::AssetType::Bond
+            )
+        })
+        // an option's prices are its underlying's
+        .map(|position| match &position.option {
+            Some(option) => option.underlying.clone(),
+            None => position.symbol.clone(),
+        })
+        .chain(watchlist.symbols().cloned())
+        .collect()
+}
+
+#[cfg(not(feature = "finnhub"))]
+const PROVIDERS: &[&str] = &["alphavantage", "yahoo"];
+#[cfg(feature = "finnhub")]
+const PROVIDERS: &[&str] = &["alphavantage", "yahoo", "finnhub"];
+
+// the key is only needed once a request misses the cache
+fn alpha_vantage_builder() -> portfolio::PortfolioClientBuilder {
+    let builder = match &config().api_keys.alpha_vantage {
+        Some(api_key) => portfolio::PortfolioClient::builder(api_key.clone()),
+        None => portfolio::PortfolioClient::builder_from_env(),
+    };
+    let builder = builder
+        .offline(OFFLINE.load(Ordering::Relaxed))
+        .rate_limiter(rate_limiter());
+    match RECORDING.get() {
+        Some(recording) => builder.recording(recording.clone()),
+        None => builder,
+    }
+}
+
+// `-v` logs each request, cache lookup and rate limit wait to stderr, and `-vv`
+// also what's cached and recorded, and the HTTP libraries' own logs
+fn trace(verbosity: u64) {
+    use tracing_subscriber::filter::{LevelFilter, Targets};
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::util::SubscriberInitExt;
+
+    let targets = match verbosity {
+        0 => return,
+        1 => Targets::new().with_target("portfolio", LevelFilter::DEBUG),
+        _ => Targets::new()
+            .with_target("portfolio", LevelFilter::TRACE)
+            .with_default(LevelFilter::DEBUG),
+    };
+    tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_writer(io::stderr)
+                .with_ansi(std::env::var_os("NO_COLOR").is_none() && io::stderr().is_terminal()),
+        )
+        .with(targets)
+        .init();
+}
+
+// on stderr, unless it isn't a terminal or `--quiet` is given
+fn progress_bar() -> indicatif::ProgressBar {
+    if QUIET.load(Ordering::Relaxed) || !io::stderr().is_terminal() {
+        return indicatif::ProgressBar::hidden();
+    }
+    let style = indicatif::ProgressStyle::with_template("{bar:30} {pos}/{len} {msg} (ETA {eta})")
+        .expect("progress bar template is valid");
+    indicatif::ProgressBar::new(0).with_style(style)
+}
+
+fn report_progress(bar: &indicatif::ProgressBar, progress: portfolio::Progress) {
+    bar.set_length(progress.total as u64);
+    bar.set_position(progress.completed as u64);
+    bar.set_message(progress.symbol.to_string());
+}
+
+// with a progress bar while each symbol's history is fetched
+fn fetch_equity_curve(
+    ledger: &portfolio::Ledger,
+    provider: &dyn portfolio::Provider,
+) -> portfolio::EquityCurve {
+    let bar = progress_bar();
+    let curve = ledger
+        .fetch_equity_curve_with_progress(provider, |progress| report_progress(&bar, progress));
+    bar.finish_and_clear();
+    curve.or_exit()
+}
+
+fn cache() -> portfolio::Cache {
+    let dir = match &config().cache_dir {
+        Some(dir) => dir.clone(),
+        None => portfolio::Cache::default_dir().or_exit(),
+    };
+    portfolio::Cache::new(dir, Duration::from_secs(15 * 60))
+}
+
+fn alpha_vantage_client() -> portfolio::PortfolioClient {
+    alpha_vantage_builder().cache(cache()).build().or_exit()
+}
+
+// `30s`, `15m`, `1h` or a bare number of seconds
+fn parse_interval(interval: &str) -> Duration {
+    let (number, unit) = match interval.find(|c: char| !c.is_ascii_digit()) {
+        Some(idx) => interval.split_at(idx),
+        None => (interval, "s"),
+    };
+    let number: u64 = number
+        .parse()
+        .unwrap_or_else(|_err| exit(EXIT_USAGE, "interval must be a number of s, m or h"));
+    match unit {
+        "s" => Duration::from_secs(number),
+        "m" => Duration::from_secs(number * 60),
+        "h" => Duration::from_secs(number * 60 * 60),
+        unit => exit(EXIT_USAGE, &format!("unknown interval unit `{}`", unit)),
+    }
+}
+
+// `10d`, `6w`, `6m`, `1y` or `max`
+fn period_start(period: &str, today: chrono::NaiveDate) -> Option<chrono::NaiveDate> {
+    if period == "max" {
+        return None;
+    }
+    const MALFORMED: &str = "period must look like 10d, 6w, 6m or 1y";
+    let unit = period
+        .chars()
+        .next_back()
+        .unwrap_or_else(|| exit(EXIT_USAGE, MALFORMED));
+    let number: u32 = period[..period.len() - unit.len_utf8()]
+        .parse()
+        .unwrap_or_else(|_err| exit(EXIT_USAGE, MALFORMED));
+    let start = match unit {
+        'd' => today.checked_sub_signed(chrono::Duration::days(number.into())),
+        'w' => today.checked_sub_signed(chrono::Duration::weeks(number.into())),
+        'm' => today.checked_sub_months(chrono::Months::new(number)),
+        'y' => number
+            .checked_mul(12)
+            .and_then(|months| today.checked_sub_months(chrono::Months::new(months))),
+        unit => exit(EXIT_USAGE, &format!("unknown period unit `{}`", unit)),
+    };
+    Some(start.unwrap_or_else(|| exit(EXIT_USAGE, &format!("period `{}` is too long", period))))
+}
+
+fn provider(matches: &ArgMatches) -> Box<dyn portfolio::Provider> {
+    provider_with_cache(matches, cache())
+}
+
+fn provider_with_cache(
+    matches: &ArgMatches,
+    cache: portfolio::Cache,
+) -> Box<dyn portfolio::Provider> {
+    provider_with(matches, cache, rate_limiter())
+}
+
+fn provider_with(
+    matches: &ArgMatches,
+    cache: portfolio::Cache,
+    rate_limiter: Arc<portfolio::RateLimiter>,
+) -> Box<dyn portfolio::Provider> {
+    let kind = matches
+        .value_of("provider")
+        .or(config().provider.as_deref())
+        .unwrap_or("alphavantage")
+        .parse()
+        .or_exit();
+
+    if OFFLINE.load(Ordering::Relaxed) && kind != portfolio::ProviderKind::AlphaVantage {
+        exit(
+            EXIT_USAGE,
+            "only the alphavantage provider can answer from the cache offline",
+        );
+    }
+    if RECORDING.get().is_some() && kind != portfolio::ProviderKind::AlphaVantage {
+        exit(
+            EXIT_USAGE,
+            "only the alphavantage provider's responses can be recorded or replayed",
+        );
+    }
+    match kind {
+        portfolio::ProviderKind::AlphaVantage => Box::new(
+            alpha_vantage_builder()
+                .cache(cache)
+                .rate_limiter(rate_limiter)
+                .build()
+                .or_exit(),
+        ),
+        portfolio::ProviderKind::Yahoo => Box::new(portfolio::YahooClient::new()),
+        #[cfg(feature = "finnhub")]
+        portfolio::ProviderKind::Finnhub => Box::new(finnhub_client()),
+    }
+}
+
+#[cfg(feature = "finnhub")]
+fn finnhub_client() -> portfolio::FinnhubClient {
+    match &config().api_keys.finnhub {
+        Some(api_key) => portfolio::FinnhubClient::new(api_key.clone()),
+        None => portfolio::FinnhubClient::from_env(),
+    }
+}
+
+const DIMENSIONS: &[&str] = &[
+    "asset-class",
+    "sector",
+    "geography",
+    "currency",
+    "tag",
+    "account",
+];
+
+fn main() {
+    let matches = match app::app().get_matches_safe() {
+        Ok(matches) => matches,
+        Err(err) if err.use_stderr() => exit(EXIT_USAGE, &err.message),
+        Err(err) => err.exit(),
+    };
+    QUIET.store(matches.is_present("quiet"), Ordering::Relaxed);
+    OFFLINE.store(matches.is_present("offline"), Ordering::Relaxed);
+    trace(matches.occurrences_of("verbose"));
+    if let Some(dir) = matches.value_of("record") {
+        RECORDING.set(portfolio::Recording::record(dir)).ok();
+    } else if let Some(dir) = matches.value_of("replay") {
+        RECORDING.set(portfolio::Recording::replay(dir)).ok();
+    }
+
+    CONFIG.set(load_config(&matches)).unwrap();
+    if let Some(tier) = matches.value_of("tier").or(config().tier.as_deref()) {
+        TIER.set(tier.parse().or_exit()).ok();
+    }
+
+    match matches.subcommand() {
+        ("latest-price", Some(matches)) => market::latest_price(matches),
+        ("price-on", Some(matches)) => market::price_on(matches),
+        ("summary", Some(matches)) => market::summary(matches),
+        ("chart", Some(matches)) => market::chart(matches),
+        ("series", Some(matches)) => market::series(matches),
+        ("indicator", Some(matches)) => market::indicator(matches),
+        ("dividends", Some(matches)) => market::dividends(matches),
+        ("income-forecast", Some(matches)) => holdings::income_forecast(matches),
+        ("earnings-calendar", Some(matches)) => market::earnings_calendar(matches),
+        ("economy", Some(matches)) => market::economy(matches),
+        ("sectors", Some(matches)) => market::sectors(matches),
+        ("news", Some(matches)) => market::news(matches),
+        ("info", Some(matches)) => market::info(matches),
+        ("fx", Some(matches)) => market::fx(matches),
+        ("search", Some(matches)) => market::search(matches),
+        ("add-position", Some(matches)) => holdings::add_position(matches),
+        ("base-currency", Some(matches)) => holdings::base_currency(matches),
+        ("positions", Some(matches)) => holdings::positions(matches),
+        ("value", Some(matches)) => holdings::value(matches),
+        ("allocation", Some(matches)) => holdings::allocation(matches),
+        ("rebalance", Some(matches)) => holdings::rebalance(matches),
+        ("target", Some(matches)) => holdings::target(matches),
+        ("cash", Some(matches)) => holdings::cash(matches),
+        ("history", Some(matches)) => ledger::history(matches),
+        ("gains", Some(matches)) => ledger::gains(matches),
+        ("performance", Some(matches)) => ledger::performance(matches),
+        ("backtest", Some(matches)) => analysis::backtest(matches),
+        ("compare", Some(matches)) => ledger::compare(matches),
+        ("correlations", Some(matches)) => analysis::correlations(matches),
+        ("attribution", Some(matches)) => ledger::attribution(matches),
+        ("risk", Some(matches)) => analysis::risk(matches),
+        ("movers", Some(matches)) => market::movers(matches),
+        ("screen", Some(matches)) => analysis::screen(matches),
+        ("simulate-dca", Some(matches)) => analysis::simulate_dca(matches),
+        ("watch", Some(matches)) => market::watch(matches),
+        ("alert", Some(matches)) => alerts::alert(matches),
+        ("serve", Some(matches)) => serve::serve(matches),
+        ("check-alerts", Some(matches)) => alerts::check_alerts(matches),
+        ("refresh", Some(matches)) => daemon::refresh(matches),
+        ("daemon", Some(matches)) => daemon::daemon(matches),
+        ("sync-splits", Some(matches)) => ledger::sync_splits(matches),
+        ("drip", Some(matches)) => ledger::drip(matches),
+        ("import", Some(matches)) => ledger::import(matches),
+        ("completions", Some(matches)) => app::print_completions(matches),
+        (&_, _) => println!("Command not recognised"),
+    };
+}
@@ -0,0 +1,1014 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+use std::thread;
+use std::time::Instant;
+
+use clap::ArgMatches;
+use portfolio::indicators;
+use portfolio::render::{self, Align, Cell, Table};
+use serde::Serialize;
+
+use crate::{
+    alpha_vantage_client, cache, chart_file, exit, load_portfolio, output, parse_interval,
+    period_start, print_csv, print_json, print_quotes_freshness, provider, provider_with_cache,
+    stored_symbols, symbol_currency, watchlist_path, Failure, OrExit, Output, EXIT_NO_DATA,
+    EXIT_USAGE,
+};
+
+pub fn quotes_table(quotes: &[portfolio::Quote]) -> Table {
+    let mut table = Table::new(&[
+        ("SYMBOL", Align::Left),
+        ("PRICE", Align::Right),
+        ("CHANGE", Align::Right),
+        ("CHANGE %", Align::Right),
+    ]);
+    for quote in quotes {
+        let currency = symbol_currency(&quote.symbol);
+        table.row(vec![
+            Cell::new(quote.symbol.as_str()),
+            Cell::new(render::money(quote.price, currency)),
+            Cell::change(quote.change, render::signed_money(quote.change, currency)),
+            Cell::change(quote.change, render::percent(quote.change_percent)),
+        ]);
+    }
+    table
+}
+
+pub fn latest_price(matches: &ArgMatches) {
+    let symbols: Vec<portfolio::Symbol> = matches
+        .values_of("symbol")
+        .unwrap()
+        .map(|symbol| symbol.to_uppercase().into())
+        .collect();
+    #[cfg(feature = "streaming")]
+    if matches.is_present("stream") {
+        crate::stream::stream_quotes(matches, &symbols);
+        return;
+    }
+    let interval = matches.value_of("watch").map(parse_interval);
+
+    // refreshes skip the cache but still keep it warm for other commands
+    let provider = match interval {
+        Some(_interval) => provider_with_cache(matches, cache().write_only()),
+        None => provider(matches),
+    };
+    loop {
+        let started = Instant::now();
+        let quotes = match (provider.quotes(&symbols), interval) {
+            (Ok(quotes), _) => quotes,
+            (Err(err), Some(interval)) => {
+                eprintln!("Refresh failed: {}", err);
+                thread::sleep(interval.saturating_sub(started.elapsed()));
+                continue;
+            }
+            (Err(err), None) => exit(err.exit_code(), &err.to_string()),
+        };
+        match output(matches) {
+            Output::Table => {
+                if let Some(interval) = interval {
+                    // clear the screen like watch(1)
+                    print!("\x1b[2J\x1b[H");
+                    println!(
+                        "Every {}s: {}\n",
+                        interval.as_secs(),
+                        chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
+                    );
+                }
+                print!("{}", quotes_table(&quotes));
+                print_quotes_freshness(&quotes);
+            }
+            Output::Csv => print_csv(&quotes),
+            Output::Json => print_json(&quotes),
+        }
+
+        match interval {
+            Some(interval) => thread::sleep(interval.saturating_sub(started.elapsed())),
+            None => break,
+        }
+    }
+}
+
+pub fn price_on(matches: &ArgMatches) {
+    let symbol = matches.value_of("symbol").unwrap().to_uppercase();
+    let date = matches.value_of("date").unwrap().parse().or_exit();
+
+    let price = portfolio::price_on(&*provider(matches), symbol.into(), date).or_exit();
+
+    match output(matches) {
+        Output::Table => {
+            let mut columns = vec![("SYMBOL", Align::Left), ("DATE", Align::Left)];
+            if matches.is_present("ohlc") {
+                columns.extend([
+                    ("OPEN", Align::Right),
+                    ("HIGH", Align::Right),
+                    ("LOW", Align::Right),
+                ]);
+            }
+            columns.extend([("CLOSE", Align::Right), ("ADJ CLOSE", Align::Right)]);
+            let mut table = Table::new(&columns);
+
+            let currency = render::currency_symbol(price.symbol.currency());
+            let mut cells = vec![Cell::new(&*price.symbol), Cell::new(price.date.to_string())];
+            if matches.is_present("ohlc") {
+                cells.extend([
+                    Cell::new(render::money(price.day.open, currency)),
+                    Cell::new(render::money(price.day.high, currency)),
+                    Cell::new(render::money(price.day.low, currency)),
+                ]);
+            }
+            cells.extend([
+                Cell::new(render::money(price.day.close, currency)),
+                Cell::new(render::money(price.day.adjusted_close, currency)),
+            ]);
+            table.row(cells);
+            print!("{}", table);
+            if price.date != price.requested {
+                match portfolio::calendar::holiday(price.requested) {
+                    Some(holiday) => {
+                        println!("{} was a market holiday ({})", price.requested, holiday)
+                    }
+                    None => println!("{} wasn't a trading day", price.requested),
+                }
+            }
+        }
+        Output::Csv => {
+            #[derive(Serialize)]
+            struct Row<'a> {
+                symbol: &'a str,
+                requested: chrono::NaiveDate,
+                date: chrono::NaiveDate,
+                open: f64,
+                high: f64,
+                low: f64,
+                close: f64,
+                adjusted_close: f64,
+                volume: f64,
+            }
+            print_csv(&[Row {
+                symbol: &price.symbol,
+                requested: price.requested,
+                date: price.date,
+                open: price.day.open,
+                high: price.day.high,
+                low: price.day.low,
+                close: price.day.close,
+                adjusted_close: price.day.adjusted_close,
+                volume: price.day.volume,
+            }]);
+        }
+        Output::Json => print_json(&price),
+    }
+}
+
+pub fn summary(matches: &ArgMatches) {
+    let symbol = matches.value_of("symbol").unwrap();
+
+    let time_period = match matches.value_of("from") {
+        Some(from) => portfolio::TimePeriod::Range(
+            from.parse().or_exit(),
+            match matches.value_of("to") {
+                Some(to) => to.parse().or_exit(),
+                None => portfolio::calendar::today(),
+            },
+        ),
+        None => portfolio::TimePeriod::Year,
+    };
+
+    let summary = portfolio::summary(&*provider(matches), symbol.into(), time_period).or_exit();
+
+    match output(matches) {
+        Output::Table => {
+            let change = summary.change;
+            let currency = symbol_currency(symbol);
+            let mut table = Table::new(&[(symbol, Align::Left), ("", Align::Right)]);
+            table.row(vec![
+                "Period".into(),
+                format!("{} to {}", summary.start_date, summary.end_date).into(),
+            ]);
+            table.row(vec![
+                "Latest".into(),
+                render::money(summary.latest_price, currency).into(),
+            ]);
+            table.row(vec![
+                "Earliest".into(),
+                render::money(summary.earliest_price, currency).into(),
+            ]);
+            table.row(vec![
+                "High".into(),
+                render::money(summary.max_price, currency).into(),
+            ]);
+            table.row(vec![
+                "Low".into(),
+                render::money(summary.min_price, currency).into(),
+            ]);
+            table.row(vec![
+                "Change".into(),
+                Cell::change(change, render::signed_money(change, currency)),
+            ]);
+            table.row(vec![
+                "Change %".into(),
+                Cell::change(change, render::percent(summary.change_percent)),
+            ]);
+            table.row(vec![
+                "Annualised".into(),
+                Cell::change(
+                    summary.annualised_return,
+                    render::percent(summary.annualised_return),
+                ),
+            ]);
+            table.row(vec![
+                "Avg volume".into(),
+                render::number(summary.average_daily_volume, 0).into(),
+            ]);
+            if let (Some(high), Some(from_high)) =
+                (summary.fifty_two_week_high, summary.from_high_percent)
+            {
+                table.row(vec![
+                    "52w high".into(),
+                    render::money(high, currency).into(),
+                ]);
+                table.row(vec![
+                    "From high".into(),
+                    Cell::change(from_high, render::percent(from_high)),
+                ]);
+            }
+            if let (Some(low), Some(from_low)) =
+                (summary.fifty_two_week_low, summary.from_low_percent)
+            {
+                table.row(vec!["52w low".into(), render::money(low, currency).into()]);
+                table.row(vec![
+                    "From low".into(),
+                    Cell::change(from_low, render::percent(from_low)),
+                ]);
+            }
+            print!("{}", table);
+        }
+        Output::Csv => print_csv(&[summary]),
+        Output::Json => print_json(&summary),
+    }
+}
+
+pub fn chart(matches: &ArgMatches) {
+    let symbol = matches.value_of("symbol").unwrap();
+    let today = portfolio::calendar::today();
+    let start = period_start(matches.value_of("period").unwrap(), today);
+
+    let output_size = match start {
+        Some(start) if (today - start).num_days() <= 100 => portfolio::OutputSize::Compact,
+        _ => portfolio::OutputSize::Full,
+    };
+    let series = provider(matches)
+        .daily_series(symbol.into(), output_size)
+        .or_exit();
+    let days: Vec<(&chrono::NaiveDate, &portfolio::TimeSeriesDay)> = match start {
+        Some(start) => series.days.range(start..).collect(),
+        None => series.days.iter().collect(),
+    };
+    let (first, last) = match (days.first(), days.last()) {
+        (Some((first, _)), Some((last, _))) => (first.to_string(), last.to_string()),
+        _ => {
+            exit(
+                EXIT_NO_DATA,
+                &format!("No prices for {} in that period", symbol),
+            );
+        }
+    };
+    if let Some(path) = chart_file(matches) {
+        #[cfg(feature = "charts")]
+        portfolio::charts::price_chart(
+            path,
+            symbol,
+            &days.iter().map(|(date, day)| (**date, day.close)).collect(),
+        )
+        .or_exit();
+        #[cfg(not(feature = "charts"))]
+        crate::charts_disabled(path);
+        return;
+    }
+    if output(matches) == Output::Json {
+        print_json(&days.into_iter().collect::<BTreeMap<_, _>>());
+        return;
+    }
+    let width: usize = matches.value_of("width").unwrap().parse().or_exit();
+    let height = matches.value_of("height").unwrap().parse().or_exit();
+
+    if matches.is_present("compact") {
+        let closes: Vec<f64> = days.iter().map(|(_date, day)| day.close).collect();
+        let sparkline = render::sparkline(&render::resample(&closes, width));
+        println!("{} {} {} {}", symbol, first, sparkline, last);
+        return;
+    }
+
+    let chart = match matches.value_of("style").unwrap() {
+        "candle" => render::Chart::candlestick(
+            days.iter()
+                .map(|(_date, day)| render::Candle {
+                    open: day.open,
+                    high: day.high,
+                    low: day.low,
+                    close: day.close,
+                })
+                .collect(),
+        ),
+        _ => render::Chart::line(
+            &days
+                .iter()
+                .map(|(_date, day)| day.close)
+                .collect::<Vec<_>>(),
+        ),
+    };
+    print!("{}", chart.width(width).height(height).labels(first, last));
+}
+
+pub fn series(matches: &ArgMatches) {
+    let symbol = matches.value_of("symbol").unwrap();
+    let frequency = match matches.value_of("frequency").unwrap() {
+        "weekly" => portfolio::Frequency::Weekly,
+        "monthly" => portfolio::Frequency::Monthly,
+        _ => portfolio::Frequency::Daily,
+    };
+
+    let series = provider(matches).series(symbol.into(), frequency).or_exit();
+
+    match output(matches) {
+        Output::Table => {
+            println!(
+                "{:<10} {:>12} {:>12} {:>12} {:>12} {:>14}",
+                "DATE", "OPEN", "HIGH", "LOW", "CLOSE", "VOLUME"
+            );
+            for (date, bar) in &series.bars {
+                println!(
+                    "{:<10} {:>12.2} {:>12.2} {:>12.2} {:>12.2} {:>14}",
+                    date, bar.open, bar.high, bar.low, bar.close, bar.volume
+                );
+            }
+        }
+        Output::Csv => series.to_csv(io::stdout()).unwrap(),
+        Output::Json => print_json(&series),
+    }
+}
+
+pub fn indicator(matches: &ArgMatches) {
+    let symbol = matches.value_of("symbol").unwrap();
+    let period = |name: &str| -> Option<usize> {
+        matches
+            .value_of(name)
+            .map(|period| period.parse().or_exit())
+    };
+
+    let mut requested: Vec<(String, indicators::Indicator)> = vec![];
+    if let Some(period) = period("sma") {
+        requested.push((
+            format!("SMA({})", period),
+            indicators::Indicator::Sma(period),
+        ));
+    }
+    if let Some(period) = period("ema") {
+        requested.push((
+            format!("EMA({})", period),
+            indicators::Indicator::Ema(period),
+        ));
+    }
+    if let Some(period) = period("rsi") {
+        requested.push((
+            format!("RSI({})", period),
+            indicators::Indicator::Rsi(period),
+        ));
+    }
+    if matches.is_present("macd") {
+        requested.push((
+            "MACD".to_string(),
+            indicators::Indicator::Macd {
+                fast: 12,
+                slow: 26,
+                signal: 9,
+            },
+        ));
+    }
+    if let Some(period) = period("bollinger") {
+        requested.push((
+            format!("BB({})", period),
+            indicators::Indicator::BollingerBands {
+                period,
+                deviations: 2.0,
+            },
+        ));
+    }
+
+    let outputs: Vec<(String, indicators::IndicatorOutput)> = if matches.is_present("remote") {
+        let client = alpha_vantage_client();
+        requested
+            .into_iter()
+            .map(|(label, indicator)| {
+                let output = client
+                    .indicator(symbol.into(), indicator, indicators::Source::Remote)
+                    .or_exit();
+                (label, output)
+            })
+            .collect()
+    } else {
+        let series = provider(matches)
+            .daily_series(symbol.into(), portfolio::OutputSize::Full)
+            .or_exit();
+        requested
+            .into_iter()
+            .map(|(label, indicator)| (label, indicators::compute(&series, indicator)))
+            .collect()
+    };
+
+    let mut columns: Vec<(String, &indicators::IndicatorSeries)> = vec![];
+    for (label, output) in &outputs {
+        let values = output.columns();
+        let single = values.len() == 1;
+        for (name, values) in values {
+            if single {
+                columns.push((label.clone(), values));
+            } else {
+                columns.push((format!("{} {}", label, name.to_uppercase()), values));
+            }
+        }
+    }
+
+    let dates: BTreeSet<chrono::NaiveDate> = columns
+        .iter()
+        .flat_map(|(_name, values)| values.keys().cloned())
+        .collect();
+
+    match output(matches) {
+        Output::Table => {
+            let mut headers = vec![("DATE", Align::Left)];
+            for (name, _values) in &columns {
+                headers.push((name, Align::Right));
+            }
+            let mut table = Table::new(&headers);
+            for date in &dates {
+                let mut row = vec![Cell::new(date.to_string())];
+                for (_name, values) in &columns {
+                    row.push(match values.get(date) {
+                        Some(value) => Cell::new(render::number(*value, 2)),
+                        None => Cell::empty(),
+                    });
+                }
+                table.row(row);
+            }
+            print!("{}", table);
+        }
+        Output::Csv => {
+            let mut writer = csv::Writer::from_writer(io::stdout());
+            let mut headers = vec!["date".to_string()];
+            headers.extend(columns.iter().map(|(name, _values)| name.to_lowercase()));
+            writer.write_record(&headers).unwrap();
+            for date in &dates {
+                let mut record = vec![date.to_string()];
+                for (_name, values) in &columns {
+                    record.push(values.get(date).map_or(String::new(), f64::to_string));
+                }
+                writer.write_record(&record).unwrap();
+            }
+            writer.flush().or_exit();
+        }
+        Output::Json => print_json(
+            &outputs
+                .iter()
+                .map(|(label, output)| (label.as_str(), output))
+                .collect::<BTreeMap<_, _>>(),
+        ),
+    }
+}
+
+pub fn dividends(matches: &ArgMatches) {
+    let symbol = matches.value_of("symbol").unwrap();
+
+    let history = portfolio::dividend_history(&*provider(matches), symbol.into()).or_exit();
+
+    match output(matches) {
+        Output::Table => {
+            let currency = symbol_currency(symbol);
+            let mut table = Table::new(&[("DATE", Align::Left), ("AMOUNT", Align::Right)]);
+            for payment in &history.payments {
+                table.row(vec![
+                    Cell::new(payment.date.to_string()),
+                    Cell::new(render::money(payment.amount, currency)),
+                ]);
+            }
+            print!("{}", table);
+            println!();
+            println!(
+                "Trailing 12 months: {} ({}% yield at {})",
+                render::money(history.trailing_twelve_months(), currency),
+                render::number(history.trailing_yield(), 2),
+                render::money(history.latest_price, currency)
+            );
+        }
+        Output::Csv => print_csv(&history.payments),
+        Output::Json => print_json(&history),
+    }
+}
+
+pub fn earnings_calendar(matches: &ArgMatches) {
+    let soon: i64 = matches.value_of("soon").unwrap().parse().or_exit();
+    let symbols = stored_symbols(matches);
+    let today = portfolio::calendar::today();
+
+    let mut events: Vec<portfolio::EarningsEvent> = alpha_vantage_client()
+        .earnings_calendar()
+        .or_exit()
+        .into_iter()
+        .filter(|event| {
+            event.report_date >= today && symbols.contains(&event.symbol.as_str().into())
+        })
+        .collect();
+    events.sort_by(|a, b| (a.report_date, &a.symbol).cmp(&(b.report_date, &b.symbol)));
+
+    match output(matches) {
+        Output::Table => {
+            if events.is_empty() {
+                println!("No upcoming earnings for held or watched symbols");
+                return;
+            }
+            let mut table = Table::new(&[
+                ("DATE", Align::Left),
+                ("SYMBOL", Align::Left),
+                ("NAME", Align::Left),
+                ("QUARTER", Align::Left),
+                ("ESTIMATE", Align::Right),
+                ("", Align::Left),
+            ]);
+            for event in &events {
+                let days = (event.report_date - today).num_days();
+                table.row(vec![
+                    Cell::new(event.report_date.to_string()),
+                    Cell::new(&event.symbol),
+                    Cell::new(&event.name),
+                    Cell::new(event.fiscal_date_ending.to_string()),
+                    match event.estimate {
+                        Some(estimate) => Cell::new(render::number(estimate, 2)),
+                        None => Cell::empty(),
+                    },
+                    match days {
+                        0 => Cell::new("today"),
+                        1 => Cell::new("tomorrow"),
+                        days if days <= soon => Cell::new(format!("in {} days", days)),
+                        _ => Cell::empty(),
+                    },
+                ]);
+            }
+            print!("{}", table);
+        }
+        Output::Csv => print_csv(&events),
+        Output::Json => print_json(&events),
+    }
+}
+
+pub fn economy(matches: &ArgMatches) {
+    let maturity = matches.value_of("maturity").unwrap().parse().or_exit();
+    let client = alpha_vantage_client();
+    let series: Vec<portfolio::EconomicSeries> = [
+        portfolio::EconomicIndicator::FederalFundsRate,
+        portfolio::EconomicIndicator::TreasuryYield(maturity),
+        portfolio::EconomicIndicator::Cpi,
+        portfolio::EconomicIndicator::RealGdp,
+    ]
+    .iter()
+    .map(|indicator| client.economic_indicator(*indicator).or_exit())
+    .collect();
+
+    #[derive(Serialize)]
+    struct Row<'a> {
+        name: &'a str,
+        date: chrono::NaiveDate,
+        value: f64,
+        year_earlier: Option<f64>,
+        unit: &'a str,
+    }
+    let rows: Vec<Row> = series
+        .iter()
+        .filter_map(|series| {
+            let (date, value) = series.latest()?;
+            Some(Row {
+                name: &series.name,
+                date: *date,
+                value: *value,
+                year_earlier: series
+                    .on(*date - chrono::Months::new(12))
+                    .map(|(_date, value)| *value),
+                unit: &series.unit,
+            })
+        })
+        .collect();
+
+    match output(matches) {
+        Output::Table => {
+            let mut table = Table::new(&[
+                ("INDICATOR", Align::Left),
+                ("DATE", Align::Left),
+                ("VALUE", Align::Right),
+                ("YEAR EARLIER", Align::Right),
+                ("UNIT", Align::Left),
+            ]);
+            for row in &rows {
+                table.row(vec![
+                    Cell::new(row.name),
+                    Cell::new(row.date.to_string()),
+                    Cell::new(render::number(row.value, 2)),
+                    match row.year_earlier {
+                        Some(value) => Cell::new(render::number(value, 2)),
+                        None => Cell::empty(),
+                    },
+                    Cell::new(row.unit),
+                ]);
+            }
+            print!("{}", table);
+        }
+        Output::Csv => print_csv(&rows),
+        Output::Json => print_json(&series),
+    }
+}
+
+pub fn sectors(matches: &ArgMatches) {
+    let client = alpha_vantage_client();
+    let performance = client.sector_performance().or_exit();
+    let portfolio = load_portfolio(matches);
+    let valuation = portfolio.valuation(&*provider(matches)).or_exit();
+    let overviews = portfolio::fetch_overviews(&client, &valuation);
+    let allocation = portfolio::Allocation::new(
+        &valuation,
+        &overviews,
+        portfolio::Dimension::Sector,
+        portfolio.targets(),
+    );
+
+    // without a target, a sector is overweight above an equal share of the market
+    let equal_share = 100.0 / performance.sectors().count().max(1) as f64;
+    #[derive(Serialize)]
+    struct Row<'a> {
+        sector: &'a str,
+        one_day: Option<f64>,
+        five_day: Option<f64>,
+        one_month: Option<f64>,
+        year_to_date: Option<f64>,
+        one_year: Option<f64>,
+        weight: f64,
+        target: Option<f64>,
+        overweight: bool,
+    }
+    let rows: Vec<Row> = performance
+        .sectors()
+        .map(|sector| {
+            let held = allocation
+                .weights
+                .iter()
+                .find(|weight| weight.name.eq_ignore_ascii_case(sector));
+            let weight = held.map_or(0.0, |held| held.weight);
+            let target = held.and_then(|held| held.target);
+            Row {
+                sector,
+                one_day: performance.one_day.get(sector).copied(),
+                five_day: performance.five_day.get(sector).copied(),
+                one_month: performance.one_month.get(sector).copied(),
+                year_to_date: performance.year_to_date.get(sector).copied(),
+                one_year: performance.one_year.get(sector).copied(),
+                weight,
+                target,
+                overweight: weight > target.unwrap_or(equal_share),
+            }
+        })
+        .collect();
+
+    match output(matches) {
+        Output::Table => {
+            let change = |value: Option<f64>| match value {
+                Some(value) => Cell::change(value, render::percent(value)),
+                None => Cell::empty(),
+            };
+            let mut table = Table::new(&[
+                ("SECTOR", Align::Left),
+                ("DAY", Align::Right),
+                ("5 DAYS", Align::Right),
+                ("MONTH", Align::Right),
+                ("YTD", Align::Right),
+                ("YEAR", Align::Right),
+                ("WEIGHT", Align::Right),
+                ("", Align::Left),
+            ]);
+            for row in &rows {
+                table.row(vec![
+                    Cell::new(row.sector),
+                    change(row.one_day),
+                    change(row.five_day),
+                    change(row.one_month),
+                    change(row.year_to_date),
+                    change(row.one_year),
+                    Cell::new(format!("{}%", render::number(row.weight, 2))),
+                    if row.overweight {
+                        Cell::new("overweight")
+                    } else {
+                        Cell::empty()
+                    },
+                ]);
+            }
+            print!("{}", table);
+        }
+        Output::Csv => print_csv(&rows),
+        Output::Json => print_json(&rows),
+    }
+}
+
+pub fn news(matches: &ArgMatches) {
+    let symbol = matches.value_of("symbol").unwrap();
+    let limit = matches.value_of("limit").unwrap().parse().or_exit();
+    let articles = alpha_vantage_client().news(symbol.into(), limit).or_exit();
+
+    // the score for the symbol itself, falling back to the article's
+    let sentiment = |article: &portfolio::NewsArticle| {
+        article.sentiment_for(symbol).map_or(
+            (
+                article.overall_sentiment_score,
+                article.overall_sentiment_label.clone(),
+            ),
+            |sentiment| (sentiment.sentiment_score, sentiment.sentiment_label.clone()),
+        )
+    };
+    match output(matches) {
+        Output::Table => {
+            let mut table = Table::new(&[
+                ("PUBLISHED", Align::Left),
+                ("SENTIMENT", Align::Right),
+                ("", Align::Left),
+                ("SOURCE", Align::Left),
+                ("HEADLINE", Align::Left),
+            ]);
+            for article in &articles {
+                let (score, label) = sentiment(article);
+                table.row(vec![
+                    Cell::new(article.published.format("%Y-%m-%d %H:%M").to_string()),
+                    Cell::change(score, render::number(score, 2)),
+                    Cell::new(label),
+                    Cell::new(&article.source),
+                    Cell::new(&article.title),
+                ]);
+            }
+            print!("{}", table);
+        }
+        Output::Csv => {
+            #[derive(Serialize)]
+            struct Row<'a> {
+                published: chrono::NaiveDateTime,
+                source: &'a str,
+                title: &'a str,
+                url: &'a str,
+                sentiment_score: f64,
+                sentiment_label: String,
+            }
+            let rows: Vec<Row> = articles
+                .iter()
+                .map(|article| {
+                    let (sentiment_score, sentiment_label) = sentiment(article);
+                    Row {
+                        published: article.published,
+                        source: &article.source,
+                        title: &article.title,
+                        url: &article.url,
+                        sentiment_score,
+                        sentiment_label,
+                    }
+                })
+                .collect();
+            print_csv(&rows);
+        }
+        Output::Json => print_json(&articles),
+    }
+}
+
+pub fn info(matches: &ArgMatches) {
+    let symbol = matches.value_of("symbol").unwrap();
+
+    let overview = alpha_vantage_client()
+        .company_overview(symbol.into())
+        .or_exit();
+
+    match output(matches) {
+        Output::Table => {
+            let currency = match overview.currency.as_str() {
+                "" => symbol_currency(&overview.symbol),
+                code => render::currency_symbol(code),
+            };
+            let optional = |value: Option<f64>, format: &dyn Fn(f64) -> String| match value {
+                Some(value) => format(value),
+                None => "-".to_string(),
+            };
+
+            let mut table = Table::new(&[(&overview.symbol, Align::Left), ("", Align::Left)]);
+            table.row(vec!["Name".into(), overview.name.as_str().into()]);
+            table.row(vec!["Exchange".into(), overview.exchange.as_str().into()]);
+            table.row(vec!["Sector".into(), overview.sector.as_str().into()]);
+            table.row(vec!["Industry".into(), overview.industry.as_str().into()]);
+            table.row(vec![
+                "Market cap".into(),
+                optional(overview.market_capitalization, &|value| {
+                    render::money(value, currency)
+                })
+                .into(),
+            ]);
+            table.row(vec![
+                "P/E".into(),
+                optional(overview.pe_ratio, &|value| render::number(value, 2)).into(),
+            ]);
+            table.row(vec![
+                "EPS".into(),
+                optional(overview.eps, &|value| render::money(value, currency)).into(),
+            ]);
+            table.row(vec![
+                "Dividend yield".into(),
+                optional(overview.dividend_yield, &|value| {
+                    format!("{}%", render::number(value * 100.0, 2))
+                })
+                .into(),
+            ]);
+            table.row(vec![
+                "52 week range".into(),
+                format!(
+                    "{} - {}",
+                    optional(overview.week_52_low, &|value| render::money(
+                        value, currency
+                    )),
+                    optional(overview.week_52_high, &|value| render::money(
+                        value, currency
+                    ))
+                )
+                .into(),
+            ]);
+            print!("{}", table);
+        }
+        Output::Csv => print_csv(&[overview]),
+        Output::Json => print_json(&overview),
+    }
+}
+
+pub fn fx(matches: &ArgMatches) {
+    let pair = matches.value_of("pair").unwrap().replace('/', "");
+    if pair.len() != 6 {
+        exit(EXIT_USAGE, "currency pair must look like `EURUSD`");
+    }
+    let (from, to) = pair.split_at(3);
+
+    let rate = alpha_vantage_client().exchange_rate(from, to).or_exit();
+
+    match output(matches) {
+        Output::Table => {
+            let mut table = Table::new(&[
+                ("PAIR", Align::Left),
+                ("RATE", Align::Right),
+                ("BID", Align::Right),
+                ("ASK", Align::Right),
+                ("UPDATED", Align::Left),
+            ]);
+            table.row(vec![
+                Cell::new(format!("{}/{}", rate.from, rate.to)),
+                Cell::new(render::number(rate.rate, 4)),
+                Cell::new(render::number(rate.bid, 4)),
+                Cell::new(render::number(rate.ask, 4)),
+                Cell::new(format!("{} {}", rate.last_refreshed, rate.time_zone)),
+            ]);
+            print!("{}", table);
+        }
+        Output::Csv => print_csv(&[rate]),
+        Output::Json => print_json(&rate),
+    }
+}
+
+pub fn search(matches: &ArgMatches) {
+    let query = matches.value_of("query").unwrap();
+
+    let results = alpha_vantage_client().search_symbols(query).or_exit();
+
+    match output(matches) {
+        Output::Table => {
+            println!(
+                "{:<12} {:<40} {:<10} {:<20} {:<8} {:>6}",
+                "SYMBOL", "NAME", "TYPE", "REGION", "CURRENCY", "SCORE"
+            );
+            for result in results {
+                println!(
+                    "{:<12} {:<40} {:<10} {:<20} {:<8} {:>6.4}",
+                    result.symbol,
+                    result.name,
+                    result.instrument_type,
+                    result.region,
+                    result.currency,
+                    result.match_score
+                );
+            }
+        }
+        Output::Csv => print_csv(&results),
+        Output::Json => print_json(&results),
+    }
+}
+
+pub fn movers(matches: &ArgMatches) {
+    let window = match matches.value_of("window").unwrap() {
+        "week" => portfolio::Frequency::Weekly,
+        "month" => portfolio::Frequency::Monthly,
+        _ => portfolio::Frequency::Daily,
+    };
+    let limit: usize = matches.value_of("limit").unwrap().parse().or_exit();
+
+    let provider = provider(matches);
+    let mut movers = vec![];
+    for symbol in stored_symbols(matches) {
+        match portfolio::mover(&*provider, symbol.clone(), window) {
+            Ok(mover) => movers.push(mover),
+            Err(err) => eprintln!("Skipping {}: {}", &*symbol, err),
+        }
+    }
+    // the biggest gainers and the biggest losers, best first
+    movers.sort_by(|a, b| b.change_percent.total_cmp(&a.change_percent));
+    if movers.len() > 2 * limit {
+        movers.drain(limit..movers.len() - limit);
+    }
+
+    match output(matches) {
+        Output::Table => {
+            let mut table = Table::new(&[
+                ("SYMBOL", Align::Left),
+                ("PRICE", Align::Right),
+                ("CHANGE", Align::Right),
+                ("CHANGE %", Align::Right),
+            ]);
+            for mover in &movers {
+                table.row(vec![
+                    Cell::new(&*mover.symbol),
+                    Cell::new(render::money(
+                        mover.price,
+                        render::currency_symbol(mover.symbol.currency()),
+                    )),
+                    Cell::change(
+                        mover.change,
+                        render::signed_money(
+                            mover.change,
+                            render::currency_symbol(mover.symbol.currency()),
+                        ),
+                    ),
+                    Cell::change(mover.change, render::percent(mover.change_percent)),
+                ]);
+            }
+            print!("{}", table);
+        }
+        Output::Csv => print_csv(&movers),
+        Output::Json => print_json(&movers),
+    }
+}
+
+pub fn watch(matches: &ArgMatches) {
+    let path = watchlist_path(matches);
+    let mut watchlist = portfolio::Watchlist::load(&path).or_exit();
+
+    match matches.subcommand() {
+        ("add", Some(matches)) => {
+            for symbol in matches.values_of("symbol").unwrap() {
+                watchlist.add(symbol.to_uppercase());
+            }
+            watchlist.save(&path).or_exit();
+            if output(matches) == Output::Json {
+                print_json(&watchlist.symbols().collect::<Vec<_>>());
+            }
+        }
+        ("remove", Some(matches)) => {
+            for symbol in matches.values_of("symbol").unwrap() {
+                watchlist.remove(&symbol.to_uppercase().into());
+            }
+            watchlist.save(&path).or_exit();
+            if output(matches) == Output::Json {
+                print_json(&watchlist.symbols().collect::<Vec<_>>());
+            }
+        }
+        ("list", Some(matches)) => match output(matches) {
+            Output::Table => {
+                for symbol in watchlist.symbols() {
+                    println!("{}", &**symbol);
+                }
+            }
+            Output::Csv => {
+                #[derive(Serialize)]
+                struct Row<'a> {
+                    symbol: &'a str,
+                }
+                print_csv(watchlist.symbols().map(|symbol| Row { symbol }))
+            }
+            Output::Json => print_json(&watchlist.symbols().collect::<Vec<_>>()),
+        },
+        ("quotes", Some(matches)) => {
+            let quotes = watchlist.quotes(&*provider(matches)).or_exit();
+
+            match output(matches) {
+                Output::Table => {
+                    print!("{}", quotes_table(&quotes));
+                    print_quotes_freshness(&quotes);
+                }
+                Output::Csv => print_csv(&quotes),
+                Output::Json => print_json(&quotes),
+            }
+        }
+        _ => unreachable!(),
+    }
+}
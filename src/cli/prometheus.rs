@@ -0,0 +1,122 @@
+use std::collections::BTreeMap;
+
+use portfolio::money::to_f64;
+
+use crate::serve::ServerStats;
+
+// in Prometheus' text format, with lots of the same symbol combined
+pub fn prometheus_metrics(valuation: &portfolio::Valuation, stats: &ServerStats) -> String {
+    let currency = format!(
+        "currency=\"{}\"",
+        prometheus_label(&valuation.base_currency)
+    );
+    let mut positions: BTreeMap<&str, (f64, f64, f64)> = BTreeMap::new();
+    for position in &valuation.positions {
+        let totals = positions.entry(&position.position.symbol).or_default();
+        totals.0 += position.base_market_value();
+        totals.1 += position.base_day_change();
+        totals.2 += to_f64(position.position.quantity);
+    }
+    let by_symbol = |value: fn(&(f64, f64, f64)) -> f64, in_currency: bool| {
+        positions
+            .iter()
+            .map(|(symbol, totals)| {
+                let mut labels = format!("symbol=\"{}\"", prometheus_label(symbol));
+                if in_currency {
+                    labels = format!("{},{}", labels, currency);
+                }
+                (labels, value(totals))
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let mut out = String::new();
+    let mut metric = |name: &str, kind: &str, help: &str, samples: Vec<(String, f64)>| {
+        out.push_str(&format!(
+            "# HELP {} {}\n# TYPE {} {}\n",
+            name, help, name, kind
+        ));
+        for (labels, value) in samples {
+            if labels.is_empty() {
+                out.push_str(&format!("{} {}\n", name, value));
+            } else {
+                out.push_str(&format!("{}{{{}}} {}\n", name, labels, value));
+            }
+        }
+    };
+    let unlabelled = |value: f64| vec![(String::new(), value)];
+    metric(
+        "portfolio_market_value",
+        "gauge",
+        "Market value of the portfolio.",
+        vec![(currency.clone(), valuation.market_value())],
+    );
+    metric(
+        "portfolio_cost_basis",
+        "gauge",
+        "Cost basis of the portfolio.",
+        vec![(currency.clone(), valuation.cost_basis())],
+    );
+    metric(
+        "portfolio_day_change",
+        "gauge",
+        "Change in the portfolio's market value since the previous close.",
+        vec![(currency.clone(), valuation.day_change())],
+    );
+    metric(
+        "portfolio_unrealised_pnl",
+        "gauge",
+        "Unrealised profit or loss of the portfolio.",
+        vec![(currency.clone(), valuation.unrealised_pnl())],
+    );
+    metric(
+        "portfolio_position_market_value",
+        "gauge",
+        "Market value of a position.",
+        by_symbol(|totals| totals.0, true),
+    );
+    metric(
+        "portfolio_position_day_change",
+        "gauge",
+        "Change in a position's market value since the previous close.",
+        by_symbol(|totals| totals.1, true),
+    );
+    metric(
+        "portfolio_position_quantity",
+        "gauge",
+        "Units held of a position.",
+        by_symbol(|totals| totals.2, false),
+    );
+    metric(
+        "portfolio_cache_hits_total",
+        "counter",
+        "API responses served from the cache.",
+        unlabelled(stats.cache.hits() as f64),
+    );
+    metric(
+        "portfolio_cache_misses_total",
+        "counter",
+        "API requests that missed the cache.",
+        unlabelled(stats.cache.misses() as f64),
+    );
+    metric(
+        "portfolio_api_requests_today",
+        "gauge",
+        "Requests made to Alpha Vantage today (UTC).",
+        unlabelled(f64::from(stats.rate_limiter.requests_today())),
+    );
+    metric(
+        "portfolio_api_requests_available",
+        "gauge",
+        "Requests that can be made to Alpha Vantage now without waiting.",
+        unlabelled(stats.rate_limiter.available()),
+    );
+    out
+}
+
+fn prometheus_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
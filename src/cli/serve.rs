@@ -0,0 +1,156 @@
+use std::io::{self, BufRead, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::time::Duration;
+
+use clap::ArgMatches;
+use portfolio::metrics;
+
+use crate::{
+    cache, filter_ledger, ledger_path, prometheus, provider_with, rate_limiter, try_load_portfolio,
+    Failure, OrExit, EXIT_API, EXIT_NO_DATA, EXIT_USAGE,
+};
+
+// answers one request at a time, reloading the portfolio and ledger for each so
+// that changes made by other commands show up
+pub fn serve(matches: &ArgMatches) {
+    let port: u16 = matches.value_of("port").unwrap().parse().or_exit();
+    let host = matches.value_of("bind").unwrap();
+    let listener = TcpListener::bind((host, port)).or_exit();
+    println!("Listening on http://{}:{}", host, port);
+
+    let cache = cache();
+    let stats = ServerStats {
+        cache: cache.stats(),
+        rate_limiter: rate_limiter(),
+    };
+    let provider = provider_with(matches, cache, stats.rate_limiter.clone());
+    for stream in listener.incoming() {
+        let result =
+            stream.and_then(|mut stream| respond(matches, &*provider, &stats, &mut stream));
+        if let Err(err) = result {
+            eprintln!("Request failed: {}", err);
+        }
+    }
+}
+
+// kept by `serve` for `/metrics`, as the provider's client owns the cache and limiter
+pub struct ServerStats {
+    pub cache: Arc<portfolio::CacheStats>,
+    pub rate_limiter: Arc<portfolio::RateLimiter>,
+}
+
+fn respond(
+    matches: &ArgMatches,
+    provider: &dyn portfolio::Provider,
+    stats: &ServerStats,
+    stream: &mut TcpStream,
+) -> io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+    let mut reader = io::BufReader::new(&*stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // the headers are read and ignored, up to the blank line that ends them
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("");
+    let target = parts.next().unwrap_or("");
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let (status, content_type, body) = match method {
+        "GET" => match route(matches, provider, stats, path, query) {
+            Ok((content_type, body)) => (200, content_type, body),
+            Err((status, message)) => (status, JSON, error_json(&message)),
+        },
+        _ => (405, JSON, error_json("only GET requests are supported")),
+    };
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        502 => "Bad Gateway",
+        _ => "Internal Server Error",
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        content_type,
+        body.len(),
+        body
+    )
+}
+
+fn error_json(message: &str) -> String {
+    serde_json::to_string_pretty(&serde_json::json!({ "error": message })).unwrap()
+}
+
+// the HTTP status for what would be the command's exit status
+fn http_error<E: Failure>(err: E) -> (u16, String) {
+    let status = match err.exit_code() {
+        EXIT_USAGE => 400,
+        EXIT_NO_DATA => 404,
+        EXIT_API => 502,
+        _ => 500,
+    };
+    (status, err.to_string())
+}
+
+const JSON: &str = "application/json";
+const PROMETHEUS: &str = "text/plain; version=0.0.4";
+
+fn route(
+    matches: &ArgMatches,
+    provider: &dyn portfolio::Provider,
+    stats: &ServerStats,
+    path: &str,
+    query: &str,
+) -> Result<(&'static str, String), (u16, String)> {
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let body = match segments.as_slice() {
+        ["quotes", symbol] => {
+            let quote = provider
+                .quote(symbol.to_uppercase().into())
+                .map_err(http_error)?;
+            serde_json::to_string_pretty(&quote)
+        }
+        ["portfolio", "value"] => {
+            let portfolio = try_load_portfolio(matches).map_err(http_error)?;
+            let valuation = portfolio.valuation(provider).map_err(http_error)?;
+            serde_json::to_string_pretty(&valuation)
+        }
+        ["portfolio", "performance"] => {
+            let risk_free_rate = query
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("risk_free="))
+                .unwrap_or("0")
+                .parse::<f64>()
+                .map_err(http_error)?;
+            let ledger = filter_ledger(
+                matches,
+                portfolio::Ledger::load(ledger_path(matches)).map_err(http_error)?,
+            );
+            let curve = ledger.fetch_equity_curve(provider).map_err(http_error)?;
+            let metrics = metrics::for_equity_curve(&curve, risk_free_rate)
+                .ok_or((404, "not enough history".to_string()))?;
+            serde_json::to_string_pretty(&metrics)
+        }
+        ["metrics"] => {
+            let portfolio = try_load_portfolio(matches).map_err(http_error)?;
+            let valuation = portfolio.valuation(provider).map_err(http_error)?;
+            return Ok((
+                PROMETHEUS,
+                prometheus::prometheus_metrics(&valuation, stats),
+            ));
+        }
+        _ => return Err((404, format!("no route for {}", path))),
+    };
+    body.map(|body| (JSON, body))
+        .map_err(|err| (500, err.to_string()))
+}
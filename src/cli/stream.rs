@@ -0,0 +1,63 @@
+use std::io;
+
+use clap::ArgMatches;
+
+use crate::market::quotes_table;
+use crate::{exit, finnhub_client, output, provider, OrExit, Output, EXIT_API};
+
+// the quotes from the provider, then updated from Finnhub's trades as they happen
+pub fn stream_quotes(matches: &ArgMatches, symbols: &[portfolio::Symbol]) {
+    use futures::StreamExt;
+
+    let mut quotes = provider(matches).quotes(symbols).or_exit();
+    let client = finnhub_client();
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .or_exit();
+    runtime.block_on(async {
+        let stream = portfolio::streaming::finnhub_quotes(&client, quotes.clone())
+            .await
+            .or_exit();
+        futures::pin_mut!(stream);
+        let mut writer = csv::Writer::from_writer(io::stdout());
+
+        let mut update = None;
+        loop {
+            match output(matches) {
+                Output::Table => {
+                    print!("\x1b[2J\x1b[H");
+                    println!(
+                        "Streaming: {}\n",
+                        chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
+                    );
+                    print!("{}", quotes_table(&quotes));
+                }
+                // a record per update rather than the whole table
+                Output::Csv => {
+                    if let Some(quote) = &update {
+                        writer.serialize(quote).unwrap();
+                        writer.flush().unwrap();
+                    }
+                }
+                Output::Json => {
+                    if let Some(quote) = &update {
+                        std::println!("{}", serde_json::to_string(quote).unwrap());
+                    }
+                }
+            }
+
+            let quote = match stream.next().await {
+                Some(quote) => quote.or_exit(),
+                None => exit(EXIT_API, "The stream closed"),
+            };
+            if let Some(current) = quotes
+                .iter_mut()
+                .find(|current| current.symbol == quote.symbol)
+            {
+                *current = quote.clone();
+            }
+            update = Some(quote);
+        }
+    });
+}
@@ -0,0 +1,1163 @@
+use std::env;
+use std::io::Read;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::Datelike;
+use serde::de::DeserializeOwned;
+
+use crate::economy::EconomicResponse;
+use crate::fx::ExchangeRateResponse;
+use crate::indicators::{self, Indicator, IndicatorOutput, Source};
+use crate::news::NewsResponse;
+use crate::overview::overview_from_value;
+use crate::paginate::Paginator;
+use crate::quote::GlobalQuoteResponse;
+use crate::search::SymbolSearchResponse;
+use crate::series::{AdjustedSeriesResponse, FromCsv};
+use crate::{
+    aio, ApiError, ApiTier, BalanceSheet, Cache, CashFlow, CompanyOverview, CryptoDailySeries,
+    DailySeries, DividendHistory, Earnings, EarningsEvent, EconomicIndicator, EconomicSeries,
+    EquitySummary, ErrorContext, ExchangeRate, Frequency, FxDailySeries, IncomeStatement, Interval,
+    IntradaySeries, IpoEvent, Listing, NewsArticle, OutputSize, Quote, RateLimiter, Recording,
+    RecordingMode, RetryPolicy, SectorPerformance, Series, Statements, Symbol, SymbolMatch,
+    TimePeriod,
+};
+
+pub(crate) const API_URL: &str = "https://www.alphavantage.co/query";
+
+// passed to the callbacks of `refresh_all` and `Ledger::fetch_equity_curve_with_progress`
+// as each symbol finishes
+#[derive(Debug)]
+pub struct Progress<'a> {
+    pub symbol: &'a Symbol,
+    pub completed: usize,
+    pub total: usize,
+    pub error: Option<&'a ApiError>,
+}
+
+pub(crate) fn quote_params(symbol: &Symbol) -> [(&'static str, &str); 2] {
+    [("function", "GLOBAL_QUOTE"), ("symbol", symbol)]
+}
+
+pub(crate) fn overview_params(symbol: &Symbol) -> [(&'static str, &str); 2] {
+    [("function", "OVERVIEW"), ("symbol", symbol)]
+}
+
+pub(crate) fn fundamentals_params<'a>(
+    function: &'static str,
+    symbol: &'a Symbol,
+) -> [(&'static str, &'a str); 2] {
+    [("function", function), ("symbol", symbol)]
+}
+
+pub(crate) fn exchange_rate_params<'a>(from: &'a str, to: &'a str) -> [(&'static str, &'a str); 3] {
+    [
+        ("function", "CURRENCY_EXCHANGE_RATE"),
+        ("from_currency", from),
+        ("to_currency", to),
+    ]
+}
+
+pub(crate) fn fx_daily_params<'a>(
+    from: &'a str,
+    to: &'a str,
+    output_size: &OutputSize,
+) -> [(&'static str, &'a str); 4] {
+    [
+        ("function", "FX_DAILY"),
+        ("from_symbol", from),
+        ("to_symbol", to),
+        ("outputsize", output_size.as_str()),
+    ]
+}
+
+pub(crate) fn crypto_daily_params<'a>(
+    symbol: &'a Symbol,
+    market: &'a str,
+) -> [(&'static str, &'a str); 3] {
+    [
+        ("function", "DIGITAL_CURRENCY_DAILY"),
+        ("symbol", symbol),
+        ("market", market),
+    ]
+}
+
+pub(crate) fn symbol_search_params(keywords: &str) -> [(&'static str, &str); 2] {
+    [("function", "SYMBOL_SEARCH"), ("keywords", keywords)]
+}
+
+// every report due in the next three months
+pub(crate) const EARNINGS_CALENDAR_PARAMS: [(&str, &str); 2] =
+    [("function", "EARNINGS_CALENDAR"), ("horizon", "3month")];
+
+pub(crate) const IPO_CALENDAR_PARAMS: [(&str, &str); 1] = [("function", "IPO_CALENDAR")];
+
+// the stocks and ETFs trading today
+pub(crate) const LISTING_STATUS_PARAMS: [(&str, &str); 2] =
+    [("function", "LISTING_STATUS"), ("state", "active")];
+
+pub(crate) const SECTOR_PARAMS: [(&str, &str); 1] = [("function", "SECTOR")];
+
+// the latest articles first; Alpha Vantage returns up to 50 by default
+pub(crate) fn news_params(symbol: &Symbol) -> [(&'static str, &str); 3] {
+    [
+        ("function", "NEWS_SENTIMENT"),
+        ("tickers", symbol),
+        ("sort", "LATEST"),
+    ]
+}
+
+pub(crate) fn daily_series_params<'a>(
+    symbol: &'a Symbol,
+    output_size: &OutputSize,
+) -> [(&'static str, &'a str); 3] {
+    [
+        ("function", "TIME_SERIES_DAILY_ADJUSTED"),
+        ("symbol", symbol),
+        ("outputsize", output_size.as_str()),
+    ]
+}
+
+pub(crate) fn adjusted_series_params(
+    symbol: &Symbol,
+    frequency: Frequency,
+) -> [(&'static str, &str); 2] {
+    [("function", frequency.function()), ("symbol", symbol)]
+}
+
+pub(crate) fn intraday_series_params<'a>(
+    symbol: &'a Symbol,
+    interval: &Interval,
+) -> [(&'static str, &'a str); 3] {
+    [
+        ("function", "TIME_SERIES_INTRADAY"),
+        ("symbol", symbol),
+        ("interval", interval.as_str()),
+    ]
+}
+
+// every bar of one month, e.g. `2024-01`
+pub(crate) fn intraday_month_params<'a>(
+    symbol: &'a Symbol,
+    interval: &Interval,
+    month: &'a str,
+) -> [(&'static str, &'a str); 5] {
+    [
+        ("function", "TIME_SERIES_INTRADAY"),
+        ("symbol", symbol),
+        ("interval", interval.as_str()),
+        ("month", month),
+        ("outputsize", "full"),
+    ]
+}
+
+// each month from `from`'s to `to`'s, as Alpha Vantage names them
+pub(crate) fn intraday_months(from: chrono::NaiveDate, to: chrono::NaiveDate) -> Vec<String> {
+    let mut months = vec![];
+    let mut month = from.with_day(1).unwrap_or(from);
+    while month <= to {
+        months.push(month.format("%Y-%m").to_string());
+        month = month + chrono::Months::new(1);
+    }
+    months
+}
+
+// the stitched months' bars from `from` to `to`
+pub(crate) fn assemble_intraday(
+    months: Paginator<IntradaySeries>,
+    from: chrono::NaiveDate,
+    to: chrono::NaiveDate,
+) -> Result<IntradaySeries, ApiError> {
+    let (metadata, mut bars) = months.finish().ok_or(ApiError::NoData)?;
+    bars.retain(|datetime, _bar| (from..=to).contains(&datetime.date()));
+    if bars.is_empty() {
+        return Err(ApiError::NoData);
+    }
+    Ok(IntradaySeries { metadata, bars })
+}
+
+// Alpha Vantage's premium data for US stocks: quotes, and daily and intraday
+// series, are 15 minutes delayed on the free tier
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Entitlement {
+    Realtime,
+    Delayed,
+}
+impl Entitlement {
+    fn as_str(self) -> &'static str {
+        match self {
+            Entitlement::Realtime => "realtime",
+            Entitlement::Delayed => "delayed",
+        }
+    }
+}
+
+// how time series are requested; CSV is a fraction of the size of Alpha
+// Vantage's JSON and quicker to parse, but has none of its metadata
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum DataType {
+    #[default]
+    Json,
+    Csv,
+}
+
+// bodies are asked for compressed, which full histories are a fraction of
+// the size as
+pub(crate) const ACCEPT_ENCODING: &str = "gzip, deflate";
+
+pub(crate) fn content_encoding(headers: &reqwest::header::HeaderMap) -> Option<String> {
+    headers
+        .get(reqwest::header::CONTENT_ENCODING)?
+        .to_str()
+        .ok()
+        .map(|encoding| encoding.trim().to_lowercase())
+}
+
+pub(crate) fn decompress(encoding: Option<&str>, bytes: &[u8]) -> Result<String, ApiError> {
+    let mut body = String::new();
+    match encoding {
+        Some("gzip") => flate2::read::GzDecoder::new(bytes).read_to_string(&mut body),
+        Some("deflate") => flate2::read::ZlibDecoder::new(bytes).read_to_string(&mut body),
+        _ => return Ok(String::from_utf8_lossy(bytes).into_owned()),
+    }
+    .map_err(ApiError::Decompression)?;
+    Ok(body)
+}
+
+pub(crate) const API_KEY_VAR: &str = "VANTAGE_API_KEY";
+
+// answers requests in place of Alpha Vantage, e.g. with `testing::Fixtures`. Gets the
+// query parameters without the API key and returns the response body.
+pub trait Transport: Send + Sync {
+    fn get(&self, params: &[(&str, &str)]) -> Result<String, ApiError>;
+}
+
+pub(crate) struct ClientOptions {
+    // `None` reads `VANTAGE_API_KEY` when a request is first made
+    api_key: Option<String>,
+    pub(crate) cache: Option<Cache>,
+    pub(crate) rate_limiter: Arc<RateLimiter>,
+    pub(crate) retry: RetryPolicy,
+    pub(crate) max_concurrency: usize,
+    // answers only from the cache, however old
+    pub(crate) offline: bool,
+    pub(crate) base_url: String,
+    pub(crate) transport: Option<Arc<dyn Transport>>,
+    pub(crate) recording: Option<Recording>,
+    entitlement: Option<Entitlement>,
+    datatype: DataType,
+}
+impl ClientOptions {
+    fn new(api_key: Option<String>) -> ClientOptions {
+        ClientOptions {
+            api_key,
+            cache: None,
+            rate_limiter: Arc::new(RateLimiter::new(ApiTier::Free)),
+            retry: RetryPolicy::default(),
+            max_concurrency: 4,
+            offline: false,
+            base_url: API_URL.to_string(),
+            transport: None,
+            recording: None,
+            entitlement: None,
+            datatype: DataType::Json,
+        }
+    }
+
+    // a time series, entitled and in the client's data type
+    pub(crate) fn time_series<'a>(
+        &self,
+        params: &[(&'static str, &'a str)],
+    ) -> Vec<(&'static str, &'a str)> {
+        let mut params = self.entitled(params);
+        if self.datatype == DataType::Csv {
+            params.push(("datatype", "csv"));
+        }
+        params
+    }
+
+    pub(crate) fn series_decoder<T: DeserializeOwned + FromCsv>(&self) -> Decoder<T> {
+        match self.datatype {
+            DataType::Json => decode_json,
+            DataType::Csv => decode_csv_series,
+        }
+    }
+
+    pub(crate) fn merge_daily_series(&self, previous: &str, compact: &str) -> Option<String> {
+        match self.datatype {
+            DataType::Json => merge_daily_series(previous, compact),
+            DataType::Csv => merge_daily_csv(previous, compact),
+        }
+    }
+
+    // with the entitlement, for the functions it applies to, so realtime and
+    // delayed responses are cached apart
+    pub(crate) fn entitled<'a>(
+        &self,
+        params: &[(&'static str, &'a str)],
+    ) -> Vec<(&'static str, &'a str)> {
+        let mut params = params.to_vec();
+        if let Some(entitlement) = self.entitlement {
+            params.push(("entitlement", entitlement.as_str()));
+        }
+        params
+    }
+
+    pub(crate) fn premium(&self, what: &'static str) -> Result<(), ApiError> {
+        match self.rate_limiter.tier() {
+            ApiTier::Free => Err(ApiError::PremiumRequired(what)),
+            ApiTier::Premium { .. } => Ok(()),
+        }
+    }
+
+    // a month that's over never changes, so it's never downloaded again
+    pub(crate) fn finished_month(
+        &self,
+        params: &[(&str, &str)],
+        month: &str,
+    ) -> Option<Result<IntradaySeries, ApiError>> {
+        let current = chrono::Utc::now().date_naive().format("%Y-%m").to_string();
+        if month >= current.as_str() || self.offline {
+            return None;
+        }
+        let body = self.previous(params)?;
+        Some(self.parse(params, &body, self.series_decoder()))
+    }
+
+    pub(crate) fn online(&self) -> Result<(), ApiError> {
+        if self.offline {
+            return Err(ApiError::Offline(self.cache.as_ref().and_then(Cache::age)));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn api_key(&self) -> Result<String, ApiError> {
+        match &self.api_key {
+            Some(api_key) => Ok(api_key.clone()),
+            None => env::var(API_KEY_VAR).map_err(|_| ApiError::MissingCredentials(API_KEY_VAR)),
+        }
+    }
+
+    pub(crate) fn cached<T>(
+        &self,
+        params: &[(&str, &str)],
+        decode: Decoder<T>,
+    ) -> Option<Result<T, ApiError>> {
+        if self.recording.is_some() {
+            return None;
+        }
+        let cache = self.cache.as_ref()?;
+        let body = if self.offline {
+            cache.latest(params)
+        } else {
+            cache.get(params)
+        };
+        tracing::debug!(
+            function = param(params, "function"),
+            symbol = param(params, "symbol"),
+            hit = body.is_some(),
+            "cache"
+        );
+        Some(decode(params, &body?))
+    }
+
+    // when the cached response was stored, or now when there's no cache
+    pub(crate) fn retrieved_at(&self, params: &[(&str, &str)]) -> chrono::DateTime<chrono::Utc> {
+        self.cache
+            .as_ref()
+            .filter(|_cache| self.recording.is_none())
+            .and_then(|cache| cache.modified(params))
+            .map_or_else(chrono::Utc::now, chrono::DateTime::from)
+    }
+
+    pub(crate) fn previous(&self, params: &[(&str, &str)]) -> Option<String> {
+        if self.recording.is_some() {
+            return None;
+        }
+        self.cache.as_ref()?.latest(params)
+    }
+
+    // how long to wait before the request can be made
+    pub(crate) fn reserve(&self, params: &[(&str, &str)]) -> Result<Duration, ApiError> {
+        let wait = self.rate_limiter.reserve()?;
+        if !wait.is_zero() {
+            tracing::debug!(
+                function = param(params, "function"),
+                symbol = param(params, "symbol"),
+                wait = ?wait,
+                "waiting for the rate limit"
+            );
+        }
+        Ok(wait)
+    }
+
+    pub(crate) fn retrying(
+        &self,
+        params: &[(&str, &str)],
+        attempt: u32,
+        err: &ApiError,
+    ) -> Duration {
+        let delay = self.retry.delay(attempt, err);
+        tracing::debug!(
+            function = param(params, "function"),
+            symbol = param(params, "symbol"),
+            attempt,
+            delay = ?delay,
+            error = %err,
+            "retrying"
+        );
+        delay
+    }
+
+    pub(crate) fn replayed(&self, params: &[(&str, &str)]) -> Option<Result<String, ApiError>> {
+        self.recording.as_ref()?.replayed(params)
+    }
+
+    pub(crate) fn record(&self, params: &[(&str, &str)], body: &str) -> Result<(), ApiError> {
+        match &self.recording {
+            Some(recording) => recording.save(params, body),
+            None => Ok(()),
+        }
+    }
+
+    pub(crate) fn parse<T>(
+        &self,
+        params: &[(&str, &str)],
+        body: &str,
+        decode: Decoder<T>,
+    ) -> Result<T, ApiError> {
+        let parsed = decode(params, body)?;
+        let replaying = self
+            .recording
+            .as_ref()
+            .is_some_and(|recording| recording.mode() == RecordingMode::Replay);
+        if let (Some(cache), false) = (&self.cache, replaying) {
+            cache.put(params, body);
+        }
+        Ok(parsed)
+    }
+}
+
+pub(crate) type Decoder<T> = fn(&[(&str, &str)], &str) -> Result<T, ApiError>;
+
+// the body as it came, once it's known not to be an error
+pub(crate) fn decode_body(params: &[(&str, &str)], body: &str) -> Result<String, ApiError> {
+    // errors come as JSON whatever the data type
+    if !body.trim_start().starts_with('{') {
+        return Ok(body.to_string());
+    }
+    let value: serde_json::Value = serde_json::from_str(body)?;
+    match error_from_payload(params, &value) {
+        Some(err) => Err(err),
+        None => Ok(body.to_string()),
+    }
+}
+
+const DAILY_SERIES_KEY: &str = "Time Series (Daily)";
+
+// a full daily history cached on an earlier day, with the days since from a compact
+// one. None if the compact history doesn't reach back to it, or if a dividend or
+// split since then has changed the adjusted closes it holds.
+pub(crate) fn merge_daily_series(previous: &str, compact: &str) -> Option<String> {
+    let mut merged: serde_json::Value = serde_json::from_str(previous).ok()?;
+    let compact: serde_json::Value = serde_json::from_str(compact).ok()?;
+    let recent = compact.get(DAILY_SERIES_KEY)?.as_object()?;
+    let days = merged.get_mut(DAILY_SERIES_KEY)?.as_object_mut()?;
+
+    let latest = days.keys().max()?.clone();
+    if *recent.keys().min()? > latest {
+        return None;
+    }
+    for (date, day) in recent.iter().filter(|(date, _day)| **date > latest) {
+        let field = |key: &str| day.get(key)?.as_str()?.parse::<f64>().ok();
+        let dividend = field("7. dividend amount").unwrap_or(0.0);
+        let split = field("8. split coefficient").unwrap_or(1.0);
+        if dividend != 0.0 || split != 1.0 {
+            return None;
+        }
+        days.insert(date.clone(), day.clone());
+    }
+
+    let refreshed = compact.pointer("/Meta Data/3. Last Refreshed");
+    let metadata = merged
+        .get_mut("Meta Data")
+        .and_then(|meta| meta.as_object_mut());
+    if let (Some(refreshed), Some(metadata)) = (refreshed, metadata) {
+        metadata.insert("3. Last Refreshed".to_string(), refreshed.clone());
+    }
+    Some(merged.to_string())
+}
+
+// as `merge_daily_series`, for CSV, whose rows are the latest first
+pub(crate) fn merge_daily_csv(previous: &str, compact: &str) -> Option<String> {
+    let read = |body: &str| -> Option<(csv::StringRecord, Vec<csv::StringRecord>)> {
+        let mut reader = csv::Reader::from_reader(body.as_bytes());
+        let headers = reader.headers().ok()?.clone();
+        let rows = reader.records().collect::<Result<_, _>>().ok()?;
+        Some((headers, rows))
+    };
+    let (headers, days) = read(previous)?;
+    let (compact_headers, recent) = read(compact)?;
+    if headers != compact_headers {
+        return None;
+    }
+    let column = |name: &str| headers.iter().position(|header| header == name);
+    let (date, dividend, split) = (
+        column("timestamp")?,
+        column("dividend_amount")?,
+        column("split_coefficient")?,
+    );
+
+    let latest = days.first()?.get(date)?;
+    if recent.last()?.get(date)? > latest {
+        return None;
+    }
+    let mut merged = csv::Writer::from_writer(vec![]);
+    merged.write_record(&headers).ok()?;
+    for day in recent
+        .iter()
+        .filter(|day| day.get(date).is_some_and(|day| day > latest))
+    {
+        let field = |index: usize| day.get(index)?.parse::<f64>().ok();
+        if field(dividend).unwrap_or(0.0) != 0.0 || field(split).unwrap_or(1.0) != 1.0 {
+            return None;
+        }
+        merged.write_record(day).ok()?;
+    }
+    for day in &days {
+        merged.write_record(day).ok()?;
+    }
+    String::from_utf8(merged.into_inner().ok()?).ok()
+}
+
+pub(crate) fn decode_csv_series<T: FromCsv>(
+    params: &[(&str, &str)],
+    body: &str,
+) -> Result<T, ApiError> {
+    let rows = decode_csv(params, body)?;
+    if rows.is_empty() {
+        return Err(ApiError::NoData);
+    }
+    T::from_csv(params, rows)
+}
+
+pub(crate) fn decode_json<T: DeserializeOwned>(
+    params: &[(&str, &str)],
+    body: &str,
+) -> Result<T, ApiError> {
+    let value: serde_json::Value = serde_json::from_str(body)?;
+    if let Some(err) = error_from_payload(params, &value) {
+        return Err(err);
+    }
+    Ok(serde_json::from_value(value)?)
+}
+
+// the calendar and listing functions answer in CSV, but still report errors as JSON
+pub(crate) fn decode_csv<T: DeserializeOwned>(
+    params: &[(&str, &str)],
+    body: &str,
+) -> Result<Vec<T>, ApiError> {
+    if body.trim_start().starts_with('{') {
+        let value: serde_json::Value = serde_json::from_str(body)?;
+        return Err(error_from_payload(params, &value).unwrap_or(ApiError::NoData));
+    }
+    csv::Reader::from_reader(body.as_bytes())
+        .deserialize()
+        .collect::<Result<Vec<T>, csv::Error>>()
+        .map_err(ApiError::from)
+}
+
+pub(crate) fn error_context(params: &[(&str, &str)]) -> ErrorContext {
+    ErrorContext::new("Alpha Vantage", param(params, "function"), params)
+}
+
+pub(crate) fn param<'a>(params: &[(&str, &'a str)], key: &str) -> &'a str {
+    params
+        .iter()
+        .find(|(name, _value)| *name == key)
+        .map_or("", |(_name, value)| value)
+}
+
+// at debug level, for `-v`
+pub(crate) fn log_request<T>(
+    provider: &str,
+    function: &str,
+    params: &[(&str, &str)],
+    started: Instant,
+    result: &Result<T, ApiError>,
+) {
+    match result {
+        Ok(_response) => tracing::debug!(
+            provider,
+            function,
+            symbol = param(params, "symbol"),
+            elapsed = ?started.elapsed(),
+            "request"
+        ),
+        Err(err) => tracing::debug!(
+            provider,
+            function,
+            symbol = param(params, "symbol"),
+            elapsed = ?started.elapsed(),
+            error = %err,
+            "request failed"
+        ),
+    }
+}
+
+// Alpha Vantage answers an unknown symbol with a bare "Invalid API call"; other
+// errors, such as a bad API key or parameter, name what was wrong
+fn about_symbol(message: &str) -> bool {
+    let lowercase = message.to_lowercase();
+    lowercase.contains("symbol")
+        || (lowercase.starts_with("invalid api call") && !lowercase.contains("parameter"))
+}
+
+fn error_from_payload(params: &[(&str, &str)], value: &serde_json::Value) -> Option<ApiError> {
+    if let Some(message) = value.get("Error Message").and_then(|v| v.as_str()) {
+        return Some(match params.iter().find(|(key, _value)| *key == "symbol") {
+            Some((_key, symbol)) if about_symbol(message) => {
+                ApiError::InvalidSymbol(symbol.to_string())
+            }
+            _ => ApiError::Api(message.to_string()),
+        });
+    }
+    if let Some(note) = value.get("Note").and_then(|v| v.as_str()) {
+        return Some(ApiError::RateLimited(note.to_string()));
+    }
+    if let Some(information) = value.get("Information").and_then(|v| v.as_str()) {
+        let lowercase = information.to_lowercase();
+        if lowercase.contains("rate limit") || lowercase.contains("call frequency") {
+            return Some(ApiError::RateLimited(information.to_string()));
+        }
+        return Some(ApiError::Api(information.to_string()));
+    }
+    None
+}
+
+pub struct PortfolioClient {
+    client: reqwest::blocking::Client,
+    options: ClientOptions,
+}
+
+pub struct PortfolioClientBuilder {
+    options: ClientOptions,
+    proxies: Vec<reqwest::Proxy>,
+    timeout: Option<Duration>,
+}
+impl PortfolioClientBuilder {
+    pub fn cache(mut self, cache: Cache) -> PortfolioClientBuilder {
+        self.options.cache = Some(cache);
+        self
+    }
+
+    pub fn tier(self, tier: ApiTier) -> PortfolioClientBuilder {
+        self.rate_limiter(Arc::new(RateLimiter::new(tier)))
+    }
+
+    pub fn rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> PortfolioClientBuilder {
+        self.options.rate_limiter = rate_limiter;
+        self
+    }
+
+    pub fn retry(mut self, retry: RetryPolicy) -> PortfolioClientBuilder {
+        self.options.retry = retry;
+        self
+    }
+
+    // for a proxy or mock server that speaks Alpha Vantage's API
+    pub fn base_url<S: Into<String>>(mut self, base_url: S) -> PortfolioClientBuilder {
+        self.options.base_url = base_url.into();
+        self
+    }
+
+    pub fn transport<T: Transport + 'static>(mut self, transport: T) -> PortfolioClientBuilder {
+        self.options.transport = Some(Arc::new(transport));
+        self
+    }
+
+    // reads every response from the recording rather than the API, or saves
+    // each one to it. Either way the cache isn't read, so every request is
+    // recorded, and a replay doesn't write to it either.
+    pub fn recording(mut self, recording: Recording) -> PortfolioClientBuilder {
+        self.options.recording = Some(recording);
+        self
+    }
+
+    // needs a premium key; realtime quotes and series for US stocks
+    pub fn entitlement(mut self, entitlement: Entitlement) -> PortfolioClientBuilder {
+        self.options.entitlement = Some(entitlement);
+        self
+    }
+
+    pub fn datatype(mut self, datatype: DataType) -> PortfolioClientBuilder {
+        self.options.datatype = datatype;
+        self
+    }
+
+    pub fn offline(mut self, offline: bool) -> PortfolioClientBuilder {
+        self.options.offline = offline;
+        self
+    }
+
+    pub fn max_concurrency(mut self, max_concurrency: usize) -> PortfolioClientBuilder {
+        self.options.max_concurrency = max_concurrency.max(1);
+        self
+    }
+
+    pub fn proxy(mut self, proxy: reqwest::Proxy) -> PortfolioClientBuilder {
+        self.proxies.push(proxy);
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> PortfolioClientBuilder {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn build(self) -> Result<PortfolioClient, ApiError> {
+        let mut client_builder = reqwest::blocking::Client::builder();
+        for proxy in self.proxies {
+            client_builder = client_builder.proxy(proxy);
+        }
+        if let Some(timeout) = self.timeout {
+            client_builder = client_builder.timeout(timeout);
+        }
+
+        Ok(PortfolioClient {
+            client: client_builder.build()?,
+            options: self.options,
+        })
+    }
+
+    pub fn build_async(self) -> Result<aio::PortfolioClient, ApiError> {
+        let mut client_builder = reqwest::Client::builder();
+        for proxy in self.proxies {
+            client_builder = client_builder.proxy(proxy);
+        }
+        if let Some(timeout) = self.timeout {
+            client_builder = client_builder.timeout(timeout);
+        }
+
+        Ok(aio::PortfolioClient::from_parts(
+            client_builder.build()?,
+            self.options,
+        ))
+    }
+}
+
+impl PortfolioClient {
+    pub fn new<S: Into<String>>(api_key: S) -> PortfolioClient {
+        PortfolioClient {
+            client: reqwest::blocking::Client::new(),
+            options: ClientOptions::new(Some(api_key.into())),
+        }
+    }
+
+    pub fn from_env() -> PortfolioClient {
+        PortfolioClient {
+            client: reqwest::blocking::Client::new(),
+            options: ClientOptions::new(None),
+        }
+    }
+
+    pub fn builder<S: Into<String>>(api_key: S) -> PortfolioClientBuilder {
+        PortfolioClientBuilder {
+            options: ClientOptions::new(Some(api_key.into())),
+            proxies: vec![],
+            timeout: None,
+        }
+    }
+
+    pub fn builder_from_env() -> PortfolioClientBuilder {
+        PortfolioClientBuilder {
+            options: ClientOptions::new(None),
+            proxies: vec![],
+            timeout: None,
+        }
+    }
+
+    fn query<T: DeserializeOwned>(&self, params: &[(&str, &str)]) -> Result<T, ApiError> {
+        self.request(params, decode_json)
+    }
+
+    fn query_csv<T: DeserializeOwned>(&self, params: &[(&str, &str)]) -> Result<Vec<T>, ApiError> {
+        self.request(params, decode_csv)
+    }
+
+    fn query_series<T: DeserializeOwned + FromCsv>(
+        &self,
+        params: &[(&str, &str)],
+    ) -> Result<T, ApiError> {
+        self.request(params, self.options.series_decoder())
+    }
+
+    fn request<T>(&self, params: &[(&str, &str)], decode: Decoder<T>) -> Result<T, ApiError> {
+        if let Some(cached) = self.options.cached(params, decode) {
+            return cached.map_err(|err| err.with_context(error_context(params)));
+        }
+        self.download(params, decode)
+    }
+
+    fn download<T>(&self, params: &[(&str, &str)], decode: Decoder<T>) -> Result<T, ApiError> {
+        let mut attempt = 1;
+        loop {
+            match self.fetch(params, decode) {
+                Err(err) if self.options.retry.should_retry(attempt, &err) => {
+                    thread::sleep(self.options.retrying(params, attempt, &err));
+                    attempt += 1;
+                }
+                result => return result.map_err(|err| err.with_context(error_context(params))),
+            }
+        }
+    }
+
+    fn fetch<T>(&self, params: &[(&str, &str)], decode: Decoder<T>) -> Result<T, ApiError> {
+        if let Some(body) = self.options.replayed(params) {
+            return self.options.parse(params, &body?, decode);
+        }
+        self.options.online()?;
+        let api_key = self.options.api_key()?;
+        thread::sleep(self.options.reserve(params)?);
+
+        let started = Instant::now();
+        let body = match &self.options.transport {
+            Some(transport) => transport.get(params),
+            None => self
+                .client
+                .get(&self.options.base_url)
+                .header(reqwest::header::ACCEPT_ENCODING, ACCEPT_ENCODING)
+                .query(params)
+                .query(&[("apikey", &api_key)])
+                .send()
+                .and_then(|resp| resp.error_for_status())
+                .and_then(|resp| {
+                    let encoding = content_encoding(resp.headers());
+                    Ok((encoding, resp.bytes()?))
+                })
+                // the URL holds the API key
+                .map_err(|err| ApiError::from(err.without_url()))
+                .and_then(|(encoding, bytes)| decompress(encoding.as_deref(), &bytes)),
+        };
+        log_request(
+            "Alpha Vantage",
+            param(params, "function"),
+            params,
+            started,
+            &body,
+        );
+        let body = body?;
+
+        self.options.record(params, &body)?;
+        self.options.parse(params, &body, decode)
+    }
+
+    // a full history cached on an earlier day is brought up to date from the
+    // compact one where it can be, rather than downloaded again
+    pub fn daily_series(
+        &self,
+        symbol: Symbol,
+        output_size: OutputSize,
+    ) -> Result<DailySeries, ApiError> {
+        let params = self
+            .options
+            .time_series(&daily_series_params(&symbol, &output_size));
+        if let OutputSize::Compact = output_size {
+            return self.query_series(&params);
+        }
+        let decode = self.options.series_decoder();
+        if let Some(cached) = self.options.cached(&params, decode) {
+            return cached.map_err(|err| err.with_context(error_context(&params)));
+        }
+        if let Some(previous) = self.options.previous(&params) {
+            let compact = self.request(
+                &self
+                    .options
+                    .time_series(&daily_series_params(&symbol, &OutputSize::Compact)),
+                decode_body,
+            )?;
+            if let Some(merged) = self.options.merge_daily_series(&previous, &compact) {
+                return self
+                    .options
+                    .parse(&params, &merged, decode)
+                    .map_err(|err| err.with_context(error_context(&params)));
+            }
+        }
+        self.download(&params, decode)
+    }
+
+    pub fn series(&self, symbol: Symbol, frequency: Frequency) -> Result<Series, ApiError> {
+        match frequency {
+            Frequency::Daily => self
+                .daily_series(symbol, OutputSize::Full)
+                .map(Series::from),
+            Frequency::Weekly | Frequency::Monthly => self
+                .query_series::<AdjustedSeriesResponse>(
+                    &self
+                        .options
+                        .time_series(&adjusted_series_params(&symbol, frequency)),
+                )
+                .map(|response| response.into_series(frequency)),
+        }
+    }
+
+    pub fn intraday_series(
+        &self,
+        symbol: Symbol,
+        interval: Interval,
+    ) -> Result<IntradaySeries, ApiError> {
+        self.query_series(
+            &self
+                .options
+                .time_series(&intraday_series_params(&symbol, &interval)),
+        )
+    }
+
+    // a premium history of whole months, from the one `from` is in to `to`'s,
+    // as one series; each month is a request
+    pub fn intraday_history(
+        &self,
+        symbol: Symbol,
+        interval: Interval,
+        from: chrono::NaiveDate,
+        to: chrono::NaiveDate,
+    ) -> Result<IntradaySeries, ApiError> {
+        self.options.premium("extended intraday history")?;
+        let pages = intraday_months(from, to);
+        let mut months = Paginator::new(pages.len(), &self.options.rate_limiter)?;
+        for month in pages {
+            let params = self
+                .options
+                .time_series(&intraday_month_params(&symbol, &interval, &month));
+            let series = match self.options.finished_month(&params, &month) {
+                Some(series) => series.map_err(|err| err.with_context(error_context(&params)))?,
+                None => self.query_series(&params)?,
+            };
+            months.push(series);
+        }
+        assemble_intraday(months, from, to)
+    }
+
+    pub fn quote(&self, symbol: Symbol) -> Result<Quote, ApiError> {
+        let params = self.options.entitled(&quote_params(&symbol));
+        let mut quote = self
+            .query::<GlobalQuoteResponse>(&params)
+            .and_then(GlobalQuoteResponse::into_quote)?;
+        quote.retrieved_at = Some(self.options.retrieved_at(&params));
+        Ok(quote)
+    }
+
+    pub fn quotes(&self, symbols: &[Symbol]) -> Result<Vec<Quote>, ApiError> {
+        if symbols.is_empty() {
+            return Ok(vec![]);
+        }
+        let workers = self.options.max_concurrency.min(symbols.len());
+
+        let mut results: Vec<Option<Result<Quote, ApiError>>> =
+            symbols.iter().map(|_symbol| None).collect();
+        thread::scope(|scope| {
+            let handles: Vec<_> = (0..workers)
+                .map(|worker| {
+                    scope.spawn(move || {
+                        symbols
+                            .iter()
+                            .enumerate()
+                            .skip(worker)
+                            .step_by(workers)
+                            .map(|(idx, symbol)| (idx, self.quote(symbol.clone())))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+
+            for handle in handles {
+                for (idx, result) in handle.join().expect("quote worker panicked") {
+                    results[idx] = Some(result);
+                }
+            }
+        });
+
+        results
+            .into_iter()
+            .map(|result| result.expect("every symbol is assigned to a worker"))
+            .collect()
+    }
+
+    // the full daily history of each symbol, up to `max_concurrency` at once and
+    // within the rate limit, so that a cache given to the client is refreshed
+    pub fn refresh_all<F>(
+        &self,
+        symbols: &[Symbol],
+        progress: F,
+    ) -> Vec<Result<DailySeries, ApiError>>
+    where
+        F: Fn(Progress) + Sync,
+    {
+        let next = AtomicUsize::new(0);
+        let completed = AtomicUsize::new(0);
+        let results: Mutex<Vec<Option<Result<DailySeries, ApiError>>>> =
+            Mutex::new(symbols.iter().map(|_symbol| None).collect());
+        thread::scope(|scope| {
+            for _worker in 0..self.options.max_concurrency.min(symbols.len()) {
+                // each worker takes the next symbol, so one slow download holds up no others
+                scope.spawn(|| loop {
+                    let idx = next.fetch_add(1, Ordering::Relaxed);
+                    let symbol = match symbols.get(idx) {
+                        Some(symbol) => symbol,
+                        None => break,
+                    };
+                    let result = self.daily_series(symbol.clone(), OutputSize::Full);
+                    let mut results = results.lock().unwrap();
+                    progress(Progress {
+                        symbol,
+                        completed: completed.fetch_add(1, Ordering::Relaxed) + 1,
+                        total: symbols.len(),
+                        error: result.as_ref().err(),
+                    });
+                    results[idx] = Some(result);
+                });
+            }
+        });
+
+        results
+            .into_inner()
+            .unwrap()
+            .into_iter()
+            .map(|result| result.expect("every symbol is taken by a worker"))
+            .collect()
+    }
+
+    pub fn company_overview(&self, symbol: Symbol) -> Result<CompanyOverview, ApiError> {
+        self.query(&overview_params(&symbol))
+            .and_then(overview_from_value)
+    }
+
+    pub fn earnings(&self, symbol: Symbol) -> Result<Earnings, ApiError> {
+        self.query(&fundamentals_params("EARNINGS", &symbol))
+    }
+
+    pub fn income_statement(
+        &self,
+        symbol: Symbol,
+    ) -> Result<Statements<IncomeStatement>, ApiError> {
+        self.query(&fundamentals_params("INCOME_STATEMENT", &symbol))
+    }
+
+    pub fn balance_sheet(&self, symbol: Symbol) -> Result<Statements<BalanceSheet>, ApiError> {
+        self.query(&fundamentals_params("BALANCE_SHEET", &symbol))
+    }
+
+    pub fn cash_flow(&self, symbol: Symbol) -> Result<Statements<CashFlow>, ApiError> {
+        self.query(&fundamentals_params("CASH_FLOW", &symbol))
+    }
+
+    pub fn exchange_rate(&self, from: &str, to: &str) -> Result<ExchangeRate, ApiError> {
+        self.query::<ExchangeRateResponse>(&exchange_rate_params(from, to))
+            .map(|response| response.rate)
+    }
+
+    pub fn fx_daily_series(
+        &self,
+        from: &str,
+        to: &str,
+        output_size: OutputSize,
+    ) -> Result<FxDailySeries, ApiError> {
+        self.query(&fx_daily_params(from, to, &output_size))
+    }
+
+    pub fn crypto_daily_series(
+        &self,
+        symbol: Symbol,
+        market: &str,
+    ) -> Result<CryptoDailySeries, ApiError> {
+        self.query(&crypto_daily_params(&symbol, market))
+    }
+
+    pub fn crypto_quote(&self, symbol: Symbol, market: &str) -> Result<Quote, ApiError> {
+        let mut quote = self
+            .crypto_daily_series(symbol.clone(), market)?
+            .to_quote()?;
+        quote.retrieved_at = Some(
+            self.options
+                .retrieved_at(&crypto_daily_params(&symbol, market)),
+        );
+        Ok(quote)
+    }
+
+    pub fn indicator(
+        &self,
+        symbol: Symbol,
+        indicator: Indicator,
+        source: Source,
+    ) -> Result<IndicatorOutput, ApiError> {
+        match source {
+            Source::Local => {
+                let series = self.daily_series(symbol, OutputSize::Full)?;
+                Ok(indicators::compute(&series, indicator))
+            }
+            Source::Remote => {
+                let params = indicators::remote_params(&symbol, indicator);
+                let params: Vec<(&str, &str)> = params
+                    .iter()
+                    .map(|(key, value)| (*key, value.as_str()))
+                    .collect();
+                self.query(&params)
+                    .and_then(|value| indicators::remote_output(indicator, value))
+            }
+        }
+    }
+
+    pub fn search_symbols(&self, keywords: &str) -> Result<Vec<SymbolMatch>, ApiError> {
+        self.query::<SymbolSearchResponse>(&symbol_search_params(keywords))
+            .map(|response| response.best_matches)
+    }
+
+    pub fn earnings_calendar(&self) -> Result<Vec<EarningsEvent>, ApiError> {
+        self.query_csv(&EARNINGS_CALENDAR_PARAMS)
+    }
+
+    pub fn upcoming_ipos(&self) -> Result<Vec<IpoEvent>, ApiError> {
+        self.query_csv(&IPO_CALENDAR_PARAMS)
+    }
+
+    pub fn listed_symbols(&self) -> Result<Vec<Listing>, ApiError> {
+        self.query_csv(&LISTING_STATUS_PARAMS)
+    }
+
+    pub fn economic_indicator(
+        &self,
+        indicator: EconomicIndicator,
+    ) -> Result<EconomicSeries, ApiError> {
+        self.query::<EconomicResponse>(&indicator.params())
+            .map(EconomicResponse::into_series)
+    }
+
+    pub fn sector_performance(&self) -> Result<SectorPerformance, ApiError> {
+        self.query(&SECTOR_PARAMS)
+    }
+
+    pub fn news(&self, symbol: Symbol, limit: usize) -> Result<Vec<NewsArticle>, ApiError> {
+        let mut feed = self.query::<NewsResponse>(&news_params(&symbol))?.feed;
+        feed.truncate(limit);
+        Ok(feed)
+    }
+
+    pub fn get_latest_price_for_equity(&self, symbol: Symbol) -> Result<f64, ApiError> {
+        Ok(self.quote(symbol)?.price)
+    }
+
+    pub fn summary_for_equity(
+        &self,
+        symbol: Symbol,
+        time_period: TimePeriod,
+    ) -> Result<EquitySummary, ApiError> {
+        crate::summary(self, symbol, time_period)
+    }
+
+    pub fn dividend_history(&self, symbol: Symbol) -> Result<DividendHistory, ApiError> {
+        crate::dividend_history(self, symbol)
+    }
+}
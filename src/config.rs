@@ -0,0 +1,77 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{config_dir, StorageError};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiKeys {
+    pub alpha_vantage: Option<String>,
+    pub finnhub: Option<String>,
+    pub openfigi: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub api_keys: ApiKeys,
+    pub provider: Option<String>,
+    // `free`, or a premium Alpha Vantage plan's requests per minute
+    pub tier: Option<String>,
+    pub base_currency: Option<String>,
+    pub cache_dir: Option<PathBuf>,
+    pub output: Option<String>,
+}
+impl Config {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Config, StorageError> {
+        let path = path.as_ref();
+        let read = || match fs::read_to_string(path) {
+            Ok(contents) => Ok(toml::from_str(&contents)?),
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(err) => Err(err.into()),
+        };
+        read().map_err(StorageError::in_file(path))
+    }
+
+    // environment variables override the file
+    pub fn with_env(mut self) -> Config {
+        let var = |name| {
+            env::var(name)
+                .ok()
+                .filter(|value: &String| !value.is_empty())
+        };
+        if let Some(key) = var("VANTAGE_API_KEY") {
+            self.api_keys.alpha_vantage = Some(key);
+        }
+        if let Some(key) = var("FINNHUB_API_KEY") {
+            self.api_keys.finnhub = Some(key);
+        }
+        if let Some(key) = var("OPENFIGI_API_KEY") {
+            self.api_keys.openfigi = Some(key);
+        }
+        if let Some(provider) = var("PORTFOLIO_PROVIDER") {
+            self.provider = Some(provider);
+        }
+        if let Some(tier) = var("PORTFOLIO_TIER") {
+            self.tier = Some(tier);
+        }
+        if let Some(base_currency) = var("PORTFOLIO_BASE_CURRENCY") {
+            self.base_currency = Some(base_currency);
+        }
+        if let Some(cache_dir) = var("PORTFOLIO_CACHE_DIR") {
+            self.cache_dir = Some(PathBuf::from(cache_dir));
+        }
+        if let Some(output) = var("PORTFOLIO_OUTPUT") {
+            self.output = Some(output);
+        }
+        self
+    }
+}
+
+pub fn default_config_path() -> Result<PathBuf, StorageError> {
+    Ok(config_dir()?.join("config.toml"))
+}
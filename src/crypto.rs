@@ -0,0 +1,99 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_aux::field_attributes::deserialize_number_from_string;
+
+use crate::series::{deserialize_last_refreshed, to_utc};
+use crate::{ApiError, Quote};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CryptoMetadata {
+    #[serde(rename(deserialize = "1. Information"))]
+    pub information: String,
+    #[serde(rename(deserialize = "2. Digital Currency Code"))]
+    pub symbol: String,
+    #[serde(rename(deserialize = "3. Digital Currency Name"))]
+    pub name: String,
+    #[serde(rename(deserialize = "4. Market Code"))]
+    pub market: String,
+    #[serde(rename(deserialize = "5. Market Name"))]
+    pub market_name: String,
+    #[serde(
+        rename(deserialize = "6. Last Refreshed"),
+        deserialize_with = "deserialize_last_refreshed"
+    )]
+    pub last_refreshed: chrono::NaiveDateTime,
+    #[serde(rename(deserialize = "7. Time Zone"))]
+    pub time_zone: String,
+}
+impl CryptoMetadata {
+    pub fn last_refreshed_utc(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        to_utc(self.last_refreshed, &self.time_zone)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CryptoBar {
+    #[serde(
+        rename(deserialize = "1. open"),
+        deserialize_with = "deserialize_number_from_string"
+    )]
+    pub open: f64,
+    #[serde(
+        rename(deserialize = "2. high"),
+        deserialize_with = "deserialize_number_from_string"
+    )]
+    pub high: f64,
+    #[serde(
+        rename(deserialize = "3. low"),
+        deserialize_with = "deserialize_number_from_string"
+    )]
+    pub low: f64,
+    #[serde(
+        rename(deserialize = "4. close"),
+        deserialize_with = "deserialize_number_from_string"
+    )]
+    pub close: f64,
+    #[serde(
+        rename(deserialize = "5. volume"),
+        deserialize_with = "deserialize_number_from_string"
+    )]
+    pub volume: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CryptoDailySeries {
+    #[serde(rename(deserialize = "Meta Data"))]
+    pub metadata: CryptoMetadata,
+    #[serde(rename(deserialize = "Time Series (Digital Currency Daily)"))]
+    pub days: BTreeMap<chrono::NaiveDate, CryptoBar>,
+}
+impl CryptoDailySeries {
+    pub fn latest(&self) -> Option<(&chrono::NaiveDate, &CryptoBar)> {
+        self.days.iter().next_back()
+    }
+
+    pub fn earliest(&self) -> Option<(&chrono::NaiveDate, &CryptoBar)> {
+        self.days.iter().next()
+    }
+
+    pub fn to_quote(&self) -> Result<Quote, ApiError> {
+        let mut days = self.days.iter().rev();
+        let (date, latest) = days.next().ok_or(ApiError::NoData)?;
+        let previous_close = days.next().map_or(latest.open, |(_date, day)| day.close);
+
+        Ok(Quote {
+            symbol: self.metadata.symbol.clone(),
+            open: latest.open,
+            high: latest.high,
+            low: latest.low,
+            price: latest.close,
+            volume: latest.volume,
+            latest_trading_day: *date,
+            previous_close,
+            change: latest.close - previous_close,
+            change_percent: (latest.close - previous_close) / previous_close * 100.0,
+            retrieved_at: None,
+        })
+    }
+}
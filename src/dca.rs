@@ -0,0 +1,157 @@
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use crate::{metrics, ApiError, Series};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Schedule {
+    Weekly,
+    Fortnightly,
+    Monthly,
+    Quarterly,
+}
+impl Schedule {
+    // counted from the start so that monthly purchases don't drift after a short month
+    fn nth(self, start: chrono::NaiveDate, n: u32) -> chrono::NaiveDate {
+        match self {
+            Schedule::Weekly => start + chrono::Duration::weeks(n.into()),
+            Schedule::Fortnightly => start + chrono::Duration::weeks(2 * i64::from(n)),
+            Schedule::Monthly => start + chrono::Months::new(n),
+            Schedule::Quarterly => start + chrono::Months::new(3 * n),
+        }
+    }
+}
+impl FromStr for Schedule {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<Schedule, ApiError> {
+        match s.to_lowercase().as_str() {
+            "week" | "weekly" => Ok(Schedule::Weekly),
+            "fortnight" | "fortnightly" => Ok(Schedule::Fortnightly),
+            "month" | "monthly" => Ok(Schedule::Monthly),
+            "quarter" | "quarterly" => Ok(Schedule::Quarterly),
+            _ => Err(ApiError::Api(format!("unknown schedule `{}`", s))),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct DcaPurchase {
+    pub date: chrono::NaiveDate,
+    pub price: f64,
+    pub units: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DcaSimulation {
+    pub amount: f64,
+    pub schedule: Schedule,
+    pub start_date: chrono::NaiveDate,
+    pub end_date: chrono::NaiveDate,
+    pub purchases: Vec<DcaPurchase>,
+    pub units: f64,
+    pub dividends: f64,
+    pub latest_price: f64,
+}
+impl DcaSimulation {
+    pub fn invested(&self) -> f64 {
+        self.amount * self.purchases.len() as f64
+    }
+
+    pub fn market_value(&self) -> f64 {
+        self.units * self.latest_price
+    }
+
+    pub fn gain(&self) -> f64 {
+        self.market_value() + self.dividends - self.invested()
+    }
+
+    pub fn average_price(&self) -> f64 {
+        self.invested() / self.units
+    }
+
+    pub fn annualised_return(&self) -> Option<f64> {
+        let mut flows: Vec<(chrono::NaiveDate, f64)> = self
+            .purchases
+            .iter()
+            .map(|purchase| (purchase.date, -self.amount))
+            .collect();
+        flows.push((self.end_date, self.market_value() + self.dividends));
+        metrics::xirr(&flows)
+    }
+}
+
+// invests `amount` at the close of the first trading day on or after each
+// scheduled date; units follow later splits and dividends are kept as cash
+pub fn simulate_dca(
+    series: &Series,
+    amount: f64,
+    schedule: Schedule,
+    start_date: chrono::NaiveDate,
+) -> Option<DcaSimulation> {
+    let mut purchases = vec![];
+    let mut units = 0.0;
+    let mut dividends = 0.0;
+    let mut scheduled = 0;
+    for (date, bar) in series.range(start_date..) {
+        units *= bar.split_coefficient;
+        dividends += units * bar.dividend_amount;
+
+        if *date >= schedule.nth(start_date, scheduled) && bar.close > 0.0 {
+            let bought = amount / bar.close;
+            units += bought;
+            purchases.push(DcaPurchase {
+                date: *date,
+                price: bar.close,
+                units: bought,
+            });
+            while schedule.nth(start_date, scheduled) <= *date {
+                scheduled += 1;
+            }
+        }
+    }
+
+    let (end_date, latest) = series.latest()?;
+    if purchases.is_empty() {
+        return None;
+    }
+    Some(DcaSimulation {
+        amount,
+        schedule,
+        start_date,
+        end_date: *end_date,
+        purchases,
+        units,
+        dividends,
+        latest_price: latest.close,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    #[test]
+    fn purchases_follow_splits() {
+        // weekly from 2024-01-01, with a 2:1 split on 2024-01-29
+        let series = Series::from(testing::split_series("IBM", 28));
+        let start = "2024-01-01".parse().unwrap();
+        let simulation = simulate_dca(&series, 100.0, Schedule::Weekly, start).unwrap();
+        assert_eq!(simulation.purchases.len(), 8);
+        assert_eq!(simulation.units, 16.0);
+        assert_eq!(simulation.average_price(), 50.0);
+        assert_eq!(simulation.gain(), 0.0);
+    }
+
+    #[test]
+    fn monthly_purchases_do_not_drift() {
+        let start = "2024-01-31".parse().unwrap();
+        let dates: Vec<String> = (0..3)
+            .map(|n| Schedule::Monthly.nth(start, n).to_string())
+            .collect();
+        assert_eq!(dates, ["2024-01-31", "2024-02-29", "2024-03-31"]);
+    }
+}
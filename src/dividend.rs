@@ -0,0 +1,157 @@
+use serde::Serialize;
+
+use crate::{ApiError, OutputSize, Symbol, TimeSeriesDay, TimeSeriesProvider};
+
+const MONTHS: usize = 12;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DividendPayment {
+    pub date: chrono::NaiveDate,
+    pub amount: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DividendHistory {
+    pub symbol: Symbol,
+    pub latest_date: chrono::NaiveDate,
+    pub latest_price: f64,
+    pub payments: Vec<DividendPayment>,
+}
+impl DividendHistory {
+    pub fn trailing_twelve_months(&self) -> f64 {
+        let start = self.latest_date - chrono::Duration::days(365);
+        self.payments
+            .iter()
+            .filter(|payment| payment.date > start)
+            .map(|payment| payment.amount)
+            .sum()
+    }
+
+    pub fn trailing_yield(&self) -> f64 {
+        self.trailing_twelve_months() / self.latest_price * 100.0
+    }
+}
+
+pub(crate) fn payments<'a, I>(bars: I) -> Vec<DividendPayment>
+where
+    I: IntoIterator<Item = (&'a chrono::NaiveDate, &'a TimeSeriesDay)>,
+{
+    bars.into_iter()
+        .filter(|(_date, day)| day.dividend_amount > 0.0)
+        .map(|(date, day)| DividendPayment {
+            date: *date,
+            amount: day.dividend_amount,
+        })
+        .collect()
+}
+
+pub fn dividend_history<P: TimeSeriesProvider + ?Sized>(
+    provider: &P,
+    symbol: Symbol,
+) -> Result<DividendHistory, ApiError> {
+    let series = provider.daily_series(symbol.clone(), OutputSize::Full)?;
+    let (latest_date, latest_price) = series
+        .latest()
+        .map(|(date, day)| (*date, day.close))
+        .ok_or(ApiError::NoData)?;
+
+    Ok(DividendHistory {
+        symbol,
+        latest_date,
+        latest_price,
+        payments: series.dividends(),
+    })
+}
+
+// the payments per share of the twelve months before `start`, a year on, and
+// scaled to `forward_rate` (the declared annual dividend) when there is one
+pub fn projected_dividends(
+    payments: &[DividendPayment],
+    forward_rate: Option<f64>,
+    start: chrono::NaiveDate,
+) -> Vec<DividendPayment> {
+    let year = chrono::Months::new(12);
+    let trailing: Vec<&DividendPayment> = payments
+        .iter()
+        .filter(|payment| payment.date >= start - year && payment.date < start)
+        .collect();
+    let trailing_total: f64 = trailing.iter().map(|payment| payment.amount).sum();
+    let scale = match forward_rate {
+        Some(rate) if rate > 0.0 && trailing_total > 0.0 => rate / trailing_total,
+        _ => 1.0,
+    };
+    trailing
+        .into_iter()
+        .map(|payment| DividendPayment {
+            date: payment.date + year,
+            amount: payment.amount * scale,
+        })
+        .collect()
+}
+
+#[derive(Debug, Serialize)]
+pub struct HoldingIncome {
+    pub symbol: Symbol,
+    pub quantity: f64,
+    // the expected payments per share
+    pub payments: Vec<DividendPayment>,
+    // income in each of the forecast's months
+    pub monthly: Vec<f64>,
+    pub total: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IncomeForecast {
+    // the first day of each month
+    pub months: Vec<chrono::NaiveDate>,
+    pub holdings: Vec<HoldingIncome>,
+    pub monthly: Vec<f64>,
+    pub total: f64,
+}
+impl IncomeForecast {
+    // twelve months of income from `start`, a first of the month, with each
+    // holding's quantity and payments per share (in the currency the income
+    // should be in, e.g. converted with the holding's exchange rate).
+    pub fn new<I>(start: chrono::NaiveDate, holdings: I) -> IncomeForecast
+    where
+        I: IntoIterator<Item = (Symbol, f64, Vec<DividendPayment>)>,
+    {
+        let months: Vec<chrono::NaiveDate> = (0..MONTHS as u32)
+            .map(|month| start + chrono::Months::new(month))
+            .collect();
+        let month_of = |date: chrono::NaiveDate| {
+            months
+                .iter()
+                .rposition(|month| *month <= date)
+                .filter(|_idx| date < start + chrono::Months::new(MONTHS as u32))
+        };
+
+        let holdings: Vec<HoldingIncome> = holdings
+            .into_iter()
+            .map(|(symbol, quantity, payments)| {
+                let mut monthly = vec![0.0; MONTHS];
+                for payment in &payments {
+                    if let Some(idx) = month_of(payment.date) {
+                        monthly[idx] += quantity * payment.amount;
+                    }
+                }
+                HoldingIncome {
+                    symbol,
+                    quantity,
+                    payments,
+                    total: monthly.iter().sum(),
+                    monthly,
+                }
+            })
+            .collect();
+        let monthly: Vec<f64> = (0..MONTHS)
+            .map(|idx| holdings.iter().map(|holding| holding.monthly[idx]).sum())
+            .collect();
+        IncomeForecast {
+            months,
+            total: monthly.iter().sum(),
+            holdings,
+            monthly,
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ApiError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum Maturity {
+    #[serde(rename = "3month")]
+    ThreeMonth,
+    #[serde(rename = "2year")]
+    TwoYear,
+    #[serde(rename = "5year")]
+    FiveYear,
+    #[serde(rename = "7year")]
+    SevenYear,
+    #[serde(rename = "10year")]
+    TenYear,
+    #[serde(rename = "30year")]
+    ThirtyYear,
+}
+impl Maturity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Maturity::ThreeMonth => "3month",
+            Maturity::TwoYear => "2year",
+            Maturity::FiveYear => "5year",
+            Maturity::SevenYear => "7year",
+            Maturity::TenYear => "10year",
+            Maturity::ThirtyYear => "30year",
+        }
+    }
+}
+impl FromStr for Maturity {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<Maturity, ApiError> {
+        match s.to_lowercase().as_str() {
+            "3m" | "3month" => Ok(Maturity::ThreeMonth),
+            "2y" | "2year" => Ok(Maturity::TwoYear),
+            "5y" | "5year" => Ok(Maturity::FiveYear),
+            "7y" | "7year" => Ok(Maturity::SevenYear),
+            "10y" | "10year" => Ok(Maturity::TenYear),
+            "30y" | "30year" => Ok(Maturity::ThirtyYear),
+            _ => Err(ApiError::Api(format!("unknown maturity `{}`", s))),
+        }
+    }
+}
+
+// monthly, apart from real GDP which is quarterly
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EconomicIndicator {
+    TreasuryYield(Maturity),
+    Cpi,
+    FederalFundsRate,
+    RealGdp,
+}
+impl EconomicIndicator {
+    pub(crate) fn params(self) -> Vec<(&'static str, &'static str)> {
+        match self {
+            EconomicIndicator::TreasuryYield(maturity) => vec![
+                ("function", "TREASURY_YIELD"),
+                ("interval", "monthly"),
+                ("maturity", maturity.as_str()),
+            ],
+            EconomicIndicator::Cpi => vec![("function", "CPI"), ("interval", "monthly")],
+            EconomicIndicator::FederalFundsRate => {
+                vec![("function", "FEDERAL_FUNDS_RATE"), ("interval", "monthly")]
+            }
+            EconomicIndicator::RealGdp => vec![("function", "REAL_GDP"), ("interval", "quarterly")],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EconomicSeries {
+    pub name: String,
+    pub interval: String,
+    pub unit: String,
+    pub values: BTreeMap<chrono::NaiveDate, f64>,
+}
+impl EconomicSeries {
+    pub fn latest(&self) -> Option<(&chrono::NaiveDate, &f64)> {
+        self.values.iter().next_back()
+    }
+
+    // the value on or before `date`
+    pub fn on(&self, date: chrono::NaiveDate) -> Option<(&chrono::NaiveDate, &f64)> {
+        self.values.range(..=date).next_back()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Observation {
+    date: chrono::NaiveDate,
+    value: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct EconomicResponse {
+    name: String,
+    interval: String,
+    unit: String,
+    data: Vec<Observation>,
+}
+impl EconomicResponse {
+    // periods without a figure yet are published as `.`
+    pub(crate) fn into_series(self) -> EconomicSeries {
+        EconomicSeries {
+            name: self.name,
+            interval: self.interval,
+            unit: self.unit,
+            values: self
+                .data
+                .into_iter()
+                .filter_map(|observation| Some((observation.date, observation.value.parse().ok()?)))
+                .collect(),
+        }
+    }
+}
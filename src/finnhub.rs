@@ -0,0 +1,232 @@
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use crate::client::log_request;
+use crate::{
+    ApiError, DailySeries, ErrorContext, Frequency, Metadata, OutputSize, Quote, QuoteProvider,
+    Series, Symbol, TimeSeriesDay, TimeSeriesProvider,
+};
+
+const API_URL: &str = "https://finnhub.io/api/v1";
+const API_KEY_VAR: &str = "FINNHUB_API_KEY";
+
+#[derive(Debug, Deserialize)]
+struct QuoteResponse {
+    #[serde(rename = "c")]
+    current: f64,
+    #[serde(rename = "d")]
+    change: Option<f64>,
+    #[serde(rename = "dp")]
+    change_percent: Option<f64>,
+    #[serde(rename = "h")]
+    high: f64,
+    #[serde(rename = "l")]
+    low: f64,
+    #[serde(rename = "o")]
+    open: f64,
+    #[serde(rename = "pc")]
+    previous_close: f64,
+    #[serde(rename = "t")]
+    timestamp: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct CandleResponse {
+    #[serde(rename = "s")]
+    status: String,
+    #[serde(rename = "t", default)]
+    timestamps: Vec<i64>,
+    #[serde(rename = "o", default)]
+    open: Vec<f64>,
+    #[serde(rename = "h", default)]
+    high: Vec<f64>,
+    #[serde(rename = "l", default)]
+    low: Vec<f64>,
+    #[serde(rename = "c", default)]
+    close: Vec<f64>,
+    #[serde(rename = "v", default)]
+    volume: Vec<f64>,
+}
+
+pub struct FinnhubClient {
+    client: reqwest::blocking::Client,
+    api_key: Option<String>,
+}
+impl FinnhubClient {
+    pub fn new<S: Into<String>>(api_key: S) -> FinnhubClient {
+        FinnhubClient {
+            client: reqwest::blocking::Client::new(),
+            api_key: Some(api_key.into()),
+        }
+    }
+
+    // `FINNHUB_API_KEY` is read when a request is first made
+    pub fn from_env() -> FinnhubClient {
+        FinnhubClient {
+            client: reqwest::blocking::Client::new(),
+            api_key: None,
+        }
+    }
+
+    pub(crate) fn api_key(&self) -> Result<String, ApiError> {
+        match &self.api_key {
+            Some(api_key) => Ok(api_key.clone()),
+            None => {
+                std::env::var(API_KEY_VAR).map_err(|_| ApiError::MissingCredentials(API_KEY_VAR))
+            }
+        }
+    }
+
+    fn get<T: DeserializeOwned>(&self, path: &str, params: &[(&str, &str)]) -> Result<T, ApiError> {
+        let started = Instant::now();
+        let result = self.fetch(path, params);
+        log_request("Finnhub", path, params, started, &result);
+        result.map_err(|err| err.with_context(ErrorContext::new("Finnhub", path, params)))
+    }
+
+    fn fetch<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        params: &[(&str, &str)],
+    ) -> Result<T, ApiError> {
+        let resp = self
+            .client
+            .get(format!("{}{}", API_URL, path))
+            .query(params)
+            .header("X-Finnhub-Token", self.api_key()?)
+            .send()?;
+
+        if resp.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(ApiError::RateLimited(resp.text()?));
+        }
+        Ok(resp.error_for_status()?.json()?)
+    }
+
+    fn candles(
+        &self,
+        symbol: &Symbol,
+        resolution: &str,
+        from: chrono::DateTime<chrono::Utc>,
+    ) -> Result<BTreeMap<chrono::NaiveDate, TimeSeriesDay>, ApiError> {
+        let from = from.timestamp().to_string();
+        let to = chrono::Utc::now().timestamp().to_string();
+        let candles: CandleResponse = self.get(
+            "/stock/candle",
+            &[
+                ("symbol", symbol),
+                ("resolution", resolution),
+                ("from", &from),
+                ("to", &to),
+            ],
+        )?;
+        if candles.status != "ok" {
+            return Err(ApiError::NoData);
+        }
+
+        Ok(candles
+            .timestamps
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, timestamp)| {
+                let date = chrono::DateTime::from_timestamp(*timestamp, 0)?.date_naive();
+                let close = *candles.close.get(idx)?;
+                Some((
+                    date,
+                    TimeSeriesDay {
+                        open: *candles.open.get(idx)?,
+                        high: *candles.high.get(idx)?,
+                        low: *candles.low.get(idx)?,
+                        close,
+                        adjusted_close: close,
+                        volume: candles.volume.get(idx).cloned().unwrap_or(0.0),
+                        dividend_amount: 0.0,
+                        split_coefficient: 1.0,
+                    },
+                ))
+            })
+            .collect())
+    }
+}
+
+fn metadata(
+    symbol: &Symbol,
+    bars: &BTreeMap<chrono::NaiveDate, TimeSeriesDay>,
+    output_size: Option<&str>,
+) -> Metadata {
+    Metadata {
+        information: "Finnhub stock candles".to_string(),
+        symbol: symbol.to_string(),
+        last_refreshed: bars
+            .keys()
+            .next_back()
+            .map(|date| date.and_time(chrono::NaiveTime::MIN))
+            .unwrap_or_default(),
+        output_size: output_size.map(String::from),
+        time_zone: "UTC".to_string(),
+    }
+}
+
+impl QuoteProvider for FinnhubClient {
+    fn quote(&self, symbol: Symbol) -> Result<Quote, ApiError> {
+        let quote: QuoteResponse = self.get("/quote", &[("symbol", &symbol)])?;
+        if quote.timestamp == 0 {
+            return Err(ApiError::NoData);
+        }
+
+        let change = quote.change.unwrap_or(quote.current - quote.previous_close);
+        Ok(Quote {
+            symbol: symbol.to_string(),
+            open: quote.open,
+            high: quote.high,
+            low: quote.low,
+            price: quote.current,
+            volume: 0.0,
+            latest_trading_day: chrono::DateTime::from_timestamp(quote.timestamp, 0)
+                .unwrap_or_default()
+                .date_naive(),
+            previous_close: quote.previous_close,
+            change,
+            change_percent: quote
+                .change_percent
+                .unwrap_or(change / quote.previous_close * 100.0),
+            retrieved_at: Some(chrono::Utc::now()),
+        })
+    }
+}
+
+impl TimeSeriesProvider for FinnhubClient {
+    fn daily_series(
+        &self,
+        symbol: Symbol,
+        output_size: OutputSize,
+    ) -> Result<DailySeries, ApiError> {
+        let (from, output_size_name) = match output_size {
+            OutputSize::Compact => (chrono::Utc::now() - chrono::Duration::days(150), "Compact"),
+            OutputSize::Full => (chrono::DateTime::UNIX_EPOCH, "Full Size"),
+        };
+        let days = self.candles(&symbol, "D", from)?;
+
+        Ok(DailySeries {
+            metadata: metadata(&symbol, &days, Some(output_size_name)),
+            days,
+        })
+    }
+
+    fn series(&self, symbol: Symbol, frequency: Frequency) -> Result<Series, ApiError> {
+        let resolution = match frequency {
+            Frequency::Daily => "D",
+            Frequency::Weekly => "W",
+            Frequency::Monthly => "M",
+        };
+        let bars = self.candles(&symbol, resolution, chrono::DateTime::UNIX_EPOCH)?;
+
+        Ok(Series {
+            frequency,
+            metadata: metadata(&symbol, &bars, None),
+            bars,
+        })
+    }
+}
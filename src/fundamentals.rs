@@ -0,0 +1,189 @@
+use serde::{Deserialize, Serialize};
+
+use crate::overview::deserialize_optional_number;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Earnings {
+    pub symbol: String,
+    #[serde(rename(deserialize = "annualEarnings"), default)]
+    pub annual: Vec<AnnualEarnings>,
+    #[serde(rename(deserialize = "quarterlyEarnings"), default)]
+    pub quarterly: Vec<QuarterlyEarnings>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AnnualEarnings {
+    #[serde(rename(deserialize = "fiscalDateEnding"))]
+    pub fiscal_date_ending: chrono::NaiveDate,
+    #[serde(
+        rename(deserialize = "reportedEPS"),
+        deserialize_with = "deserialize_optional_number",
+        default
+    )]
+    pub reported_eps: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QuarterlyEarnings {
+    #[serde(rename(deserialize = "fiscalDateEnding"))]
+    pub fiscal_date_ending: chrono::NaiveDate,
+    #[serde(rename(deserialize = "reportedDate"))]
+    pub reported_date: chrono::NaiveDate,
+    #[serde(
+        rename(deserialize = "reportedEPS"),
+        deserialize_with = "deserialize_optional_number",
+        default
+    )]
+    pub reported_eps: Option<f64>,
+    #[serde(
+        rename(deserialize = "estimatedEPS"),
+        deserialize_with = "deserialize_optional_number",
+        default
+    )]
+    pub estimated_eps: Option<f64>,
+    #[serde(deserialize_with = "deserialize_optional_number", default)]
+    pub surprise: Option<f64>,
+    #[serde(
+        rename(deserialize = "surprisePercentage"),
+        deserialize_with = "deserialize_optional_number",
+        default
+    )]
+    pub surprise_percentage: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(bound(deserialize = "R: Deserialize<'de>"))]
+pub struct Statements<R> {
+    pub symbol: String,
+    #[serde(rename(deserialize = "annualReports"), default)]
+    pub annual: Vec<R>,
+    #[serde(rename(deserialize = "quarterlyReports"), default)]
+    pub quarterly: Vec<R>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IncomeStatement {
+    #[serde(rename(deserialize = "fiscalDateEnding"))]
+    pub fiscal_date_ending: chrono::NaiveDate,
+    #[serde(rename(deserialize = "reportedCurrency"))]
+    pub reported_currency: String,
+    #[serde(
+        rename(deserialize = "totalRevenue"),
+        deserialize_with = "deserialize_optional_number",
+        default
+    )]
+    pub total_revenue: Option<f64>,
+    #[serde(
+        rename(deserialize = "costOfRevenue"),
+        deserialize_with = "deserialize_optional_number",
+        default
+    )]
+    pub cost_of_revenue: Option<f64>,
+    #[serde(
+        rename(deserialize = "grossProfit"),
+        deserialize_with = "deserialize_optional_number",
+        default
+    )]
+    pub gross_profit: Option<f64>,
+    #[serde(
+        rename(deserialize = "operatingIncome"),
+        deserialize_with = "deserialize_optional_number",
+        default
+    )]
+    pub operating_income: Option<f64>,
+    #[serde(deserialize_with = "deserialize_optional_number", default)]
+    pub ebitda: Option<f64>,
+    #[serde(
+        rename(deserialize = "netIncome"),
+        deserialize_with = "deserialize_optional_number",
+        default
+    )]
+    pub net_income: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BalanceSheet {
+    #[serde(rename(deserialize = "fiscalDateEnding"))]
+    pub fiscal_date_ending: chrono::NaiveDate,
+    #[serde(rename(deserialize = "reportedCurrency"))]
+    pub reported_currency: String,
+    #[serde(
+        rename(deserialize = "totalAssets"),
+        deserialize_with = "deserialize_optional_number",
+        default
+    )]
+    pub total_assets: Option<f64>,
+    #[serde(
+        rename(deserialize = "totalLiabilities"),
+        deserialize_with = "deserialize_optional_number",
+        default
+    )]
+    pub total_liabilities: Option<f64>,
+    #[serde(
+        rename(deserialize = "totalShareholderEquity"),
+        deserialize_with = "deserialize_optional_number",
+        default
+    )]
+    pub total_shareholder_equity: Option<f64>,
+    #[serde(
+        rename(deserialize = "cashAndCashEquivalentsAtCarryingValue"),
+        deserialize_with = "deserialize_optional_number",
+        default
+    )]
+    pub cash_and_equivalents: Option<f64>,
+    #[serde(
+        rename(deserialize = "longTermDebt"),
+        deserialize_with = "deserialize_optional_number",
+        default
+    )]
+    pub long_term_debt: Option<f64>,
+    #[serde(
+        rename(deserialize = "commonStockSharesOutstanding"),
+        deserialize_with = "deserialize_optional_number",
+        default
+    )]
+    pub shares_outstanding: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CashFlow {
+    #[serde(rename(deserialize = "fiscalDateEnding"))]
+    pub fiscal_date_ending: chrono::NaiveDate,
+    #[serde(rename(deserialize = "reportedCurrency"))]
+    pub reported_currency: String,
+    #[serde(
+        rename(deserialize = "operatingCashflow"),
+        deserialize_with = "deserialize_optional_number",
+        default
+    )]
+    pub operating_cashflow: Option<f64>,
+    #[serde(
+        rename(deserialize = "capitalExpenditures"),
+        deserialize_with = "deserialize_optional_number",
+        default
+    )]
+    pub capital_expenditures: Option<f64>,
+    #[serde(
+        rename(deserialize = "cashflowFromInvestment"),
+        deserialize_with = "deserialize_optional_number",
+        default
+    )]
+    pub cashflow_from_investment: Option<f64>,
+    #[serde(
+        rename(deserialize = "cashflowFromFinancing"),
+        deserialize_with = "deserialize_optional_number",
+        default
+    )]
+    pub cashflow_from_financing: Option<f64>,
+    #[serde(
+        rename(deserialize = "dividendPayout"),
+        deserialize_with = "deserialize_optional_number",
+        default
+    )]
+    pub dividend_payout: Option<f64>,
+}
+impl CashFlow {
+    pub fn free_cashflow(&self) -> Option<f64> {
+        Some(self.operating_cashflow? - self.capital_expenditures?)
+    }
+}
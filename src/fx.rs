@@ -0,0 +1,111 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_aux::field_attributes::deserialize_number_from_string;
+
+use crate::series::{deserialize_last_refreshed, to_utc};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExchangeRate {
+    #[serde(rename(deserialize = "1. From_Currency Code"))]
+    pub from: String,
+    #[serde(rename(deserialize = "3. To_Currency Code"))]
+    pub to: String,
+    #[serde(
+        rename(deserialize = "5. Exchange Rate"),
+        deserialize_with = "deserialize_number_from_string"
+    )]
+    pub rate: f64,
+    #[serde(rename(deserialize = "6. Last Refreshed"))]
+    pub last_refreshed: String,
+    #[serde(rename(deserialize = "7. Time Zone"))]
+    pub time_zone: String,
+    #[serde(
+        rename(deserialize = "8. Bid Price"),
+        deserialize_with = "deserialize_number_from_string"
+    )]
+    pub bid: f64,
+    #[serde(
+        rename(deserialize = "9. Ask Price"),
+        deserialize_with = "deserialize_number_from_string"
+    )]
+    pub ask: f64,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ExchangeRateResponse {
+    #[serde(rename = "Realtime Currency Exchange Rate")]
+    pub(crate) rate: ExchangeRate,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FxMetadata {
+    #[serde(rename(deserialize = "1. Information"))]
+    pub information: String,
+    #[serde(rename(deserialize = "2. From Symbol"))]
+    pub from: String,
+    #[serde(rename(deserialize = "3. To Symbol"))]
+    pub to: String,
+    #[serde(rename(deserialize = "4. Output Size"))]
+    pub output_size: String,
+    #[serde(
+        rename(deserialize = "5. Last Refreshed"),
+        deserialize_with = "deserialize_last_refreshed"
+    )]
+    pub last_refreshed: chrono::NaiveDateTime,
+    #[serde(rename(deserialize = "6. Time Zone"))]
+    pub time_zone: String,
+}
+impl FxMetadata {
+    pub fn last_refreshed_utc(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        to_utc(self.last_refreshed, &self.time_zone)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FxBar {
+    #[serde(
+        rename(deserialize = "1. open"),
+        deserialize_with = "deserialize_number_from_string"
+    )]
+    pub open: f64,
+    #[serde(
+        rename(deserialize = "2. high"),
+        deserialize_with = "deserialize_number_from_string"
+    )]
+    pub high: f64,
+    #[serde(
+        rename(deserialize = "3. low"),
+        deserialize_with = "deserialize_number_from_string"
+    )]
+    pub low: f64,
+    #[serde(
+        rename(deserialize = "4. close"),
+        deserialize_with = "deserialize_number_from_string"
+    )]
+    pub close: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FxDailySeries {
+    #[serde(rename(deserialize = "Meta Data"))]
+    pub metadata: FxMetadata,
+    #[serde(rename(deserialize = "Time Series FX (Daily)"))]
+    pub days: BTreeMap<chrono::NaiveDate, FxBar>,
+}
+impl FxDailySeries {
+    pub fn latest(&self) -> Option<(&chrono::NaiveDate, &FxBar)> {
+        self.days.iter().next_back()
+    }
+
+    pub fn earliest(&self) -> Option<(&chrono::NaiveDate, &FxBar)> {
+        self.days.iter().next()
+    }
+
+    pub fn rate_on(&self, date: chrono::NaiveDate) -> Option<f64> {
+        self.days
+            .range(..=date)
+            .next_back()
+            .map(|(_date, bar)| bar.close)
+    }
+}
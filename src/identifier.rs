@@ -0,0 +1,158 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::{ApiError, Symbol, Transaction};
+
+// an instrument as brokers identify it, rather than by where it trades
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Identifier {
+    Isin(String),
+    Cusip(String),
+}
+impl Identifier {
+    // only an ISIN or CUSIP whose check digit is right, so tickers aren't
+    // mistaken for either
+    pub fn parse(s: &str) -> Option<Identifier> {
+        let s = s.trim().to_uppercase();
+        if is_isin(&s) {
+            Some(Identifier::Isin(s))
+        } else if is_cusip(&s) {
+            Some(Identifier::Cusip(s))
+        } else {
+            None
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Identifier::Isin(isin) => isin,
+            Identifier::Cusip(cusip) => cusip,
+        }
+    }
+}
+impl fmt::Display for Identifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Identifier::Isin(isin) => write!(f, "ISIN {}", isin),
+            Identifier::Cusip(cusip) => write!(f, "CUSIP {}", cusip),
+        }
+    }
+}
+
+// digits as themselves and letters from 10 for A
+fn value(c: char) -> Option<u32> {
+    c.to_digit(36)
+}
+
+// two letters for the country, nine characters and a Luhn check digit over
+// their values
+fn is_isin(s: &str) -> bool {
+    // checked for ASCII before slicing, which could split a character
+    if s.len() != 12
+        || !s.chars().all(|c| c.is_ascii_alphanumeric())
+        || !s[..2].chars().all(|c| c.is_ascii_uppercase())
+        || !s[11..].chars().all(|c| c.is_ascii_digit())
+    {
+        return false;
+    }
+    let digits: String = s
+        .chars()
+        .filter_map(value)
+        .map(|value| value.to_string())
+        .collect();
+    let sum: u32 = digits
+        .chars()
+        .rev()
+        .filter_map(|c| c.to_digit(10))
+        .enumerate()
+        .map(|(idx, digit)| match idx % 2 {
+            0 => digit,
+            _ => (digit * 2) / 10 + (digit * 2) % 10,
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+// eight characters and a check digit, doubling every second value
+fn is_cusip(s: &str) -> bool {
+    if s.len() != 9 || !s.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return false;
+    }
+    let sum: u32 = s[..8]
+        .chars()
+        .filter_map(value)
+        .enumerate()
+        .map(|(idx, value)| {
+            let value = if idx % 2 == 1 { value * 2 } else { value };
+            value / 10 + value % 10
+        })
+        .sum();
+    s[8..].chars().next().and_then(|c| c.to_digit(10)) == Some((10 - sum % 10) % 10)
+}
+
+// finds the tickers instruments trade under, with none for those it can't
+pub trait SymbolResolver {
+    fn resolve(&self, identifiers: &[Identifier]) -> Result<Vec<Option<Symbol>>, ApiError>;
+}
+
+// identifiers and the tickers they were resolved to, kept so each is only
+// looked up once
+#[derive(Debug, Default)]
+pub struct IdentifierMap {
+    symbols: BTreeMap<String, Symbol>,
+}
+impl IdentifierMap {
+    pub fn new() -> IdentifierMap {
+        IdentifierMap::default()
+    }
+
+    pub fn get(&self, identifier: &Identifier) -> Option<&Symbol> {
+        self.symbols.get(identifier.as_str())
+    }
+
+    pub fn insert(&mut self, identifier: &Identifier, symbol: Symbol) {
+        self.symbols.insert(identifier.as_str().to_string(), symbol);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Symbol)> {
+        self.symbols.iter()
+    }
+
+    // replaces each transaction's ISIN or CUSIP with its ticker, from the map
+    // or, for those not in it yet, from `resolver`, returning the ones that
+    // couldn't be resolved. `resolver` can be none to only use the map.
+    pub fn resolve(
+        &mut self,
+        transactions: &mut [Transaction],
+        resolver: Option<&dyn SymbolResolver>,
+    ) -> Result<Vec<Identifier>, ApiError> {
+        let mut unknown: Vec<Identifier> = transactions
+            .iter()
+            .filter_map(|transaction| Identifier::parse(transaction.symbol()?))
+            .filter(|identifier| self.get(identifier).is_none())
+            .collect();
+        unknown.sort();
+        unknown.dedup();
+
+        if let (Some(resolver), false) = (resolver, unknown.is_empty()) {
+            let symbols = resolver.resolve(&unknown)?;
+            for (identifier, symbol) in unknown.iter().zip(symbols) {
+                if let Some(symbol) = symbol {
+                    self.insert(identifier, symbol);
+                }
+            }
+        }
+
+        for transaction in transactions.iter_mut() {
+            let symbol = transaction
+                .symbol()
+                .and_then(|symbol| Identifier::parse(symbol))
+                .and_then(|identifier| self.get(&identifier).cloned());
+            if let Some(symbol) = symbol {
+                transaction.set_symbol(symbol);
+            }
+        }
+        unknown.retain(|identifier| self.get(identifier).is_none());
+        Ok(unknown)
+    }
+}
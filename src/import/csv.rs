@@ -0,0 +1,362 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::str::FromStr;
+
+use ::csv::{ReaderBuilder, StringRecord};
+
+use super::ImportError;
+use crate::money::Decimal;
+use crate::portfolio::default_currency;
+use crate::{Symbol, Transaction};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BrokerFormat {
+    Generic,
+    CommSec,
+    InteractiveBrokers,
+}
+impl FromStr for BrokerFormat {
+    type Err = ImportError;
+
+    fn from_str(s: &str) -> Result<BrokerFormat, ImportError> {
+        match s.to_lowercase().as_str() {
+            "generic" => Ok(BrokerFormat::Generic),
+            "commsec" => Ok(BrokerFormat::CommSec),
+            "ibkr" | "interactive-brokers" => Ok(BrokerFormat::InteractiveBrokers),
+            _ => Err(ImportError::UnknownFormat(s.to_string())),
+        }
+    }
+}
+
+struct Row<'a> {
+    line: u64,
+    columns: &'a HashMap<String, usize>,
+    record: &'a StringRecord,
+}
+impl<'a> Row<'a> {
+    fn invalid<S: Into<String>>(&self, message: S) -> ImportError {
+        ImportError::InvalidRow {
+            line: self.line,
+            message: message.into(),
+        }
+    }
+
+    fn get(&self, column: &str) -> Option<&'a str> {
+        self.columns
+            .get(column)
+            .and_then(|idx| self.record.get(*idx))
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+    }
+
+    fn required(&self, column: &str) -> Result<&'a str, ImportError> {
+        self.get(column)
+            .ok_or_else(|| self.invalid(format!("missing `{}`", column)))
+    }
+
+    fn number(&self, column: &str) -> Result<Option<Decimal>, ImportError> {
+        self.get(column)
+            .map(|value| {
+                value
+                    .replace(',', "")
+                    .trim_start_matches('$')
+                    .parse()
+                    .map_err(|_err| self.invalid(format!("`{}` is not a number", column)))
+            })
+            .transpose()
+    }
+
+    fn required_number(&self, column: &str) -> Result<Decimal, ImportError> {
+        self.number(column)?
+            .ok_or_else(|| self.invalid(format!("missing `{}`", column)))
+    }
+
+    // a number of shares traded, which must be some
+    fn quantity(&self, column: &str) -> Result<Decimal, ImportError> {
+        let quantity = self.required_number(column)?;
+        if quantity <= Decimal::ZERO {
+            return Err(self.invalid(format!("`{}` must be more than 0", column)));
+        }
+        Ok(quantity)
+    }
+
+    fn currency(&self, column: &str) -> String {
+        self.get(column)
+            .map_or_else(default_currency, str::to_uppercase)
+    }
+
+    // separated by semicolons, e.g. `retirement;speculative`
+    fn tags(&self, column: &str) -> Vec<String> {
+        self.get(column).map_or_else(Vec::new, |tags| {
+            tags.split(';')
+                .map(str::trim)
+                .filter(|tag| !tag.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+    }
+
+    fn date(&self, column: &str, format: &str) -> Result<chrono::NaiveDate, ImportError> {
+        chrono::NaiveDate::parse_from_str(self.required(column)?, format)
+            .map_err(|_err| self.invalid(format!("`{}` is not a date", column)))
+    }
+}
+
+pub fn import<R: Read>(reader: R, format: BrokerFormat) -> Result<Vec<Transaction>, ImportError> {
+    let mut reader = ReaderBuilder::new().flexible(true).from_reader(reader);
+
+    let columns: HashMap<String, usize> = reader
+        .headers()?
+        .iter()
+        .enumerate()
+        .map(|(idx, name)| (name.trim().to_lowercase(), idx))
+        .collect();
+    for column in required_columns(format) {
+        if !columns.contains_key(*column) {
+            return Err(ImportError::MissingColumn(column.to_string()));
+        }
+    }
+
+    let mut transactions = vec![];
+    for record in reader.records() {
+        let record = record?;
+        let row = Row {
+            line: record.position().map_or(0, |position| position.line()),
+            columns: &columns,
+            record: &record,
+        };
+
+        let transaction = match format {
+            BrokerFormat::Generic => parse_generic(&row)?,
+            BrokerFormat::CommSec => parse_commsec(&row)?,
+            BrokerFormat::InteractiveBrokers => parse_interactive_brokers(&row)?,
+        };
+        transactions.extend(transaction);
+    }
+    Ok(transactions)
+}
+
+// like `import`, with the file named in any error
+pub fn import_file<P: AsRef<Path>>(
+    path: P,
+    format: BrokerFormat,
+) -> Result<Vec<Transaction>, ImportError> {
+    let path = path.as_ref();
+    File::open(path)
+        .map_err(ImportError::from)
+        .and_then(|file| import(file, format))
+        .map_err(|error| ImportError::File(path.to_path_buf(), Box::new(error)))
+}
+
+fn required_columns(format: BrokerFormat) -> &'static [&'static str] {
+    match format {
+        BrokerFormat::Generic => &["date", "type"],
+        BrokerFormat::CommSec => &["date", "details", "debit($)", "credit($)"],
+        BrokerFormat::InteractiveBrokers => &["tradedate", "symbol", "quantity", "tradeprice"],
+    }
+}
+
+// date,type,symbol,quantity,price,fees,amount,ratio,lot,currency,tags,account
+// with an `isin` or `cusip` column in place of `symbol` if need be
+fn parse_generic(row: &Row) -> Result<Option<Transaction>, ImportError> {
+    let date = row.date("date", "%Y-%m-%d")?;
+    let symbol = || match row.get("isin").or_else(|| row.get("cusip")) {
+        Some(identifier) if row.get("symbol").is_none() => Ok(Symbol::new(identifier)),
+        _ => row.required("symbol").map(Symbol::new),
+    };
+    let fees = row.number("fees")?.unwrap_or(Decimal::ZERO);
+    let currency = row.currency("currency");
+    let tags = row.tags("tags");
+    let account = row.get("account").map(str::to_string);
+
+    let transaction = match row.required("type")?.to_lowercase().as_str() {
+        "buy" => Transaction::Buy {
+            date,
+            symbol: symbol()?,
+            quantity: row.quantity("quantity")?,
+            price: row.required_number("price")?,
+            fees,
+            currency,
+            tags,
+            account,
+            reinvested: false,
+        },
+        "sell" => Transaction::Sell {
+            date,
+            symbol: symbol()?,
+            quantity: row.quantity("quantity")?,
+            price: row.required_number("price")?,
+            fees,
+            lot: match row.get("lot") {
+                Some(_lot) => Some(row.date("lot", "%Y-%m-%d")?),
+                None => None,
+            },
+            currency,
+            tags,
+            account,
+        },
+        "dividend" => Transaction::Dividend {
+            date,
+            symbol: symbol()?,
+            amount: row.required_number("amount")?,
+            currency,
+            tags,
+            account,
+        },
+        "split" => Transaction::Split {
+            date,
+            symbol: symbol()?,
+            ratio: row.required_number("ratio")?,
+        },
+        "deposit" => Transaction::Deposit {
+            date,
+            amount: row.required_number("amount")?,
+            currency,
+            account,
+        },
+        "withdrawal" => Transaction::Withdrawal {
+            date,
+            amount: row.required_number("amount")?,
+            currency,
+            account,
+        },
+        other => return Err(row.invalid(format!("unknown transaction type `{}`", other))),
+    };
+    Ok(Some(transaction))
+}
+
+// Date,Reference,Details,Debit($),Credit($),Balance($)
+// where trade details look like `B 100 VAS @ 85.500000`
+fn parse_commsec(row: &Row) -> Result<Option<Transaction>, ImportError> {
+    let date = row.date("date", "%d/%m/%Y")?;
+    let details = row.required("details")?;
+    let debit = row.number("debit($)")?;
+    let credit = row.number("credit($)")?;
+    let currency = "AUD".to_string();
+
+    let parts: Vec<&str> = details.split_whitespace().collect();
+    if let [side @ ("B" | "S"), quantity, ticker, "@", price] = parts.as_slice() {
+        let quantity: Decimal = quantity
+            .parse()
+            .map_err(|_err| row.invalid("trade quantity is not a number"))?;
+        if quantity <= Decimal::ZERO {
+            return Err(row.invalid("trade quantity must be more than 0"));
+        }
+        let price: Decimal = price
+            .parse()
+            .map_err(|_err| row.invalid("trade price is not a number"))?;
+        let symbol = Symbol::new(format!("{}.AX", ticker));
+        let consideration = quantity * price;
+
+        return Ok(Some(if *side == "B" {
+            Transaction::Buy {
+                date,
+                symbol,
+                quantity,
+                price,
+                fees: debit.map_or(Decimal::ZERO, |debit| debit - consideration),
+                currency,
+                tags: vec![],
+                account: None,
+                reinvested: false,
+            }
+        } else {
+            Transaction::Sell {
+                date,
+                symbol,
+                quantity,
+                price,
+                fees: credit.map_or(Decimal::ZERO, |credit| consideration - credit),
+                lot: None,
+                currency,
+                tags: vec![],
+                account: None,
+            }
+        }));
+    }
+
+    // dividends and distributions are credited as, say, `Direct Credit 401579
+    // BHP DIV AUI24/00812345`, taken to name the payer's ticker first
+    let marker = parts.iter().position(|part| {
+        matches!(
+            part.to_uppercase().as_str(),
+            "DIV" | "DIVIDEND" | "DIST" | "DISTRIBUTION"
+        )
+    });
+    if let (Some(amount), Some(marker)) = (credit, marker) {
+        let ticker = parts[..marker]
+            .iter()
+            .find(|part| {
+                !part.eq_ignore_ascii_case("direct")
+                    && !part.eq_ignore_ascii_case("credit")
+                    && !part.chars().all(|c| c.is_ascii_digit())
+            })
+            .ok_or_else(|| row.invalid("dividend credit doesn't name a ticker"))?;
+        return Ok(Some(Transaction::Dividend {
+            date,
+            symbol: Symbol::new(format!("{}.AX", ticker)),
+            amount,
+            currency,
+            tags: vec![],
+            account: None,
+        }));
+    }
+
+    Ok(match (debit, credit) {
+        (_, Some(amount)) => Some(Transaction::Deposit {
+            date,
+            amount,
+            currency,
+            account: None,
+        }),
+        (Some(amount), None) => Some(Transaction::Withdrawal {
+            date,
+            amount,
+            currency,
+            account: None,
+        }),
+        (None, None) => None,
+    })
+}
+
+// Flex query trades: TradeDate,Symbol,Quantity,TradePrice,IBCommission,CurrencyPrimary
+// with negative quantities for sells
+fn parse_interactive_brokers(row: &Row) -> Result<Option<Transaction>, ImportError> {
+    let date = row.date("tradedate", "%Y%m%d")?;
+    let symbol = Symbol::new(row.required("symbol")?);
+    let quantity = row.required_number("quantity")?;
+    if quantity == Decimal::ZERO {
+        return Err(row.invalid("`quantity` must not be 0"));
+    }
+    let price = row.required_number("tradeprice")?;
+    let fees = row.number("ibcommission")?.unwrap_or(Decimal::ZERO).abs();
+    let currency = row.currency("currencyprimary");
+
+    Ok(Some(if quantity > Decimal::ZERO {
+        Transaction::Buy {
+            date,
+            symbol,
+            quantity,
+            price,
+            fees,
+            currency,
+            tags: vec![],
+            account: None,
+            reinvested: false,
+        }
+    } else {
+        Transaction::Sell {
+            date,
+            symbol,
+            quantity: -quantity,
+            price,
+            fees,
+            lot: None,
+            currency,
+            tags: vec![],
+            account: None,
+        }
+    }))
+}
@@ -0,0 +1,55 @@
+pub mod csv;
+
+use std::error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+pub use self::csv::BrokerFormat;
+
+#[derive(Debug)]
+pub enum ImportError {
+    Io(io::Error),
+    Csv(::csv::Error),
+    MissingColumn(String),
+    InvalidRow { line: u64, message: String },
+    UnknownFormat(String),
+    // the file being imported when the error happened
+    File(PathBuf, Box<ImportError>),
+}
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImportError::Io(err) => write!(f, "{}", err),
+            ImportError::Csv(err) => write!(f, "invalid CSV: {}", err),
+            ImportError::MissingColumn(column) => write!(f, "there's no `{}` column", column),
+            ImportError::InvalidRow { line, message } => write!(f, "line {}: {}", line, message),
+            ImportError::UnknownFormat(format) => write!(
+                f,
+                "unknown format `{}`, expected generic, commsec or ibkr",
+                format
+            ),
+            ImportError::File(path, err) => write!(f, "{}: {}", path.display(), err),
+        }
+    }
+}
+impl error::Error for ImportError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            ImportError::Io(err) => Some(err),
+            ImportError::Csv(err) => Some(err),
+            ImportError::File(_path, err) => Some(err.as_ref()),
+            _ => None,
+        }
+    }
+}
+impl From<io::Error> for ImportError {
+    fn from(error: io::Error) -> ImportError {
+        ImportError::Io(error)
+    }
+}
+impl From<::csv::Error> for ImportError {
+    fn from(error: ::csv::Error) -> ImportError {
+        ImportError::Csv(error)
+    }
+}
@@ -0,0 +1,318 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::{ApiError, DailySeries};
+
+pub type IndicatorSeries = BTreeMap<chrono::NaiveDate, f64>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Indicator {
+    Sma(usize),
+    Ema(usize),
+    Rsi(usize),
+    Macd {
+        fast: usize,
+        slow: usize,
+        signal: usize,
+    },
+    BollingerBands {
+        period: usize,
+        deviations: f64,
+    },
+}
+impl Indicator {
+    pub(crate) fn function(&self) -> &'static str {
+        match self {
+            Indicator::Sma(_) => "SMA",
+            Indicator::Ema(_) => "EMA",
+            Indicator::Rsi(_) => "RSI",
+            Indicator::Macd { .. } => "MACD",
+            Indicator::BollingerBands { .. } => "BBANDS",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Source {
+    Local,
+    Remote,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum IndicatorOutput {
+    Single(IndicatorSeries),
+    Macd(Macd),
+    BollingerBands(BollingerBands),
+}
+impl IndicatorOutput {
+    pub fn columns(&self) -> Vec<(&'static str, &IndicatorSeries)> {
+        match self {
+            IndicatorOutput::Single(values) => vec![("value", values)],
+            IndicatorOutput::Macd(macd) => vec![
+                ("macd", &macd.macd),
+                ("signal", &macd.signal),
+                ("histogram", &macd.histogram),
+            ],
+            IndicatorOutput::BollingerBands(bands) => vec![
+                ("upper", &bands.upper),
+                ("middle", &bands.middle),
+                ("lower", &bands.lower),
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Macd {
+    pub macd: IndicatorSeries,
+    pub signal: IndicatorSeries,
+    pub histogram: IndicatorSeries,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BollingerBands {
+    pub upper: IndicatorSeries,
+    pub middle: IndicatorSeries,
+    pub lower: IndicatorSeries,
+}
+
+pub fn compute(series: &DailySeries, indicator: Indicator) -> IndicatorOutput {
+    match indicator {
+        Indicator::Sma(period) => IndicatorOutput::Single(sma(series, period)),
+        Indicator::Ema(period) => IndicatorOutput::Single(ema(series, period)),
+        Indicator::Rsi(period) => IndicatorOutput::Single(rsi(series, period)),
+        Indicator::Macd { fast, slow, signal } => {
+            IndicatorOutput::Macd(macd(series, fast, slow, signal))
+        }
+        Indicator::BollingerBands { period, deviations } => {
+            IndicatorOutput::BollingerBands(bollinger_bands(series, period, deviations))
+        }
+    }
+}
+
+// adjusted for splits and dividends, so neither shows up as a fall in price
+pub fn adjusted_closes(series: &DailySeries) -> IndicatorSeries {
+    series
+        .days
+        .iter()
+        .map(|(date, day)| (*date, day.adjusted_close))
+        .collect()
+}
+
+pub fn sma(series: &DailySeries, period: usize) -> IndicatorSeries {
+    sma_of(&adjusted_closes(series), period)
+}
+
+pub fn ema(series: &DailySeries, period: usize) -> IndicatorSeries {
+    ema_of(&adjusted_closes(series), period)
+}
+
+pub fn rsi(series: &DailySeries, period: usize) -> IndicatorSeries {
+    let closes: Vec<(chrono::NaiveDate, f64)> = adjusted_closes(series).into_iter().collect();
+    if period == 0 || closes.len() <= period {
+        return IndicatorSeries::new();
+    }
+
+    let changes: Vec<f64> = closes
+        .windows(2)
+        .map(|pair| pair[1].1 - pair[0].1)
+        .collect();
+    let mut average_gain =
+        changes[..period].iter().map(|c| c.max(0.0)).sum::<f64>() / period as f64;
+    let mut average_loss =
+        changes[..period].iter().map(|c| (-c).max(0.0)).sum::<f64>() / period as f64;
+
+    let rsi_value = |gain: f64, loss: f64| {
+        if loss == 0.0 {
+            100.0
+        } else {
+            100.0 - 100.0 / (1.0 + gain / loss)
+        }
+    };
+
+    let mut result = IndicatorSeries::new();
+    result.insert(closes[period].0, rsi_value(average_gain, average_loss));
+    for (idx, change) in changes.iter().enumerate().skip(period) {
+        // Wilder's smoothing
+        average_gain = (average_gain * (period - 1) as f64 + change.max(0.0)) / period as f64;
+        average_loss = (average_loss * (period - 1) as f64 + (-change).max(0.0)) / period as f64;
+        result.insert(closes[idx + 1].0, rsi_value(average_gain, average_loss));
+    }
+    result
+}
+
+pub fn macd(series: &DailySeries, fast: usize, slow: usize, signal: usize) -> Macd {
+    let closes = adjusted_closes(series);
+    let fast = ema_of(&closes, fast);
+    let slow = ema_of(&closes, slow);
+
+    let macd: IndicatorSeries = slow
+        .iter()
+        .filter_map(|(date, slow)| fast.get(date).map(|fast| (*date, fast - slow)))
+        .collect();
+    let signal = ema_of(&macd, signal);
+    let histogram = signal
+        .iter()
+        .filter_map(|(date, signal)| macd.get(date).map(|macd| (*date, macd - signal)))
+        .collect();
+
+    Macd {
+        macd,
+        signal,
+        histogram,
+    }
+}
+
+pub fn bollinger_bands(series: &DailySeries, period: usize, deviations: f64) -> BollingerBands {
+    let values: Vec<(chrono::NaiveDate, f64)> = adjusted_closes(series).into_iter().collect();
+
+    let mut bands = BollingerBands {
+        upper: IndicatorSeries::new(),
+        middle: IndicatorSeries::new(),
+        lower: IndicatorSeries::new(),
+    };
+    if period == 0 {
+        return bands;
+    }
+    for window in values.windows(period) {
+        let (date, _close) = window[period - 1];
+        let mean = window.iter().map(|(_date, close)| close).sum::<f64>() / period as f64;
+        let variance = window
+            .iter()
+            .map(|(_date, close)| (close - mean).powi(2))
+            .sum::<f64>()
+            / period as f64;
+        let width = deviations * variance.sqrt();
+
+        bands.upper.insert(date, mean + width);
+        bands.middle.insert(date, mean);
+        bands.lower.insert(date, mean - width);
+    }
+    bands
+}
+
+pub fn sma_of(values: &IndicatorSeries, period: usize) -> IndicatorSeries {
+    let values: Vec<(&chrono::NaiveDate, &f64)> = values.iter().collect();
+    if period == 0 {
+        return IndicatorSeries::new();
+    }
+    values
+        .windows(period)
+        .map(|window| {
+            let (date, _value) = window[period - 1];
+            let sum: f64 = window.iter().map(|(_date, value)| *value).sum();
+            (*date, sum / period as f64)
+        })
+        .collect()
+}
+
+pub fn ema_of(values: &IndicatorSeries, period: usize) -> IndicatorSeries {
+    let values: Vec<(&chrono::NaiveDate, &f64)> = values.iter().collect();
+    if period == 0 || values.len() < period {
+        return IndicatorSeries::new();
+    }
+
+    // seeded with the simple average of the first `period` values
+    let alpha = 2.0 / (period as f64 + 1.0);
+    let mut current = values[..period]
+        .iter()
+        .map(|(_date, value)| *value)
+        .sum::<f64>()
+        / period as f64;
+
+    let mut result = IndicatorSeries::new();
+    result.insert(*values[period - 1].0, current);
+    for (date, value) in &values[period..] {
+        current = alpha * *value + (1.0 - alpha) * current;
+        result.insert(**date, current);
+    }
+    result
+}
+
+pub(crate) fn remote_params(symbol: &str, indicator: Indicator) -> Vec<(&'static str, String)> {
+    let mut params = vec![
+        ("function", indicator.function().to_string()),
+        ("symbol", symbol.to_string()),
+        ("interval", "daily".to_string()),
+        ("series_type", "close".to_string()),
+    ];
+    match indicator {
+        Indicator::Sma(period) | Indicator::Ema(period) | Indicator::Rsi(period) => {
+            params.push(("time_period", period.to_string()));
+        }
+        Indicator::Macd { fast, slow, signal } => {
+            params.push(("fastperiod", fast.to_string()));
+            params.push(("slowperiod", slow.to_string()));
+            params.push(("signalperiod", signal.to_string()));
+        }
+        Indicator::BollingerBands { period, deviations } => {
+            params.push(("time_period", period.to_string()));
+            params.push(("nbdevup", deviations.to_string()));
+            params.push(("nbdevdn", deviations.to_string()));
+        }
+    }
+    params
+}
+
+pub(crate) fn remote_output(
+    indicator: Indicator,
+    value: serde_json::Value,
+) -> Result<IndicatorOutput, ApiError> {
+    let key = format!("Technical Analysis: {}", indicator.function());
+    let days: BTreeMap<chrono::NaiveDate, BTreeMap<String, String>> =
+        serde_json::from_value(value.get(&key).cloned().ok_or(ApiError::NoData)?)?;
+
+    let field = |name: &str| -> Result<IndicatorSeries, ApiError> {
+        days.iter()
+            .filter_map(|(date, fields)| fields.get(name).map(|value| (date, value)))
+            .map(|(date, value)| {
+                value
+                    .parse()
+                    .map(|value| (*date, value))
+                    .map_err(|_err| ApiError::Api(format!("invalid {} value `{}`", name, value)))
+            })
+            .collect()
+    };
+
+    Ok(match indicator {
+        Indicator::Sma(_) | Indicator::Ema(_) | Indicator::Rsi(_) => {
+            IndicatorOutput::Single(field(indicator.function())?)
+        }
+        Indicator::Macd { .. } => IndicatorOutput::Macd(Macd {
+            macd: field("MACD")?,
+            signal: field("MACD_Signal")?,
+            histogram: field("MACD_Hist")?,
+        }),
+        Indicator::BollingerBands { .. } => IndicatorOutput::BollingerBands(BollingerBands {
+            upper: field("Real Upper Band")?,
+            middle: field("Real Middle Band")?,
+            lower: field("Real Lower Band")?,
+        }),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    #[test]
+    fn splits_are_adjusted_for() {
+        let series = testing::split_series("IBM", 30);
+        assert!(sma(&series, 10).values().all(|value| *value == 50.0));
+        assert!(ema(&series, 10)
+            .values()
+            .all(|value| (value - 50.0).abs() < 1e-9));
+        let bands = bollinger_bands(&series, 20, 2.0);
+        assert!(bands
+            .upper
+            .values()
+            .all(|value| (value - 50.0).abs() < 1e-9));
+        assert!(macd(&series, 12, 26, 9)
+            .macd
+            .values()
+            .all(|value| value.abs() < 1e-9));
+    }
+}
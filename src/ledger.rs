@@ -0,0 +1,799 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::money::{self, Decimal, Money, Quantity};
+use crate::portfolio::default_currency;
+use crate::{
+    ApiError, AssetType, DailySeries, OutputSize, Portfolio, Position, Progress, Symbol,
+    TimeSeriesProvider,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Transaction {
+    Buy {
+        date: chrono::NaiveDate,
+        symbol: Symbol,
+        quantity: Quantity,
+        price: Money,
+        fees: Money,
+        #[serde(default = "default_currency")]
+        currency: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        tags: Vec<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        account: Option<String>,
+        // bought with a dividend paid on the same date
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        reinvested: bool,
+    },
+    Sell {
+        date: chrono::NaiveDate,
+        symbol: Symbol,
+        quantity: Quantity,
+        price: Money,
+        fees: Money,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        lot: Option<chrono::NaiveDate>,
+        #[serde(default = "default_currency")]
+        currency: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        tags: Vec<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        account: Option<String>,
+    },
+    Dividend {
+        date: chrono::NaiveDate,
+        symbol: Symbol,
+        amount: Money,
+        #[serde(default = "default_currency")]
+        currency: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        tags: Vec<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        account: Option<String>,
+    },
+    // applies to the symbol in every account
+    Split {
+        date: chrono::NaiveDate,
+        symbol: Symbol,
+        ratio: Decimal,
+    },
+    Deposit {
+        date: chrono::NaiveDate,
+        amount: Money,
+        #[serde(default = "default_currency")]
+        currency: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        account: Option<String>,
+    },
+    Withdrawal {
+        date: chrono::NaiveDate,
+        amount: Money,
+        #[serde(default = "default_currency")]
+        currency: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        account: Option<String>,
+    },
+}
+impl Transaction {
+    pub fn date(&self) -> chrono::NaiveDate {
+        match self {
+            Transaction::Buy { date, .. }
+            | Transaction::Sell { date, .. }
+            | Transaction::Dividend { date, .. }
+            | Transaction::Split { date, .. }
+            | Transaction::Deposit { date, .. }
+            | Transaction::Withdrawal { date, .. } => *date,
+        }
+    }
+
+    pub fn flow(&self) -> Money {
+        match self {
+            Transaction::Buy {
+                quantity,
+                price,
+                fees,
+                ..
+            } => quantity * price + fees,
+            Transaction::Sell {
+                quantity,
+                price,
+                fees,
+                ..
+            } => -(quantity * price - fees),
+            Transaction::Dividend { amount, .. } => -amount,
+            Transaction::Split { .. }
+            | Transaction::Deposit { .. }
+            | Transaction::Withdrawal { .. } => Money::ZERO,
+        }
+    }
+
+    pub fn symbol(&self) -> Option<&Symbol> {
+        match self {
+            Transaction::Buy { symbol, .. }
+            | Transaction::Sell { symbol, .. }
+            | Transaction::Dividend { symbol, .. }
+            | Transaction::Split { symbol, .. } => Some(symbol),
+            Transaction::Deposit { .. } | Transaction::Withdrawal { .. } => None,
+        }
+    }
+
+    pub fn currency(&self) -> Option<&str> {
+        match self {
+            Transaction::Buy { currency, .. }
+            | Transaction::Sell { currency, .. }
+            | Transaction::Dividend { currency, .. }
+            | Transaction::Deposit { currency, .. }
+            | Transaction::Withdrawal { currency, .. } => Some(currency),
+            Transaction::Split { .. } => None,
+        }
+    }
+
+    pub fn tags(&self) -> &[String] {
+        match self {
+            Transaction::Buy { tags, .. }
+            | Transaction::Sell { tags, .. }
+            | Transaction::Dividend { tags, .. } => tags,
+            Transaction::Split { .. }
+            | Transaction::Deposit { .. }
+            | Transaction::Withdrawal { .. } => &[],
+        }
+    }
+
+    pub fn account(&self) -> Option<&str> {
+        match self {
+            Transaction::Buy { account, .. }
+            | Transaction::Sell { account, .. }
+            | Transaction::Dividend { account, .. }
+            | Transaction::Deposit { account, .. }
+            | Transaction::Withdrawal { account, .. } => account.as_deref(),
+            Transaction::Split { .. } => None,
+        }
+    }
+
+    // does nothing to splits
+    pub fn set_account(&mut self, name: &str) {
+        if let Transaction::Buy { account, .. }
+        | Transaction::Sell { account, .. }
+        | Transaction::Dividend { account, .. }
+        | Transaction::Deposit { account, .. }
+        | Transaction::Withdrawal { account, .. } = self
+        {
+            *account = Some(name.to_string());
+        }
+    }
+
+    // does nothing to deposits and withdrawals
+    pub fn set_symbol(&mut self, symbol: Symbol) {
+        if let Transaction::Buy {
+            symbol: existing, ..
+        }
+        | Transaction::Sell {
+            symbol: existing, ..
+        }
+        | Transaction::Dividend {
+            symbol: existing, ..
+        }
+        | Transaction::Split {
+            symbol: existing, ..
+        } = self
+        {
+            *existing = symbol;
+        }
+    }
+
+    // does nothing to splits, deposits and withdrawals
+    pub fn add_tag(&mut self, tag: &str) {
+        if let Transaction::Buy { tags, .. }
+        | Transaction::Sell { tags, .. }
+        | Transaction::Dividend { tags, .. } = self
+        {
+            if !tags.iter().any(|existing| existing == tag) {
+                tags.push(tag.to_string());
+            }
+        }
+    }
+
+    // the change to the cash balance in `currency()`
+    pub fn cash_flow(&self) -> Money {
+        match self {
+            Transaction::Deposit { amount, .. } => *amount,
+            Transaction::Withdrawal { amount, .. } => -amount,
+            _ => -self.flow(),
+        }
+    }
+}
+
+struct Holding {
+    quantity: Quantity,
+    cost_basis: Money,
+    opened: chrono::NaiveDate,
+    currency: String,
+    // from the buys that opened or added to it
+    tags: BTreeSet<String>,
+}
+
+// holdings and cash are kept apart by account
+struct Replay {
+    holdings: BTreeMap<(Option<String>, Symbol), Holding>,
+    cash: BTreeMap<(Option<String>, String), Money>,
+    realised_gains: Money,
+    dividends: Money,
+    net_deposits: Money,
+}
+impl Replay {
+    fn new() -> Replay {
+        Replay {
+            holdings: BTreeMap::new(),
+            cash: BTreeMap::new(),
+            realised_gains: Money::ZERO,
+            dividends: Money::ZERO,
+            net_deposits: Money::ZERO,
+        }
+    }
+
+    fn apply(&mut self, transaction: &Transaction) {
+        let account = transaction.account().map(str::to_string);
+        if let Some(currency) = transaction.currency() {
+            *self
+                .cash
+                .entry((account.clone(), currency.to_string()))
+                .or_default() += transaction.cash_flow();
+        }
+
+        match transaction {
+            Transaction::Buy {
+                date,
+                symbol,
+                quantity,
+                price,
+                fees,
+                currency,
+                tags,
+                ..
+            } => {
+                let holding = self
+                    .holdings
+                    .entry((account, symbol.clone()))
+                    .or_insert(Holding {
+                        quantity: Quantity::ZERO,
+                        cost_basis: Money::ZERO,
+                        opened: *date,
+                        currency: currency.clone(),
+                        tags: BTreeSet::new(),
+                    });
+                holding.quantity += quantity;
+                holding.cost_basis += quantity * price + fees;
+                holding.tags.extend(tags.iter().cloned());
+            }
+            Transaction::Sell {
+                symbol,
+                quantity,
+                price,
+                fees,
+                ..
+            } => {
+                // only shares that were held are booked, with their share of the fees
+                let key = (account, symbol.clone());
+                if let Some(holding) = self.holdings.get_mut(&key) {
+                    let sold = (*quantity).min(holding.quantity);
+                    if sold > Quantity::ZERO {
+                        let cost_of_sold = holding.cost_basis * sold / holding.quantity;
+                        holding.quantity -= sold;
+                        holding.cost_basis -= cost_of_sold;
+                        self.realised_gains += sold * price - fees * sold / quantity - cost_of_sold;
+                    }
+                    if holding.quantity <= Quantity::ZERO {
+                        self.holdings.remove(&key);
+                    }
+                }
+            }
+            Transaction::Dividend { amount, .. } => self.dividends += amount,
+            Transaction::Split { symbol, ratio, .. } => {
+                for ((_account, held), holding) in &mut self.holdings {
+                    if held == symbol {
+                        holding.quantity *= ratio;
+                    }
+                }
+            }
+            Transaction::Deposit { amount, .. } => self.net_deposits += amount,
+            Transaction::Withdrawal { amount, .. } => self.net_deposits -= amount,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Ledger {
+    transactions: Vec<Transaction>,
+    // symbols whose dividends are reinvested
+    reinvested: BTreeSet<Symbol>,
+}
+impl Ledger {
+    pub fn new() -> Ledger {
+        Ledger::default()
+    }
+
+    pub fn record(&mut self, transaction: Transaction) {
+        self.transactions.push(transaction);
+    }
+
+    pub fn transactions(&self) -> &[Transaction] {
+        &self.transactions
+    }
+
+    // the transactions in the account, and every split
+    pub fn for_account(&self, account: &str) -> Ledger {
+        Ledger {
+            transactions: self
+                .transactions
+                .iter()
+                .filter(|transaction| match transaction {
+                    Transaction::Split { .. } => true,
+                    transaction => transaction.account() == Some(account),
+                })
+                .cloned()
+                .collect(),
+            reinvested: self.reinvested.clone(),
+        }
+    }
+
+    pub fn accounts(&self) -> BTreeSet<&str> {
+        self.transactions
+            .iter()
+            .filter_map(Transaction::account)
+            .collect()
+    }
+
+    // every transaction in a symbol that any of its transactions is tagged with one
+    // of `tags`, so sells and splits still apply to tagged buys. Cash can't be told
+    // apart by tag, so deposits and withdrawals are left out.
+    pub fn tagged(&self, tags: &[String]) -> Ledger {
+        let symbols: BTreeSet<&Symbol> = self
+            .transactions
+            .iter()
+            .filter(|transaction| transaction.tags().iter().any(|tag| tags.contains(tag)))
+            .filter_map(Transaction::symbol)
+            .collect();
+        Ledger {
+            transactions: self
+                .transactions
+                .iter()
+                .filter(|transaction| {
+                    transaction
+                        .symbol()
+                        .is_some_and(|symbol| symbols.contains(symbol))
+                })
+                .cloned()
+                .collect(),
+            reinvested: self.reinvested.clone(),
+        }
+    }
+
+    pub fn reinvested(&self) -> &BTreeSet<Symbol> {
+        &self.reinvested
+    }
+
+    pub fn set_reinvested(&mut self, symbol: Symbol, reinvested: bool) {
+        if reinvested {
+            self.reinvested.insert(symbol);
+        } else {
+            self.reinvested.remove(&symbol);
+        }
+    }
+
+    // for each of the symbol's ex-dividend dates in `series`, buys each account's
+    // dividend worth of shares at the day's close, recording the dividend too
+    // unless one was already recorded on that date. Returns the number of buys.
+    pub fn apply_reinvestment(&mut self, symbol: &Symbol, series: &DailySeries) -> usize {
+        let mut count = 0;
+        for (date, day) in series
+            .days
+            .iter()
+            .filter(|(_date, day)| day.dividend_amount > 0.0 && day.close > 0.0)
+        {
+            // the holdings of the day before, which are entitled to the dividend
+            let mut replay = Replay::new();
+            for transaction in self.sorted() {
+                if transaction.date() < *date {
+                    replay.apply(transaction);
+                }
+            }
+            let holders: Vec<(Option<String>, Quantity, String)> = replay
+                .holdings
+                .into_iter()
+                .filter(|((_account, held), _holding)| held == symbol)
+                .map(|((account, _symbol), holding)| (account, holding.quantity, holding.currency))
+                .collect();
+
+            for (account, held, currency) in holders {
+                let recorded = |wanted: fn(&Transaction) -> bool| {
+                    self.transactions.iter().any(|transaction| {
+                        wanted(transaction)
+                            && transaction.date() == *date
+                            && transaction.symbol() == Some(symbol)
+                            && transaction.account() == account.as_deref()
+                    })
+                };
+                if recorded(|transaction| {
+                    matches!(
+                        transaction,
+                        Transaction::Buy {
+                            reinvested: true,
+                            ..
+                        }
+                    )
+                }) {
+                    continue;
+                }
+                let amount = (held * money::from_f64(day.dividend_amount)).round_dp(2);
+                let price = money::from_f64(day.close);
+                if !recorded(|transaction| matches!(transaction, Transaction::Dividend { .. })) {
+                    self.transactions.push(Transaction::Dividend {
+                        date: *date,
+                        symbol: symbol.clone(),
+                        amount,
+                        currency: currency.clone(),
+                        tags: vec![],
+                        account: account.clone(),
+                    });
+                }
+                self.transactions.push(Transaction::Buy {
+                    date: *date,
+                    symbol: symbol.clone(),
+                    quantity: (amount / price).round_dp(6),
+                    price,
+                    fees: Money::ZERO,
+                    currency,
+                    tags: vec![],
+                    account,
+                    reinvested: true,
+                });
+                count += 1;
+            }
+        }
+        count
+    }
+
+    pub fn sync_reinvestment<P: TimeSeriesProvider + ?Sized>(
+        &mut self,
+        provider: &P,
+    ) -> Result<usize, ApiError> {
+        let mut count = 0;
+        for symbol in self.reinvested.clone() {
+            let series = provider.daily_series(symbol.clone(), OutputSize::Full)?;
+            count += self.apply_reinvestment(&symbol, &series);
+        }
+        Ok(count)
+    }
+
+    pub fn apply_splits(&mut self, symbol: &Symbol, series: &DailySeries) -> usize {
+        let first_purchase = self
+            .transactions
+            .iter()
+            .filter_map(|transaction| match transaction {
+                Transaction::Buy {
+                    date,
+                    symbol: bought,
+                    ..
+                } if bought == symbol => Some(*date),
+                _ => None,
+            })
+            .min();
+        let first_purchase = match first_purchase {
+            Some(date) => date,
+            None => return 0,
+        };
+
+        let splits: Vec<Transaction> = series
+            .days
+            .iter()
+            .filter(|(date, day)| **date > first_purchase && day.split_coefficient != 1.0)
+            .filter(|(date, _day)| {
+                !self.transactions.iter().any(|transaction| {
+                    matches!(transaction, Transaction::Split { date: recorded, symbol: split, .. }
+                        if recorded == *date && split == symbol)
+                })
+            })
+            .map(|(date, day)| Transaction::Split {
+                date: *date,
+                symbol: symbol.clone(),
+                ratio: money::from_f64(day.split_coefficient),
+            })
+            .collect();
+
+        let count = splits.len();
+        self.transactions.extend(splits);
+        count
+    }
+
+    pub fn sync_splits<P: TimeSeriesProvider + ?Sized>(
+        &mut self,
+        provider: &P,
+    ) -> Result<usize, ApiError> {
+        let symbols: BTreeSet<Symbol> = self
+            .transactions
+            .iter()
+            .filter_map(|transaction| match transaction {
+                Transaction::Buy { symbol, .. } => Some(symbol.clone()),
+                _ => None,
+            })
+            .collect();
+
+        let mut count = 0;
+        for symbol in symbols {
+            let series = provider.daily_series(symbol.clone(), OutputSize::Full)?;
+            count += self.apply_splits(&symbol, &series);
+        }
+        Ok(count)
+    }
+
+    // cash is only tracked once the ledger records deposits or withdrawals,
+    // otherwise a ledger of trades alone would look overdrawn
+    pub fn tracks_cash(&self) -> bool {
+        self.transactions.iter().any(|transaction| {
+            matches!(
+                transaction,
+                Transaction::Deposit { .. } | Transaction::Withdrawal { .. }
+            )
+        })
+    }
+
+    // across every account
+    pub fn cash_balances(&self) -> BTreeMap<String, Money> {
+        let mut cash = BTreeMap::new();
+        for ((_account, currency), balance) in self.replay().cash {
+            *cash.entry(currency).or_default() += balance;
+        }
+        cash.retain(|_currency, balance: &mut Money| !balance.is_zero());
+        cash
+    }
+
+    pub fn positions(&self) -> Vec<Position> {
+        let replay = self.replay();
+        let mut positions: Vec<Position> = replay
+            .holdings
+            .into_iter()
+            .map(|((account, symbol), holding)| Position {
+                currency: holding.currency,
+                tags: holding.tags.into_iter().collect(),
+                account,
+                ..Position::new(symbol, holding.quantity, holding.cost_basis, holding.opened)
+            })
+            .collect();
+
+        if let (true, Some(first)) = (self.tracks_cash(), self.sorted().first()) {
+            for ((account, currency), balance) in replay.cash {
+                if balance.is_zero() {
+                    continue;
+                }
+                positions.push(Position {
+                    asset_type: AssetType::Cash,
+                    currency: currency.clone(),
+                    account,
+                    ..Position::new(currency, balance, balance, first.date())
+                });
+            }
+        }
+        positions
+    }
+
+    pub fn portfolio(&self) -> Portfolio {
+        let mut portfolio = Portfolio::new();
+        for position in self.positions() {
+            portfolio.add_position(position);
+        }
+        portfolio
+    }
+
+    pub fn realised_gains(&self) -> Money {
+        self.replay().realised_gains
+    }
+
+    pub fn dividends(&self) -> Money {
+        self.replay().dividends
+    }
+
+    pub fn net_deposits(&self) -> Money {
+        self.replay().net_deposits
+    }
+
+    pub(crate) fn sorted(&self) -> Vec<&Transaction> {
+        let mut transactions: Vec<&Transaction> = self.transactions.iter().collect();
+        transactions.sort_by_key(|transaction| transaction.date());
+        transactions
+    }
+
+    fn replay(&self) -> Replay {
+        let mut replay = Replay::new();
+        for transaction in self.sorted() {
+            replay.apply(transaction);
+        }
+        replay
+    }
+
+    pub fn equity_curve(&self, prices: &HashMap<Symbol, DailySeries>) -> EquityCurve {
+        let transactions = self.sorted();
+        let inception = match transactions.first() {
+            Some(transaction) => transaction.date(),
+            None => return EquityCurve::default(),
+        };
+
+        let dates: BTreeSet<chrono::NaiveDate> = prices
+            .values()
+            .flat_map(|series| series.days.range(inception..).map(|(date, _day)| *date))
+            .collect();
+
+        let mut replay = Replay::new();
+        let mut pending = transactions.into_iter().peekable();
+        let mut days = BTreeMap::new();
+        for date in dates {
+            let mut net_flow = Money::ZERO;
+            while let Some(transaction) = pending.next_if(|transaction| transaction.date() <= date)
+            {
+                net_flow += transaction.flow();
+                replay.apply(transaction);
+            }
+
+            let market_value = replay
+                .holdings
+                .iter()
+                .map(|((_account, symbol), holding)| {
+                    let close = prices
+                        .get(symbol)
+                        .and_then(|series| series.days.range(..=date).next_back())
+                        .map_or(0.0, |(_date, day)| day.close);
+                    money::to_f64(holding.quantity) * close
+                })
+                .sum();
+
+            days.insert(
+                date,
+                EquityPoint {
+                    market_value,
+                    cost_basis: money::to_f64(
+                        replay
+                            .holdings
+                            .values()
+                            .map(|holding| holding.cost_basis)
+                            .sum(),
+                    ),
+                    net_deposits: money::to_f64(replay.net_deposits),
+                    realised_gains: money::to_f64(replay.realised_gains),
+                    dividends: money::to_f64(replay.dividends),
+                    net_flow: money::to_f64(net_flow),
+                },
+            );
+        }
+
+        EquityCurve { days }
+    }
+
+    pub fn fetch_equity_curve<P: TimeSeriesProvider + ?Sized>(
+        &self,
+        provider: &P,
+    ) -> Result<EquityCurve, ApiError> {
+        self.fetch_equity_curve_with_progress(provider, |_progress| ())
+    }
+
+    // stops at the first symbol whose history can't be fetched
+    pub fn fetch_equity_curve_with_progress<P, F>(
+        &self,
+        provider: &P,
+        mut progress: F,
+    ) -> Result<EquityCurve, ApiError>
+    where
+        P: TimeSeriesProvider + ?Sized,
+        F: FnMut(Progress),
+    {
+        let symbols: BTreeSet<&Symbol> = self
+            .transactions
+            .iter()
+            .filter_map(Transaction::symbol)
+            .collect();
+
+        let mut prices = HashMap::new();
+        for (idx, symbol) in symbols.iter().enumerate() {
+            let result = provider.daily_series((*symbol).clone(), OutputSize::Full);
+            progress(Progress {
+                symbol,
+                completed: idx + 1,
+                total: symbols.len(),
+                error: result.as_ref().err(),
+            });
+            prices.insert((*symbol).clone(), result?);
+        }
+        Ok(self.equity_curve(&prices))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EquityPoint {
+    pub market_value: f64,
+    pub cost_basis: f64,
+    pub net_deposits: f64,
+    pub realised_gains: f64,
+    pub dividends: f64,
+    pub net_flow: f64,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct EquityCurve {
+    pub days: BTreeMap<chrono::NaiveDate, EquityPoint>,
+}
+impl EquityCurve {
+    pub fn latest(&self) -> Option<(&chrono::NaiveDate, &EquityPoint)> {
+        self.days.iter().next_back()
+    }
+
+    pub fn earliest(&self) -> Option<(&chrono::NaiveDate, &EquityPoint)> {
+        self.days.iter().next()
+    }
+
+    pub fn values(&self) -> BTreeMap<chrono::NaiveDate, f64> {
+        self.days
+            .iter()
+            .map(|(date, point)| (*date, point.market_value))
+            .collect()
+    }
+
+    pub fn time_weighted_index(&self) -> BTreeMap<chrono::NaiveDate, f64> {
+        let mut index = BTreeMap::new();
+        let mut level = 100.0;
+        let mut previous: f64 = 0.0;
+        for (date, point) in &self.days {
+            if previous > 0.0 {
+                level *= (point.market_value - point.net_flow) / previous;
+            }
+            if previous > 0.0 || point.market_value > 0.0 {
+                index.insert(*date, level);
+            }
+            previous = point.market_value;
+        }
+        index
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{buy, ledger, sell};
+
+    #[test]
+    fn sells_of_unheld_shares_realise_nothing() {
+        let ledger = ledger(vec![sell("2024-03-08", "IBM", 10, 100)]);
+        assert_eq!(ledger.realised_gains(), Money::ZERO);
+    }
+
+    #[test]
+    fn oversells_realise_only_what_was_held() {
+        let mut oversell = sell("2024-03-08", "IBM", 10, 120);
+        if let Transaction::Sell { fees, .. } = &mut oversell {
+            *fees = Decimal::from(10);
+        }
+        let ledger = ledger(vec![buy("2024-01-02", "IBM", 5, 100), oversell]);
+        // 5 × (120 - 100), less half the fees
+        assert_eq!(ledger.realised_gains(), Decimal::from(95));
+        assert!(ledger.positions().is_empty());
+    }
+
+    #[test]
+    fn splits_carry_the_cost_basis() {
+        let split = Transaction::Split {
+            date: "2024-02-01".parse().unwrap(),
+            symbol: Symbol::new("IBM"),
+            ratio: Decimal::from(2),
+        };
+        let ledger = ledger(vec![
+            buy("2024-01-02", "IBM", 10, 100),
+            split,
+            sell("2024-03-08", "IBM", 5, 60),
+        ]);
+        // sold at 60 what cost 50 a share after the split
+        assert_eq!(ledger.realised_gains(), Decimal::from(50));
+        let positions = ledger.positions();
+        assert_eq!(positions[0].quantity, Decimal::from(15));
+        assert_eq!(positions[0].cost_basis, Decimal::from(750));
+    }
+}
@@ -1,195 +1,410 @@
 use std::cmp::Ordering;
-use std::collections::HashMap;
-use std::env;
-use std::ops::Deref;
+use std::error;
+use std::fmt;
+use std::ops::Bound;
 
-use lazy_static::lazy_static;
-use reqwest;
-use serde::Deserialize;
-use serde_aux::field_attributes::deserialize_number_from_string;
+use serde::Serialize;
 
-lazy_static! {
-    static ref CLIENT: reqwest::Client = reqwest::Client::new();
-    static ref VANTAGE_API_KEY: String =
-        env::var("VANTAGE_API_KEY").expect("`VANTAGE_API_KEY` environment variable must be set");
-}
+pub mod aio;
+mod alerts;
+mod allocation;
+mod attribution;
+pub mod backtest;
+mod bond;
+mod cache;
+pub mod calendar;
+#[cfg(feature = "charts")]
+pub mod charts;
+mod client;
+mod config;
+mod crypto;
+mod dca;
+mod dividend;
+mod economy;
+#[cfg(feature = "finnhub")]
+mod finnhub;
+mod fundamentals;
+mod fx;
+mod identifier;
+pub mod import;
+pub mod indicators;
+mod ledger;
+mod lots;
+pub mod metrics;
+pub mod money;
+mod news;
+mod notify;
+mod openfigi;
+mod options;
+mod overview;
+mod paginate;
+mod portfolio;
+mod provider;
+mod quote;
+mod ratelimit;
+mod rebalance;
+mod recording;
+pub mod render;
+mod retry;
+mod risk;
+mod screen;
+mod search;
+mod sector;
+mod series;
+mod storage;
+#[cfg(feature = "sqlite")]
+mod store;
+#[cfg(feature = "streaming")]
+pub mod streaming;
+mod symbol;
+mod tax;
+pub mod testing;
+mod watchlist;
+mod yahoo;
+
+pub use crate::alerts::{AlertRule, Alerts, Condition, TriggeredAlert};
+pub use crate::allocation::{fetch_overviews, Allocation, AllocationWeight, Dimension};
+pub use crate::attribution::{attribution, Attribution, HoldingAttribution};
+pub use crate::bond::Bond;
+pub use crate::cache::{Cache, CacheStats};
+pub use crate::calendar::{EarningsEvent, IpoEvent, Listing};
+pub use crate::client::{
+    DataType, Entitlement, PortfolioClient, PortfolioClientBuilder, Progress, Transport,
+};
+pub use crate::config::{default_config_path, ApiKeys, Config};
+pub use crate::crypto::{CryptoBar, CryptoDailySeries, CryptoMetadata};
+pub use crate::dca::{simulate_dca, DcaPurchase, DcaSimulation, Schedule};
+pub use crate::dividend::{
+    dividend_history, projected_dividends, DividendHistory, DividendPayment, HoldingIncome,
+    IncomeForecast,
+};
+pub use crate::economy::{EconomicIndicator, EconomicSeries, Maturity};
+#[cfg(feature = "finnhub")]
+pub use crate::finnhub::FinnhubClient;
+pub use crate::fundamentals::{
+    AnnualEarnings, BalanceSheet, CashFlow, Earnings, IncomeStatement, QuarterlyEarnings,
+    Statements,
+};
+pub use crate::fx::{ExchangeRate, FxBar, FxDailySeries, FxMetadata};
+pub use crate::identifier::{Identifier, IdentifierMap, SymbolResolver};
+pub use crate::ledger::{EquityCurve, EquityPoint, Ledger, Transaction};
+pub use crate::lots::{GainsReport, Lot, LotBook, LotMethod, RealisedGain};
+pub use crate::money::{Decimal, Money, Quantity};
+pub use crate::news::{NewsArticle, TickerSentiment};
+pub use crate::notify::{Notifier, NotifyError};
+pub use crate::openfigi::OpenFigiClient;
+pub use crate::options::{Greeks, OptionContract, OptionKind};
+pub use crate::overview::CompanyOverview;
+pub use crate::portfolio::{
+    AssetType, Portfolio, Position, PositionValuation, Targets, Valuation, DEFAULT_CURRENCY,
+};
+pub use crate::provider::{
+    mover, price_on, summary, HistoricalPrice, Mover, Provider, ProviderKind, QuoteProvider,
+    TimeSeriesProvider,
+};
+pub use crate::quote::Quote;
+pub use crate::ratelimit::{ApiTier, RateLimiter};
+pub use crate::rebalance::{rebalance, Rebalance, Trade};
+pub use crate::recording::{Recording, RecordingMode};
+pub use crate::retry::RetryPolicy;
+pub use crate::risk::{risk_report, HoldingRisk, RiskReport};
+pub use crate::screen::{screen, Screen, ScreenMatch};
+pub use crate::search::SymbolMatch;
+pub use crate::sector::SectorPerformance;
+pub use crate::series::{
+    DailySeries, FiftyTwoWeekRange, Frequency, Interval, IntradayBar, IntradayMetadata,
+    IntradaySeries, Metadata, OutputSize, Series, TimeSeriesDay,
+};
+pub use crate::storage::{
+    config_dir, default_alerts_path, default_identifiers_path, default_ledger_path,
+    default_portfolio_path, default_watchlist_path, StorageError, SCHEMA_VERSION,
+};
+#[cfg(feature = "sqlite")]
+pub use crate::store::Store;
+pub use crate::symbol::{Symbol, SymbolError};
+pub use crate::tax::{estimate_tax, Jurisdiction, TaxEstimate, TaxRules};
+pub use crate::watchlist::Watchlist;
+pub use crate::yahoo::YahooClient;
 
-pub struct Symbol(String);
-impl Symbol {
-    pub fn new<S: Into<String>>(s: S) -> Symbol {
-        Symbol(s.into())
+#[derive(Debug, Clone)]
+pub struct ErrorContext {
+    pub provider: &'static str,
+    pub endpoint: String,
+    pub symbol: Option<Symbol>,
+}
+impl ErrorContext {
+    pub(crate) fn new<S: Into<String>>(
+        provider: &'static str,
+        endpoint: S,
+        params: &[(&str, &str)],
+    ) -> ErrorContext {
+        ErrorContext {
+            provider,
+            endpoint: endpoint.into(),
+            symbol: params
+                .iter()
+                .find(|(key, _value)| *key == "symbol")
+                .map(|(_key, symbol)| Symbol::new(*symbol)),
+        }
     }
 }
-impl<S> From<S> for Symbol
-where
-    S: Into<String>,
-{
-    fn from(s: S) -> Symbol {
-        Symbol::new(s)
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.provider, self.endpoint)?;
+        if let Some(symbol) = &self.symbol {
+            write!(f, " for {}", &**symbol)?;
+        }
+        Ok(())
     }
 }
 
-impl Deref for Symbol {
-    type Target = str;
-    fn deref(&self) -> &str {
-        self.0.as_str()
-    }
+#[derive(Debug)]
+pub enum ApiError {
+    Reqwest(reqwest::Error),
+    Json(serde_json::Error),
+    Csv(csv::Error),
+    #[cfg(feature = "streaming")]
+    // boxed because it is much larger than the other errors
+    WebSocket(Box<tokio_tungstenite::tungstenite::Error>),
+    DailyLimitExceeded,
+    InvalidSymbol(String),
+    RateLimited(String),
+    Api(String),
+    NoData,
+    Unsupported(&'static str),
+    PremiumRequired(&'static str),
+    MissingCredentials(&'static str),
+    // how long ago anything was last cached, if ever
+    Offline(Option<std::time::Duration>),
+    Decompression(std::io::Error),
+    // a response that couldn't be recorded, or replayed
+    Recording(std::path::PathBuf, std::io::Error),
+    Context {
+        context: ErrorContext,
+        source: Box<ApiError>,
+    },
 }
+impl ApiError {
+    pub(crate) fn with_context(self, context: ErrorContext) -> ApiError {
+        match self {
+            ApiError::Context { .. } => self,
+            error => ApiError::Context {
+                context,
+                source: Box::new(error),
+            },
+        }
+    }
+
+    pub fn context(&self) -> Option<&ErrorContext> {
+        match self {
+            ApiError::Context { context, .. } => Some(context),
+            _ => None,
+        }
+    }
 
-enum DailyOutputSize {
-    Compact,
-    Full,
+    // the underlying error, without the request it came from
+    pub fn kind(&self) -> &ApiError {
+        match self {
+            ApiError::Context { source, .. } => source.kind(),
+            error => error,
+        }
+    }
 }
-impl DailyOutputSize {
-    fn as_str(&self) -> &'static str {
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            DailyOutputSize::Compact => "compact",
-            DailyOutputSize::Full => "full",
+            ApiError::Reqwest(err) => write!(f, "request failed: {}", err),
+            ApiError::Json(err) => write!(f, "couldn't parse the response: {}", err),
+            ApiError::Csv(err) => write!(f, "couldn't parse the response: {}", err),
+            #[cfg(feature = "streaming")]
+            ApiError::WebSocket(err) => write!(f, "the stream failed: {}", err),
+            ApiError::DailyLimitExceeded => write!(f, "the daily request limit has been reached"),
+            ApiError::InvalidSymbol(symbol) => write!(f, "unknown symbol `{}`", symbol),
+            ApiError::RateLimited(message) => write!(f, "rate limited: {}", message),
+            ApiError::Api(message) => write!(f, "{}", message),
+            ApiError::NoData => write!(f, "no data was returned"),
+            ApiError::Unsupported(what) => write!(f, "{} aren't supported by this provider", what),
+            ApiError::PremiumRequired(what) => write!(f, "{} needs a premium API key", what),
+            ApiError::MissingCredentials(var) => write!(f, "no API key, set `{}`", var),
+            ApiError::Offline(Some(age)) => write!(
+                f,
+                "not cached, and offline (the cache was last updated {} ago)",
+                render::age(*age)
+            ),
+            ApiError::Offline(None) => write!(f, "not cached, and offline (the cache is empty)"),
+            ApiError::Decompression(err) => write!(f, "couldn't decompress the response: {}", err),
+            ApiError::Recording(path, err) => write!(f, "recording {}: {}", path.display(), err),
+            ApiError::Context { context, source } => write!(f, "{}: {}", context, source),
         }
     }
 }
-
-#[derive(Debug)]
-pub enum ApiError {
-    Reqwest(reqwest::Error),
+impl error::Error for ApiError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            ApiError::Reqwest(err) => Some(err),
+            ApiError::Json(err) => Some(err),
+            ApiError::Csv(err) => Some(err),
+            #[cfg(feature = "streaming")]
+            ApiError::WebSocket(err) => Some(err.as_ref()),
+            ApiError::Decompression(err) => Some(err),
+            ApiError::Recording(_path, err) => Some(err),
+            ApiError::Context { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
 }
 impl From<reqwest::Error> for ApiError {
     fn from(error: reqwest::Error) -> ApiError {
         ApiError::Reqwest(error)
     }
 }
-
-#[derive(Debug, Deserialize)]
-struct TimeSeriesDay {
-    #[serde(
-        rename = "1. open",
-        deserialize_with = "deserialize_number_from_string"
-    )]
-    open: f64,
-    #[serde(
-        rename = "2. high",
-        deserialize_with = "deserialize_number_from_string"
-    )]
-    high: f64,
-    #[serde(rename = "3. low", deserialize_with = "deserialize_number_from_string")]
-    low: f64,
-    #[serde(
-        rename = "4. close",
-        deserialize_with = "deserialize_number_from_string"
-    )]
-    close: f64,
-    #[serde(
-        rename = "5. adjusted close",
-        deserialize_with = "deserialize_number_from_string"
-    )]
-    adjusted_close: f64,
-    #[serde(
-        rename = "6. volume",
-        deserialize_with = "deserialize_number_from_string"
-    )]
-    volume: f64,
-    #[serde(
-        rename = "7. dividend amount",
-        deserialize_with = "deserialize_number_from_string"
-    )]
-    dividend_amount: f64,
-    #[serde(
-        rename = "8. split coefficient",
-        deserialize_with = "deserialize_number_from_string"
-    )]
-    split_coefficient: f64,
-}
-
-#[derive(Debug, Deserialize)]
-struct TimeSeriesDailyResponse {
-    #[serde(rename = "Meta Data")]
-    metadata: serde_json::Value,
-    #[serde(rename = "Time Series (Daily)")]
-    time_series: HashMap<chrono::NaiveDate, TimeSeriesDay>,
-}
-
-fn get_time_series_daily(
-    client: &reqwest::Client,
-    symbol: Symbol,
-    output_size: DailyOutputSize,
-) -> Result<TimeSeriesDailyResponse, ApiError> {
-    client
-        .get("https://www.alphavantage.co/query")
-        .query(&[
-            ("function", "TIME_SERIES_DAILY_ADJUSTED"),
-            ("symbol", &*symbol),
-            ("apikey", &*VANTAGE_API_KEY),
-            ("outputsize", output_size.as_str()),
-        ])
-        .send()
-        .and_then(|resp| resp.error_for_status())
-        .and_then(|mut resp| resp.json())
-        .map_err(|err| err.into())
-}
-
-pub fn get_latest_price_for_equity(symbol: Symbol) -> Result<f64, ApiError> {
-    let result = get_time_series_daily(&CLIENT, symbol, DailyOutputSize::Compact)?;
-
-    Ok(result
-        .time_series
-        .iter()
-        .max_by_key(|&(date, data)| date)
-        .map(|(date, data)| data.close)
-        .unwrap())
+impl From<serde_json::Error> for ApiError {
+    fn from(error: serde_json::Error) -> ApiError {
+        ApiError::Json(error)
+    }
+}
+impl From<csv::Error> for ApiError {
+    fn from(error: csv::Error) -> ApiError {
+        ApiError::Csv(error)
+    }
+}
+#[cfg(feature = "streaming")]
+impl From<tokio_tungstenite::tungstenite::Error> for ApiError {
+    fn from(error: tokio_tungstenite::tungstenite::Error) -> ApiError {
+        ApiError::WebSocket(Box::new(error))
+    }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum TimePeriod {
     Month,
     Year,
     AllTime,
+    Days(u32),
+    Range(chrono::NaiveDate, chrono::NaiveDate),
+}
+impl TimePeriod {
+    // a period starting on a weekend or holiday is measured from the close before
+    fn start(self, today: chrono::NaiveDate) -> Option<chrono::NaiveDate> {
+        let start = match self {
+            TimePeriod::Month => today - chrono::Months::new(1),
+            TimePeriod::Year => today - chrono::Months::new(12),
+            TimePeriod::AllTime => return None,
+            TimePeriod::Days(days) => today - chrono::Duration::days(days.into()),
+            TimePeriod::Range(from, _to) => from,
+        };
+        Some(calendar::trading_day_on_or_before(start))
+    }
+
+    pub fn range(
+        self,
+        today: chrono::NaiveDate,
+    ) -> (Bound<chrono::NaiveDate>, Bound<chrono::NaiveDate>) {
+        let start = match self.start(today) {
+            Some(start) => Bound::Included(start),
+            None => Bound::Unbounded,
+        };
+        match self {
+            TimePeriod::Range(_from, to) => (start, Bound::Included(to)),
+            _ => (start, Bound::Unbounded),
+        }
+    }
+
+    // a compact daily series covers the last 100 trading days
+    pub(crate) fn fits_compact(self, today: chrono::NaiveDate) -> bool {
+        self.start(today)
+            .is_some_and(|start| calendar::trading_days_between(start, today) < 100)
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct EquitySummary {
-    latest_price: f64,
-    earliest_price: f64,
-    max_price: f64,
-    min_price: f64,
+    pub start_date: chrono::NaiveDate,
+    pub end_date: chrono::NaiveDate,
+    pub latest_price: f64,
+    pub earliest_price: f64,
+    pub max_price: f64,
+    pub min_price: f64,
+    pub change: f64,
+    pub change_percent: f64,
+    pub annualised_return: f64,
+    pub average_daily_volume: f64,
+    pub fifty_two_week_high: Option<f64>,
+    pub fifty_two_week_low: Option<f64>,
+    pub from_high_percent: Option<f64>,
+    pub from_low_percent: Option<f64>,
+    // as the provider gave it in the series' metadata
+    pub last_refreshed: Option<chrono::DateTime<chrono::Utc>>,
 }
-pub fn summary_for_equity(
-    symbol: Symbol,
+
+// the 52 week range comes from `daily`, the full daily series, or from `series`
+// itself when that is the full daily series
+fn summarise(
+    series: Series,
+    daily: Option<&Series>,
     time_period: TimePeriod,
 ) -> Result<EquitySummary, ApiError> {
-    let now = chrono::Utc::now();
-    let today = now.date().naive_local();
+    let today = series.metadata.today();
+    let full = daily.unwrap_or(&series);
+    let range = full
+        .range(time_period.range(today))
+        .next_back()
+        .and_then(|(end, _bar)| FiftyTwoWeekRange::of(&full.bars, *end));
+    let series = series.slice(time_period.range(today));
 
-    let time_series = get_time_series_daily(&CLIENT, symbol, DailyOutputSize::Full)?.time_series;
+    let (end_date, latest_price) = series
+        .latest()
+        .map(|(date, data)| (*date, data.close))
+        .ok_or(ApiError::NoData)?;
+    let (start_date, earliest_price) = series
+        .earliest()
+        .map(|(date, data)| (*date, data.close))
+        .ok_or(ApiError::NoData)?;
+
+    let change = latest_price - earliest_price;
+    let change_percent = change / earliest_price * 100.0;
+    let days = (end_date - start_date).num_days();
+    let annualised_return = if days > 0 {
+        ((latest_price / earliest_price).powf(365.25 / days as f64) - 1.0) * 100.0
+    } else {
+        change_percent
+    };
 
-    let time_series: HashMap<_, _> = time_series
-        .into_iter()
-        .filter(|(date, data)| match time_period {
-            TimePeriod::Month => *date + chrono::Duration::days(30) >= today,
-            TimePeriod::Year => *date + chrono::Duration::days(365) >= today,
-            TimePeriod::AllTime => true,
-        })
-        .collect();
+    // weekly and monthly bars hold several sessions of volume
+    let sessions_per_bar = match series.frequency {
+        Frequency::Daily => 1.0,
+        Frequency::Weekly => 5.0,
+        Frequency::Monthly => 21.0,
+    };
+    let average_daily_volume = series.bars.values().map(|data| data.volume).sum::<f64>()
+        / (series.bars.len() as f64 * sessions_per_bar);
 
     Ok(EquitySummary {
-        latest_price: time_series
-            .iter()
-            .max_by_key(|&(date, data)| date)
-            .map(|(_date, data)| data.close)
-            .unwrap(),
-        earliest_price: time_series
-            .iter()
-            .min_by_key(|&(date, data)| date)
-            .map(|(_date, data)| data.close)
-            .unwrap(),
-        max_price: time_series
+        start_date,
+        end_date,
+        latest_price,
+        earliest_price,
+        change,
+        change_percent,
+        annualised_return,
+        average_daily_volume,
+        fifty_two_week_high: range.map(|range| range.high),
+        fifty_two_week_low: range.map(|range| range.low),
+        from_high_percent: range.map(|range| range.from_high(latest_price)),
+        from_low_percent: range.map(|range| range.from_low(latest_price)),
+        last_refreshed: series.metadata.last_refreshed_utc(),
+        max_price: series
+            .bars
             .values()
             .map(|data| data.high)
             .max_by(f64_ord_panic)
-            .unwrap(),
-        min_price: time_series
+            .ok_or(ApiError::NoData)?,
+        min_price: series
+            .bars
             .values()
             .map(|data| data.low)
             .min_by(f64_ord_panic)
-            .unwrap(),
+            .ok_or(ApiError::NoData)?,
     })
 }
 
@@ -0,0 +1,490 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use crate::{ApiError, Ledger, Money, Quantity, Symbol, Transaction};
+
+// gains on shares held for longer are long term
+pub(crate) const LONG_TERM_MONTHS: u32 = 12;
+const WASH_SALE_DAYS: i64 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LotMethod {
+    Fifo,
+    Lifo,
+    AverageCost,
+    SpecificLot,
+}
+impl FromStr for LotMethod {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<LotMethod, ApiError> {
+        match s.to_lowercase().as_str() {
+            "fifo" => Ok(LotMethod::Fifo),
+            "lifo" => Ok(LotMethod::Lifo),
+            "average" | "average-cost" => Ok(LotMethod::AverageCost),
+            "specific" | "specific-lot" => Ok(LotMethod::SpecificLot),
+            _ => Err(ApiError::Api(format!("unknown lot method `{}`", s))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Lot {
+    pub acquired: chrono::NaiveDate,
+    pub quantity: Quantity,
+    pub cost_basis: Money,
+    // the buys it came from, counted in the order they were applied
+    #[serde(skip)]
+    buys: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RealisedGain {
+    pub symbol: Symbol,
+    pub acquired: chrono::NaiveDate,
+    pub sold: chrono::NaiveDate,
+    pub quantity: Quantity,
+    pub proceeds: Money,
+    pub cost_basis: Money,
+    pub currency: String,
+    // a loss with the same symbol bought within 30 days of the sale
+    pub wash_sale: bool,
+    // the buys the lot came from, which don't count as replacing it
+    #[serde(skip)]
+    pub(crate) buys: Vec<usize>,
+}
+impl RealisedGain {
+    pub fn gain(&self) -> Money {
+        self.proceeds - self.cost_basis
+    }
+
+    // by the calendar, so a year that takes in 29 February is still a year
+    pub fn held_longer_than(&self, months: u32) -> bool {
+        self.acquired
+            .checked_add_months(chrono::Months::new(months))
+            .is_some_and(|anniversary| self.sold > anniversary)
+    }
+
+    pub fn long_term(&self) -> bool {
+        self.held_longer_than(LONG_TERM_MONTHS)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct GainsReport {
+    pub start_date: chrono::NaiveDate,
+    pub end_date: chrono::NaiveDate,
+    pub gains: Vec<RealisedGain>,
+}
+impl GainsReport {
+    // tax years are named by the calendar year they end in, so with a July
+    // start, 2023 runs from 2022-07-01 to 2023-06-30
+    pub fn for_year(
+        gains: Vec<RealisedGain>,
+        year: i32,
+        (start_month, start_day): (u32, u32),
+    ) -> Result<GainsReport, ApiError> {
+        let start_year = if (start_month, start_day) == (1, 1) {
+            Some(year)
+        } else {
+            year.checked_sub(1)
+        };
+        let dates = start_year.and_then(|start_year| {
+            let start = chrono::NaiveDate::from_ymd_opt(start_year, start_month, start_day)?;
+            let end = chrono::NaiveDate::from_ymd_opt(start_year + 1, start_month, start_day)?;
+            Some((start, end.pred_opt()?))
+        });
+        let (start_date, end_date) = dates.ok_or_else(|| {
+            ApiError::Api(format!(
+                "there's no tax year {} starting on {}-{}",
+                year, start_month, start_day
+            ))
+        })?;
+
+        Ok(GainsReport {
+            start_date,
+            end_date,
+            gains: gains
+                .into_iter()
+                .filter(|gain| gain.sold >= start_date && gain.sold <= end_date)
+                .collect(),
+        })
+    }
+
+    pub fn proceeds(&self) -> Money {
+        self.gains.iter().map(|gain| gain.proceeds).sum()
+    }
+
+    pub fn cost_basis(&self) -> Money {
+        self.gains.iter().map(|gain| gain.cost_basis).sum()
+    }
+
+    pub fn short_term(&self) -> Money {
+        self.gains
+            .iter()
+            .filter(|gain| !gain.long_term())
+            .map(RealisedGain::gain)
+            .sum()
+    }
+
+    pub fn long_term(&self) -> Money {
+        self.gains
+            .iter()
+            .filter(|gain| gain.long_term())
+            .map(RealisedGain::gain)
+            .sum()
+    }
+
+    pub fn total(&self) -> Money {
+        self.short_term() + self.long_term()
+    }
+
+    pub fn wash_sales(&self) -> impl Iterator<Item = &RealisedGain> {
+        self.gains.iter().filter(|gain| gain.wash_sale)
+    }
+}
+
+// lots are matched within the account they were bought in
+#[derive(Debug, Default)]
+pub struct LotBook {
+    lots: BTreeMap<(Option<String>, Symbol), Vec<Lot>>,
+    realised: Vec<RealisedGain>,
+    buys: usize,
+}
+impl LotBook {
+    pub fn new() -> LotBook {
+        LotBook::default()
+    }
+
+    pub fn open_lots(&self, account: Option<&str>, symbol: &Symbol) -> &[Lot] {
+        self.lots
+            .get(&(account.map(str::to_string), symbol.clone()))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    pub fn realised(&self) -> &[RealisedGain] {
+        &self.realised
+    }
+
+    pub fn apply(&mut self, transaction: &Transaction, method: LotMethod) -> Result<(), ApiError> {
+        let account = transaction.account().map(str::to_string);
+        match transaction {
+            Transaction::Buy {
+                date,
+                symbol,
+                quantity,
+                price,
+                fees,
+                ..
+            } => {
+                let buy = self.buys;
+                self.buys += 1;
+                // nothing to divide the cost between
+                if *quantity <= Quantity::ZERO {
+                    return Ok(());
+                }
+                self.lots
+                    .entry((account, symbol.clone()))
+                    .or_default()
+                    .push(Lot {
+                        acquired: *date,
+                        quantity: *quantity,
+                        cost_basis: quantity * price + fees,
+                        buys: vec![buy],
+                    });
+            }
+            Transaction::Sell {
+                date,
+                symbol,
+                quantity,
+                price,
+                fees,
+                lot,
+                currency,
+                ..
+            } => {
+                if *quantity <= Quantity::ZERO {
+                    return Ok(());
+                }
+                // a sale of shares that weren't held would drop out of the gains
+                let lots = self.lots.entry((account, symbol.clone())).or_default();
+                lots.retain(|lot| lot.quantity > Quantity::ZERO);
+                let held: Quantity = lots.iter().map(|lot| lot.quantity).sum();
+                if *quantity > held {
+                    return Err(ApiError::Api(format!(
+                        "{} sold {} {} but only {} were held",
+                        date, quantity, &**symbol, held
+                    )));
+                }
+                let sold = *quantity;
+                // fees are shared between the matched lots by quantity
+                let proceeds_per_unit = (quantity * price - fees) / quantity;
+
+                let matched = match method {
+                    LotMethod::AverageCost => average_cost(lots, sold),
+                    LotMethod::Fifo => take(lots, sold, |_lots| Some(0)).unwrap_or_default(),
+                    LotMethod::Lifo => {
+                        take(lots, sold, |lots| Some(lots.len() - 1)).unwrap_or_default()
+                    }
+                    LotMethod::SpecificLot => match lot {
+                        Some(acquired) => {
+                            let named: Quantity = lots
+                                .iter()
+                                .filter(|lot| lot.acquired == *acquired)
+                                .map(|lot| lot.quantity)
+                                .sum();
+                            if named == Quantity::ZERO {
+                                return Err(ApiError::Api(format!(
+                                    "no lot of {} bought on {} is left to sell on {}",
+                                    &**symbol, acquired, date
+                                )));
+                            }
+                            if named < sold {
+                                return Err(ApiError::Api(format!(
+                                    "{} sold {} {} from the lot bought on {}, which has only {} left",
+                                    date, sold, &**symbol, acquired, named
+                                )));
+                            }
+                            take(lots, sold, |lots| {
+                                lots.iter().position(|lot| lot.acquired == *acquired)
+                            })
+                            .unwrap_or_default()
+                        }
+                        // a sell that doesn't name its lot closes the oldest
+                        None => take(lots, sold, |_lots| Some(0)).unwrap_or_default(),
+                    },
+                };
+                lots.retain(|lot| lot.quantity > Quantity::ZERO);
+
+                self.realised
+                    .extend(matched.into_iter().map(|lot| RealisedGain {
+                        symbol: symbol.clone(),
+                        acquired: lot.acquired,
+                        sold: *date,
+                        quantity: lot.quantity,
+                        proceeds: lot.quantity * proceeds_per_unit,
+                        cost_basis: lot.cost_basis,
+                        currency: currency.clone(),
+                        wash_sale: false,
+                        buys: lot.buys,
+                    }));
+            }
+            Transaction::Split { symbol, ratio, .. } => {
+                for ((_account, held), lots) in &mut self.lots {
+                    if held == symbol {
+                        for lot in lots {
+                            lot.quantity *= ratio;
+                        }
+                    }
+                }
+            }
+            Transaction::Dividend { .. }
+            | Transaction::Deposit { .. }
+            | Transaction::Withdrawal { .. } => {}
+        }
+        Ok(())
+    }
+}
+
+// removes `quantity` from the lots, choosing which lot to draw from next with
+// `next`, or none if it runs out of lots to choose
+fn take<F: Fn(&[Lot]) -> Option<usize>>(
+    lots: &mut Vec<Lot>,
+    mut quantity: Quantity,
+    next: F,
+) -> Option<Vec<Lot>> {
+    let mut matched = vec![];
+    while quantity > Quantity::ZERO && !lots.is_empty() {
+        let idx = next(lots)?;
+        let lot = &mut lots[idx];
+        let taken = quantity.min(lot.quantity);
+        let cost = lot.cost_basis * taken / lot.quantity;
+        lot.quantity -= taken;
+        lot.cost_basis -= cost;
+        quantity -= taken;
+        matched.push(Lot {
+            acquired: lot.acquired,
+            quantity: taken,
+            cost_basis: cost,
+            buys: lot.buys.clone(),
+        });
+        if lot.quantity <= Quantity::ZERO {
+            lots.remove(idx);
+        }
+    }
+    Some(matched)
+}
+
+// every lot is reduced in proportion, so the remaining cost per unit is unchanged
+fn average_cost(lots: &mut [Lot], quantity: Quantity) -> Vec<Lot> {
+    let held: Quantity = lots.iter().map(|lot| lot.quantity).sum();
+    let total_cost: Money = lots.iter().map(|lot| lot.cost_basis).sum();
+    let fraction = quantity / held;
+    let acquired = match lots.iter().map(|lot| lot.acquired).min() {
+        Some(acquired) => acquired,
+        None => return vec![],
+    };
+    for lot in lots.iter_mut() {
+        lot.quantity -= lot.quantity * fraction;
+        lot.cost_basis -= lot.cost_basis * fraction;
+    }
+    vec![Lot {
+        acquired,
+        quantity,
+        cost_basis: total_cost * fraction,
+        buys: lots.iter().flat_map(|lot| lot.buys.clone()).collect(),
+    }]
+}
+
+impl Ledger {
+    pub fn lots(&self, method: LotMethod) -> Result<LotBook, ApiError> {
+        let mut book = LotBook::new();
+        for transaction in self.sorted() {
+            book.apply(transaction, method)?;
+        }
+        Ok(book)
+    }
+
+    // with wash sales flagged: purchases in any account count, but not the buys
+    // of the lots closed by the same sale
+    pub fn realised_lots(&self, method: LotMethod) -> Result<Vec<RealisedGain>, ApiError> {
+        let mut realised = self.lots(method)?.realised;
+        // numbered as the lot book numbers them
+        let buys: Vec<(chrono::NaiveDate, &Symbol)> = self
+            .sorted()
+            .into_iter()
+            .filter_map(|transaction| match transaction {
+                Transaction::Buy { date, symbol, .. } => Some((*date, symbol)),
+                _ => None,
+            })
+            .collect();
+        let flagged: Vec<bool> = realised
+            .iter()
+            .map(|gain| {
+                let closed: Vec<usize> = realised
+                    .iter()
+                    .filter(|other| other.symbol == gain.symbol && other.sold == gain.sold)
+                    .flat_map(|other| other.buys.iter().copied())
+                    .collect();
+                gain.gain() < Money::ZERO
+                    && buys.iter().enumerate().any(|(buy, (date, symbol))| {
+                        **symbol == gain.symbol
+                            && (*date - gain.sold).num_days().abs() <= WASH_SALE_DAYS
+                            && !closed.contains(&buy)
+                    })
+            })
+            .collect();
+        for (gain, wash_sale) in realised.iter_mut().zip(flagged) {
+            gain.wash_sale = wash_sale;
+        }
+        Ok(realised)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::money::Decimal;
+    use crate::testing::{buy, ledger, sell, sell_lot};
+
+    #[test]
+    fn sells_of_unheld_shares_are_errors() {
+        let ledger = ledger(vec![sell("2024-03-08", "IBM", 1, 100)]);
+        assert!(ledger.realised_lots(LotMethod::Fifo).is_err());
+    }
+
+    #[test]
+    fn oversells_are_errors() {
+        let ledger = ledger(vec![
+            buy("2024-01-02", "IBM", 5, 100),
+            sell("2024-03-08", "IBM", 6, 100),
+        ]);
+        for method in [LotMethod::Fifo, LotMethod::Lifo, LotMethod::AverageCost] {
+            assert!(ledger.realised_lots(method).is_err());
+        }
+    }
+
+    #[test]
+    fn specific_lots_must_cover_the_sale() {
+        let err = ledger(vec![
+            buy("2024-01-02", "IBM", 5, 100),
+            buy("2024-01-03", "IBM", 5, 110),
+            sell_lot("2024-03-08", "IBM", 6, 120, "2024-01-03"),
+        ])
+        .realised_lots(LotMethod::SpecificLot)
+        .unwrap_err();
+        assert!(err.to_string().contains("only 5 left"), "{}", err);
+
+        let gains = ledger(vec![
+            buy("2024-01-02", "IBM", 5, 100),
+            buy("2024-01-03", "IBM", 5, 110),
+            sell_lot("2024-03-08", "IBM", 4, 120, "2024-01-03"),
+        ])
+        .realised_lots(LotMethod::SpecificLot)
+        .unwrap();
+        assert_eq!(gains.len(), 1);
+        assert_eq!(gains[0].cost_basis, Decimal::from(440));
+    }
+
+    #[test]
+    fn repurchases_on_the_day_of_a_closed_lot_are_wash_sales() {
+        let gains = ledger(vec![
+            buy("2024-03-01", "IBM", 5, 100),
+            buy("2024-03-01", "IBM", 5, 100),
+            sell("2024-03-10", "IBM", 5, 80),
+        ])
+        .realised_lots(LotMethod::Fifo)
+        .unwrap();
+        assert_eq!(gains.len(), 1);
+        assert!(gains[0].wash_sale);
+    }
+
+    #[test]
+    fn averaged_lots_closed_together_are_not_wash_sales() {
+        let transactions = vec![
+            buy("2024-01-02", "IBM", 5, 100),
+            buy("2024-01-20", "IBM", 5, 100),
+            sell("2024-02-01", "IBM", 10, 80),
+        ];
+        let gains = ledger(transactions.clone())
+            .realised_lots(LotMethod::AverageCost)
+            .unwrap();
+        assert_eq!(gains.len(), 1);
+        assert!(!gains[0].wash_sale);
+
+        let mut transactions = transactions;
+        transactions.push(buy("2024-02-15", "IBM", 5, 80));
+        let gains = ledger(transactions)
+            .realised_lots(LotMethod::AverageCost)
+            .unwrap();
+        assert!(gains[0].wash_sale);
+    }
+
+    #[test]
+    fn a_year_over_a_leap_day_is_a_year() {
+        let gain = |acquired: &str, sold: &str| RealisedGain {
+            symbol: Symbol::new("IBM"),
+            acquired: acquired.parse().unwrap(),
+            sold: sold.parse().unwrap(),
+            quantity: Decimal::ONE,
+            proceeds: Decimal::ONE,
+            cost_basis: Decimal::ONE,
+            currency: "USD".to_string(),
+            wash_sale: false,
+            buys: vec![],
+        };
+        assert!(!gain("2023-03-01", "2024-03-01").long_term());
+        assert!(gain("2023-03-01", "2024-03-02").long_term());
+        assert!(!gain("2024-02-29", "2025-02-28").long_term());
+        assert!(gain("2024-02-29", "2025-03-01").long_term());
+    }
+
+    #[test]
+    fn tax_years_out_of_range_are_errors() {
+        assert!(GainsReport::for_year(vec![], 300_000, (1, 1)).is_err());
+        assert!(GainsReport::for_year(vec![], i32::MIN, (7, 1)).is_err());
+        assert!(GainsReport::for_year(vec![], 2024, (2, 30)).is_err());
+    }
+}
@@ -0,0 +1,421 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::{EquityCurve, Symbol};
+
+pub(crate) const TRADING_DAYS: f64 = 252.0;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Metrics {
+    pub start_date: chrono::NaiveDate,
+    pub end_date: chrono::NaiveDate,
+    pub total_return: f64,
+    pub annualised_return: f64,
+    pub max_drawdown: f64,
+    pub volatility: f64,
+    pub sharpe_ratio: f64,
+    pub sortino_ratio: f64,
+    pub money_weighted_return: Option<f64>,
+}
+
+pub fn daily_returns(values: &BTreeMap<chrono::NaiveDate, f64>) -> Vec<f64> {
+    let values: Vec<f64> = values.values().cloned().collect();
+    values
+        .windows(2)
+        .filter(|pair| pair[0] != 0.0)
+        .map(|pair| pair[1] / pair[0] - 1.0)
+        .collect()
+}
+
+pub fn max_drawdown(values: &BTreeMap<chrono::NaiveDate, f64>) -> f64 {
+    let mut peak = f64::MIN;
+    let mut drawdown: f64 = 0.0;
+    for value in values.values() {
+        peak = peak.max(*value);
+        if peak > 0.0 {
+            drawdown = drawdown.max((peak - value) / peak);
+        }
+    }
+    drawdown * 100.0
+}
+
+// `risk_free_rate` is an annual percentage, e.g. 4.0 for 4%
+pub fn compute(values: &BTreeMap<chrono::NaiveDate, f64>, risk_free_rate: f64) -> Option<Metrics> {
+    let (start_date, first) = values.iter().find(|(_date, value)| **value > 0.0)?;
+    let (end_date, last) = values.iter().next_back()?;
+    let values: BTreeMap<chrono::NaiveDate, f64> = values
+        .range(start_date..)
+        .map(|(date, value)| (*date, *value))
+        .collect();
+
+    let returns = daily_returns(&values);
+    if returns.is_empty() {
+        return None;
+    }
+
+    let total_return = (last / first - 1.0) * 100.0;
+    let years = (*end_date - *start_date).num_days() as f64 / 365.25;
+    let annualised_return = if years > 0.0 {
+        ((last / first).powf(1.0 / years) - 1.0) * 100.0
+    } else {
+        total_return
+    };
+
+    let daily_risk_free = risk_free_rate / 100.0 / TRADING_DAYS;
+    let excess: Vec<f64> = returns.iter().map(|r| r - daily_risk_free).collect();
+    let mean_excess = excess.iter().sum::<f64>() / excess.len() as f64;
+
+    let deviation = standard_deviation(&returns);
+    let downside: Vec<f64> = excess.iter().map(|r| r.min(0.0)).collect();
+    let downside_deviation =
+        (downside.iter().map(|r| r.powi(2)).sum::<f64>() / downside.len() as f64).sqrt();
+
+    let ratio = |deviation: f64| {
+        if deviation > 0.0 {
+            mean_excess / deviation * TRADING_DAYS.sqrt()
+        } else {
+            0.0
+        }
+    };
+
+    Some(Metrics {
+        start_date: *start_date,
+        end_date: *end_date,
+        total_return,
+        annualised_return,
+        max_drawdown: max_drawdown(&values),
+        volatility: deviation * TRADING_DAYS.sqrt() * 100.0,
+        sharpe_ratio: ratio(deviation),
+        sortino_ratio: ratio(downside_deviation),
+        money_weighted_return: None,
+    })
+}
+
+// metrics over the time-weighted index, so deposits and withdrawals don't count as returns
+pub fn for_equity_curve(curve: &EquityCurve, risk_free_rate: f64) -> Option<Metrics> {
+    let mut metrics = compute(&curve.time_weighted_index(), risk_free_rate)?;
+    metrics.money_weighted_return = money_weighted_return(curve);
+    Some(metrics)
+}
+
+pub fn time_weighted_return(curve: &EquityCurve) -> Option<f64> {
+    let index = curve.time_weighted_index();
+    let (_date, last) = index.iter().next_back()?;
+    Some(last - 100.0)
+}
+
+pub fn money_weighted_return(curve: &EquityCurve) -> Option<f64> {
+    let (end_date, end) = curve.latest()?;
+    let mut flows: Vec<(chrono::NaiveDate, f64)> = curve
+        .days
+        .iter()
+        .filter(|(_date, point)| point.net_flow != 0.0)
+        .map(|(date, point)| (*date, -point.net_flow))
+        .collect();
+    flows.push((*end_date, end.market_value));
+    xirr(&flows)
+}
+
+pub fn xirr(flows: &[(chrono::NaiveDate, f64)]) -> Option<f64> {
+    let (start, _amount) = flows.iter().min_by_key(|(date, _amount)| *date)?;
+    let years: Vec<(f64, f64)> = flows
+        .iter()
+        .map(|(date, amount)| ((*date - *start).num_days() as f64 / 365.25, *amount))
+        .collect();
+    let npv = |rate: f64| -> f64 {
+        years
+            .iter()
+            .map(|(t, amount)| amount / (1.0 + rate).powf(*t))
+            .sum()
+    };
+
+    // bisection is slow but can't diverge like Newton's method
+    let (mut low, mut high) = (-0.9999, 100.0);
+    if npv(low).signum() == npv(high).signum() {
+        return None;
+    }
+    for _ in 0..200 {
+        let mid = (low + high) / 2.0;
+        if npv(mid).signum() == npv(low).signum() {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    Some((low + high) / 2.0 * 100.0)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Comparison {
+    pub start_date: chrono::NaiveDate,
+    pub end_date: chrono::NaiveDate,
+    pub portfolio_return: f64,
+    pub benchmark_return: f64,
+    pub relative_return: f64,
+    pub alpha: f64,
+    pub beta: f64,
+}
+
+pub fn compare(
+    values: &BTreeMap<chrono::NaiveDate, f64>,
+    benchmark: &BTreeMap<chrono::NaiveDate, f64>,
+    risk_free_rate: f64,
+) -> Option<Comparison> {
+    let aligned: Vec<(chrono::NaiveDate, f64, f64)> = values
+        .iter()
+        .filter(|(_date, value)| **value > 0.0)
+        .filter_map(|(date, value)| benchmark.get(date).map(|bench| (*date, *value, *bench)))
+        .collect();
+    let (start_date, first, first_benchmark) = *aligned.first()?;
+    let (end_date, last, last_benchmark) = *aligned.last()?;
+
+    let (returns, benchmark_returns): (Vec<f64>, Vec<f64>) = aligned
+        .windows(2)
+        .map(|pair| (pair[1].1 / pair[0].1 - 1.0, pair[1].2 / pair[0].2 - 1.0))
+        .unzip();
+    if returns.len() < 2 {
+        return None;
+    }
+
+    let mean_return = mean(&returns);
+    let mean_benchmark = mean(&benchmark_returns);
+    let benchmark_variance = standard_deviation(&benchmark_returns).powi(2);
+    let beta = if benchmark_variance > 0.0 {
+        covariance(&returns, &benchmark_returns) / benchmark_variance
+    } else {
+        0.0
+    };
+
+    let daily_risk_free = risk_free_rate / 100.0 / TRADING_DAYS;
+    let alpha = (mean_return - daily_risk_free - beta * (mean_benchmark - daily_risk_free))
+        * TRADING_DAYS
+        * 100.0;
+
+    let portfolio_return = (last / first - 1.0) * 100.0;
+    let benchmark_return = (last_benchmark / first_benchmark - 1.0) * 100.0;
+    Some(Comparison {
+        start_date,
+        end_date,
+        portfolio_return,
+        benchmark_return,
+        relative_return: portfolio_return - benchmark_return,
+        alpha,
+        beta,
+    })
+}
+
+// daily returns over the dates both series have prices for
+pub(crate) fn aligned_returns(
+    values: &BTreeMap<chrono::NaiveDate, f64>,
+    other: &BTreeMap<chrono::NaiveDate, f64>,
+) -> (Vec<f64>, Vec<f64>) {
+    let aligned: Vec<(f64, f64)> = values
+        .iter()
+        .filter_map(|(date, value)| other.get(date).map(|other| (*value, *other)))
+        .filter(|(value, other)| *value > 0.0 && *other > 0.0)
+        .collect();
+    aligned
+        .windows(2)
+        .map(|pair| (pair[1].0 / pair[0].0 - 1.0, pair[1].1 / pair[0].1 - 1.0))
+        .unzip()
+}
+
+pub fn correlation(
+    values: &BTreeMap<chrono::NaiveDate, f64>,
+    other: &BTreeMap<chrono::NaiveDate, f64>,
+) -> Option<f64> {
+    let (returns, other_returns) = aligned_returns(values, other);
+    if returns.len() < 2 {
+        return None;
+    }
+    let deviations = standard_deviation(&returns) * standard_deviation(&other_returns);
+    if deviations > 0.0 {
+        Some(covariance(&returns, &other_returns) / deviations)
+    } else {
+        None
+    }
+}
+
+pub fn beta(
+    values: &BTreeMap<chrono::NaiveDate, f64>,
+    benchmark: &BTreeMap<chrono::NaiveDate, f64>,
+) -> Option<f64> {
+    let (returns, benchmark_returns) = aligned_returns(values, benchmark);
+    if returns.len() < 2 {
+        return None;
+    }
+    let benchmark_variance = standard_deviation(&benchmark_returns).powi(2);
+    if benchmark_variance > 0.0 {
+        Some(covariance(&returns, &benchmark_returns) / benchmark_variance)
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Correlations {
+    pub symbols: Vec<Symbol>,
+    // `matrix[i][j]` is the correlation between `symbols[i]` and `symbols[j]`
+    pub matrix: Vec<Vec<Option<f64>>>,
+    pub benchmark: Option<Symbol>,
+    pub betas: BTreeMap<Symbol, f64>,
+    pub portfolio_beta: Option<f64>,
+}
+impl Correlations {
+    pub fn get(&self, a: &Symbol, b: &Symbol) -> Option<f64> {
+        let i = self.symbols.iter().position(|symbol| symbol == a)?;
+        let j = self.symbols.iter().position(|symbol| symbol == b)?;
+        self.matrix[i][j]
+    }
+}
+
+// `weights` are fractions of the portfolio; a holding without prices (such as
+// cash) counts towards the portfolio's beta as zero
+pub fn correlations(
+    prices: &BTreeMap<Symbol, BTreeMap<chrono::NaiveDate, f64>>,
+    weights: &BTreeMap<Symbol, f64>,
+    benchmark: Option<(Symbol, &BTreeMap<chrono::NaiveDate, f64>)>,
+) -> Correlations {
+    let symbols: Vec<Symbol> = prices.keys().cloned().collect();
+    let matrix = prices
+        .values()
+        .map(|a| {
+            prices
+                .values()
+                .map(|b| {
+                    if std::ptr::eq(a, b) {
+                        Some(1.0)
+                    } else {
+                        correlation(a, b)
+                    }
+                })
+                .collect()
+        })
+        .collect();
+
+    let betas: BTreeMap<Symbol, f64> = match &benchmark {
+        Some((_symbol, benchmark)) => prices
+            .iter()
+            .filter_map(|(symbol, prices)| Some((symbol.clone(), beta(prices, benchmark)?)))
+            .collect(),
+        None => BTreeMap::new(),
+    };
+    let portfolio_beta = benchmark.as_ref().map(|_benchmark| {
+        weights
+            .iter()
+            .map(|(symbol, weight)| weight * betas.get(symbol).copied().unwrap_or(0.0))
+            .sum()
+    });
+
+    Correlations {
+        symbols,
+        matrix,
+        benchmark: benchmark.map(|(symbol, _prices)| symbol),
+        betas,
+        portfolio_beta,
+    }
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+pub(crate) fn covariance(a: &[f64], b: &[f64]) -> f64 {
+    let (mean_a, mean_b) = (mean(a), mean(b));
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| (a - mean_a) * (b - mean_b))
+        .sum::<f64>()
+        / (a.len() - 1) as f64
+}
+
+pub(crate) fn standard_deviation(values: &[f64]) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let variance =
+        values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (values.len() - 1) as f64;
+    variance.sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{indicators, testing, EquityPoint};
+
+    fn date(date: &str) -> chrono::NaiveDate {
+        date.parse().unwrap()
+    }
+
+    #[test]
+    fn deposits_are_not_returns() {
+        // (market value, net flow): 10% growth, a deposit, then 10% more
+        let days = [
+            (100.0, 100.0),
+            (110.0, 0.0),
+            (1110.0, 1000.0),
+            (1221.0, 0.0),
+        ];
+        let curve = EquityCurve {
+            days: date("2024-01-01")
+                .iter_days()
+                .zip(days)
+                .map(|(date, (market_value, net_flow))| {
+                    let point = EquityPoint {
+                        market_value,
+                        cost_basis: 0.0,
+                        net_deposits: 0.0,
+                        realised_gains: 0.0,
+                        dividends: 0.0,
+                        net_flow,
+                    };
+                    (date, point)
+                })
+                .collect(),
+        };
+        assert!((time_weighted_return(&curve).unwrap() - 21.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn xirr_annualises() {
+        let flows = [(date("2023-01-01"), -100.0), (date("2025-01-01"), 121.0)];
+        assert!((xirr(&flows).unwrap() - 10.0).abs() < 0.01);
+        // a loss on the same flows
+        let flows = [(date("2023-01-01"), -100.0), (date("2025-01-01"), 81.0)];
+        assert!((xirr(&flows).unwrap() + 10.0).abs() < 0.01);
+        // no rate makes flows of one sign worth nothing
+        assert!(xirr(&[(date("2023-01-01"), -100.0), (date("2024-01-01"), -5.0)]).is_none());
+    }
+
+    #[test]
+    fn splits_are_not_losses() {
+        let series = testing::split_series("IBM", 30);
+        let metrics = compute(&indicators::adjusted_closes(&series), 0.0).unwrap();
+        assert_eq!(metrics.total_return, 0.0);
+        assert_eq!(metrics.max_drawdown, 0.0);
+    }
+
+    #[test]
+    fn benchmarks_are_compared_by_adjusted_closes() {
+        // the portfolio moves with the benchmark, which splits 2:1 halfway
+        let closes: Vec<(f64, f64)> = (0..40)
+            .map(|day| {
+                let adjusted = 50.0 + (day % 5) as f64;
+                (if day < 20 { adjusted * 2.0 } else { adjusted }, adjusted)
+            })
+            .collect();
+        let benchmark = testing::daily_series("SPY", "2024-01-01", &closes);
+        let benchmark = indicators::adjusted_closes(&benchmark);
+        let values = benchmark
+            .iter()
+            .map(|(date, close)| (*date, close * 3.0))
+            .collect();
+
+        let comparison = compare(&values, &benchmark, 0.0).unwrap();
+        assert!((comparison.beta - 1.0).abs() < 1e-9);
+        assert!(comparison.alpha.abs() < 1e-9);
+        assert!(comparison.relative_return.abs() < 1e-9);
+    }
+}
@@ -0,0 +1,19 @@
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+pub use rust_decimal::Decimal;
+
+// what's recorded is exact: transaction and position quantities, prices, fees,
+// cost bases, lots and realised gains and tax, so they add up. Market data
+// (quotes, series, rates) and what's derived from it (valuations, returns,
+// indicators and backtests) stays f64, since it's quoted that way and goes
+// through square roots and logarithms; convert with `from_f64` and `to_f64`
+pub type Money = Decimal;
+pub type Quantity = Decimal;
+
+// NaN and infinite values become zero
+pub fn from_f64(value: f64) -> Decimal {
+    Decimal::from_f64(value).unwrap_or_default()
+}
+
+pub fn to_f64(value: Decimal) -> f64 {
+    value.to_f64().unwrap_or_default()
+}
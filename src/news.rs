@@ -0,0 +1,59 @@
+use serde::{de, Deserialize, Deserializer, Serialize};
+use serde_aux::field_attributes::deserialize_number_from_string;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TickerSentiment {
+    pub ticker: String,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub relevance_score: f64,
+    #[serde(
+        rename(deserialize = "ticker_sentiment_score"),
+        deserialize_with = "deserialize_number_from_string"
+    )]
+    pub sentiment_score: f64,
+    #[serde(rename(deserialize = "ticker_sentiment_label"))]
+    pub sentiment_label: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewsArticle {
+    pub title: String,
+    pub url: String,
+    #[serde(
+        rename(deserialize = "time_published"),
+        deserialize_with = "deserialize_published"
+    )]
+    pub published: chrono::NaiveDateTime,
+    #[serde(default)]
+    pub source: String,
+    #[serde(default)]
+    pub summary: String,
+    // from -1 (bearish) to 1 (bullish)
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub overall_sentiment_score: f64,
+    pub overall_sentiment_label: String,
+    #[serde(default)]
+    pub ticker_sentiment: Vec<TickerSentiment>,
+}
+impl NewsArticle {
+    pub fn sentiment_for(&self, ticker: &str) -> Option<&TickerSentiment> {
+        self.ticker_sentiment
+            .iter()
+            .find(|sentiment| sentiment.ticker.eq_ignore_ascii_case(ticker))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct NewsResponse {
+    #[serde(default)]
+    pub(crate) feed: Vec<NewsArticle>,
+}
+
+// Alpha Vantage publishes times as `20240131T143000`
+fn deserialize_published<'de, D>(deserializer: D) -> Result<chrono::NaiveDateTime, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    chrono::NaiveDateTime::parse_from_str(&s, "%Y%m%dT%H%M%S").map_err(de::Error::custom)
+}
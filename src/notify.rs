@@ -0,0 +1,103 @@
+use std::io;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{render, TriggeredAlert};
+
+#[derive(Debug)]
+pub enum NotifyError {
+    Reqwest(reqwest::Error),
+    Io(io::Error),
+    Status(u16),
+    Unsupported(&'static str),
+}
+impl From<reqwest::Error> for NotifyError {
+    fn from(error: reqwest::Error) -> NotifyError {
+        NotifyError::Reqwest(error)
+    }
+}
+impl From<io::Error> for NotifyError {
+    fn from(error: io::Error) -> NotifyError {
+        NotifyError::Io(error)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Notifier {
+    Webhook { url: String },
+    Desktop,
+}
+impl Notifier {
+    pub fn notify(&self, alert: &TriggeredAlert) -> Result<(), NotifyError> {
+        match self {
+            Notifier::Webhook { url } => webhook(url, alert),
+            Notifier::Desktop => desktop(alert),
+        }
+    }
+}
+
+impl TriggeredAlert {
+    pub fn message(&self) -> String {
+        format!(
+            "{} {}: now {} ({})",
+            &*self.rule.symbol,
+            self.rule.condition.describe(),
+            render::number(self.price, 2),
+            render::percent(self.change_percent)
+        )
+    }
+}
+
+// Slack reads `text` and Discord reads `content`, and both ignore the other
+#[derive(Serialize)]
+struct WebhookPayload<'a> {
+    text: &'a str,
+    content: &'a str,
+}
+
+fn webhook(url: &str, alert: &TriggeredAlert) -> Result<(), NotifyError> {
+    let message = alert.message();
+    let response = reqwest::blocking::Client::new()
+        .post(url)
+        .json(&WebhookPayload {
+            text: &message,
+            content: &message,
+        })
+        .send()?;
+    if !response.status().is_success() {
+        return Err(NotifyError::Status(response.status().as_u16()));
+    }
+    Ok(())
+}
+
+#[cfg(feature = "desktop")]
+fn desktop(alert: &TriggeredAlert) -> Result<(), NotifyError> {
+    use std::process::Command;
+
+    let title = format!("Portfolio alert: {}", &*alert.rule.symbol);
+    let message = alert.message();
+    let status = if cfg!(target_os = "macos") {
+        Command::new("osascript")
+            .arg("-e")
+            .arg(format!(
+                "display notification {:?} with title {:?}",
+                message, title
+            ))
+            .status()?
+    } else {
+        Command::new("notify-send")
+            .arg(title)
+            .arg(message)
+            .status()?
+    };
+    if !status.success() {
+        return Err(NotifyError::Unsupported("desktop notifications"));
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "desktop"))]
+fn desktop(_alert: &TriggeredAlert) -> Result<(), NotifyError> {
+    Err(NotifyError::Unsupported("desktop notifications"))
+}
@@ -0,0 +1,154 @@
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use crate::client::log_request;
+use crate::{ApiError, ErrorContext, Identifier, Symbol, SymbolResolver};
+
+const MAPPING_URL: &str = "https://api.openfigi.com/v3/mapping";
+
+// OpenFIGI's exchange codes and the suffixes of the symbols they're quoted
+// under, the preferred listing of an instrument first; US listings have none
+const EXCHANGES: &[(&str, &str)] = &[
+    ("US", ""),
+    ("UN", ""),
+    ("UW", ""),
+    ("UQ", ""),
+    ("UA", ""),
+    ("UP", ""),
+    ("LN", ".LON"),
+    ("CN", ".TRT"),
+    ("CT", ".TRT"),
+    ("CV", ".TRV"),
+    ("GY", ".DEX"),
+    ("GR", ".FRK"),
+    ("GF", ".FRK"),
+    ("AU", ".AX"),
+    ("AT", ".AX"),
+    ("NA", ".AMS"),
+    ("FP", ".PAR"),
+    ("IB", ".BSE"),
+    ("IS", ".NSE"),
+    ("CG", ".SHH"),
+    ("CS", ".SHZ"),
+    ("HK", ".HK"),
+    ("JP", ".T"),
+    ("JT", ".T"),
+];
+
+#[derive(Serialize)]
+struct MappingJob<'a> {
+    #[serde(rename = "idType")]
+    id_type: &'static str,
+    #[serde(rename = "idValue")]
+    id_value: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct MappingResult {
+    #[serde(default)]
+    data: Vec<Instrument>,
+    error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Instrument {
+    ticker: Option<String>,
+    #[serde(rename = "exchCode")]
+    exchange_code: Option<String>,
+}
+
+// the instrument's listing on the most preferred exchange, e.g. `BRK/B` on
+// `US` as `BRK.B`
+fn listing(instruments: &[Instrument]) -> Option<Symbol> {
+    let (instrument, suffix) = instruments
+        .iter()
+        .filter_map(|instrument| {
+            let code = instrument.exchange_code.as_deref()?;
+            let rank = EXCHANGES
+                .iter()
+                .position(|(exchange, _suffix)| *exchange == code)?;
+            Some((rank, instrument, EXCHANGES[rank].1))
+        })
+        .min_by_key(|(rank, _instrument, _suffix)| *rank)
+        .map(|(_rank, instrument, suffix)| (instrument, suffix))?;
+    let ticker = instrument
+        .ticker
+        .as_deref()?
+        .trim()
+        .replace(['/', ' '], ".");
+    Symbol::parse(&format!("{}{}", ticker, suffix)).ok()
+}
+
+// resolves ISINs and CUSIPs without an API key, or more of them at a time
+// with one
+#[derive(Default)]
+pub struct OpenFigiClient {
+    client: reqwest::blocking::Client,
+    api_key: Option<String>,
+}
+impl OpenFigiClient {
+    pub fn new() -> OpenFigiClient {
+        OpenFigiClient::default()
+    }
+
+    pub fn with_api_key<S: Into<String>>(api_key: S) -> OpenFigiClient {
+        OpenFigiClient {
+            client: reqwest::blocking::Client::new(),
+            api_key: Some(api_key.into()),
+        }
+    }
+
+    // the most jobs a request can have
+    fn batch_size(&self) -> usize {
+        match self.api_key {
+            Some(_) => 100,
+            None => 10,
+        }
+    }
+
+    fn mapping(&self, identifiers: &[Identifier]) -> Result<Vec<MappingResult>, ApiError> {
+        let jobs: Vec<MappingJob> = identifiers
+            .iter()
+            .map(|identifier| MappingJob {
+                id_type: match identifier {
+                    Identifier::Isin(_) => "ID_ISIN",
+                    Identifier::Cusip(_) => "ID_CUSIP",
+                },
+                id_value: identifier.as_str(),
+            })
+            .collect();
+        let mut request = self.client.post(MAPPING_URL).json(&jobs);
+        if let Some(api_key) = &self.api_key {
+            request = request.header("X-OPENFIGI-APIKEY", api_key);
+        }
+        let results: Vec<MappingResult> = request.send()?.error_for_status()?.json()?;
+        if results.len() != identifiers.len() {
+            return Err(ApiError::Api(format!(
+                "{} results for {} identifiers",
+                results.len(),
+                identifiers.len()
+            )));
+        }
+        Ok(results)
+    }
+}
+impl SymbolResolver for OpenFigiClient {
+    fn resolve(&self, identifiers: &[Identifier]) -> Result<Vec<Option<Symbol>>, ApiError> {
+        let mut symbols = vec![];
+        for batch in identifiers.chunks(self.batch_size()) {
+            let started = Instant::now();
+            let results = self.mapping(batch);
+            log_request("OpenFIGI", "mapping", &[], started, &results);
+            let results = results
+                .map_err(|err| err.with_context(ErrorContext::new("OpenFIGI", "mapping", &[])))?;
+            for result in results {
+                if let Some(error) = result.error {
+                    tracing::debug!(error, "OpenFIGI couldn't map an identifier");
+                }
+                symbols.push(listing(&result.data));
+            }
+        }
+        Ok(symbols)
+    }
+}
@@ -0,0 +1,215 @@
+use std::f64::consts::PI;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ApiError, Symbol};
+
+const DAYS_PER_YEAR: f64 = 365.0;
+
+fn default_multiplier() -> f64 {
+    100.0
+}
+
+fn default_volatility() -> f64 {
+    30.0
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OptionKind {
+    Call,
+    Put,
+}
+impl FromStr for OptionKind {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<OptionKind, ApiError> {
+        match s.to_lowercase().as_str() {
+            "call" => Ok(OptionKind::Call),
+            "put" => Ok(OptionKind::Put),
+            _ => Err(ApiError::Api(format!("unknown option kind `{}`", s))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Greeks {
+    pub delta: f64,
+    pub gamma: f64,
+    // the change in price per day
+    pub theta: f64,
+    // the change in price per percentage point of volatility
+    pub vega: f64,
+    // the change in price per percentage point of the risk free rate
+    pub rho: f64,
+}
+
+// a European option on a quoted underlying; a position's quantity is a number
+// of contracts, each of `multiplier` shares
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OptionContract {
+    pub underlying: Symbol,
+    pub kind: OptionKind,
+    pub strike: f64,
+    pub expiry: chrono::NaiveDate,
+    #[serde(default = "default_multiplier")]
+    pub multiplier: f64,
+    // annualised, in percent; there are no option quotes to imply it from
+    #[serde(default = "default_volatility")]
+    pub volatility: f64,
+    // in percent
+    #[serde(default)]
+    pub risk_free_rate: f64,
+}
+impl OptionContract {
+    pub fn days_to_expiry(&self, date: chrono::NaiveDate) -> i64 {
+        (self.expiry - date).num_days()
+    }
+
+    fn years_to_expiry(&self, date: chrono::NaiveDate) -> f64 {
+        self.days_to_expiry(date).max(0) as f64 / DAYS_PER_YEAR
+    }
+
+    // per share
+    pub fn intrinsic_value(&self, spot: f64) -> f64 {
+        match self.kind {
+            OptionKind::Call => (spot - self.strike).max(0.0),
+            OptionKind::Put => (self.strike - spot).max(0.0),
+        }
+    }
+
+    // the Black-Scholes d1 and d2, or none once there's no time or volatility left
+    fn d(&self, spot: f64, date: chrono::NaiveDate) -> Option<(f64, f64, f64)> {
+        let t = self.years_to_expiry(date);
+        let sigma = self.volatility / 100.0;
+        if t <= 0.0 || sigma <= 0.0 || spot <= 0.0 || self.strike <= 0.0 {
+            return None;
+        }
+        let r = self.risk_free_rate / 100.0;
+        let d1 = ((spot / self.strike).ln() + (r + sigma.powi(2) / 2.0) * t) / (sigma * t.sqrt());
+        Some((d1, d1 - sigma * t.sqrt(), t))
+    }
+
+    // the Black-Scholes price per share
+    pub fn theoretical_price(&self, spot: f64, date: chrono::NaiveDate) -> f64 {
+        let (d1, d2, t) = match self.d(spot, date) {
+            Some(d) => d,
+            None => return self.intrinsic_value(spot),
+        };
+        let discount = self.strike * (-self.risk_free_rate / 100.0 * t).exp();
+        match self.kind {
+            OptionKind::Call => spot * normal_cdf(d1) - discount * normal_cdf(d2),
+            OptionKind::Put => discount * normal_cdf(-d2) - spot * normal_cdf(-d1),
+        }
+    }
+
+    // per share; an expired option has none but delta
+    pub fn greeks(&self, spot: f64, date: chrono::NaiveDate) -> Greeks {
+        let (d1, d2, t) = match self.d(spot, date) {
+            Some(d) => d,
+            None => {
+                let in_the_money = self.intrinsic_value(spot) > 0.0;
+                let delta = match (self.kind, in_the_money) {
+                    (_, false) => 0.0,
+                    (OptionKind::Call, true) => 1.0,
+                    (OptionKind::Put, true) => -1.0,
+                };
+                return Greeks {
+                    delta,
+                    gamma: 0.0,
+                    theta: 0.0,
+                    vega: 0.0,
+                    rho: 0.0,
+                };
+            }
+        };
+        let r = self.risk_free_rate / 100.0;
+        let sigma = self.volatility / 100.0;
+        let discount = self.strike * (-r * t).exp();
+        let decay = -spot * normal_pdf(d1) * sigma / (2.0 * t.sqrt());
+        let (delta, theta, rho) = match self.kind {
+            OptionKind::Call => (
+                normal_cdf(d1),
+                decay - r * discount * normal_cdf(d2),
+                t * discount * normal_cdf(d2),
+            ),
+            OptionKind::Put => (
+                normal_cdf(d1) - 1.0,
+                decay + r * discount * normal_cdf(-d2),
+                -t * discount * normal_cdf(-d2),
+            ),
+        };
+        Greeks {
+            delta,
+            gamma: normal_pdf(d1) / (spot * sigma * t.sqrt()),
+            theta: theta / DAYS_PER_YEAR,
+            vega: spot * normal_pdf(d1) * t.sqrt() / 100.0,
+            rho: rho / 100.0,
+        }
+    }
+}
+
+fn normal_pdf(x: f64) -> f64 {
+    (-x * x / 2.0).exp() / (2.0 * PI).sqrt()
+}
+
+// Abramowitz and Stegun 26.2.17, accurate to 7.5e-8
+fn normal_cdf(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.231_641_9 * x.abs());
+    let polynomial = t
+        * (0.319_381_530
+            + t * (-0.356_563_782
+                + t * (1.781_477_937 + t * (-1.821_255_978 + t * 1.330_274_429))));
+    let upper = normal_pdf(x) * polynomial;
+    if x >= 0.0 {
+        1.0 - upper
+    } else {
+        upper
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contract(kind: OptionKind) -> OptionContract {
+        OptionContract {
+            underlying: Symbol::new("IBM"),
+            kind,
+            strike: 100.0,
+            expiry: "2025-01-01".parse().unwrap(),
+            multiplier: default_multiplier(),
+            volatility: 20.0,
+            risk_free_rate: 5.0,
+        }
+    }
+
+    #[test]
+    fn prices_match_black_scholes() {
+        // a year out, at the money
+        let date = "2024-01-02".parse().unwrap();
+        let call = contract(OptionKind::Call);
+        let put = contract(OptionKind::Put);
+        assert!((call.theoretical_price(100.0, date) - 10.4506).abs() < 1e-3);
+        assert!((put.theoretical_price(100.0, date) - 5.5735).abs() < 1e-3);
+        assert!((call.greeks(100.0, date).delta - 0.6368).abs() < 1e-3);
+
+        // put-call parity
+        let parity = 100.0 - 100.0 * (-0.05f64).exp();
+        let difference = call.theoretical_price(100.0, date) - put.theoretical_price(100.0, date);
+        assert!((difference - parity).abs() < 1e-6);
+    }
+
+    #[test]
+    fn expired_options_are_worth_their_intrinsic_value() {
+        let date = "2025-01-02".parse().unwrap();
+        let call = contract(OptionKind::Call);
+        assert_eq!(call.theoretical_price(120.0, date), 20.0);
+        assert_eq!(call.greeks(120.0, date).delta, 1.0);
+        assert_eq!(
+            contract(OptionKind::Put).theoretical_price(120.0, date),
+            0.0
+        );
+    }
+}
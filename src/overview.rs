@@ -0,0 +1,85 @@
+use serde::{de, Deserialize, Deserializer, Serialize};
+
+use crate::ApiError;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CompanyOverview {
+    #[serde(rename(deserialize = "Symbol"))]
+    pub symbol: String,
+    #[serde(rename(deserialize = "Name"))]
+    pub name: String,
+    #[serde(rename(deserialize = "Description"), default)]
+    pub description: String,
+    #[serde(rename(deserialize = "Exchange"), default)]
+    pub exchange: String,
+    #[serde(rename(deserialize = "Currency"), default)]
+    pub currency: String,
+    #[serde(rename(deserialize = "Country"), default)]
+    pub country: String,
+    #[serde(rename(deserialize = "Sector"), default)]
+    pub sector: String,
+    #[serde(rename(deserialize = "Industry"), default)]
+    pub industry: String,
+    #[serde(
+        rename(deserialize = "MarketCapitalization"),
+        deserialize_with = "deserialize_optional_number",
+        default
+    )]
+    pub market_capitalization: Option<f64>,
+    #[serde(
+        rename(deserialize = "PERatio"),
+        deserialize_with = "deserialize_optional_number",
+        default
+    )]
+    pub pe_ratio: Option<f64>,
+    #[serde(
+        rename(deserialize = "EPS"),
+        deserialize_with = "deserialize_optional_number",
+        default
+    )]
+    pub eps: Option<f64>,
+    #[serde(
+        rename(deserialize = "DividendYield"),
+        deserialize_with = "deserialize_optional_number",
+        default
+    )]
+    pub dividend_yield: Option<f64>,
+    // the declared annual dividend
+    #[serde(
+        rename(deserialize = "DividendPerShare"),
+        deserialize_with = "deserialize_optional_number",
+        default
+    )]
+    pub dividend_per_share: Option<f64>,
+    #[serde(
+        rename(deserialize = "52WeekHigh"),
+        deserialize_with = "deserialize_optional_number",
+        default
+    )]
+    pub week_52_high: Option<f64>,
+    #[serde(
+        rename(deserialize = "52WeekLow"),
+        deserialize_with = "deserialize_optional_number",
+        default
+    )]
+    pub week_52_low: Option<f64>,
+}
+
+pub(crate) fn overview_from_value(value: serde_json::Value) -> Result<CompanyOverview, ApiError> {
+    match value.as_object() {
+        Some(fields) if fields.is_empty() => Err(ApiError::NoData),
+        _ => Ok(serde_json::from_value(value)?),
+    }
+}
+
+// missing figures are reported as "None" or "-"
+pub(crate) fn deserialize_optional_number<'de, D>(deserializer: D) -> Result<Option<f64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    match s.as_str() {
+        "" | "None" | "-" => Ok(None),
+        s => s.parse().map(Some).map_err(de::Error::custom),
+    }
+}
@@ -0,0 +1,74 @@
+use std::collections::BTreeMap;
+
+use crate::{ApiError, IntradaySeries, RateLimiter};
+
+// one response of a series that only comes a slice at a time
+pub(crate) trait Page {
+    type Key: Ord;
+    type Row;
+    type Metadata;
+
+    fn into_parts(self) -> (Self::Metadata, BTreeMap<Self::Key, Self::Row>);
+}
+impl Page for IntradaySeries {
+    type Key = chrono::NaiveDateTime;
+    type Row = crate::IntradayBar;
+    type Metadata = crate::IntradayMetadata;
+
+    fn into_parts(self) -> (Self::Metadata, BTreeMap<Self::Key, Self::Row>) {
+        (self.metadata, self.bars)
+    }
+}
+
+type Rows<P> = BTreeMap<<P as Page>::Key, <P as Page>::Row>;
+
+// stitches the pages of a series together in the order they're requested,
+// oldest first, so where they overlap the later page's rows are kept along
+// with its metadata
+pub(crate) struct Paginator<P: Page> {
+    pages: usize,
+    metadata: Option<P::Metadata>,
+    rows: Rows<P>,
+    overlapping: usize,
+}
+impl<P: Page> Paginator<P> {
+    // fails before anything's requested when the rest of the day's requests
+    // wouldn't cover every page, even though some may be cached, rather than
+    // using them up on a series that can't be finished
+    pub(crate) fn new(pages: usize, rate_limiter: &RateLimiter) -> Result<Paginator<P>, ApiError> {
+        let remaining = rate_limiter.remaining_today();
+        if remaining.is_some_and(|remaining| (remaining as usize) < pages) {
+            return Err(ApiError::DailyLimitExceeded);
+        }
+        Ok(Paginator {
+            pages,
+            metadata: None,
+            rows: BTreeMap::new(),
+            overlapping: 0,
+        })
+    }
+
+    pub(crate) fn push(&mut self, page: P) {
+        let (metadata, rows) = page.into_parts();
+        for (key, row) in rows {
+            if self.rows.insert(key, row).is_some() {
+                self.overlapping += 1;
+            }
+        }
+        self.metadata = Some(metadata);
+    }
+
+    // none without any rows
+    pub(crate) fn finish(self) -> Option<(P::Metadata, Rows<P>)> {
+        tracing::debug!(
+            pages = self.pages,
+            rows = self.rows.len(),
+            overlapping = self.overlapping,
+            "stitched pages"
+        );
+        match self.metadata {
+            Some(metadata) if !self.rows.is_empty() => Some((metadata, self.rows)),
+            _ => None,
+        }
+    }
+}
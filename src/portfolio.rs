@@ -0,0 +1,425 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::money::{self, Money, Quantity};
+use crate::{ApiError, Bond, OptionContract, Quote, QuoteProvider, Symbol};
+
+pub const DEFAULT_CURRENCY: &str = "USD";
+
+pub(crate) fn default_currency() -> String {
+    DEFAULT_CURRENCY.to_string()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AssetType {
+    #[default]
+    Equity,
+    Etf,
+    Crypto,
+    Cash,
+    Bond,
+    Option,
+}
+impl AssetType {
+    pub fn label(self) -> &'static str {
+        match self {
+            AssetType::Equity => "Equity",
+            AssetType::Etf => "ETF",
+            AssetType::Crypto => "Crypto",
+            AssetType::Cash => "Cash",
+            AssetType::Bond => "Bond",
+            AssetType::Option => "Option",
+        }
+    }
+}
+impl FromStr for AssetType {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<AssetType, ApiError> {
+        match s.to_lowercase().as_str() {
+            "equity" => Ok(AssetType::Equity),
+            "etf" => Ok(AssetType::Etf),
+            "crypto" => Ok(AssetType::Crypto),
+            "cash" => Ok(AssetType::Cash),
+            "bond" => Ok(AssetType::Bond),
+            "option" => Ok(AssetType::Option),
+            _ => Err(ApiError::Api(format!("unknown asset type `{}`", s))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Targets {
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub symbol: BTreeMap<String, f64>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub sector: BTreeMap<String, f64>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub asset_class: BTreeMap<String, f64>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub geography: BTreeMap<String, f64>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub currency: BTreeMap<String, f64>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tag: BTreeMap<String, f64>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub account: BTreeMap<String, f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Position {
+    pub symbol: Symbol,
+    pub quantity: Quantity,
+    pub cost_basis: Money,
+    pub purchase_date: chrono::NaiveDate,
+    #[serde(default)]
+    pub asset_type: AssetType,
+    #[serde(default = "default_currency")]
+    pub currency: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sector: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account: Option<String>,
+    // the terms of a bond position
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bond: Option<Bond>,
+    // the terms of an option position
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub option: Option<OptionContract>,
+}
+impl Position {
+    pub fn new<S: Into<Symbol>>(
+        symbol: S,
+        quantity: Quantity,
+        cost_basis: Money,
+        purchase_date: chrono::NaiveDate,
+    ) -> Position {
+        Position {
+            symbol: symbol.into(),
+            quantity,
+            cost_basis,
+            purchase_date,
+            asset_type: AssetType::Equity,
+            currency: default_currency(),
+            sector: None,
+            country: None,
+            tags: vec![],
+            note: None,
+            account: None,
+            bond: None,
+            option: None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Portfolio {
+    positions: Vec<Position>,
+    base_currency: String,
+    targets: Targets,
+}
+impl Default for Portfolio {
+    fn default() -> Portfolio {
+        Portfolio {
+            positions: vec![],
+            base_currency: default_currency(),
+            targets: Targets::default(),
+        }
+    }
+}
+impl Portfolio {
+    pub fn new() -> Portfolio {
+        Portfolio::default()
+    }
+
+    pub fn add_position(&mut self, position: Position) {
+        self.positions.push(position);
+    }
+
+    pub fn positions(&self) -> &[Position] {
+        &self.positions
+    }
+
+    pub fn retain_account(&mut self, account: &str) {
+        self.positions
+            .retain(|position| position.account.as_deref() == Some(account));
+    }
+
+    pub fn accounts(&self) -> BTreeSet<&str> {
+        self.positions
+            .iter()
+            .filter_map(|position| position.account.as_deref())
+            .collect()
+    }
+
+    // keeps only the positions with any of `tags`
+    pub fn retain_tagged(&mut self, tags: &[String]) {
+        self.positions
+            .retain(|position| position.tags.iter().any(|tag| tags.contains(tag)));
+    }
+
+    pub fn base_currency(&self) -> &str {
+        &self.base_currency
+    }
+
+    pub fn set_base_currency<S: Into<String>>(&mut self, currency: S) {
+        self.base_currency = currency.into();
+    }
+
+    pub fn targets(&self) -> &Targets {
+        &self.targets
+    }
+
+    pub fn targets_mut(&mut self) -> &mut Targets {
+        &mut self.targets
+    }
+
+    pub fn valuation<P: QuoteProvider + ?Sized>(
+        &self,
+        provider: &P,
+    ) -> Result<Valuation, ApiError> {
+        // rather than under-report the portfolio's value without it
+        if let Some(position) = self
+            .positions
+            .iter()
+            .find(|position| position.asset_type == AssetType::Bond && position.bond.is_none())
+        {
+            return Err(ApiError::Api(format!(
+                "bond {} has no terms to value it by",
+                &*position.symbol
+            )));
+        }
+        let symbols_of = |asset_type: AssetType| {
+            let mut symbols: Vec<(Symbol, &str)> = self
+                .positions
+                .iter()
+                .filter(|position| position.asset_type == asset_type)
+                .map(|position| (position.symbol.clone(), position.currency.as_str()))
+                .collect();
+            symbols.sort();
+            symbols.dedup_by(|(a, _), (b, _)| a == b);
+            symbols
+        };
+
+        // options are valued from their underlying's quote
+        let mut equities: Vec<Symbol> = symbols_of(AssetType::Equity)
+            .into_iter()
+            .chain(symbols_of(AssetType::Etf))
+            .map(|(symbol, _currency)| symbol)
+            .chain(
+                self.positions
+                    .iter()
+                    .filter_map(|position| position.option.as_ref())
+                    .map(|option| option.underlying.clone()),
+            )
+            .collect();
+        equities.sort();
+        equities.dedup();
+        let mut quotes: HashMap<Symbol, Quote> = equities
+            .iter()
+            .cloned()
+            .zip(provider.quotes(&equities)?)
+            .collect();
+        for (symbol, market) in symbols_of(AssetType::Crypto) {
+            let quote = provider.crypto_quote(symbol.clone(), market)?;
+            quotes.insert(symbol, quote);
+        }
+
+        let mut currencies: Vec<&str> = self
+            .positions
+            .iter()
+            .map(|position| position.currency.as_str())
+            .filter(|currency| *currency != self.base_currency)
+            .collect();
+        currencies.sort_unstable();
+        currencies.dedup();
+
+        let mut rates = HashMap::new();
+        for currency in currencies {
+            let rate = provider.exchange_rate(currency, &self.base_currency)?;
+            rates.insert(currency.to_string(), rate);
+        }
+
+        Ok(self.valuation_with_quotes(&quotes, &rates))
+    }
+
+    pub fn valuation_with_quotes(
+        &self,
+        quotes: &HashMap<Symbol, Quote>,
+        rates: &HashMap<String, f64>,
+    ) -> Valuation {
+        let priced: Vec<&Quote> = self
+            .positions
+            .iter()
+            .filter_map(|position| match (position.asset_type, &position.option) {
+                (AssetType::Cash | AssetType::Bond, _) => None,
+                (AssetType::Option, Some(option)) => quotes.get(&option.underlying),
+                _ => quotes.get(&position.symbol),
+            })
+            .collect();
+        Valuation {
+            base_currency: self.base_currency.clone(),
+            prices_as_of: priced.iter().map(|quote| quote.latest_trading_day).min(),
+            retrieved_at: priced.iter().filter_map(|quote| quote.retrieved_at).min(),
+            positions: self
+                .positions
+                .iter()
+                .filter_map(|position| {
+                    let fx_rate = if position.currency == self.base_currency {
+                        1.0
+                    } else {
+                        *rates.get(&position.currency)?
+                    };
+                    // cash is held as a quantity of its currency, and bonds are
+                    // valued from their terms rather than quoted
+                    let today = crate::calendar::today();
+                    let yesterday = today.pred_opt().unwrap_or(today);
+                    let mut underlying_price = None;
+                    let (price, previous_close) = match (position.asset_type, &position.bond) {
+                        (AssetType::Cash, _) => (1.0, 1.0),
+                        (AssetType::Bond, Some(bond)) => {
+                            (bond.dirty_price(today), bond.dirty_price(yesterday))
+                        }
+                        // per contract, from the underlying's price and the day before's
+                        (AssetType::Option, _) => {
+                            let option = position.option.as_ref()?;
+                            let quote = quotes.get(&option.underlying)?;
+                            underlying_price = Some(quote.price);
+                            (
+                                option.multiplier * option.theoretical_price(quote.price, today),
+                                option.multiplier
+                                    * option.theoretical_price(quote.previous_close, yesterday),
+                            )
+                        }
+                        _ => quotes
+                            .get(&position.symbol)
+                            .map(|quote| (quote.price, quote.previous_close))?,
+                    };
+                    Some(PositionValuation {
+                        position: position.clone(),
+                        price,
+                        previous_close,
+                        fx_rate,
+                        underlying_price,
+                    })
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct PositionValuation {
+    pub position: Position,
+    pub price: f64,
+    pub previous_close: f64,
+    pub fx_rate: f64,
+    // for options
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub underlying_price: Option<f64>,
+}
+impl PositionValuation {
+    pub fn market_value(&self) -> f64 {
+        money::to_f64(self.position.quantity) * self.price
+    }
+
+    pub fn day_change(&self) -> f64 {
+        money::to_f64(self.position.quantity) * (self.price - self.previous_close)
+    }
+
+    pub fn unrealised_pnl(&self) -> f64 {
+        self.market_value() - money::to_f64(self.position.cost_basis)
+    }
+
+    pub fn unrealised_pnl_percent(&self) -> f64 {
+        self.unrealised_pnl() / money::to_f64(self.position.cost_basis) * 100.0
+    }
+
+    pub fn base_market_value(&self) -> f64 {
+        self.market_value() * self.fx_rate
+    }
+
+    pub fn base_cost_basis(&self) -> f64 {
+        money::to_f64(self.position.cost_basis) * self.fx_rate
+    }
+
+    pub fn base_day_change(&self) -> f64 {
+        self.day_change() * self.fx_rate
+    }
+
+    pub fn base_unrealised_pnl(&self) -> f64 {
+        self.unrealised_pnl() * self.fx_rate
+    }
+
+    // per contract, for options
+    pub fn intrinsic_value(&self) -> Option<f64> {
+        let option = self.position.option.as_ref()?;
+        Some(option.multiplier * option.intrinsic_value(self.underlying_price?))
+    }
+
+    pub fn extrinsic_value(&self) -> Option<f64> {
+        Some(self.price - self.intrinsic_value()?)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Valuation {
+    pub base_currency: String,
+    // from the stalest of the quotes
+    pub prices_as_of: Option<chrono::NaiveDate>,
+    pub retrieved_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub positions: Vec<PositionValuation>,
+}
+impl Valuation {
+    pub fn market_value(&self) -> f64 {
+        self.positions
+            .iter()
+            .map(PositionValuation::base_market_value)
+            .sum()
+    }
+
+    pub fn day_change(&self) -> f64 {
+        self.positions
+            .iter()
+            .map(PositionValuation::base_day_change)
+            .sum()
+    }
+
+    pub fn cost_basis(&self) -> f64 {
+        self.positions
+            .iter()
+            .map(PositionValuation::base_cost_basis)
+            .sum()
+    }
+
+    pub fn unrealised_pnl(&self) -> f64 {
+        self.market_value() - self.cost_basis()
+    }
+
+    pub fn unrealised_pnl_percent(&self) -> f64 {
+        self.unrealised_pnl() / self.cost_basis() * 100.0
+    }
+
+    // each symbol's share of the market value, with lots of the same symbol combined
+    pub fn weights(&self) -> BTreeMap<Symbol, f64> {
+        let total = self.market_value();
+        let mut weights = BTreeMap::new();
+        if total <= 0.0 {
+            return weights;
+        }
+        for position in &self.positions {
+            *weights.entry(position.position.symbol.clone()).or_default() +=
+                position.base_market_value() / total;
+        }
+        weights
+    }
+}
@@ -0,0 +1,214 @@
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use crate::{
+    calendar, summarise, ApiError, DailySeries, EquitySummary, Frequency, OutputSize,
+    PortfolioClient, Quote, Series, Symbol, TimePeriod, TimeSeriesDay,
+};
+
+pub trait QuoteProvider {
+    fn quote(&self, symbol: Symbol) -> Result<Quote, ApiError>;
+
+    fn quotes(&self, symbols: &[Symbol]) -> Result<Vec<Quote>, ApiError> {
+        symbols
+            .iter()
+            .map(|symbol| self.quote(symbol.clone()))
+            .collect()
+    }
+
+    fn crypto_quote(&self, _symbol: Symbol, _market: &str) -> Result<Quote, ApiError> {
+        Err(ApiError::Unsupported("crypto quotes"))
+    }
+
+    fn exchange_rate(&self, _from: &str, _to: &str) -> Result<f64, ApiError> {
+        Err(ApiError::Unsupported("exchange rates"))
+    }
+}
+
+pub trait TimeSeriesProvider {
+    fn daily_series(
+        &self,
+        symbol: Symbol,
+        output_size: OutputSize,
+    ) -> Result<DailySeries, ApiError>;
+
+    fn series(&self, symbol: Symbol, frequency: Frequency) -> Result<Series, ApiError> {
+        let daily = Series::from(self.daily_series(symbol, OutputSize::Full)?);
+        Ok(daily.resample(frequency))
+    }
+}
+
+pub trait Provider: QuoteProvider + TimeSeriesProvider {}
+impl<P: QuoteProvider + TimeSeriesProvider> Provider for P {}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProviderKind {
+    AlphaVantage,
+    Yahoo,
+    #[cfg(feature = "finnhub")]
+    Finnhub,
+}
+impl FromStr for ProviderKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<ProviderKind, String> {
+        match s.to_lowercase().as_str() {
+            "alphavantage" | "alpha-vantage" => Ok(ProviderKind::AlphaVantage),
+            "yahoo" => Ok(ProviderKind::Yahoo),
+            #[cfg(feature = "finnhub")]
+            "finnhub" => Ok(ProviderKind::Finnhub),
+            _ => Err(format!("unknown provider `{}`", s)),
+        }
+    }
+}
+
+pub fn summary<P: TimeSeriesProvider + ?Sized>(
+    provider: &P,
+    symbol: Symbol,
+    time_period: TimePeriod,
+) -> Result<EquitySummary, ApiError> {
+    let today = calendar::today();
+    let series = match time_period {
+        TimePeriod::AllTime => provider.series(symbol.clone(), Frequency::Monthly)?,
+        _ if time_period.fits_compact(today) => provider
+            .daily_series(symbol.clone(), OutputSize::Compact)?
+            .into(),
+        _ => {
+            return summarise(
+                provider.series(symbol, Frequency::Daily)?,
+                None,
+                time_period,
+            )
+        }
+    };
+    // the 52 week figures are left out rather than failing the summary
+    let daily = provider.series(symbol, Frequency::Daily).ok();
+
+    summarise(series, daily.as_ref(), time_period)
+}
+
+#[derive(Debug, Serialize)]
+pub struct HistoricalPrice {
+    pub symbol: Symbol,
+    pub requested: chrono::NaiveDate,
+    // the trading day the prices are from, which is before `requested` when
+    // that was a weekend or holiday
+    pub date: chrono::NaiveDate,
+    #[serde(flatten)]
+    pub day: TimeSeriesDay,
+}
+
+pub fn price_on<P: TimeSeriesProvider + ?Sized>(
+    provider: &P,
+    symbol: Symbol,
+    date: chrono::NaiveDate,
+) -> Result<HistoricalPrice, ApiError> {
+    // recent dates are usually in the compact series, which covers 100 trading days
+    let today = calendar::today();
+    let mut series = None;
+    if calendar::trading_days_between(date, today) < 100 {
+        let compact = provider.daily_series(symbol.clone(), OutputSize::Compact)?;
+        if compact
+            .earliest()
+            .is_some_and(|(earliest, _day)| *earliest <= date)
+        {
+            series = Some(compact);
+        }
+    }
+    let series = match series {
+        Some(series) => series,
+        None => provider.daily_series(symbol.clone(), OutputSize::Full)?,
+    };
+
+    let (traded, day) = series.on(date).ok_or(ApiError::NoData)?;
+    Ok(HistoricalPrice {
+        symbol,
+        requested: date,
+        date: *traded,
+        day: day.clone(),
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Mover {
+    pub symbol: Symbol,
+    pub window: Frequency,
+    pub date: chrono::NaiveDate,
+    pub price: f64,
+    pub start_price: f64,
+    pub change: f64,
+    pub change_percent: f64,
+}
+
+// the change over the latest session from the quote, or over the week or month
+// to the latest close from the compact daily series
+pub fn mover<P: Provider + ?Sized>(
+    provider: &P,
+    symbol: Symbol,
+    window: Frequency,
+) -> Result<Mover, ApiError> {
+    let start = match window {
+        Frequency::Daily => {
+            let quote = provider.quote(symbol.clone())?;
+            return Ok(Mover {
+                symbol,
+                window,
+                date: quote.latest_trading_day,
+                price: quote.price,
+                start_price: quote.previous_close,
+                change: quote.change,
+                change_percent: quote.change_percent,
+            });
+        }
+        Frequency::Weekly => |date| date - chrono::Duration::days(7),
+        Frequency::Monthly => |date| date - chrono::Months::new(1),
+    };
+
+    let series = provider.daily_series(symbol.clone(), OutputSize::Compact)?;
+    let (date, latest) = series.latest().ok_or(ApiError::NoData)?;
+    let (_date, earlier) = series.on(start(*date)).ok_or(ApiError::NoData)?;
+    // adjusted closes so that a split or dividend in the window isn't a move
+    let change = latest.adjusted_close - earlier.adjusted_close;
+    Ok(Mover {
+        symbol,
+        window,
+        date: *date,
+        price: latest.adjusted_close,
+        start_price: earlier.adjusted_close,
+        change,
+        change_percent: change / earlier.adjusted_close * 100.0,
+    })
+}
+
+impl QuoteProvider for PortfolioClient {
+    fn quote(&self, symbol: Symbol) -> Result<Quote, ApiError> {
+        PortfolioClient::quote(self, symbol)
+    }
+
+    fn quotes(&self, symbols: &[Symbol]) -> Result<Vec<Quote>, ApiError> {
+        PortfolioClient::quotes(self, symbols)
+    }
+
+    fn crypto_quote(&self, symbol: Symbol, market: &str) -> Result<Quote, ApiError> {
+        PortfolioClient::crypto_quote(self, symbol, market)
+    }
+
+    fn exchange_rate(&self, from: &str, to: &str) -> Result<f64, ApiError> {
+        Ok(PortfolioClient::exchange_rate(self, from, to)?.rate)
+    }
+}
+
+impl TimeSeriesProvider for PortfolioClient {
+    fn daily_series(
+        &self,
+        symbol: Symbol,
+        output_size: OutputSize,
+    ) -> Result<DailySeries, ApiError> {
+        PortfolioClient::daily_series(self, symbol, output_size)
+    }
+
+    fn series(&self, symbol: Symbol, frequency: Frequency) -> Result<Series, ApiError> {
+        PortfolioClient::series(self, symbol, frequency)
+    }
+}
@@ -0,0 +1,77 @@
+use serde::{de, Deserialize, Deserializer, Serialize};
+use serde_aux::field_attributes::deserialize_number_from_string;
+
+use crate::ApiError;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Quote {
+    #[serde(rename(deserialize = "01. symbol"))]
+    pub symbol: String,
+    #[serde(
+        rename(deserialize = "02. open"),
+        deserialize_with = "deserialize_number_from_string"
+    )]
+    pub open: f64,
+    #[serde(
+        rename(deserialize = "03. high"),
+        deserialize_with = "deserialize_number_from_string"
+    )]
+    pub high: f64,
+    #[serde(
+        rename(deserialize = "04. low"),
+        deserialize_with = "deserialize_number_from_string"
+    )]
+    pub low: f64,
+    #[serde(
+        rename(deserialize = "05. price"),
+        deserialize_with = "deserialize_number_from_string"
+    )]
+    pub price: f64,
+    #[serde(
+        rename(deserialize = "06. volume"),
+        deserialize_with = "deserialize_number_from_string"
+    )]
+    pub volume: f64,
+    #[serde(rename(deserialize = "07. latest trading day"))]
+    pub latest_trading_day: chrono::NaiveDate,
+    #[serde(
+        rename(deserialize = "08. previous close"),
+        deserialize_with = "deserialize_number_from_string"
+    )]
+    pub previous_close: f64,
+    #[serde(
+        rename(deserialize = "09. change"),
+        deserialize_with = "deserialize_number_from_string"
+    )]
+    pub change: f64,
+    #[serde(
+        rename(deserialize = "10. change percent"),
+        deserialize_with = "deserialize_percent_from_string"
+    )]
+    pub change_percent: f64,
+    // when the quote was downloaded, so a cached one shows its age
+    #[serde(default)]
+    pub retrieved_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct GlobalQuoteResponse {
+    #[serde(rename = "Global Quote")]
+    quote: serde_json::Value,
+}
+impl GlobalQuoteResponse {
+    pub(crate) fn into_quote(self) -> Result<Quote, ApiError> {
+        match self.quote.as_object() {
+            Some(fields) if fields.is_empty() => Err(ApiError::NoData),
+            _ => Ok(serde_json::from_value(self.quote)?),
+        }
+    }
+}
+
+fn deserialize_percent_from_string<'de, D>(deserializer: D) -> Result<f64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    s.trim_end_matches('%').parse().map_err(de::Error::custom)
+}
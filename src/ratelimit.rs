@@ -0,0 +1,130 @@
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::ApiError;
+
+#[derive(Debug, Clone, Copy)]
+pub enum ApiTier {
+    Free,
+    Premium { requests_per_minute: u32 },
+}
+impl ApiTier {
+    fn requests_per_minute(self) -> u32 {
+        match self {
+            ApiTier::Free => 5,
+            ApiTier::Premium {
+                requests_per_minute,
+            } => requests_per_minute,
+        }
+    }
+
+    fn requests_per_day(self) -> Option<u32> {
+        match self {
+            ApiTier::Free => Some(500),
+            ApiTier::Premium { .. } => None,
+        }
+    }
+}
+// `free`, or a premium plan's requests per minute
+impl FromStr for ApiTier {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<ApiTier, ApiError> {
+        match s.to_lowercase().as_str() {
+            "free" => Ok(ApiTier::Free),
+            requests => match requests.parse() {
+                Ok(requests_per_minute) if requests_per_minute > 0 => Ok(ApiTier::Premium {
+                    requests_per_minute,
+                }),
+                _ => Err(ApiError::Api(format!(
+                    "unknown API tier `{}`; give free or a premium plan's requests per minute",
+                    s
+                ))),
+            },
+        }
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+    day: chrono::NaiveDate,
+    requests_today: u32,
+}
+
+pub struct RateLimiter {
+    tier: ApiTier,
+    bucket: Mutex<Bucket>,
+}
+impl RateLimiter {
+    pub fn new(tier: ApiTier) -> RateLimiter {
+        RateLimiter {
+            tier,
+            bucket: Mutex::new(Bucket {
+                tokens: f64::from(tier.requests_per_minute()),
+                refilled_at: Instant::now(),
+                day: chrono::Utc::now().date_naive(),
+                requests_today: 0,
+            }),
+        }
+    }
+
+    pub fn tier(&self) -> ApiTier {
+        self.tier
+    }
+
+    pub fn requests_today(&self) -> u32 {
+        let bucket = self.bucket.lock().unwrap();
+        if bucket.day == chrono::Utc::now().date_naive() {
+            bucket.requests_today
+        } else {
+            0
+        }
+    }
+
+    // the requests left until the daily limit, or none without one
+    pub fn remaining_today(&self) -> Option<u32> {
+        let limit = self.tier.requests_per_day()?;
+        Some(limit.saturating_sub(self.requests_today()))
+    }
+
+    // requests that can be made now without waiting, below zero while requests wait
+    pub fn available(&self) -> f64 {
+        let capacity = f64::from(self.tier.requests_per_minute());
+        let bucket = self.bucket.lock().unwrap();
+        let elapsed = bucket.refilled_at.elapsed().as_secs_f64();
+        (bucket.tokens + elapsed * capacity / 60.0).min(capacity)
+    }
+
+    pub(crate) fn reserve(&self) -> Result<Duration, ApiError> {
+        let capacity = f64::from(self.tier.requests_per_minute());
+        let per_second = capacity / 60.0;
+
+        let mut bucket = self.bucket.lock().unwrap();
+
+        let today = chrono::Utc::now().date_naive();
+        if bucket.day != today {
+            bucket.day = today;
+            bucket.requests_today = 0;
+        }
+        if let Some(limit) = self.tier.requests_per_day() {
+            if bucket.requests_today >= limit {
+                return Err(ApiError::DailyLimitExceeded);
+            }
+        }
+        bucket.requests_today += 1;
+
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * per_second).min(capacity);
+        bucket.refilled_at = now;
+
+        bucket.tokens -= 1.0;
+        if bucket.tokens >= 0.0 {
+            Ok(Duration::from_secs(0))
+        } else {
+            Ok(Duration::from_secs_f64(-bucket.tokens / per_second))
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::Serialize;
+
+use crate::{AssetType, PositionValuation, Symbol, Targets, Valuation};
+
+#[derive(Debug, Serialize)]
+pub struct Trade {
+    pub symbol: Symbol,
+    pub price: f64,
+    pub fx_rate: f64,
+    pub quantity: f64,
+    pub current_value: f64,
+    pub target_value: f64,
+}
+impl Trade {
+    pub fn value(&self) -> f64 {
+        self.quantity * self.price * self.fx_rate
+    }
+
+    pub fn is_buy(&self) -> bool {
+        self.quantity > 0.0
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Rebalance {
+    pub market_value: f64,
+    pub cash: f64,
+    pub trades: Vec<Trade>,
+}
+impl Rebalance {
+    pub fn remaining_cash(&self) -> f64 {
+        self.cash - self.trades.iter().map(Trade::value).sum::<f64>()
+    }
+}
+
+struct Holding {
+    price: f64,
+    fx_rate: f64,
+    asset_type: AssetType,
+    value: f64,
+}
+
+// Symbol targets take precedence over asset class targets. Holdings without a
+// target and cash positions are left alone, and `cash` is new money in the base
+// currency. `prices` supplies base currency prices for targeted symbols that
+// aren't held yet.
+pub fn rebalance(
+    valuation: &Valuation,
+    targets: &Targets,
+    prices: &HashMap<Symbol, f64>,
+    cash: f64,
+    min_trade_size: f64,
+) -> Rebalance {
+    let mut holdings: BTreeMap<Symbol, Holding> = BTreeMap::new();
+    for position in &valuation.positions {
+        if position.position.asset_type == AssetType::Cash {
+            continue;
+        }
+        let holding = holdings
+            .entry(position.position.symbol.clone())
+            .or_insert_with(|| holding_of(position));
+        holding.value += position.base_market_value();
+    }
+
+    let total = valuation.market_value() + cash;
+    let mut target_values: BTreeMap<Symbol, f64> = BTreeMap::new();
+    if !targets.symbol.is_empty() {
+        for (symbol, weight) in &targets.symbol {
+            let symbol = Symbol::new(symbol.as_str());
+            if !holdings.contains_key(&symbol) {
+                match prices.get(&symbol) {
+                    Some(price) => {
+                        holdings.insert(
+                            symbol.clone(),
+                            Holding {
+                                price: *price,
+                                fx_rate: 1.0,
+                                asset_type: AssetType::Equity,
+                                value: 0.0,
+                            },
+                        );
+                    }
+                    None => continue,
+                }
+            }
+            target_values.insert(symbol, total * weight / 100.0);
+        }
+    } else {
+        for (class, weight) in &targets.asset_class {
+            let members: Vec<(&Symbol, &Holding)> = holdings
+                .iter()
+                .filter(|(_symbol, holding)| holding.asset_type.label() == class.as_str())
+                .collect();
+            let class_value: f64 = members.iter().map(|(_symbol, holding)| holding.value).sum();
+            let class_target = total * weight / 100.0;
+
+            // the class is scaled as a whole, keeping its internal weights
+            if class_value > 0.0 {
+                for (symbol, holding) in members {
+                    target_values
+                        .insert(symbol.clone(), class_target * holding.value / class_value);
+                }
+            }
+        }
+    }
+
+    let trades = target_values
+        .into_iter()
+        .filter_map(|(symbol, target_value)| {
+            let holding = &holdings[&symbol];
+            let difference = target_value - holding.value;
+            if difference.abs() < min_trade_size || holding.price <= 0.0 {
+                return None;
+            }
+
+            let quantity = difference / (holding.price * holding.fx_rate);
+            let quantity = match holding.asset_type {
+                AssetType::Crypto => (quantity * 1e8).trunc() / 1e8,
+                _ => quantity.trunc(),
+            };
+            let trade = Trade {
+                symbol,
+                price: holding.price,
+                fx_rate: holding.fx_rate,
+                quantity,
+                current_value: holding.value,
+                target_value,
+            };
+            Some(trade)
+                .filter(|trade| trade.quantity != 0.0 && trade.value().abs() >= min_trade_size)
+        })
+        .collect();
+
+    Rebalance {
+        market_value: total,
+        cash,
+        trades,
+    }
+}
+
+fn holding_of(position: &PositionValuation) -> Holding {
+    Holding {
+        price: position.price,
+        fx_rate: position.fx_rate,
+        asset_type: position.position.asset_type,
+        value: 0.0,
+    }
+}
@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use crate::cache::file_name;
+use crate::ApiError;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RecordingMode {
+    Record,
+    Replay,
+}
+
+// every raw response saved to `dir` as it's received, or answered from there
+// in the order it was, so a run can be repeated exactly without the API. The
+// API key is never part of a recording.
+#[derive(Clone)]
+pub struct Recording {
+    dir: PathBuf,
+    mode: RecordingMode,
+    // how many times each request has been made, by any client given a clone
+    requests: Arc<Mutex<HashMap<String, usize>>>,
+}
+impl Recording {
+    pub fn record<P: Into<PathBuf>>(dir: P) -> Recording {
+        Recording::new(dir.into(), RecordingMode::Record)
+    }
+
+    pub fn replay<P: Into<PathBuf>>(dir: P) -> Recording {
+        Recording::new(dir.into(), RecordingMode::Replay)
+    }
+
+    fn new(dir: PathBuf, mode: RecordingMode) -> Recording {
+        Recording {
+            dir,
+            mode,
+            requests: Arc::default(),
+        }
+    }
+
+    pub fn mode(&self) -> RecordingMode {
+        self.mode
+    }
+
+    // e.g. `GLOBAL_QUOTE-IBM-2.json` for the second IBM quote
+    fn next_path(&self, params: &[(&str, &str)]) -> PathBuf {
+        let mut requests = self.requests.lock().unwrap();
+        let count = requests.entry(file_name(params, "")).or_insert(0);
+        *count += 1;
+        self.dir
+            .join(format!("{}.json", file_name(params, &count.to_string())))
+    }
+
+    pub(crate) fn replayed(&self, params: &[(&str, &str)]) -> Option<Result<String, ApiError>> {
+        if self.mode != RecordingMode::Replay {
+            return None;
+        }
+        let path = self.next_path(params);
+        tracing::trace!(path = %path.display(), "replaying");
+        Some(fs::read_to_string(&path).map_err(|err| ApiError::Recording(path, err)))
+    }
+
+    pub(crate) fn save(&self, params: &[(&str, &str)], body: &str) -> Result<(), ApiError> {
+        if self.mode != RecordingMode::Record {
+            return Ok(());
+        }
+        let path = self.next_path(params);
+        tracing::trace!(path = %path.display(), "recording");
+        fs::create_dir_all(&self.dir)
+            .and_then(|()| fs::write(&path, body))
+            .map_err(|err| ApiError::Recording(path, err))
+    }
+}
@@ -0,0 +1,473 @@
+use std::env;
+use std::fmt;
+use std::io::{self, IsTerminal};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Align {
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Colour {
+    Red,
+    Green,
+}
+impl Colour {
+    fn code(self) -> &'static str {
+        match self {
+            Colour::Red => "\x1b[31m",
+            Colour::Green => "\x1b[32m",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Cell {
+    text: String,
+    colour: Option<Colour>,
+}
+impl Cell {
+    pub fn new<S: Into<String>>(text: S) -> Cell {
+        Cell {
+            text: text.into(),
+            colour: None,
+        }
+    }
+
+    pub fn empty() -> Cell {
+        Cell::new("")
+    }
+
+    pub fn change<S: Into<String>>(value: f64, text: S) -> Cell {
+        let colour = if value > 0.0 {
+            Some(Colour::Green)
+        } else if value < 0.0 {
+            Some(Colour::Red)
+        } else {
+            None
+        };
+        Cell {
+            text: text.into(),
+            colour,
+        }
+    }
+}
+impl<S: Into<String>> From<S> for Cell {
+    fn from(text: S) -> Cell {
+        Cell::new(text)
+    }
+}
+
+pub struct Table {
+    columns: Vec<(String, Align)>,
+    rows: Vec<Vec<Cell>>,
+    colour: bool,
+}
+impl Table {
+    pub fn new(columns: &[(&str, Align)]) -> Table {
+        Table {
+            columns: columns
+                .iter()
+                .map(|(name, align)| (name.to_string(), *align))
+                .collect(),
+            rows: vec![],
+            colour: colour_enabled(),
+        }
+    }
+
+    pub fn colour(mut self, colour: bool) -> Table {
+        self.colour = colour;
+        self
+    }
+
+    pub fn row(&mut self, cells: Vec<Cell>) {
+        self.rows.push(cells);
+    }
+
+    fn widths(&self) -> Vec<usize> {
+        self.columns
+            .iter()
+            .enumerate()
+            .map(|(idx, (name, _align))| {
+                self.rows
+                    .iter()
+                    .filter_map(|row| row.get(idx))
+                    .map(|cell| cell.text.chars().count())
+                    .chain(Some(name.chars().count()))
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect()
+    }
+}
+impl fmt::Display for Table {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let widths = self.widths();
+        let write_line = |f: &mut fmt::Formatter, cells: Vec<(&str, Option<Colour>)>| {
+            let mut line = String::new();
+            for (idx, (text, colour)) in cells.into_iter().enumerate() {
+                if idx > 0 {
+                    line.push_str("  ");
+                }
+                let (_name, align) = &self.columns[idx];
+                let padding = " ".repeat(widths[idx].saturating_sub(text.chars().count()));
+                let text = match colour {
+                    Some(colour) if self.colour => format!("{}{}\x1b[0m", colour.code(), text),
+                    _ => text.to_string(),
+                };
+                match align {
+                    Align::Left => {
+                        line.push_str(&text);
+                        line.push_str(&padding);
+                    }
+                    Align::Right => {
+                        line.push_str(&padding);
+                        line.push_str(&text);
+                    }
+                }
+            }
+            writeln!(f, "{}", line.trim_end())
+        };
+
+        write_line(
+            f,
+            self.columns
+                .iter()
+                .map(|(name, _align)| (name.as_str(), None))
+                .collect(),
+        )?;
+        for row in &self.rows {
+            write_line(
+                f,
+                (0..self.columns.len())
+                    .map(|idx| match row.get(idx) {
+                        Some(cell) => (cell.text.as_str(), cell.colour),
+                        None => ("", None),
+                    })
+                    .collect(),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+pub fn colour_enabled() -> bool {
+    env::var_os("NO_COLOR").is_none() && io::stdout().is_terminal()
+}
+
+pub fn number(value: f64, decimals: usize) -> String {
+    let formatted = format!("{:.*}", decimals, value.abs());
+    let (whole, fraction) = match formatted.find('.') {
+        Some(idx) => formatted.split_at(idx),
+        None => (formatted.as_str(), ""),
+    };
+
+    let mut grouped = String::new();
+    for (idx, digit) in whole.chars().enumerate() {
+        if idx > 0 && (whole.len() - idx) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+
+    let sign = if value < 0.0 && formatted.chars().any(|c| c.is_ascii_digit() && c != '0') {
+        "-"
+    } else {
+        ""
+    };
+    format!("{}{}{}", sign, grouped, fraction)
+}
+
+pub fn currency_symbol(code: &str) -> &str {
+    match code {
+        "USD" | "AUD" | "CAD" | "NZD" | "HKD" | "SGD" => "$",
+        "EUR" => "€",
+        "GBP" => "£",
+        "JPY" | "CNY" => "¥",
+        "INR" => "₹",
+        code => code,
+    }
+}
+
+pub fn money(value: f64, currency: &str) -> String {
+    let formatted = number(value, 2);
+    match formatted.strip_prefix('-') {
+        Some(formatted) => format!("-{}{}", currency, formatted),
+        None => format!("{}{}", currency, formatted),
+    }
+}
+
+pub fn signed_money(value: f64, currency: &str) -> String {
+    if value > 0.0 {
+        format!("+{}", money(value, currency))
+    } else {
+        money(value, currency)
+    }
+}
+
+// roughly how long ago, e.g. `5m` or `3d`
+pub fn age(age: std::time::Duration) -> String {
+    let minutes = age.as_secs() / 60;
+    match minutes {
+        0 => "<1m".to_string(),
+        1..=59 => format!("{}m", minutes),
+        60..=1439 => format!("{}h", minutes / 60),
+        _ => format!("{}d", minutes / 1440),
+    }
+}
+
+pub fn percent(value: f64) -> String {
+    if value.is_nan() {
+        return "-".to_string();
+    }
+    if value > 0.0 {
+        format!("+{}%", number(value, 2))
+    } else {
+        format!("{}%", number(value, 2))
+    }
+}
+
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+pub fn sparkline(values: &[f64]) -> String {
+    let (min, max) = bounds(values.iter().copied());
+    values
+        .iter()
+        .map(|value| {
+            let level = if max > min {
+                ((value - min) / (max - min) * (SPARKS.len() - 1) as f64).round() as usize
+            } else {
+                SPARKS.len() / 2
+            };
+            SPARKS[level.min(SPARKS.len() - 1)]
+        })
+        .collect()
+}
+
+// the last value of each of (at most) `width` evenly sized groups
+pub fn resample(values: &[f64], width: usize) -> Vec<f64> {
+    buckets(values.len(), width)
+        .filter_map(|range| values[range].last().copied())
+        .collect()
+}
+
+fn buckets(len: usize, width: usize) -> impl Iterator<Item = std::ops::Range<usize>> {
+    let columns = len.min(width.max(1));
+    (0..columns).map(move |column| column * len / columns..(column + 1) * len / columns)
+}
+
+fn bounds<I: Iterator<Item = f64>>(values: I) -> (f64, f64) {
+    values.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| {
+        (min.min(value), max.max(value))
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Candle {
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+}
+impl Candle {
+    fn merge(self, next: Candle) -> Candle {
+        Candle {
+            open: self.open,
+            high: self.high.max(next.high),
+            low: self.low.min(next.low),
+            close: next.close,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChartStyle {
+    Line,
+    Candlestick,
+}
+
+pub struct Chart {
+    candles: Vec<Candle>,
+    style: ChartStyle,
+    width: usize,
+    height: usize,
+    labels: Option<(String, String)>,
+    colour: bool,
+}
+impl Chart {
+    pub fn line(values: &[f64]) -> Chart {
+        let candles = values
+            .iter()
+            .map(|value| Candle {
+                open: *value,
+                high: *value,
+                low: *value,
+                close: *value,
+            })
+            .collect();
+        Chart::new(candles, ChartStyle::Line)
+    }
+
+    pub fn candlestick(candles: Vec<Candle>) -> Chart {
+        Chart::new(candles, ChartStyle::Candlestick)
+    }
+
+    fn new(candles: Vec<Candle>, style: ChartStyle) -> Chart {
+        Chart {
+            candles,
+            style,
+            width: 80,
+            height: 20,
+            labels: None,
+            colour: colour_enabled(),
+        }
+    }
+
+    pub fn width(mut self, width: usize) -> Chart {
+        self.width = width.max(1);
+        self
+    }
+
+    pub fn height(mut self, height: usize) -> Chart {
+        self.height = height.max(2);
+        self
+    }
+
+    pub fn labels<S: Into<String>>(mut self, start: S, end: S) -> Chart {
+        self.labels = Some((start.into(), end.into()));
+        self
+    }
+
+    pub fn colour(mut self, colour: bool) -> Chart {
+        self.colour = colour;
+        self
+    }
+
+    // consecutive candles are merged so the chart fits in `width` columns
+    fn columns(&self) -> Vec<Candle> {
+        buckets(self.candles.len(), self.width)
+            .filter_map(|range| self.candles[range].iter().copied().reduce(Candle::merge))
+            .collect()
+    }
+}
+impl fmt::Display for Chart {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let columns = self.columns();
+        if columns.is_empty() {
+            return Ok(());
+        }
+        let (min, max) = bounds(
+            columns
+                .iter()
+                .flat_map(|candle| vec![candle.low, candle.high]),
+        );
+        let rows = self.height;
+        let row_of = |value: f64| -> usize {
+            if max > min {
+                ((max - value) / (max - min) * (rows - 1) as f64).round() as usize
+            } else {
+                rows / 2
+            }
+        };
+
+        let mut grid = vec![vec![(' ', None); columns.len()]; rows];
+        let mut previous: Option<usize> = None;
+        for (x, candle) in columns.iter().enumerate() {
+            match self.style {
+                ChartStyle::Line => {
+                    let row = row_of(candle.close);
+                    if let Some(previous) = previous {
+                        for cell in grid
+                            .iter_mut()
+                            .take(row.max(previous))
+                            .skip(row.min(previous))
+                        {
+                            cell[x] = ('│', None);
+                        }
+                    }
+                    grid[row][x] = ('•', None);
+                    previous = Some(row);
+                }
+                ChartStyle::Candlestick => {
+                    let colour = if candle.close >= candle.open {
+                        Colour::Green
+                    } else {
+                        Colour::Red
+                    };
+                    let body = (
+                        row_of(candle.open.max(candle.close)),
+                        row_of(candle.open.min(candle.close)),
+                    );
+                    for (y, cell) in grid
+                        .iter_mut()
+                        .enumerate()
+                        .take(row_of(candle.low) + 1)
+                        .skip(row_of(candle.high))
+                    {
+                        let c = if y >= body.0 && y <= body.1 {
+                            '┃'
+                        } else {
+                            '│'
+                        };
+                        cell[x] = (c, Some(colour));
+                    }
+                }
+            }
+        }
+
+        let line_colour = match (columns.first(), columns.last()) {
+            (Some(first), Some(last)) if last.close > first.open => Some(Colour::Green),
+            (Some(first), Some(last)) if last.close < first.open => Some(Colour::Red),
+            _ => None,
+        };
+        let axis_labels = [number(max, 2), number((max + min) / 2.0, 2), number(min, 2)];
+        let label_width = axis_labels.iter().map(String::len).max().unwrap_or(0);
+
+        for (y, row) in grid.iter().enumerate() {
+            let label = if y == 0 {
+                &axis_labels[0]
+            } else if y == rows / 2 {
+                &axis_labels[1]
+            } else if y == rows - 1 {
+                &axis_labels[2]
+            } else {
+                ""
+            };
+            let mut line = format!("{:>width$} ┤", label, width = label_width);
+            for (c, colour) in row {
+                let colour = match self.style {
+                    ChartStyle::Line if *c != ' ' => line_colour,
+                    _ => *colour,
+                };
+                match colour {
+                    Some(colour) if self.colour => {
+                        line.push_str(&format!("{}{}\x1b[0m", colour.code(), c))
+                    }
+                    _ => line.push(*c),
+                }
+            }
+            writeln!(f, "{}", line.trim_end())?;
+        }
+        writeln!(
+            f,
+            "{} └{}",
+            " ".repeat(label_width),
+            "─".repeat(columns.len())
+        )?;
+        if let Some((start, end)) = &self.labels {
+            let gap = columns
+                .len()
+                .saturating_sub(start.chars().count() + end.chars().count());
+            writeln!(
+                f,
+                "{}  {}{}{}",
+                " ".repeat(label_width),
+                start,
+                " ".repeat(gap.max(1)),
+                end
+            )?;
+        }
+        Ok(())
+    }
+}
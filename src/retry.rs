@@ -0,0 +1,67 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+use crate::ApiError;
+
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    pub jitter: bool,
+}
+impl Default for RetryPolicy {
+    fn default() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+            jitter: true,
+        }
+    }
+}
+impl RetryPolicy {
+    pub fn none() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 1,
+            ..RetryPolicy::default()
+        }
+    }
+
+    // rate limit notes are per minute, so they wait out the longest backoff
+    pub(crate) fn delay(&self, attempt: u32, error: &ApiError) -> Duration {
+        let backoff = match error {
+            ApiError::RateLimited(_) => self.max_backoff,
+            _ => self
+                .initial_backoff
+                .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+                .min(self.max_backoff),
+        };
+        if self.jitter {
+            // somewhere between half and all of the backoff
+            let random = RandomState::new().build_hasher().finish() as f64 / u64::MAX as f64;
+            backoff.mul_f64(0.5 + random / 2.0)
+        } else {
+            backoff
+        }
+    }
+
+    pub(crate) fn should_retry(&self, attempt: u32, error: &ApiError) -> bool {
+        attempt < self.max_attempts && is_transient(error)
+    }
+}
+
+fn is_transient(error: &ApiError) -> bool {
+    match error {
+        ApiError::Reqwest(err) => {
+            err.is_timeout()
+                || err.is_connect()
+                || err.status().is_some_and(|status| {
+                    status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+                })
+        }
+        ApiError::RateLimited(_) => true,
+        _ => false,
+    }
+}
@@ -0,0 +1,104 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::metrics::{self, TRADING_DAYS};
+use crate::Symbol;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HoldingRisk {
+    pub symbol: Symbol,
+    pub weight: f64,
+    // annualised, in percent
+    pub volatility: Option<f64>,
+    // share of the portfolio's variance, in percent; these sum to 100
+    pub contribution: Option<f64>,
+    pub concentrated: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RiskReport {
+    pub holdings: Vec<HoldingRisk>,
+    pub volatility: f64,
+    pub concentration_limit: f64,
+    // the number of equally weighted holdings with the same concentration
+    pub effective_holdings: f64,
+}
+impl RiskReport {
+    pub fn concentrated(&self) -> impl Iterator<Item = &HoldingRisk> {
+        self.holdings.iter().filter(|holding| holding.concentrated)
+    }
+}
+
+// `weights` are fractions of the portfolio and `concentration_limit` is the
+// largest weight that isn't flagged. Holdings without prices, such as cash,
+// are left out of the portfolio's variance.
+pub fn risk_report(
+    prices: &BTreeMap<Symbol, BTreeMap<chrono::NaiveDate, f64>>,
+    weights: &BTreeMap<Symbol, f64>,
+    concentration_limit: f64,
+) -> RiskReport {
+    let priced: Vec<(&Symbol, f64, &BTreeMap<chrono::NaiveDate, f64>)> = weights
+        .iter()
+        .filter_map(|(symbol, weight)| Some((symbol, *weight, prices.get(symbol)?)))
+        .collect();
+
+    // each holding's covariance with the whole portfolio
+    let covariances: Vec<f64> = priced
+        .iter()
+        .map(|(_symbol, _weight, a)| {
+            priced
+                .iter()
+                .map(|(_symbol, weight, b)| {
+                    let (a, b) = metrics::aligned_returns(a, b);
+                    if a.len() < 2 {
+                        0.0
+                    } else {
+                        weight * metrics::covariance(&a, &b)
+                    }
+                })
+                .sum()
+        })
+        .collect();
+    let variance: f64 = priced
+        .iter()
+        .zip(&covariances)
+        .map(|((_symbol, weight, _prices), covariance)| weight * covariance)
+        .sum();
+
+    let holdings = weights
+        .iter()
+        .map(|(symbol, weight)| {
+            let index = priced.iter().position(|(priced, _, _)| *priced == symbol);
+            let volatility = prices.get(symbol).and_then(|prices| {
+                let returns = metrics::daily_returns(prices);
+                if returns.len() < 2 {
+                    return None;
+                }
+                Some(metrics::standard_deviation(&returns) * TRADING_DAYS.sqrt() * 100.0)
+            });
+            let contribution = index.map(|index| {
+                if variance > 0.0 {
+                    weight * covariances[index] / variance * 100.0
+                } else {
+                    0.0
+                }
+            });
+            HoldingRisk {
+                symbol: symbol.clone(),
+                weight: *weight,
+                volatility,
+                contribution,
+                concentrated: *weight > concentration_limit,
+            }
+        })
+        .collect();
+
+    let squares: f64 = weights.values().map(|weight| weight.powi(2)).sum();
+    RiskReport {
+        holdings,
+        volatility: variance.max(0.0).sqrt() * TRADING_DAYS.sqrt() * 100.0,
+        concentration_limit,
+        effective_holdings: if squares > 0.0 { 1.0 / squares } else { 0.0 },
+    }
+}
@@ -0,0 +1,163 @@
+use serde::Serialize;
+
+use crate::{indicators, DailySeries, Symbol};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Screen {
+    // the fast moving average crossed above the slow one in the last `within`
+    // trading days and is still above it
+    GoldenCross {
+        fast: usize,
+        slow: usize,
+        within: usize,
+    },
+    DeathCross {
+        fast: usize,
+        slow: usize,
+        within: usize,
+    },
+    RsiOversold {
+        period: usize,
+        threshold: f64,
+    },
+}
+impl Screen {
+    pub fn describe(&self) -> String {
+        match self {
+            Screen::GoldenCross { fast, slow, .. } => {
+                format!("golden cross (SMA {}/{})", fast, slow)
+            }
+            Screen::DeathCross { fast, slow, .. } => {
+                format!("death cross (SMA {}/{})", fast, slow)
+            }
+            Screen::RsiOversold { period, threshold } => {
+                format!("RSI({}) below {}", period, threshold)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScreenMatch {
+    pub symbol: Symbol,
+    pub signal: String,
+    // the day the averages crossed, or the latest day for RSI
+    pub date: chrono::NaiveDate,
+    pub close: f64,
+    pub fast: Option<f64>,
+    pub slow: Option<f64>,
+    pub rsi: Option<f64>,
+}
+
+pub fn screen(symbol: &Symbol, series: &DailySeries, screen: Screen) -> Option<ScreenMatch> {
+    let (_latest, day) = series.latest()?;
+    let matched = |date, fast, slow, rsi| ScreenMatch {
+        symbol: symbol.clone(),
+        signal: screen.describe(),
+        date,
+        close: day.close,
+        fast,
+        slow,
+        rsi,
+    };
+
+    match screen {
+        Screen::GoldenCross { fast, slow, within } => crossed(series, fast, slow, within, true)
+            .map(|(date, fast, slow)| matched(date, Some(fast), Some(slow), None)),
+        Screen::DeathCross { fast, slow, within } => crossed(series, fast, slow, within, false)
+            .map(|(date, fast, slow)| matched(date, Some(fast), Some(slow), None)),
+        Screen::RsiOversold { period, threshold } => {
+            let (date, rsi) = indicators::rsi(series, period).into_iter().next_back()?;
+            if rsi < threshold {
+                Some(matched(date, None, None, Some(rsi)))
+            } else {
+                None
+            }
+        }
+    }
+}
+
+// the date of the latest cross in the given direction and today's averages, if
+// the cross was in the last `within` days
+fn crossed(
+    series: &DailySeries,
+    fast: usize,
+    slow: usize,
+    within: usize,
+    above: bool,
+) -> Option<(chrono::NaiveDate, f64, f64)> {
+    let fast_averages = indicators::sma(series, fast);
+    let slow_averages = indicators::sma(series, slow);
+    let spreads: Vec<(chrono::NaiveDate, f64)> = slow_averages
+        .iter()
+        .filter_map(|(date, slow)| {
+            let spread = fast_averages.get(date)? - slow;
+            Some((*date, if above { spread } else { -spread }))
+        })
+        .collect();
+
+    let recent = &spreads[spreads.len().saturating_sub(within + 1)..];
+    let (date, _spread) = recent
+        .windows(2)
+        .rev()
+        .find(|pair| pair[0].1 <= 0.0 && pair[1].1 > 0.0)?[1];
+    if recent.last()?.1 <= 0.0 {
+        return None;
+    }
+
+    let (latest, _spread) = spreads.last()?;
+    Some((date, fast_averages[latest], slow_averages[latest]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    #[test]
+    fn splits_are_not_signals() {
+        let symbol = Symbol::new("IBM");
+        let series = testing::split_series("IBM", 20);
+        let death_cross = Screen::DeathCross {
+            fast: 5,
+            slow: 10,
+            within: 25,
+        };
+        assert!(screen(&symbol, &series, death_cross).is_none());
+
+        let series = testing::split_series("IBM", 10);
+        let oversold = Screen::RsiOversold {
+            period: 14,
+            threshold: 30.0,
+        };
+        assert!(screen(&symbol, &series, oversold).is_none());
+    }
+
+    #[test]
+    fn crosses_are_found() {
+        let closes: Vec<(f64, f64)> = (0..40)
+            .map(|day| {
+                if day < 30 {
+                    130.0 - day as f64
+                } else {
+                    70.0 + day as f64
+                }
+            })
+            .map(|close| (close, close))
+            .collect();
+        let series = testing::daily_series("IBM", "2024-01-01", &closes);
+        let symbol = Symbol::new("IBM");
+        let cross = |above| {
+            let (fast, slow, within) = (5, 10, 10);
+            let screen_for = if above {
+                Screen::GoldenCross { fast, slow, within }
+            } else {
+                Screen::DeathCross { fast, slow, within }
+            };
+            screen(&symbol, &series, screen_for)
+        };
+        assert!(cross(true).is_some());
+        assert!(cross(false).is_none());
+    }
+}
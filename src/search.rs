@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+use serde_aux::field_attributes::deserialize_number_from_string;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SymbolMatch {
+    #[serde(rename(deserialize = "1. symbol"))]
+    pub symbol: String,
+    #[serde(rename(deserialize = "2. name"))]
+    pub name: String,
+    #[serde(rename(deserialize = "3. type"))]
+    pub instrument_type: String,
+    #[serde(rename(deserialize = "4. region"))]
+    pub region: String,
+    #[serde(rename(deserialize = "8. currency"))]
+    pub currency: String,
+    #[serde(
+        rename(deserialize = "9. matchScore"),
+        deserialize_with = "deserialize_number_from_string"
+    )]
+    pub match_score: f64,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct SymbolSearchResponse {
+    #[serde(rename = "bestMatches")]
+    pub(crate) best_matches: Vec<SymbolMatch>,
+}
@@ -0,0 +1,90 @@
+use std::collections::BTreeMap;
+
+use serde::{de, Deserialize, Deserializer, Serialize};
+
+// percentage returns by sector over each period, e.g. `one_month["Energy"]`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SectorPerformance {
+    #[serde(
+        rename(deserialize = "Rank A: Real-Time Performance"),
+        deserialize_with = "deserialize_percentages",
+        default
+    )]
+    pub real_time: BTreeMap<String, f64>,
+    #[serde(
+        rename(deserialize = "Rank B: 1 Day Performance"),
+        deserialize_with = "deserialize_percentages",
+        default
+    )]
+    pub one_day: BTreeMap<String, f64>,
+    #[serde(
+        rename(deserialize = "Rank C: 5 Day Performance"),
+        deserialize_with = "deserialize_percentages",
+        default
+    )]
+    pub five_day: BTreeMap<String, f64>,
+    #[serde(
+        rename(deserialize = "Rank D: 1 Month Performance"),
+        deserialize_with = "deserialize_percentages",
+        default
+    )]
+    pub one_month: BTreeMap<String, f64>,
+    #[serde(
+        rename(deserialize = "Rank E: 3 Month Performance"),
+        deserialize_with = "deserialize_percentages",
+        default
+    )]
+    pub three_month: BTreeMap<String, f64>,
+    #[serde(
+        rename(deserialize = "Rank F: Year-to-Date (YTD) Performance"),
+        deserialize_with = "deserialize_percentages",
+        default
+    )]
+    pub year_to_date: BTreeMap<String, f64>,
+    #[serde(
+        rename(deserialize = "Rank G: 1 Year Performance"),
+        deserialize_with = "deserialize_percentages",
+        default
+    )]
+    pub one_year: BTreeMap<String, f64>,
+    #[serde(
+        rename(deserialize = "Rank H: 3 Year Performance"),
+        deserialize_with = "deserialize_percentages",
+        default
+    )]
+    pub three_year: BTreeMap<String, f64>,
+    #[serde(
+        rename(deserialize = "Rank I: 5 Year Performance"),
+        deserialize_with = "deserialize_percentages",
+        default
+    )]
+    pub five_year: BTreeMap<String, f64>,
+    #[serde(
+        rename(deserialize = "Rank J: 10 Year Performance"),
+        deserialize_with = "deserialize_percentages",
+        default
+    )]
+    pub ten_year: BTreeMap<String, f64>,
+}
+impl SectorPerformance {
+    pub fn sectors(&self) -> impl Iterator<Item = &String> {
+        self.one_day.keys()
+    }
+}
+
+// Alpha Vantage writes each figure as a string such as `-1.23%`
+fn deserialize_percentages<'de, D>(deserializer: D) -> Result<BTreeMap<String, f64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    BTreeMap::<String, String>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(sector, percent)| {
+            percent
+                .trim_end_matches('%')
+                .parse()
+                .map(|percent| (sector, percent))
+                .map_err(de::Error::custom)
+        })
+        .collect()
+}
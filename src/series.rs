@@ -0,0 +1,649 @@
+use std::collections::{btree_map, BTreeMap};
+use std::io;
+use std::ops::RangeBounds;
+
+use chrono::Datelike;
+
+use serde::{de, Deserialize, Deserializer, Serialize};
+use serde_aux::field_attributes::deserialize_number_from_string;
+
+use crate::calendar;
+use crate::dividend::{self, DividendPayment};
+use crate::ApiError;
+
+pub enum OutputSize {
+    Compact,
+    Full,
+}
+impl OutputSize {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            OutputSize::Compact => "compact",
+            OutputSize::Full => "full",
+        }
+    }
+}
+
+pub enum Interval {
+    OneMinute,
+    FiveMinutes,
+    FifteenMinutes,
+    ThirtyMinutes,
+    SixtyMinutes,
+}
+impl Interval {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            Interval::OneMinute => "1min",
+            Interval::FiveMinutes => "5min",
+            Interval::FifteenMinutes => "15min",
+            Interval::ThirtyMinutes => "30min",
+            Interval::SixtyMinutes => "60min",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+impl Frequency {
+    pub(crate) fn function(self) -> &'static str {
+        match self {
+            Frequency::Daily => "TIME_SERIES_DAILY_ADJUSTED",
+            Frequency::Weekly => "TIME_SERIES_WEEKLY_ADJUSTED",
+            Frequency::Monthly => "TIME_SERIES_MONTHLY_ADJUSTED",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Metadata {
+    #[serde(rename(deserialize = "1. Information"))]
+    pub information: String,
+    #[serde(rename(deserialize = "2. Symbol"))]
+    pub symbol: String,
+    #[serde(
+        rename(deserialize = "3. Last Refreshed"),
+        deserialize_with = "deserialize_last_refreshed"
+    )]
+    pub last_refreshed: chrono::NaiveDateTime,
+    #[serde(rename(deserialize = "4. Output Size"), default)]
+    pub output_size: Option<String>,
+    #[serde(rename(deserialize = "5. Time Zone"), alias = "4. Time Zone")]
+    pub time_zone: String,
+}
+impl Metadata {
+    // the date where the series trades, which in the evening is behind UTC's
+    pub fn today(&self) -> chrono::NaiveDate {
+        let time_zone = self
+            .time_zone
+            .parse()
+            .unwrap_or(calendar::EXCHANGE_TIME_ZONE);
+        chrono::Utc::now().with_timezone(&time_zone).date_naive()
+    }
+
+    pub fn last_refreshed_utc(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        to_utc(self.last_refreshed, &self.time_zone)
+    }
+}
+
+// daily series give a date and intraday series a time, both in `time_zone`
+pub(crate) fn parse_last_refreshed(value: &str) -> Option<chrono::NaiveDateTime> {
+    ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|format| chrono::NaiveDateTime::parse_from_str(value, format).ok())
+        .or_else(|| {
+            chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .map(|date| date.and_time(chrono::NaiveTime::MIN))
+        })
+}
+
+pub(crate) fn deserialize_last_refreshed<'de, D>(
+    deserializer: D,
+) -> Result<chrono::NaiveDateTime, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+    parse_last_refreshed(&value)
+        .ok_or_else(|| de::Error::custom(format!("invalid last refreshed time `{}`", value)))
+}
+
+pub(crate) fn to_utc(
+    datetime: chrono::NaiveDateTime,
+    time_zone: &str,
+) -> Option<chrono::DateTime<chrono::Utc>> {
+    use chrono::TimeZone;
+
+    let time_zone: chrono_tz::Tz = time_zone.parse().ok()?;
+    time_zone
+        .from_local_datetime(&datetime)
+        .earliest()
+        .map(|datetime| datetime.with_timezone(&chrono::Utc))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeSeriesDay {
+    #[serde(
+        rename(deserialize = "1. open"),
+        deserialize_with = "deserialize_number_from_string"
+    )]
+    pub open: f64,
+    #[serde(
+        rename(deserialize = "2. high"),
+        deserialize_with = "deserialize_number_from_string"
+    )]
+    pub high: f64,
+    #[serde(
+        rename(deserialize = "3. low"),
+        deserialize_with = "deserialize_number_from_string"
+    )]
+    pub low: f64,
+    #[serde(
+        rename(deserialize = "4. close"),
+        deserialize_with = "deserialize_number_from_string"
+    )]
+    pub close: f64,
+    #[serde(
+        rename(deserialize = "5. adjusted close"),
+        deserialize_with = "deserialize_number_from_string"
+    )]
+    pub adjusted_close: f64,
+    #[serde(
+        rename(deserialize = "6. volume"),
+        deserialize_with = "deserialize_number_from_string"
+    )]
+    pub volume: f64,
+    #[serde(
+        rename(deserialize = "7. dividend amount"),
+        deserialize_with = "deserialize_number_from_string"
+    )]
+    pub dividend_amount: f64,
+    #[serde(
+        rename(deserialize = "8. split coefficient"),
+        deserialize_with = "deserialize_number_from_string",
+        default = "no_split"
+    )]
+    pub split_coefficient: f64,
+}
+
+fn no_split() -> f64 {
+    1.0
+}
+
+fn write_bars_csv<W: io::Write>(
+    bars: &BTreeMap<chrono::NaiveDate, TimeSeriesDay>,
+    writer: W,
+) -> Result<(), csv::Error> {
+    let mut writer = csv::Writer::from_writer(writer);
+    writer.write_record([
+        "date",
+        "open",
+        "high",
+        "low",
+        "close",
+        "adjusted_close",
+        "volume",
+        "dividend_amount",
+        "split_coefficient",
+    ])?;
+    for (date, day) in bars {
+        writer.write_record(&[
+            date.to_string(),
+            day.open.to_string(),
+            day.high.to_string(),
+            day.low.to_string(),
+            day.close.to_string(),
+            day.adjusted_close.to_string(),
+            day.volume.to_string(),
+            day.dividend_amount.to_string(),
+            day.split_coefficient.to_string(),
+        ])?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DailySeries {
+    #[serde(rename(deserialize = "Meta Data"))]
+    pub metadata: Metadata,
+    #[serde(rename(deserialize = "Time Series (Daily)"))]
+    pub days: BTreeMap<chrono::NaiveDate, TimeSeriesDay>,
+}
+impl DailySeries {
+    pub fn to_csv<W: io::Write>(&self, writer: W) -> Result<(), csv::Error> {
+        write_bars_csv(&self.days, writer)
+    }
+
+    pub fn dividends(&self) -> Vec<DividendPayment> {
+        dividend::payments(&self.days)
+    }
+
+    pub fn latest(&self) -> Option<(&chrono::NaiveDate, &TimeSeriesDay)> {
+        self.days.iter().next_back()
+    }
+
+    pub fn earliest(&self) -> Option<(&chrono::NaiveDate, &TimeSeriesDay)> {
+        self.days.iter().next()
+    }
+
+    pub fn fifty_two_week_range(&self) -> Option<FiftyTwoWeekRange> {
+        let (end, _day) = self.latest()?;
+        FiftyTwoWeekRange::of(&self.days, *end)
+    }
+
+    // the trading day on or before `date`
+    pub fn on(&self, date: chrono::NaiveDate) -> Option<(&chrono::NaiveDate, &TimeSeriesDay)> {
+        self.days.range(..=date).next_back()
+    }
+
+    pub fn range<R: RangeBounds<chrono::NaiveDate>>(
+        &self,
+        range: R,
+    ) -> btree_map::Range<'_, chrono::NaiveDate, TimeSeriesDay> {
+        self.days.range(range)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct FiftyTwoWeekRange {
+    pub low: f64,
+    pub high: f64,
+}
+impl FiftyTwoWeekRange {
+    // over the year of bars to `end`, or as many as there are
+    pub fn of(
+        bars: &BTreeMap<chrono::NaiveDate, TimeSeriesDay>,
+        end: chrono::NaiveDate,
+    ) -> Option<FiftyTwoWeekRange> {
+        let start = end - chrono::Duration::days(365);
+        bars.range(start..=end)
+            .map(|(_date, bar)| (bar.low, bar.high))
+            .reduce(|(low, high), (bar_low, bar_high)| (low.min(bar_low), high.max(bar_high)))
+            .map(|(low, high)| FiftyTwoWeekRange { low, high })
+    }
+
+    // percentages, zero or below for the high and zero or above for the low
+    pub fn from_high(&self, price: f64) -> f64 {
+        (price / self.high - 1.0) * 100.0
+    }
+
+    pub fn from_low(&self, price: f64) -> f64 {
+        (price / self.low - 1.0) * 100.0
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Series {
+    pub frequency: Frequency,
+    pub metadata: Metadata,
+    pub bars: BTreeMap<chrono::NaiveDate, TimeSeriesDay>,
+}
+impl Series {
+    pub fn to_csv<W: io::Write>(&self, writer: W) -> Result<(), csv::Error> {
+        write_bars_csv(&self.bars, writer)
+    }
+
+    pub fn dividends(&self) -> Vec<DividendPayment> {
+        dividend::payments(&self.bars)
+    }
+
+    pub fn latest(&self) -> Option<(&chrono::NaiveDate, &TimeSeriesDay)> {
+        self.bars.iter().next_back()
+    }
+
+    pub fn earliest(&self) -> Option<(&chrono::NaiveDate, &TimeSeriesDay)> {
+        self.bars.iter().next()
+    }
+
+    pub fn range<R: RangeBounds<chrono::NaiveDate>>(
+        &self,
+        range: R,
+    ) -> btree_map::Range<'_, chrono::NaiveDate, TimeSeriesDay> {
+        self.bars.range(range)
+    }
+
+    pub fn fifty_two_week_range(&self) -> Option<FiftyTwoWeekRange> {
+        let (end, _bar) = self.latest()?;
+        FiftyTwoWeekRange::of(&self.bars, *end)
+    }
+
+    pub fn slice<R: RangeBounds<chrono::NaiveDate>>(&self, range: R) -> Series {
+        Series {
+            frequency: self.frequency,
+            metadata: self.metadata.clone(),
+            bars: self
+                .bars
+                .range(range)
+                .map(|(date, bar)| (*date, bar.clone()))
+                .collect(),
+        }
+    }
+
+    pub fn resample(self, frequency: Frequency) -> Series {
+        if frequency == self.frequency || frequency == Frequency::Daily {
+            return self;
+        }
+
+        let period = |date: &chrono::NaiveDate| match frequency {
+            Frequency::Weekly => (date.iso_week().year(), date.iso_week().week()),
+            _ => (date.year(), date.month()),
+        };
+
+        let mut bars: BTreeMap<chrono::NaiveDate, TimeSeriesDay> = BTreeMap::new();
+        let mut current: Option<((i32, u32), chrono::NaiveDate, TimeSeriesDay)> = None;
+        for (date, day) in self.bars {
+            current = Some(match current {
+                Some((key, _last_date, mut bar)) if key == period(&date) => {
+                    bar.high = bar.high.max(day.high);
+                    bar.low = bar.low.min(day.low);
+                    bar.close = day.close;
+                    bar.adjusted_close = day.adjusted_close;
+                    bar.volume += day.volume;
+                    bar.dividend_amount += day.dividend_amount;
+                    bar.split_coefficient *= day.split_coefficient;
+                    (key, date, bar)
+                }
+                previous => {
+                    if let Some((_key, last_date, bar)) = previous {
+                        bars.insert(last_date, bar);
+                    }
+                    (period(&date), date, day)
+                }
+            });
+        }
+        if let Some((_key, last_date, bar)) = current {
+            bars.insert(last_date, bar);
+        }
+
+        Series {
+            frequency,
+            metadata: self.metadata,
+            bars,
+        }
+    }
+}
+impl From<DailySeries> for Series {
+    fn from(series: DailySeries) -> Series {
+        Series {
+            frequency: Frequency::Daily,
+            metadata: series.metadata,
+            bars: series.days,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct AdjustedSeriesResponse {
+    #[serde(rename = "Meta Data")]
+    metadata: Metadata,
+    #[serde(
+        rename = "Weekly Adjusted Time Series",
+        alias = "Monthly Adjusted Time Series"
+    )]
+    bars: BTreeMap<chrono::NaiveDate, TimeSeriesDay>,
+}
+impl AdjustedSeriesResponse {
+    pub(crate) fn into_series(self, frequency: Frequency) -> Series {
+        Series {
+            frequency,
+            metadata: self.metadata,
+            bars: self.bars,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IntradayMetadata {
+    #[serde(rename(deserialize = "1. Information"))]
+    pub information: String,
+    #[serde(rename(deserialize = "2. Symbol"))]
+    pub symbol: String,
+    #[serde(
+        rename(deserialize = "3. Last Refreshed"),
+        deserialize_with = "deserialize_last_refreshed"
+    )]
+    pub last_refreshed: chrono::NaiveDateTime,
+    #[serde(rename(deserialize = "4. Interval"))]
+    pub interval: String,
+    #[serde(rename(deserialize = "5. Output Size"))]
+    pub output_size: String,
+    #[serde(rename(deserialize = "6. Time Zone"))]
+    pub time_zone: String,
+}
+impl IntradayMetadata {
+    pub fn last_refreshed_utc(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        to_utc(self.last_refreshed, &self.time_zone)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IntradayBar {
+    #[serde(
+        rename(deserialize = "1. open"),
+        deserialize_with = "deserialize_number_from_string"
+    )]
+    pub open: f64,
+    #[serde(
+        rename(deserialize = "2. high"),
+        deserialize_with = "deserialize_number_from_string"
+    )]
+    pub high: f64,
+    #[serde(
+        rename(deserialize = "3. low"),
+        deserialize_with = "deserialize_number_from_string"
+    )]
+    pub low: f64,
+    #[serde(
+        rename(deserialize = "4. close"),
+        deserialize_with = "deserialize_number_from_string"
+    )]
+    pub close: f64,
+    #[serde(
+        rename(deserialize = "5. volume"),
+        deserialize_with = "deserialize_number_from_string"
+    )]
+    pub volume: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IntradaySeries {
+    #[serde(rename(deserialize = "Meta Data"))]
+    pub metadata: IntradayMetadata,
+    #[serde(
+        rename(deserialize = "Time Series (1min)"),
+        alias = "Time Series (5min)",
+        alias = "Time Series (15min)",
+        alias = "Time Series (30min)",
+        alias = "Time Series (60min)",
+        deserialize_with = "deserialize_datetime_keys"
+    )]
+    pub bars: BTreeMap<chrono::NaiveDateTime, IntradayBar>,
+}
+impl IntradaySeries {
+    pub fn to_csv<W: io::Write>(&self, writer: W) -> Result<(), csv::Error> {
+        let mut writer = csv::Writer::from_writer(writer);
+        writer.write_record(["datetime", "open", "high", "low", "close", "volume"])?;
+        for (datetime, bar) in &self.bars {
+            writer.write_record(&[
+                datetime.to_string(),
+                bar.open.to_string(),
+                bar.high.to_string(),
+                bar.low.to_string(),
+                bar.close.to_string(),
+                bar.volume.to_string(),
+            ])?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn latest(&self) -> Option<(&chrono::NaiveDateTime, &IntradayBar)> {
+        self.bars.iter().next_back()
+    }
+
+    pub fn earliest(&self) -> Option<(&chrono::NaiveDateTime, &IntradayBar)> {
+        self.bars.iter().next()
+    }
+}
+
+fn deserialize_datetime_keys<'de, D, V>(
+    deserializer: D,
+) -> Result<BTreeMap<chrono::NaiveDateTime, V>, D::Error>
+where
+    D: Deserializer<'de>,
+    V: Deserialize<'de>,
+{
+    BTreeMap::<String, V>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(key, value)| {
+            chrono::NaiveDateTime::parse_from_str(&key, "%Y-%m-%d %H:%M:%S")
+                .map(|datetime| (datetime, value))
+                .map_err(de::Error::custom)
+        })
+        .collect()
+}
+
+// a row of a time series in Alpha Vantage's CSV, whose daily series name the
+// columns with underscores and weekly and monthly ones with spaces
+#[derive(Debug, Deserialize)]
+pub(crate) struct CsvBar {
+    timestamp: String,
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+    #[serde(default, alias = "adjusted close")]
+    adjusted_close: Option<f64>,
+    volume: f64,
+    #[serde(default, alias = "dividend amount")]
+    dividend_amount: Option<f64>,
+    #[serde(default, alias = "split coefficient")]
+    split_coefficient: Option<f64>,
+}
+impl CsvBar {
+    fn date(&self) -> Result<chrono::NaiveDate, ApiError> {
+        chrono::NaiveDate::parse_from_str(&self.timestamp, "%Y-%m-%d")
+            .map_err(|_err| ApiError::Api(format!("invalid date `{}`", self.timestamp)))
+    }
+
+    fn into_day(self) -> TimeSeriesDay {
+        TimeSeriesDay {
+            open: self.open,
+            high: self.high,
+            low: self.low,
+            close: self.close,
+            adjusted_close: self.adjusted_close.unwrap_or(self.close),
+            volume: self.volume,
+            dividend_amount: self.dividend_amount.unwrap_or(0.0),
+            split_coefficient: self.split_coefficient.unwrap_or(1.0),
+        }
+    }
+}
+
+fn days_from_csv(
+    rows: Vec<CsvBar>,
+) -> Result<BTreeMap<chrono::NaiveDate, TimeSeriesDay>, ApiError> {
+    rows.into_iter()
+        .map(|row| Ok((row.date()?, row.into_day())))
+        .collect()
+}
+
+// CSV has none of the metadata, so it's rebuilt from the request and the rows
+fn csv_metadata(
+    params: &[(&str, &str)],
+    information: &str,
+    last_refreshed: Option<chrono::NaiveDateTime>,
+) -> Result<Metadata, ApiError> {
+    Ok(Metadata {
+        information: information.to_string(),
+        symbol: crate::client::param(params, "symbol").to_string(),
+        last_refreshed: last_refreshed.ok_or(ApiError::NoData)?,
+        output_size: params
+            .iter()
+            .find(|(key, _value)| *key == "outputsize")
+            .map(|(_key, value)| value.to_string()),
+        time_zone: "US/Eastern".to_string(),
+    })
+}
+
+// a response requested with `datatype=csv`
+pub(crate) trait FromCsv: Sized {
+    fn from_csv(params: &[(&str, &str)], rows: Vec<CsvBar>) -> Result<Self, ApiError>;
+}
+impl FromCsv for DailySeries {
+    fn from_csv(params: &[(&str, &str)], rows: Vec<CsvBar>) -> Result<DailySeries, ApiError> {
+        let days = days_from_csv(rows)?;
+        let last_refreshed = days
+            .keys()
+            .next_back()
+            .map(|date| date.and_time(chrono::NaiveTime::MIN));
+        Ok(DailySeries {
+            metadata: csv_metadata(
+                params,
+                "Daily Time Series with Splits and Dividend Events",
+                last_refreshed,
+            )?,
+            days,
+        })
+    }
+}
+impl FromCsv for AdjustedSeriesResponse {
+    fn from_csv(
+        params: &[(&str, &str)],
+        rows: Vec<CsvBar>,
+    ) -> Result<AdjustedSeriesResponse, ApiError> {
+        let bars = days_from_csv(rows)?;
+        let last_refreshed = bars
+            .keys()
+            .next_back()
+            .map(|date| date.and_time(chrono::NaiveTime::MIN));
+        Ok(AdjustedSeriesResponse {
+            metadata: csv_metadata(params, "Adjusted Prices and Volumes", last_refreshed)?,
+            bars,
+        })
+    }
+}
+impl FromCsv for IntradaySeries {
+    fn from_csv(params: &[(&str, &str)], rows: Vec<CsvBar>) -> Result<IntradaySeries, ApiError> {
+        let bars: BTreeMap<chrono::NaiveDateTime, IntradayBar> = rows
+            .into_iter()
+            .map(|row| {
+                let datetime = parse_last_refreshed(&row.timestamp)
+                    .ok_or_else(|| ApiError::Api(format!("invalid time `{}`", row.timestamp)))?;
+                let bar = IntradayBar {
+                    open: row.open,
+                    high: row.high,
+                    low: row.low,
+                    close: row.close,
+                    volume: row.volume,
+                };
+                Ok((datetime, bar))
+            })
+            .collect::<Result<_, ApiError>>()?;
+        let metadata = csv_metadata(
+            params,
+            "Intraday open, high, low, close prices and volume",
+            bars.keys().next_back().copied(),
+        )?;
+        Ok(IntradaySeries {
+            metadata: IntradayMetadata {
+                information: metadata.information,
+                symbol: metadata.symbol,
+                last_refreshed: metadata.last_refreshed,
+                interval: crate::client::param(params, "interval").to_string(),
+                output_size: metadata
+                    .output_size
+                    .unwrap_or_else(|| "Compact".to_string()),
+                time_zone: metadata.time_zone,
+            },
+            bars,
+        })
+    }
+}
@@ -0,0 +1,359 @@
+use std::collections::BTreeMap;
+use std::error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AlertRule, Alerts, Identifier, IdentifierMap, Ledger, Portfolio, Position, Symbol, Targets,
+    Transaction, Watchlist,
+};
+
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug)]
+pub enum StorageError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    Toml(toml::de::Error),
+    UnsupportedVersion(u32),
+    NoConfigDir,
+    #[cfg(feature = "sqlite")]
+    Sqlite(rusqlite::Error),
+    // the file an error happened in
+    File(PathBuf, Box<StorageError>),
+}
+impl StorageError {
+    pub(crate) fn in_file(path: &Path) -> impl FnOnce(StorageError) -> StorageError + '_ {
+        move |error| StorageError::File(path.to_path_buf(), Box::new(error))
+    }
+}
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StorageError::Io(err) => write!(f, "{}", err),
+            StorageError::Json(err) => write!(f, "invalid JSON: {}", err),
+            StorageError::Toml(err) => write!(f, "invalid TOML: {}", err),
+            StorageError::UnsupportedVersion(version) => write!(
+                f,
+                "version {} isn't supported, this build reads version {}",
+                version, SCHEMA_VERSION
+            ),
+            StorageError::NoConfigDir => write!(
+                f,
+                "no home directory to keep the configuration in, set `XDG_CONFIG_HOME`"
+            ),
+            #[cfg(feature = "sqlite")]
+            StorageError::Sqlite(err) => write!(f, "{}", err),
+            StorageError::File(path, err) => write!(f, "{}: {}", path.display(), err),
+        }
+    }
+}
+impl error::Error for StorageError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            StorageError::Io(err) => Some(err),
+            StorageError::Json(err) => Some(err),
+            StorageError::Toml(err) => Some(err),
+            #[cfg(feature = "sqlite")]
+            StorageError::Sqlite(err) => Some(err),
+            StorageError::File(_path, err) => Some(err.as_ref()),
+            _ => None,
+        }
+    }
+}
+impl From<io::Error> for StorageError {
+    fn from(error: io::Error) -> StorageError {
+        StorageError::Io(error)
+    }
+}
+impl From<serde_json::Error> for StorageError {
+    fn from(error: serde_json::Error) -> StorageError {
+        StorageError::Json(error)
+    }
+}
+impl From<toml::de::Error> for StorageError {
+    fn from(error: toml::de::Error) -> StorageError {
+        StorageError::Toml(error)
+    }
+}
+#[cfg(feature = "sqlite")]
+impl From<rusqlite::Error> for StorageError {
+    fn from(error: rusqlite::Error) -> StorageError {
+        StorageError::Sqlite(error)
+    }
+}
+
+pub fn config_dir() -> Result<PathBuf, StorageError> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".config")))
+        .map(|dir| dir.join("portfolio"))
+        .ok_or(StorageError::NoConfigDir)
+}
+
+pub fn default_portfolio_path() -> Result<PathBuf, StorageError> {
+    Ok(config_dir()?.join("portfolio.json"))
+}
+
+pub fn default_ledger_path() -> Result<PathBuf, StorageError> {
+    Ok(config_dir()?.join("ledger.json"))
+}
+
+pub fn default_watchlist_path() -> Result<PathBuf, StorageError> {
+    Ok(config_dir()?.join("watchlist.json"))
+}
+
+pub fn default_alerts_path() -> Result<PathBuf, StorageError> {
+    Ok(config_dir()?.join("alerts.json"))
+}
+
+pub fn default_identifiers_path() -> Result<PathBuf, StorageError> {
+    Ok(config_dir()?.join("identifiers.json"))
+}
+
+#[derive(Deserialize)]
+struct VersionProbe {
+    version: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PortfolioFileV1 {
+    version: u32,
+    #[serde(default)]
+    base_currency: Option<String>,
+    #[serde(default)]
+    positions: Vec<Position>,
+    #[serde(default)]
+    targets: Targets,
+}
+
+#[derive(Serialize, Deserialize)]
+struct LedgerFileV1 {
+    version: u32,
+    #[serde(default)]
+    transactions: Vec<Transaction>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    reinvested: Vec<Symbol>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct WatchlistFileV1 {
+    version: u32,
+    #[serde(default)]
+    symbols: Vec<Symbol>,
+}
+
+// ISINs and CUSIPs, and their tickers
+#[derive(Serialize, Deserialize)]
+struct IdentifiersFileV1 {
+    version: u32,
+    #[serde(default)]
+    symbols: BTreeMap<String, Symbol>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct AlertsFileV1 {
+    version: u32,
+    #[serde(default)]
+    rules: Vec<AlertRule>,
+}
+
+// a file of the current schema version, or None if there's no file yet
+fn read_json<T>(path: &Path) -> Result<Option<T>, StorageError>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let read = || {
+        let value: serde_json::Value = match fs::read(path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+
+        let VersionProbe { version } = serde_json::from_value(value.clone())?;
+        match version {
+            SCHEMA_VERSION => Ok(Some(serde_json::from_value(value)?)),
+            version => Err(StorageError::UnsupportedVersion(version)),
+        }
+    };
+    read().map_err(StorageError::in_file(path))
+}
+
+fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), StorageError> {
+    let write = || {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, serde_json::to_vec_pretty(value)?)?;
+        fs::rename(tmp_path, path)?;
+        Ok(())
+    };
+    write().map_err(StorageError::in_file(path))
+}
+
+impl Portfolio {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Portfolio, StorageError> {
+        let file: PortfolioFileV1 = match read_json(path.as_ref())? {
+            Some(file) => file,
+            None => return Ok(Portfolio::new()),
+        };
+
+        let mut portfolio = Portfolio::new();
+        if let Some(base_currency) = file.base_currency {
+            portfolio.set_base_currency(base_currency);
+        }
+        for position in file.positions {
+            portfolio.add_position(position);
+        }
+        *portfolio.targets_mut() = file.targets;
+        Ok(portfolio)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), StorageError> {
+        write_json(
+            path.as_ref(),
+            &PortfolioFileV1 {
+                version: SCHEMA_VERSION,
+                base_currency: Some(self.base_currency().to_string()),
+                positions: self.positions().to_vec(),
+                targets: self.targets().clone(),
+            },
+        )
+    }
+}
+
+impl Ledger {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Ledger, StorageError> {
+        let file: LedgerFileV1 = match read_json(path.as_ref())? {
+            Some(file) => file,
+            None => return Ok(Ledger::new()),
+        };
+
+        let mut ledger = Ledger::new();
+        for transaction in file.transactions {
+            ledger.record(transaction);
+        }
+        for symbol in file.reinvested {
+            ledger.set_reinvested(symbol, true);
+        }
+        Ok(ledger)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), StorageError> {
+        write_json(
+            path.as_ref(),
+            &LedgerFileV1 {
+                version: SCHEMA_VERSION,
+                transactions: self.transactions().to_vec(),
+                reinvested: self.reinvested().iter().cloned().collect(),
+            },
+        )
+    }
+}
+
+impl Watchlist {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Watchlist, StorageError> {
+        let file: WatchlistFileV1 = match read_json(path.as_ref())? {
+            Some(file) => file,
+            None => return Ok(Watchlist::new()),
+        };
+
+        let mut watchlist = Watchlist::new();
+        for symbol in file.symbols {
+            watchlist.add(symbol);
+        }
+        Ok(watchlist)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), StorageError> {
+        write_json(
+            path.as_ref(),
+            &WatchlistFileV1 {
+                version: SCHEMA_VERSION,
+                symbols: self.symbols().cloned().collect(),
+            },
+        )
+    }
+}
+
+impl IdentifierMap {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<IdentifierMap, StorageError> {
+        let file: IdentifiersFileV1 = match read_json(path.as_ref())? {
+            Some(file) => file,
+            None => return Ok(IdentifierMap::new()),
+        };
+
+        let mut map = IdentifierMap::new();
+        for (identifier, symbol) in file.symbols {
+            if let Some(identifier) = Identifier::parse(&identifier) {
+                map.insert(&identifier, symbol);
+            }
+        }
+        Ok(map)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), StorageError> {
+        write_json(
+            path.as_ref(),
+            &IdentifiersFileV1 {
+                version: SCHEMA_VERSION,
+                symbols: self
+                    .iter()
+                    .map(|(identifier, symbol)| (identifier.clone(), symbol.clone()))
+                    .collect(),
+            },
+        )
+    }
+}
+
+impl Alerts {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Alerts, StorageError> {
+        let file: AlertsFileV1 = match read_json(path.as_ref())? {
+            Some(file) => file,
+            None => return Ok(Alerts::new()),
+        };
+
+        let mut alerts = Alerts::new();
+        for rule in file.rules {
+            alerts.insert(rule);
+        }
+        Ok(alerts)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), StorageError> {
+        write_json(
+            path.as_ref(),
+            &AlertsFileV1 {
+                version: SCHEMA_VERSION,
+                rules: self.rules().to_vec(),
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_name_the_file() {
+        let path =
+            std::env::temp_dir().join(format!("portfolio-storage-{}.json", std::process::id()));
+        fs::write(&path, r#"{"version": 7}"#).unwrap();
+        let err = Ledger::load(&path).unwrap_err();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "{}: version 7 isn't supported, this build reads version 1",
+                path.display()
+            )
+        );
+    }
+}
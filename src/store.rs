@@ -0,0 +1,295 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::series::parse_last_refreshed;
+use crate::{
+    DailySeries, Ledger, Metadata, Quote, StorageError, Symbol, TimeSeriesDay, Transaction,
+    Watchlist,
+};
+
+const MIGRATIONS: &[&str] = &[
+    "CREATE TABLE transactions (
+        id INTEGER PRIMARY KEY,
+        date TEXT NOT NULL,
+        symbol TEXT,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX transactions_symbol ON transactions (symbol);
+
+    CREATE TABLE series_metadata (
+        symbol TEXT PRIMARY KEY,
+        information TEXT NOT NULL,
+        last_refreshed TEXT NOT NULL,
+        output_size TEXT,
+        time_zone TEXT NOT NULL
+    );
+
+    CREATE TABLE daily_bars (
+        symbol TEXT NOT NULL,
+        date TEXT NOT NULL,
+        open REAL NOT NULL,
+        high REAL NOT NULL,
+        low REAL NOT NULL,
+        close REAL NOT NULL,
+        adjusted_close REAL NOT NULL,
+        volume REAL NOT NULL,
+        dividend_amount REAL NOT NULL,
+        split_coefficient REAL NOT NULL,
+        PRIMARY KEY (symbol, date)
+    );
+
+    CREATE TABLE quotes (
+        symbol TEXT PRIMARY KEY,
+        open REAL NOT NULL,
+        high REAL NOT NULL,
+        low REAL NOT NULL,
+        price REAL NOT NULL,
+        volume REAL NOT NULL,
+        latest_trading_day TEXT NOT NULL,
+        previous_close REAL NOT NULL,
+        change REAL NOT NULL,
+        change_percent REAL NOT NULL,
+        fetched_at TEXT NOT NULL
+    );",
+    "CREATE TABLE watchlist (
+        symbol TEXT PRIMARY KEY
+    );",
+];
+
+pub struct Store {
+    conn: Connection,
+}
+impl Store {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Store, StorageError> {
+        let path = path.as_ref();
+        let open = || {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            Store::from_connection(Connection::open(path)?)
+        };
+        open().map_err(StorageError::in_file(path))
+    }
+
+    pub fn open_in_memory() -> Result<Store, StorageError> {
+        Store::from_connection(Connection::open_in_memory()?)
+    }
+
+    fn from_connection(mut conn: Connection) -> Result<Store, StorageError> {
+        let version: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+
+        let tx = conn.transaction()?;
+        for (idx, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            tx.execute_batch(migration)?;
+            tx.pragma_update(None, "user_version", idx + 1)?;
+        }
+        tx.commit()?;
+
+        Ok(Store { conn })
+    }
+
+    pub fn insert_transaction(&self, transaction: &Transaction) -> Result<(), StorageError> {
+        self.conn.execute(
+            "INSERT INTO transactions (date, symbol, data) VALUES (?1, ?2, ?3)",
+            params![
+                transaction.date(),
+                transaction.symbol().map(|symbol| &**symbol),
+                serde_json::to_string(transaction)?,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn transactions(&self) -> Result<Vec<Transaction>, StorageError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT data FROM transactions ORDER BY date, id")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+
+        let mut transactions = vec![];
+        for data in rows {
+            transactions.push(serde_json::from_str(&data?)?);
+        }
+        Ok(transactions)
+    }
+
+    pub fn ledger(&self) -> Result<Ledger, StorageError> {
+        let mut ledger = Ledger::new();
+        for transaction in self.transactions()? {
+            ledger.record(transaction);
+        }
+        Ok(ledger)
+    }
+
+    pub fn save_daily_series(&mut self, series: &DailySeries) -> Result<(), StorageError> {
+        let tx = self.conn.transaction()?;
+        {
+            let metadata = &series.metadata;
+            tx.execute(
+                "INSERT OR REPLACE INTO series_metadata
+                    (symbol, information, last_refreshed, output_size, time_zone)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    metadata.symbol,
+                    metadata.information,
+                    metadata.last_refreshed,
+                    metadata.output_size,
+                    metadata.time_zone,
+                ],
+            )?;
+
+            let mut stmt = tx.prepare(
+                "INSERT OR REPLACE INTO daily_bars
+                    (symbol, date, open, high, low, close, adjusted_close, volume,
+                     dividend_amount, split_coefficient)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            )?;
+            for (date, day) in &series.days {
+                stmt.execute(params![
+                    metadata.symbol,
+                    date,
+                    day.open,
+                    day.high,
+                    day.low,
+                    day.close,
+                    day.adjusted_close,
+                    day.volume,
+                    day.dividend_amount,
+                    day.split_coefficient,
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn daily_series(&self, symbol: &Symbol) -> Result<Option<DailySeries>, StorageError> {
+        let metadata = self
+            .conn
+            .query_row(
+                "SELECT symbol, information, last_refreshed, output_size, time_zone
+                 FROM series_metadata WHERE symbol = ?1",
+                params![&**symbol],
+                |row| {
+                    Ok(Metadata {
+                        symbol: row.get(0)?,
+                        information: row.get(1)?,
+                        last_refreshed: parse_last_refreshed(&row.get::<_, String>(2)?)
+                            .unwrap_or_default(),
+                        output_size: row.get(3)?,
+                        time_zone: row.get(4)?,
+                    })
+                },
+            )
+            .optional()?;
+        let metadata = match metadata {
+            Some(metadata) => metadata,
+            None => return Ok(None),
+        };
+
+        let mut stmt = self.conn.prepare(
+            "SELECT date, open, high, low, close, adjusted_close, volume,
+                    dividend_amount, split_coefficient
+             FROM daily_bars WHERE symbol = ?1",
+        )?;
+        let rows = stmt.query_map(params![&**symbol], |row| {
+            Ok((
+                row.get(0)?,
+                TimeSeriesDay {
+                    open: row.get(1)?,
+                    high: row.get(2)?,
+                    low: row.get(3)?,
+                    close: row.get(4)?,
+                    adjusted_close: row.get(5)?,
+                    volume: row.get(6)?,
+                    dividend_amount: row.get(7)?,
+                    split_coefficient: row.get(8)?,
+                },
+            ))
+        })?;
+
+        let days = rows.collect::<Result<BTreeMap<_, _>, _>>()?;
+        Ok(Some(DailySeries { metadata, days }))
+    }
+
+    pub fn save_quote(&self, quote: &Quote) -> Result<(), StorageError> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO quotes
+                (symbol, open, high, low, price, volume, latest_trading_day,
+                 previous_close, change, change_percent, fetched_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                quote.symbol,
+                quote.open,
+                quote.high,
+                quote.low,
+                quote.price,
+                quote.volume,
+                quote.latest_trading_day,
+                quote.previous_close,
+                quote.change,
+                quote.change_percent,
+                chrono::Utc::now(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn quote(&self, symbol: &Symbol) -> Result<Option<Quote>, StorageError> {
+        self.conn
+            .query_row(
+                "SELECT symbol, open, high, low, price, volume, latest_trading_day,
+                        previous_close, change, change_percent
+                 FROM quotes WHERE symbol = ?1",
+                params![&**symbol],
+                |row| {
+                    Ok(Quote {
+                        symbol: row.get(0)?,
+                        open: row.get(1)?,
+                        high: row.get(2)?,
+                        low: row.get(3)?,
+                        price: row.get(4)?,
+                        volume: row.get(5)?,
+                        latest_trading_day: row.get(6)?,
+                        previous_close: row.get(7)?,
+                        change: row.get(8)?,
+                        change_percent: row.get(9)?,
+                        retrieved_at: None,
+                    })
+                },
+            )
+            .optional()
+            .map_err(|err| err.into())
+    }
+
+    pub fn watch(&self, symbol: &Symbol) -> Result<(), StorageError> {
+        self.conn.execute(
+            "INSERT OR IGNORE INTO watchlist (symbol) VALUES (?1)",
+            params![&**symbol],
+        )?;
+        Ok(())
+    }
+
+    pub fn unwatch(&self, symbol: &Symbol) -> Result<(), StorageError> {
+        self.conn.execute(
+            "DELETE FROM watchlist WHERE symbol = ?1",
+            params![&**symbol],
+        )?;
+        Ok(())
+    }
+
+    pub fn watchlist(&self) -> Result<Watchlist, StorageError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT symbol FROM watchlist ORDER BY symbol")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+
+        let mut watchlist = Watchlist::new();
+        for symbol in rows {
+            watchlist.add(symbol?);
+        }
+        Ok(watchlist)
+    }
+}
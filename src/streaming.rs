@@ -0,0 +1,150 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use futures::stream::{self, SplitStream};
+use futures::{SinkExt, Stream, StreamExt};
+use serde::Deserialize;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+use crate::{ApiError, FinnhubClient, Quote};
+
+const FINNHUB_URL: &str = "wss://ws.finnhub.io";
+
+type Socket = WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>;
+
+#[derive(Debug, Deserialize)]
+struct Trade {
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "p")]
+    price: f64,
+    #[serde(rename = "v", default)]
+    volume: f64,
+    // milliseconds since the epoch
+    #[serde(rename = "t")]
+    timestamp: i64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Event {
+    Trade {
+        data: Vec<Trade>,
+    },
+    Error {
+        msg: String,
+    },
+    // pings and anything newer
+    #[serde(other)]
+    Other,
+}
+
+struct State {
+    socket: SplitStream<Socket>,
+    quotes: BTreeMap<String, Quote>,
+    pending: VecDeque<Quote>,
+    done: bool,
+}
+impl State {
+    fn trade(&mut self, trade: Trade) {
+        let quote = match self.quotes.get_mut(&trade.symbol) {
+            Some(quote) => quote,
+            None => return,
+        };
+        let date = match chrono::DateTime::from_timestamp_millis(trade.timestamp) {
+            Some(time) => time.date_naive(),
+            None => return,
+        };
+        // the first trade of a new session starts a new day's figures
+        if date > quote.latest_trading_day {
+            quote.previous_close = quote.price;
+            quote.open = trade.price;
+            quote.high = trade.price;
+            quote.low = trade.price;
+            quote.volume = 0.0;
+            quote.latest_trading_day = date;
+        }
+        quote.price = trade.price;
+        quote.retrieved_at = chrono::DateTime::from_timestamp_millis(trade.timestamp);
+        quote.high = quote.high.max(trade.price);
+        quote.low = quote.low.min(trade.price);
+        quote.volume += trade.volume;
+        quote.change = quote.price - quote.previous_close;
+        quote.change_percent = if quote.previous_close > 0.0 {
+            quote.change / quote.previous_close * 100.0
+        } else {
+            0.0
+        };
+    }
+
+    fn message(&mut self, text: &str) -> Result<(), ApiError> {
+        match serde_json::from_str(text)? {
+            Event::Trade { data } => {
+                let mut updated: Vec<String> = vec![];
+                for trade in data {
+                    if !updated.contains(&trade.symbol) {
+                        updated.push(trade.symbol.clone());
+                    }
+                    self.trade(trade);
+                }
+                for symbol in updated {
+                    if let Some(quote) = self.quotes.get(&symbol) {
+                        self.pending.push_back(quote.clone());
+                    }
+                }
+                Ok(())
+            }
+            Event::Error { msg } => Err(ApiError::Api(msg)),
+            Event::Other => Ok(()),
+        }
+    }
+}
+
+// Finnhub's trade stream, applied to `quotes` (e.g. from `QuoteProvider::quotes`)
+// so that each update carries the day's change. Yields a symbol's quote after
+// each batch of its trades, and ends when the connection closes or fails.
+pub async fn finnhub_quotes(
+    client: &FinnhubClient,
+    quotes: Vec<Quote>,
+) -> Result<impl Stream<Item = Result<Quote, ApiError>>, ApiError> {
+    let url = format!("{}?token={}", FINNHUB_URL, client.api_key()?);
+    let (socket, _response) = tokio_tungstenite::connect_async(url).await?;
+    let (mut sink, socket) = socket.split();
+    for quote in &quotes {
+        let subscribe = serde_json::json!({ "type": "subscribe", "symbol": quote.symbol });
+        sink.send(Message::Text(subscribe.to_string())).await?;
+    }
+
+    let state = State {
+        socket,
+        quotes: quotes
+            .into_iter()
+            .map(|quote| (quote.symbol.clone(), quote))
+            .collect(),
+        pending: VecDeque::new(),
+        done: false,
+    };
+    Ok(stream::unfold(
+        (state, sink),
+        |(mut state, sink)| async move {
+            loop {
+                if let Some(quote) = state.pending.pop_front() {
+                    return Some((Ok(quote), (state, sink)));
+                }
+                if state.done {
+                    return None;
+                }
+                let result = match state.socket.next().await {
+                    Some(Ok(Message::Text(text))) => state.message(&text),
+                    Some(Ok(Message::Close(_))) | None => return None,
+                    Some(Ok(_message)) => Ok(()),
+                    Some(Err(err)) => Err(err.into()),
+                };
+                if let Err(err) = result {
+                    state.done = true;
+                    return Some((Err(err), (state, sink)));
+                }
+            }
+        },
+    ))
+}
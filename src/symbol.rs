@@ -0,0 +1,156 @@
+use std::error;
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+const MAX_LENGTH: usize = 20;
+
+// the suffixes Alpha Vantage and Yahoo give symbols listed outside the US.
+// A single letter that isn't one of them, as in `BRK.B`, is a share class.
+const EXCHANGES: &[&str] = &[
+    // Alpha Vantage
+    "LON", "TRT", "TRV", "DEX", "FRK", "BSE", "NSE", "SHH", "SHZ", "AMS", "PAR", // Yahoo
+    "AX", "L", "TO", "V", "NE", "DE", "F", "PA", "AS", "BR", "MI", "MC", "LS", "SW", "ST", "OL",
+    "CO", "HE", "IR", "VI", "WA", "HK", "T", "SS", "SZ", "NS", "BO", "SI", "KS", "KQ", "TW", "NZ",
+    "SA", "MX", "JO", "TA",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SymbolError {
+    Empty,
+    TooLong(String),
+    InvalidCharacter(String, char),
+    // a `.` with nothing on one side of it
+    Malformed(String),
+    UnknownExchange(String, String),
+}
+impl fmt::Display for SymbolError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SymbolError::Empty => write!(f, "the symbol is empty"),
+            SymbolError::TooLong(symbol) => write!(
+                f,
+                "`{}` is longer than the {} characters a symbol can be",
+                symbol, MAX_LENGTH
+            ),
+            SymbolError::InvalidCharacter(symbol, c) => {
+                write!(f, "`{}` has a `{}`, which can't be in a symbol", symbol, c)
+            }
+            SymbolError::Malformed(symbol) => write!(f, "`{}` isn't a symbol", symbol),
+            SymbolError::UnknownExchange(symbol, exchange) => write!(
+                f,
+                "unknown exchange `{}` in `{}`, which should be like `VOD.LON` or `BHP.AX`",
+                exchange, symbol
+            ),
+        }
+    }
+}
+impl error::Error for SymbolError {}
+
+// always upper case. `new` takes anything, for symbols from a provider or a
+// file; symbols typed in are parsed, which checks them.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(from = "String")]
+pub struct Symbol(String);
+impl Symbol {
+    pub fn new<S: Into<String>>(s: S) -> Symbol {
+        Symbol(s.into().trim().to_uppercase())
+    }
+
+    pub fn parse(s: &str) -> Result<Symbol, SymbolError> {
+        let symbol = Symbol::new(s);
+        if symbol.is_empty() {
+            return Err(SymbolError::Empty);
+        }
+        if symbol.len() > MAX_LENGTH {
+            return Err(SymbolError::TooLong(symbol.0));
+        }
+        // `^` for indices, `=` for Yahoo's currencies and `:` for Finnhub's exchanges
+        if let Some(c) = symbol
+            .chars()
+            .find(|c| !c.is_ascii_alphanumeric() && !".-^=&:_".contains(*c))
+        {
+            return Err(SymbolError::InvalidCharacter(symbol.0, c));
+        }
+        if let Some((ticker, suffix)) = symbol.rsplit_once('.') {
+            if ticker.is_empty() || suffix.is_empty() {
+                return Err(SymbolError::Malformed(symbol.0));
+            }
+            if suffix.len() > 1 && symbol.exchange().is_none() {
+                let suffix = suffix.to_string();
+                return Err(SymbolError::UnknownExchange(symbol.0, suffix));
+            }
+        }
+        Ok(symbol)
+    }
+
+    // the symbol without its exchange suffix
+    pub fn ticker(&self) -> &str {
+        match self.exchange() {
+            Some(exchange) => &self.0[..self.0.len() - exchange.len() - 1],
+            None => &self.0,
+        }
+    }
+
+    // the suffix of a symbol listed outside the US, e.g. `LON` for `VOD.LON`
+    pub fn exchange(&self) -> Option<&str> {
+        let (ticker, suffix) = self.0.rsplit_once('.')?;
+        if ticker.is_empty() || !EXCHANGES.contains(&suffix) {
+            return None;
+        }
+        Some(suffix)
+    }
+
+    // the currency its exchange quotes in, going by the suffix; London quotes
+    // in pence
+    pub fn currency(&self) -> &'static str {
+        match self.exchange() {
+            None => "USD",
+            Some("LON" | "L") => "GBX",
+            Some("TRT" | "TRV" | "TO" | "V" | "NE") => "CAD",
+            Some("AX") => "AUD",
+            Some("NZ") => "NZD",
+            Some("BSE" | "NSE" | "NS" | "BO") => "INR",
+            Some("SHH" | "SHZ" | "SS" | "SZ") => "CNY",
+            Some("HK") => "HKD",
+            Some("T") => "JPY",
+            Some("SI") => "SGD",
+            Some("KS" | "KQ") => "KRW",
+            Some("TW") => "TWD",
+            Some("SW") => "CHF",
+            Some("ST") => "SEK",
+            Some("OL") => "NOK",
+            Some("CO") => "DKK",
+            Some("WA") => "PLN",
+            Some("SA") => "BRL",
+            Some("MX") => "MXN",
+            Some("JO") => "ZAR",
+            Some("TA") => "ILS",
+            Some(_euro) => "EUR",
+        }
+    }
+}
+impl FromStr for Symbol {
+    type Err = SymbolError;
+
+    fn from_str(s: &str) -> Result<Symbol, SymbolError> {
+        Symbol::parse(s)
+    }
+}
+impl<S> From<S> for Symbol
+where
+    S: Into<String>,
+{
+    fn from(s: S) -> Symbol {
+        Symbol::new(s)
+    }
+}
+
+impl Deref for Symbol {
+    type Target = str;
+    fn deref(&self) -> &str {
+        self.0.as_str()
+    }
+}
@@ -0,0 +1,250 @@
+use std::collections::BTreeSet;
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use crate::lots::LONG_TERM_MONTHS;
+use crate::money::{self, Decimal, Money};
+use crate::{ApiError, GainsReport, RealisedGain};
+
+// how a jurisdiction taxes realised gains
+pub trait TaxRules {
+    fn name(&self) -> &str;
+
+    fn long_term(&self, gain: &RealisedGain) -> bool;
+
+    // in percent
+    fn rate(&self, long_term: bool) -> f64;
+
+    // the net gain each tax year that isn't taxed
+    fn allowance(&self) -> Money;
+
+    // whether losses on wash sales are disallowed
+    fn wash_sale_rule(&self) -> bool;
+
+    // the month and day the tax year starts on
+    fn year_start(&self) -> (u32, u32);
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Jurisdiction {
+    pub name: String,
+    // gains on lots held for more months are long term; none treats every gain alike
+    pub long_term_months: Option<u32>,
+    pub short_term_rate: f64,
+    pub long_term_rate: f64,
+    pub allowance: Money,
+    pub wash_sale_rule: bool,
+    // month and day
+    pub year_start: (u32, u32),
+}
+impl Jurisdiction {
+    // federal rates for a single filer in the 22% bracket
+    pub fn us() -> Jurisdiction {
+        Jurisdiction {
+            name: "US".to_string(),
+            long_term_months: Some(LONG_TERM_MONTHS),
+            short_term_rate: 22.0,
+            long_term_rate: 15.0,
+            allowance: Money::ZERO,
+            wash_sale_rule: true,
+            year_start: (1, 1),
+        }
+    }
+
+    // the higher rate and the annual exempt amount, in tax years from 6 April
+    pub fn uk() -> Jurisdiction {
+        Jurisdiction {
+            name: "UK".to_string(),
+            long_term_months: None,
+            short_term_rate: 24.0,
+            long_term_rate: 24.0,
+            allowance: Decimal::new(3000, 0),
+            wash_sale_rule: false,
+            year_start: (4, 6),
+        }
+    }
+}
+impl FromStr for Jurisdiction {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<Jurisdiction, ApiError> {
+        match s.to_lowercase().as_str() {
+            "us" => Ok(Jurisdiction::us()),
+            "uk" => Ok(Jurisdiction::uk()),
+            _ => Err(ApiError::Api(format!("unknown tax jurisdiction `{}`", s))),
+        }
+    }
+}
+impl TaxRules for Jurisdiction {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn long_term(&self, gain: &RealisedGain) -> bool {
+        self.long_term_months
+            .is_some_and(|months| gain.held_longer_than(months))
+    }
+
+    fn rate(&self, long_term: bool) -> f64 {
+        if long_term {
+            self.long_term_rate
+        } else {
+            self.short_term_rate
+        }
+    }
+
+    fn allowance(&self) -> Money {
+        self.allowance
+    }
+
+    fn wash_sale_rule(&self) -> bool {
+        self.wash_sale_rule
+    }
+
+    fn year_start(&self) -> (u32, u32) {
+        self.year_start
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TaxEstimate {
+    pub jurisdiction: String,
+    pub short_term_gains: Money,
+    pub long_term_gains: Money,
+    // wash sale losses left out of the gains
+    pub disallowed_losses: Money,
+    pub allowance_used: Money,
+    pub taxable_short_term: Money,
+    pub taxable_long_term: Money,
+    pub tax: Money,
+}
+
+// a net loss of either term offsets gains of the other, and the allowance goes
+// against the more highly taxed gains first. Losses carried over from earlier
+// years aren't counted, and neither are disallowed wash sale losses, which
+// would be added to the cost basis of the shares bought instead. Gains are
+// summed as they are, so a report spanning several currencies is refused
+// rather than adding pence to dollars.
+pub fn estimate_tax(report: &GainsReport, rules: &dyn TaxRules) -> Result<TaxEstimate, ApiError> {
+    let currencies: BTreeSet<&str> = report
+        .gains
+        .iter()
+        .map(|gain| gain.currency.as_str())
+        .collect();
+    if currencies.len() > 1 {
+        return Err(ApiError::Api(format!(
+            "can't estimate tax on gains in {}; convert them into one currency first",
+            currencies.into_iter().collect::<Vec<_>>().join(" and ")
+        )));
+    }
+
+    let disallowed = |gain: &&RealisedGain| rules.wash_sale_rule() && gain.wash_sale;
+    let disallowed_losses: Money = -report
+        .gains
+        .iter()
+        .filter(disallowed)
+        .map(RealisedGain::gain)
+        .sum::<Money>();
+    let (long, short): (Vec<&RealisedGain>, Vec<&RealisedGain>) = report
+        .gains
+        .iter()
+        .filter(|gain| !disallowed(gain))
+        .partition(|gain| rules.long_term(gain));
+    let short_term_gains: Money = short.iter().map(|gain| gain.gain()).sum();
+    let long_term_gains: Money = long.iter().map(|gain| gain.gain()).sum();
+
+    let (mut short_term, mut long_term) = (short_term_gains, long_term_gains);
+    if short_term < Money::ZERO {
+        long_term += short_term;
+        short_term = Money::ZERO;
+    } else if long_term < Money::ZERO {
+        short_term += long_term;
+        long_term = Money::ZERO;
+    }
+    let (short_term, long_term) = (short_term.max(Money::ZERO), long_term.max(Money::ZERO));
+
+    let mut allowance = rules.allowance().max(Money::ZERO);
+    let mut use_allowance = |gains: Money| {
+        let used = gains.min(allowance);
+        allowance -= used;
+        gains - used
+    };
+    let (taxable_short_term, taxable_long_term) = if rules.rate(true) > rules.rate(false) {
+        let long_term = use_allowance(long_term);
+        (use_allowance(short_term), long_term)
+    } else {
+        let short_term = use_allowance(short_term);
+        (short_term, use_allowance(long_term))
+    };
+    let allowance_used = short_term + long_term - taxable_short_term - taxable_long_term;
+
+    let tax = taxable_short_term * money::from_f64(rules.rate(false) / 100.0)
+        + taxable_long_term * money::from_f64(rules.rate(true) / 100.0);
+    Ok(TaxEstimate {
+        jurisdiction: rules.name().to_string(),
+        short_term_gains,
+        long_term_gains,
+        disallowed_losses,
+        allowance_used,
+        taxable_short_term,
+        taxable_long_term,
+        tax: tax.round_dp(2),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Symbol;
+
+    fn gain(acquired: &str, sold: &str, gain: i64) -> RealisedGain {
+        RealisedGain {
+            symbol: Symbol::new("IBM"),
+            acquired: acquired.parse().unwrap(),
+            sold: sold.parse().unwrap(),
+            quantity: Decimal::ONE,
+            proceeds: Decimal::from(100 + gain),
+            cost_basis: Decimal::from(100),
+            currency: "GBP".to_string(),
+            wash_sale: false,
+            buys: vec![],
+        }
+    }
+
+    #[test]
+    fn us_long_term_is_more_than_a_calendar_year() {
+        let us = Jurisdiction::us();
+        assert!(!us.long_term(&gain("2023-03-01", "2024-03-01", 10)));
+        assert!(us.long_term(&gain("2023-03-01", "2024-03-02", 10)));
+        assert!(!Jurisdiction::uk().long_term(&gain("2020-01-01", "2024-03-01", 10)));
+    }
+
+    #[test]
+    fn losses_offset_the_other_term_before_the_allowance() {
+        let report = GainsReport {
+            start_date: "2023-04-06".parse().unwrap(),
+            end_date: "2024-04-05".parse().unwrap(),
+            gains: vec![
+                gain("2023-05-01", "2024-01-02", 5000),
+                gain("2023-05-01", "2024-01-03", -1000),
+            ],
+        };
+        let estimate = estimate_tax(&report, &Jurisdiction::uk()).unwrap();
+        assert_eq!(estimate.allowance_used, Decimal::from(3000));
+        assert_eq!(estimate.tax, Decimal::from(240));
+    }
+
+    #[test]
+    fn mixed_currencies_are_refused() {
+        let mut dollars = gain("2023-05-01", "2024-01-03", 1000);
+        dollars.currency = "USD".to_string();
+        let report = GainsReport {
+            start_date: "2023-04-06".parse().unwrap(),
+            end_date: "2024-04-05".parse().unwrap(),
+            gains: vec![gain("2023-05-01", "2024-01-02", 5000), dollars],
+        };
+        let err = estimate_tax(&report, &Jurisdiction::uk()).unwrap_err();
+        assert!(err.to_string().contains("GBP and USD"), "{}", err);
+    }
+}
@@ -0,0 +1,290 @@
+use std::sync::{Arc, Mutex};
+
+use crate::money::Decimal;
+use crate::{
+    ApiError, ApiTier, DailySeries, Ledger, Metadata, PortfolioClient, Symbol, TimeSeriesDay,
+    Transaction, Transport,
+};
+
+// responses as Alpha Vantage gives them, for IBM on 2024-03-08
+pub const GLOBAL_QUOTE: &str = r#"{
+    "Global Quote": {
+        "01. symbol": "IBM",
+        "02. open": "195.0900",
+        "03. high": "197.7700",
+        "04. low": "194.3800",
+        "05. price": "195.9500",
+        "06. volume": "3845232",
+        "07. latest trading day": "2024-03-08",
+        "08. previous close": "196.5400",
+        "09. change": "-0.5900",
+        "10. change percent": "-0.3002%"
+    }
+}"#;
+
+pub const DAILY_SERIES: &str = r#"{
+    "Meta Data": {
+        "1. Information": "Daily Time Series with Splits and Dividend Events",
+        "2. Symbol": "IBM",
+        "3. Last Refreshed": "2024-03-08",
+        "4. Output Size": "Compact",
+        "5. Time Zone": "US/Eastern"
+    },
+    "Time Series (Daily)": {
+        "2024-03-08": {
+            "1. open": "195.0900",
+            "2. high": "197.7700",
+            "3. low": "194.3800",
+            "4. close": "195.9500",
+            "5. adjusted close": "195.9500",
+            "6. volume": "3845232",
+            "7. dividend amount": "0.0000",
+            "8. split coefficient": "1.0"
+        },
+        "2024-03-07": {
+            "1. open": "197.5800",
+            "2. high": "198.7300",
+            "3. low": "196.1400",
+            "4. close": "196.5400",
+            "5. adjusted close": "196.5400",
+            "6. volume": "4604458",
+            "7. dividend amount": "0.0000",
+            "8. split coefficient": "1.0"
+        },
+        "2024-03-06": {
+            "1. open": "193.5000",
+            "2. high": "198.1300",
+            "3. low": "192.9600",
+            "4. close": "196.1600",
+            "5. adjusted close": "196.1600",
+            "6. volume": "6945818",
+            "7. dividend amount": "0.0000",
+            "8. split coefficient": "1.0"
+        },
+        "2024-03-05": {
+            "1. open": "190.9000",
+            "2. high": "193.2200",
+            "3. low": "189.6200",
+            "4. close": "191.9500",
+            "5. adjusted close": "191.9500",
+            "6. volume": "4531004",
+            "7. dividend amount": "0.0000",
+            "8. split coefficient": "1.0"
+        },
+        "2024-03-04": {
+            "1. open": "187.7600",
+            "2. high": "193.8980",
+            "3. low": "187.6000",
+            "4. close": "191.6600",
+            "5. adjusted close": "191.6600",
+            "6. volume": "9682613",
+            "7. dividend amount": "0.0000",
+            "8. split coefficient": "1.0"
+        }
+    }
+}"#;
+
+// the same, as requested with `datatype=csv`
+pub const DAILY_SERIES_CSV: &str =
+    "timestamp,open,high,low,close,adjusted_close,volume,dividend_amount,split_coefficient
+2024-03-08,195.0900,197.7700,194.3800,195.9500,195.9500,3845232,0.0000,1.0
+2024-03-07,197.5800,198.7300,196.1400,196.5400,196.5400,4604458,0.0000,1.0
+2024-03-06,193.5000,198.1300,192.9600,196.1600,196.1600,6945818,0.0000,1.0
+2024-03-05,190.9000,193.2200,189.6200,191.9500,191.9500,4531004,0.0000,1.0
+2024-03-04,187.7600,193.8980,187.6000,191.6600,191.6600,9682613,0.0000,1.0
+";
+
+pub const INVALID_SYMBOL: &str = r#"{
+    "Error Message": "Invalid API call. Please retry or visit the documentation (https://www.alphavantage.co/documentation/) for TIME_SERIES_DAILY_ADJUSTED."
+}"#;
+
+pub const RATE_LIMITED: &str = r#"{
+    "Note": "Thank you for using Alpha Vantage! Our standard API call frequency is 5 calls per minute and 500 calls per day."
+}"#;
+
+// canned responses, each answering requests that have all of its parameters
+#[derive(Default)]
+pub struct Fixtures {
+    responses: Vec<(Vec<(String, String)>, String)>,
+    requests: Mutex<Vec<Vec<(String, String)>>>,
+}
+impl Fixtures {
+    pub fn new() -> Fixtures {
+        Fixtures::default()
+    }
+
+    // IBM's quote and daily series, and an error for any other symbol
+    pub fn canned() -> Fixtures {
+        Fixtures::new()
+            .with(
+                &[("function", "GLOBAL_QUOTE"), ("symbol", "IBM")],
+                GLOBAL_QUOTE,
+            )
+            .with(
+                &[
+                    ("function", "TIME_SERIES_DAILY_ADJUSTED"),
+                    ("symbol", "IBM"),
+                ],
+                DAILY_SERIES,
+            )
+            .with(&[], INVALID_SYMBOL)
+    }
+
+    // earlier responses are matched first
+    pub fn with<S: Into<String>>(mut self, params: &[(&str, &str)], body: S) -> Fixtures {
+        let params = params
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        self.responses.push((params, body.into()));
+        self
+    }
+
+    // the parameters of every request answered so far, in order
+    pub fn requests(&self) -> Vec<Vec<(String, String)>> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+impl Transport for Fixtures {
+    fn get(&self, params: &[(&str, &str)]) -> Result<String, ApiError> {
+        self.requests.lock().unwrap().push(
+            params
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+        );
+        self.responses
+            .iter()
+            .find(|(wanted, _body)| {
+                wanted
+                    .iter()
+                    .all(|(key, value)| params.contains(&(key.as_str(), value.as_str())))
+            })
+            .map(|(_params, body)| body.clone())
+            .ok_or_else(|| ApiError::Api(format!("no fixture for {:?}", params)))
+    }
+}
+// so the fixtures can still be inspected once they're given to a client
+impl<T: Transport + ?Sized> Transport for Arc<T> {
+    fn get(&self, params: &[(&str, &str)]) -> Result<String, ApiError> {
+        (**self).get(params)
+    }
+}
+
+// a client answered by `transport`, without a cache or the free tier's rate limit
+pub fn client<T: Transport + 'static>(transport: T) -> PortfolioClient {
+    PortfolioClient::builder("demo")
+        .transport(transport)
+        .tier(ApiTier::Premium {
+            requests_per_minute: 60_000,
+        })
+        .build()
+        .expect("default client configuration is valid")
+}
+
+fn date(date: &str) -> chrono::NaiveDate {
+    date.parse().expect("dates are YYYY-MM-DD")
+}
+
+// trades without fees or tags, in USD
+pub fn buy(on: &str, symbol: &str, quantity: i64, price: i64) -> Transaction {
+    Transaction::Buy {
+        date: date(on),
+        symbol: Symbol::new(symbol),
+        quantity: Decimal::from(quantity),
+        price: Decimal::from(price),
+        fees: Decimal::ZERO,
+        currency: "USD".to_string(),
+        tags: vec![],
+        account: None,
+        reinvested: false,
+    }
+}
+
+pub fn sell(on: &str, symbol: &str, quantity: i64, price: i64) -> Transaction {
+    Transaction::Sell {
+        date: date(on),
+        symbol: Symbol::new(symbol),
+        quantity: Decimal::from(quantity),
+        price: Decimal::from(price),
+        fees: Decimal::ZERO,
+        lot: None,
+        currency: "USD".to_string(),
+        tags: vec![],
+        account: None,
+    }
+}
+
+// a sell of the lot bought on `lot`
+pub fn sell_lot(on: &str, symbol: &str, quantity: i64, price: i64, lot: &str) -> Transaction {
+    Transaction::Sell {
+        date: date(on),
+        symbol: Symbol::new(symbol),
+        quantity: Decimal::from(quantity),
+        price: Decimal::from(price),
+        fees: Decimal::ZERO,
+        lot: Some(date(lot)),
+        currency: "USD".to_string(),
+        tags: vec![],
+        account: None,
+    }
+}
+
+pub fn ledger(transactions: Vec<Transaction>) -> Ledger {
+    let mut ledger = Ledger::new();
+    for transaction in transactions {
+        ledger.record(transaction);
+    }
+    ledger
+}
+
+// a bar a day from `start` with each (close, adjusted close)
+pub fn daily_series(symbol: &str, start: &str, closes: &[(f64, f64)]) -> DailySeries {
+    let start = date(start);
+    let days = closes
+        .iter()
+        .zip(start.iter_days())
+        .map(|((close, adjusted_close), date)| {
+            let day = TimeSeriesDay {
+                open: *close,
+                high: *close,
+                low: *close,
+                close: *close,
+                adjusted_close: *adjusted_close,
+                volume: 1000.0,
+                dividend_amount: 0.0,
+                split_coefficient: 1.0,
+            };
+            (date, day)
+        })
+        .collect();
+    DailySeries {
+        metadata: Metadata {
+            information: "Daily Prices".to_string(),
+            symbol: symbol.to_string(),
+            last_refreshed: start.and_hms_opt(0, 0, 0).unwrap(),
+            output_size: None,
+            time_zone: "US/Eastern".to_string(),
+        },
+        days,
+    }
+}
+
+// `days` closes at 100 then `days` at 50 after a 2:1 split, which adjusted
+// are 50 throughout
+pub fn split_series(symbol: &str, days: usize) -> DailySeries {
+    let closes: Vec<(f64, f64)> = (0..days * 2)
+        .map(|day| {
+            if day < days {
+                (100.0, 50.0)
+            } else {
+                (50.0, 50.0)
+            }
+        })
+        .collect();
+    let mut series = daily_series(symbol, "2024-01-01", &closes);
+    if let Some(day) = series.days.values_mut().nth(days) {
+        day.split_coefficient = 2.0;
+    }
+    series
+}
@@ -0,0 +1,38 @@
+use std::collections::BTreeSet;
+
+use crate::{ApiError, Quote, QuoteProvider, Symbol};
+
+#[derive(Debug, Default)]
+pub struct Watchlist {
+    symbols: BTreeSet<Symbol>,
+}
+impl Watchlist {
+    pub fn new() -> Watchlist {
+        Watchlist::default()
+    }
+
+    pub fn add<S: Into<Symbol>>(&mut self, symbol: S) -> bool {
+        self.symbols.insert(symbol.into())
+    }
+
+    pub fn remove(&mut self, symbol: &Symbol) -> bool {
+        self.symbols.remove(symbol)
+    }
+
+    pub fn contains(&self, symbol: &Symbol) -> bool {
+        self.symbols.contains(symbol)
+    }
+
+    pub fn symbols(&self) -> impl Iterator<Item = &Symbol> {
+        self.symbols.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    pub fn quotes<P: QuoteProvider + ?Sized>(&self, provider: &P) -> Result<Vec<Quote>, ApiError> {
+        let symbols: Vec<Symbol> = self.symbols.iter().cloned().collect();
+        provider.quotes(&symbols)
+    }
+}
@@ -0,0 +1,303 @@
+use std::collections::{BTreeMap, HashMap};
+use std::time::Instant;
+
+use serde::Deserialize;
+
+use crate::client::log_request;
+use crate::{
+    ApiError, DailySeries, ErrorContext, Frequency, Metadata, OutputSize, Quote, QuoteProvider,
+    Series, Symbol, TimeSeriesDay, TimeSeriesProvider,
+};
+
+const CHART_URL: &str = "https://query1.finance.yahoo.com/v8/finance/chart";
+const USER_AGENT: &str = "Mozilla/5.0 (compatible; portfolio-cli)";
+
+#[derive(Debug, Deserialize)]
+struct ChartResponse {
+    chart: Chart,
+}
+
+#[derive(Debug, Deserialize)]
+struct Chart {
+    result: Option<Vec<ChartResult>>,
+    error: Option<ChartError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChartError {
+    code: String,
+    description: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChartMeta {
+    symbol: String,
+    regular_market_price: Option<f64>,
+    regular_market_time: Option<i64>,
+    #[serde(default)]
+    gmtoffset: i64,
+    exchange_timezone_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChartResult {
+    meta: ChartMeta,
+    #[serde(default)]
+    timestamp: Vec<i64>,
+    #[serde(default)]
+    events: ChartEvents,
+    indicators: Indicators,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ChartEvents {
+    #[serde(default)]
+    dividends: HashMap<String, DividendEvent>,
+    #[serde(default)]
+    splits: HashMap<String, SplitEvent>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DividendEvent {
+    amount: f64,
+    date: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct SplitEvent {
+    date: i64,
+    numerator: f64,
+    denominator: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct Indicators {
+    quote: Vec<QuoteIndicators>,
+    #[serde(default)]
+    adjclose: Vec<AdjClose>,
+}
+
+#[derive(Debug, Deserialize)]
+struct QuoteIndicators {
+    open: Vec<Option<f64>>,
+    high: Vec<Option<f64>>,
+    low: Vec<Option<f64>>,
+    close: Vec<Option<f64>>,
+    volume: Vec<Option<f64>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AdjClose {
+    adjclose: Vec<Option<f64>>,
+}
+
+impl ChartResult {
+    fn local_date(&self, timestamp: i64) -> chrono::NaiveDate {
+        chrono::DateTime::from_timestamp(timestamp + self.meta.gmtoffset, 0)
+            .unwrap_or_default()
+            .date_naive()
+    }
+
+    fn bars(&self) -> BTreeMap<chrono::NaiveDate, TimeSeriesDay> {
+        let mut bars = BTreeMap::new();
+        let quote = match self.indicators.quote.first() {
+            Some(quote) => quote,
+            None => return bars,
+        };
+        let adjclose = self.indicators.adjclose.first();
+
+        for (idx, timestamp) in self.timestamp.iter().enumerate() {
+            let value = |series: &Vec<Option<f64>>| series.get(idx).cloned().flatten();
+            let (open, high, low, close) = match (
+                value(&quote.open),
+                value(&quote.high),
+                value(&quote.low),
+                value(&quote.close),
+            ) {
+                (Some(open), Some(high), Some(low), Some(close)) => (open, high, low, close),
+                _ => continue,
+            };
+
+            bars.insert(
+                self.local_date(*timestamp),
+                TimeSeriesDay {
+                    open,
+                    high,
+                    low,
+                    close,
+                    adjusted_close: adjclose
+                        .and_then(|adjclose| value(&adjclose.adjclose))
+                        .unwrap_or(close),
+                    volume: value(&quote.volume).unwrap_or(0.0),
+                    dividend_amount: 0.0,
+                    split_coefficient: 1.0,
+                },
+            );
+        }
+
+        for dividend in self.events.dividends.values() {
+            if let Some(bar) = bars.get_mut(&self.local_date(dividend.date)) {
+                bar.dividend_amount += dividend.amount;
+            }
+        }
+        for split in self.events.splits.values() {
+            if let Some(bar) = bars.get_mut(&self.local_date(split.date)) {
+                bar.split_coefficient *= split.numerator / split.denominator;
+            }
+        }
+
+        bars
+    }
+
+    fn metadata(
+        &self,
+        bars: &BTreeMap<chrono::NaiveDate, TimeSeriesDay>,
+        output_size: Option<&str>,
+    ) -> Metadata {
+        Metadata {
+            information: "Yahoo Finance chart".to_string(),
+            symbol: self.meta.symbol.clone(),
+            last_refreshed: bars
+                .keys()
+                .next_back()
+                .map(|date| date.and_time(chrono::NaiveTime::MIN))
+                .unwrap_or_default(),
+            output_size: output_size.map(String::from),
+            time_zone: self.meta.exchange_timezone_name.clone().unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct YahooClient {
+    client: reqwest::blocking::Client,
+}
+impl YahooClient {
+    pub fn new() -> YahooClient {
+        YahooClient::default()
+    }
+
+    fn chart(&self, symbol: &Symbol, range: &str, interval: &str) -> Result<ChartResult, ApiError> {
+        let started = Instant::now();
+        let result = self.fetch_chart(symbol, range, interval);
+        log_request(
+            "Yahoo Finance",
+            "chart",
+            &[("symbol", &**symbol)],
+            started,
+            &result,
+        );
+        result.map_err(|err| {
+            err.with_context(ErrorContext::new(
+                "Yahoo Finance",
+                "chart",
+                &[("symbol", &**symbol)],
+            ))
+        })
+    }
+
+    fn fetch_chart(
+        &self,
+        symbol: &Symbol,
+        range: &str,
+        interval: &str,
+    ) -> Result<ChartResult, ApiError> {
+        let response: ChartResponse = self
+            .client
+            .get(format!("{}/{}", CHART_URL, &**symbol))
+            .query(&[
+                ("range", range),
+                ("interval", interval),
+                ("events", "div,split"),
+            ])
+            .header(reqwest::header::USER_AGENT, USER_AGENT)
+            .send()?
+            .json()?;
+
+        if let Some(error) = response.chart.error {
+            return Err(match error.code.as_str() {
+                "Not Found" => ApiError::InvalidSymbol(symbol.to_string()),
+                _ => ApiError::Api(error.description),
+            });
+        }
+        response
+            .chart
+            .result
+            .and_then(|results| results.into_iter().next())
+            .ok_or(ApiError::NoData)
+    }
+}
+
+impl QuoteProvider for YahooClient {
+    fn quote(&self, symbol: Symbol) -> Result<Quote, ApiError> {
+        let chart = self.chart(&symbol, "5d", "1d")?;
+        let bars = chart.bars();
+
+        let mut days = bars.iter().rev();
+        let (date, latest) = days.next().ok_or(ApiError::NoData)?;
+        let price = chart.meta.regular_market_price.unwrap_or(latest.close);
+        let previous_close = days.next().map_or(latest.open, |(_date, day)| day.close);
+
+        Ok(Quote {
+            symbol: chart.meta.symbol.clone(),
+            open: latest.open,
+            high: latest.high,
+            low: latest.low,
+            price,
+            volume: latest.volume,
+            latest_trading_day: chart
+                .meta
+                .regular_market_time
+                .map_or(*date, |time| chart.local_date(time)),
+            previous_close,
+            change: price - previous_close,
+            change_percent: (price - previous_close) / previous_close * 100.0,
+            retrieved_at: Some(chrono::Utc::now()),
+        })
+    }
+
+    fn crypto_quote(&self, symbol: Symbol, market: &str) -> Result<Quote, ApiError> {
+        self.quote(Symbol::new(format!("{}-{}", &*symbol, market)))
+    }
+
+    fn exchange_rate(&self, from: &str, to: &str) -> Result<f64, ApiError> {
+        Ok(self.quote(Symbol::new(format!("{}{}=X", from, to)))?.price)
+    }
+}
+
+impl TimeSeriesProvider for YahooClient {
+    fn daily_series(
+        &self,
+        symbol: Symbol,
+        output_size: OutputSize,
+    ) -> Result<DailySeries, ApiError> {
+        let (range, output_size_name) = match output_size {
+            OutputSize::Compact => ("6mo", "Compact"),
+            OutputSize::Full => ("max", "Full Size"),
+        };
+        let chart = self.chart(&symbol, range, "1d")?;
+        let days = chart.bars();
+
+        Ok(DailySeries {
+            metadata: chart.metadata(&days, Some(output_size_name)),
+            days,
+        })
+    }
+
+    fn series(&self, symbol: Symbol, frequency: Frequency) -> Result<Series, ApiError> {
+        let interval = match frequency {
+            Frequency::Daily => "1d",
+            Frequency::Weekly => "1wk",
+            Frequency::Monthly => "1mo",
+        };
+        let chart = self.chart(&symbol, "max", interval)?;
+        let bars = chart.bars();
+
+        Ok(Series {
+            frequency,
+            metadata: chart.metadata(&bars, None),
+            bars,
+        })
+    }
+}
@@ -0,0 +1,520 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use portfolio::import::csv::{import, import_file, BrokerFormat};
+use portfolio::import::ImportError;
+use portfolio::testing::{self, Fixtures};
+use portfolio::{
+    ApiError, ApiTier, Bond, DataType, Entitlement, GainsReport, Identifier, IdentifierMap,
+    Interval, Jurisdiction, LotMethod, OutputSize, PortfolioClient, Recording, RetryPolicy, Symbol,
+    SymbolError, SymbolResolver, TaxRules, Transaction,
+};
+
+#[test]
+fn quote() {
+    let quote = testing::client(Fixtures::canned())
+        .quote(Symbol::new("IBM"))
+        .unwrap();
+    assert_eq!(quote.symbol, "IBM");
+    assert_eq!(quote.price, 195.95);
+    assert_eq!(quote.previous_close, 196.54);
+    assert_eq!(quote.change_percent, -0.3002);
+    assert_eq!(quote.latest_trading_day.to_string(), "2024-03-08");
+}
+
+#[test]
+fn daily_series() {
+    let series = testing::client(Fixtures::canned())
+        .daily_series(Symbol::new("IBM"), OutputSize::Full)
+        .unwrap();
+    assert_eq!(series.days.len(), 5);
+    let (date, day) = series.latest().unwrap();
+    assert_eq!(date.to_string(), "2024-03-08");
+    assert_eq!(day.close, 195.95);
+    assert_eq!(day.volume, 3845232.0);
+}
+
+#[test]
+fn invalid_symbol() {
+    let err = testing::client(Fixtures::canned())
+        .quote(Symbol::new("NOPE"))
+        .unwrap_err();
+    assert!(matches!(err.kind(), ApiError::InvalidSymbol(symbol) if symbol == "NOPE"));
+}
+
+#[test]
+fn other_errors_are_not_invalid_symbols() {
+    let bad_key = r#"{"Error Message": "the parameter apikey is invalid or missing."}"#;
+    let err = testing::client(Fixtures::new().with(&[], bad_key))
+        .quote(Symbol::new("IBM"))
+        .unwrap_err();
+    assert!(matches!(err.kind(), ApiError::Api(message) if message.contains("apikey")));
+}
+
+#[test]
+fn rate_limited_requests_are_retried() {
+    let fixtures = Arc::new(Fixtures::new().with(&[], testing::RATE_LIMITED));
+    let client = PortfolioClient::builder("demo")
+        .transport(fixtures.clone())
+        .retry(RetryPolicy {
+            max_attempts: 2,
+            initial_backoff: Duration::from_secs(0),
+            max_backoff: Duration::from_secs(0),
+            jitter: false,
+        })
+        .build()
+        .unwrap();
+
+    let err = client.quote(Symbol::new("IBM")).unwrap_err();
+    assert!(matches!(err.kind(), ApiError::RateLimited(_)));
+    assert_eq!(fixtures.requests().len(), 2);
+}
+
+#[test]
+fn cached_responses_are_not_requested_again() {
+    let dir = std::env::temp_dir().join(format!("portfolio-test-{}", std::process::id()));
+    let fixtures = Arc::new(Fixtures::canned());
+    let client = PortfolioClient::builder("demo")
+        .transport(fixtures.clone())
+        .cache(portfolio::Cache::new(&dir, Duration::from_secs(60)))
+        .build()
+        .unwrap();
+
+    let first = client.quote(Symbol::new("IBM")).unwrap();
+    let second = client.quote(Symbol::new("IBM")).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(first.price, second.price);
+    assert_eq!(fixtures.requests().len(), 1);
+}
+
+#[test]
+fn recorded_responses_are_replayed() {
+    let dir = std::env::temp_dir().join(format!("portfolio-recording-{}", std::process::id()));
+    let client = PortfolioClient::builder("demo")
+        .transport(Fixtures::canned())
+        .recording(Recording::record(&dir))
+        .build()
+        .unwrap();
+    let recorded = client.quote(Symbol::new("IBM")).unwrap();
+
+    let fixtures = Arc::new(Fixtures::new());
+    let client = PortfolioClient::builder("demo")
+        .transport(fixtures.clone())
+        .recording(Recording::replay(&dir))
+        .build()
+        .unwrap();
+    let replayed = client.quote(Symbol::new("IBM")).unwrap();
+    let missing = client.quote(Symbol::new("IBM")).unwrap_err();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(recorded.price, replayed.price);
+    assert!(matches!(missing.kind(), ApiError::Recording(..)));
+    assert!(fixtures.requests().is_empty());
+}
+
+fn intraday_month(month: &str, day: &str, close: &str) -> String {
+    format!(
+        r#"{{
+    "Meta Data": {{
+        "1. Information": "Intraday (60min) open, high, low, close prices and volume",
+        "2. Symbol": "IBM",
+        "3. Last Refreshed": "{month}-{day} 16:00:00",
+        "4. Interval": "60min",
+        "5. Output Size": "Full size",
+        "6. Time Zone": "US/Eastern"
+    }},
+    "Time Series (60min)": {{
+        "{month}-{day} 16:00:00": {{
+            "1. open": "{close}",
+            "2. high": "{close}",
+            "3. low": "{close}",
+            "4. close": "{close}",
+            "5. volume": "1000"
+        }}
+    }}
+}}"#
+    )
+}
+
+#[test]
+fn intraday_history_is_assembled_from_months() {
+    let fixtures = Arc::new(
+        Fixtures::new()
+            .with(
+                &[("month", "2024-01")],
+                intraday_month("2024-01", "31", "183.66"),
+            )
+            .with(
+                &[("month", "2024-02")],
+                intraday_month("2024-02", "29", "185.03"),
+            ),
+    );
+    let client = PortfolioClient::builder("demo")
+        .transport(fixtures.clone())
+        .tier(ApiTier::Premium {
+            requests_per_minute: 60_000,
+        })
+        .entitlement(Entitlement::Realtime)
+        .build()
+        .unwrap();
+    let date = |date: &str| date.parse::<chrono::NaiveDate>().unwrap();
+    let series = client
+        .intraday_history(
+            Symbol::new("IBM"),
+            Interval::SixtyMinutes,
+            date("2024-01-15"),
+            date("2024-02-29"),
+        )
+        .unwrap();
+
+    assert_eq!(series.bars.len(), 2);
+    assert_eq!(series.earliest().unwrap().1.close, 183.66);
+    assert_eq!(series.latest().unwrap().1.close, 185.03);
+    let requests = fixtures.requests();
+    assert_eq!(requests.len(), 2);
+    assert!(requests
+        .iter()
+        .all(|params| params.contains(&("entitlement".to_string(), "realtime".to_string()))));
+}
+
+#[test]
+fn intraday_history_needs_a_premium_key() {
+    let fixtures = Arc::new(Fixtures::new());
+    let client = PortfolioClient::builder("demo")
+        .transport(fixtures.clone())
+        .build()
+        .unwrap();
+    let date = |date: &str| date.parse::<chrono::NaiveDate>().unwrap();
+    let err = client
+        .intraday_history(
+            Symbol::new("IBM"),
+            Interval::SixtyMinutes,
+            date("2024-01-01"),
+            date("2024-01-31"),
+        )
+        .unwrap_err();
+
+    assert!(matches!(err, ApiError::PremiumRequired(_)));
+    assert!(fixtures.requests().is_empty());
+}
+
+#[test]
+fn overlapping_intraday_months_are_deduplicated() {
+    // a late revision of January's last bar, as February's response gives it
+    let february = intraday_month("2024-02", "29", "185.03").replace(
+        r#""Time Series (60min)": {"#,
+        r#""Time Series (60min)": {
+        "2024-01-31 16:00:00": {
+            "1. open": "183.70",
+            "2. high": "183.70",
+            "3. low": "183.70",
+            "4. close": "183.70",
+            "5. volume": "1200"
+        },"#,
+    );
+    let client = testing::client(
+        Fixtures::new()
+            .with(
+                &[("month", "2024-01")],
+                intraday_month("2024-01", "31", "183.66"),
+            )
+            .with(&[("month", "2024-02")], february),
+    );
+    let date = |date: &str| date.parse::<chrono::NaiveDate>().unwrap();
+    let series = client
+        .intraday_history(
+            Symbol::new("IBM"),
+            Interval::SixtyMinutes,
+            date("2024-01-01"),
+            date("2024-02-29"),
+        )
+        .unwrap();
+
+    assert_eq!(series.bars.len(), 2);
+    assert_eq!(series.earliest().unwrap().1.close, 183.70);
+    assert_eq!(
+        series.metadata.last_refreshed.to_string(),
+        "2024-02-29 16:00:00"
+    );
+}
+
+#[test]
+fn daily_series_as_csv() {
+    let fixtures = Arc::new(Fixtures::new().with(
+        &[
+            ("function", "TIME_SERIES_DAILY_ADJUSTED"),
+            ("datatype", "csv"),
+        ],
+        testing::DAILY_SERIES_CSV,
+    ));
+    let client = PortfolioClient::builder("demo")
+        .transport(fixtures.clone())
+        .datatype(DataType::Csv)
+        .build()
+        .unwrap();
+    let csv = client
+        .daily_series(Symbol::new("IBM"), OutputSize::Full)
+        .unwrap();
+    let json = testing::client(Fixtures::canned())
+        .daily_series(Symbol::new("IBM"), OutputSize::Full)
+        .unwrap();
+
+    assert_eq!(csv.days.len(), json.days.len());
+    for ((csv_date, csv_day), (json_date, json_day)) in csv.days.iter().zip(&json.days) {
+        assert_eq!(csv_date, json_date);
+        assert_eq!(csv_day.close, json_day.close);
+        assert_eq!(csv_day.volume, json_day.volume);
+    }
+    assert_eq!(csv.metadata.symbol, "IBM");
+    assert_eq!(csv.metadata.last_refreshed, json.metadata.last_refreshed);
+}
+
+#[test]
+fn csv_errors_are_still_read_from_json() {
+    let client = PortfolioClient::builder("demo")
+        .transport(Fixtures::canned())
+        .datatype(DataType::Csv)
+        .build()
+        .unwrap();
+    let err = client
+        .daily_series(Symbol::new("NOPE"), OutputSize::Full)
+        .unwrap_err();
+    assert!(matches!(err.kind(), ApiError::InvalidSymbol(symbol) if symbol == "NOPE"));
+}
+
+#[test]
+fn gzipped_responses_are_decompressed() {
+    use std::io::{Read, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let server = std::thread::spawn(move || {
+        let (mut stream, _address) = listener.accept().unwrap();
+        let mut request = [0; 4096];
+        let read = stream.read(&mut request).unwrap();
+        let request = String::from_utf8_lossy(&request[..read]).to_lowercase();
+
+        let mut body = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+        body.write_all(testing::GLOBAL_QUOTE.as_bytes()).unwrap();
+        let body = body.finish().unwrap();
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Encoding: gzip\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            body.len()
+        )
+        .unwrap();
+        stream.write_all(&body).unwrap();
+        request
+    });
+
+    let quote = PortfolioClient::builder("demo")
+        .base_url(format!("http://{}/query", address))
+        .build()
+        .unwrap()
+        .quote(Symbol::new("IBM"))
+        .unwrap();
+    let request = server.join().unwrap();
+
+    assert_eq!(quote.price, 195.95);
+    assert!(request.contains("accept-encoding: gzip, deflate"));
+}
+
+#[test]
+fn symbols_are_validated() {
+    let symbol: Symbol = " vod.lon ".parse().unwrap();
+    assert_eq!(&*symbol, "VOD.LON");
+    assert_eq!(symbol.ticker(), "VOD");
+    assert_eq!(symbol.exchange(), Some("LON"));
+
+    let symbol: Symbol = "BRK.B".parse().unwrap();
+    assert_eq!(symbol.ticker(), "BRK.B");
+    assert_eq!(symbol.exchange(), None);
+
+    assert_eq!("BHP.AX".parse::<Symbol>().unwrap().exchange(), Some("AX"));
+    assert!(matches!(
+        "VOD.XYZ".parse::<Symbol>(),
+        Err(SymbolError::UnknownExchange(..))
+    ));
+    assert!(matches!(
+        "TE$T".parse::<Symbol>(),
+        Err(SymbolError::InvalidCharacter(_, '$'))
+    ));
+    assert!(matches!(" ".parse::<Symbol>(), Err(SymbolError::Empty)));
+}
+
+struct Apple;
+impl SymbolResolver for Apple {
+    fn resolve(&self, identifiers: &[Identifier]) -> Result<Vec<Option<Symbol>>, ApiError> {
+        Ok(identifiers
+            .iter()
+            .map(|identifier| (identifier.as_str() == "US0378331005").then(|| Symbol::new("AAPL")))
+            .collect())
+    }
+}
+
+#[test]
+fn identifiers_are_resolved() {
+    assert!(matches!(
+        Identifier::parse("US0378331005"),
+        Some(Identifier::Isin(_))
+    ));
+    assert!(matches!(
+        Identifier::parse("037833100"),
+        Some(Identifier::Cusip(_))
+    ));
+    assert!(Identifier::parse("US0378331006").is_none());
+    assert!(Identifier::parse("AAPL").is_none());
+    assert!(Identifier::parse("AÉBCDEFGHIJ").is_none());
+    assert!(Identifier::parse("0É7833100").is_none());
+
+    let buy = |symbol: &str| testing::buy("2024-03-08", symbol, 10, 170);
+    let mut transactions = vec![
+        buy("US0378331005"),
+        buy("GB0007188757"),
+        buy("MSFT"),
+        buy("AÉBCDEFGHIJ"),
+    ];
+    let mut map = IdentifierMap::new();
+    let unresolved = map.resolve(&mut transactions, Some(&Apple)).unwrap();
+
+    let symbols: Vec<&str> = transactions
+        .iter()
+        .map(|transaction| &**transaction.symbol().unwrap())
+        .collect();
+    assert_eq!(symbols, ["AAPL", "GB0007188757", "MSFT", "AÉBCDEFGHIJ"]);
+    assert_eq!(unresolved, [Identifier::parse("GB0007188757").unwrap()]);
+    // remembered, so no resolver is needed the next time
+    let mut transactions = vec![buy("US0378331005")];
+    assert!(map.resolve(&mut transactions, None).unwrap().is_empty());
+    assert_eq!(&**transactions[0].symbol().unwrap(), "AAPL");
+}
+
+#[test]
+fn empty_lots_are_skipped() {
+    let ledger = testing::ledger(vec![
+        testing::buy("2024-01-02", "IBM", 0, 100),
+        testing::buy("2024-01-03", "IBM", 5, 100),
+        testing::sell("2024-03-08", "IBM", 1, 100),
+    ]);
+
+    let gains = ledger.realised_lots(LotMethod::Fifo).unwrap();
+    assert_eq!(gains.len(), 1);
+    assert_eq!(gains[0].acquired.to_string(), "2024-01-03");
+    assert_eq!(gains[0].cost_basis, 100.into());
+    assert_eq!(ledger.realised_gains(), 0.into());
+}
+
+#[test]
+fn specific_lots_must_be_held() {
+    let ledger = testing::ledger(vec![
+        testing::buy("2024-01-02", "IBM", 5, 100),
+        testing::sell_lot("2024-03-08", "IBM", 1, 120, "2024-01-05"),
+    ]);
+
+    assert!(ledger.realised_lots(LotMethod::Fifo).is_ok());
+    assert!(matches!(
+        ledger.realised_lots(LotMethod::SpecificLot),
+        Err(ApiError::Api(_))
+    ));
+}
+
+#[test]
+fn coupons_are_counted_from_maturity() {
+    let date = |s: &str| s.parse::<chrono::NaiveDate>().unwrap();
+    let bond = Bond::new(5.0, 2, date("2025-08-31"), 100.0, None).unwrap();
+
+    let coupons: Vec<String> = bond
+        .coupons(date("2024-01-01"), date("2026-01-01"))
+        .iter()
+        .map(|coupon| coupon.date.to_string())
+        .collect();
+    assert_eq!(
+        coupons,
+        ["2024-02-29", "2024-08-31", "2025-02-28", "2025-08-31"]
+    );
+    // a day into the period from 2024-08-31 to 2025-02-28
+    assert!((bond.accrued_interest(date("2024-09-01")) - 2.5 / 181.0).abs() < 1e-9);
+
+    assert!(Bond::new(5.0, 5, date("2025-08-31"), 100.0, None).is_err());
+    let terms = r#"{"coupon": 5.0, "frequency": 5, "maturity": "2025-08-31"}"#;
+    assert!(serde_json::from_str::<Bond>(terms).is_err());
+}
+
+#[test]
+fn trades_need_a_quantity() {
+    let generic = "date,type,symbol,quantity,price\n\
+                   2024-01-02,buy,IBM,5,100\n\
+                   2024-01-03,buy,IBM,0,100\n";
+    assert!(matches!(
+        import(generic.as_bytes(), BrokerFormat::Generic),
+        Err(ImportError::InvalidRow { line: 3, .. })
+    ));
+    let generic = "date,type,symbol,quantity,price\n2024-01-03,sell,IBM,-1,100\n";
+    assert!(import(generic.as_bytes(), BrokerFormat::Generic).is_err());
+
+    let ibkr = "TradeDate,Symbol,Quantity,TradePrice\n20240102,IBM,0,100\n";
+    assert!(matches!(
+        import(ibkr.as_bytes(), BrokerFormat::InteractiveBrokers),
+        Err(ImportError::InvalidRow { line: 2, .. })
+    ));
+    let ibkr = "TradeDate,Symbol,Quantity,TradePrice\n20240102,IBM,-3,100\n";
+    let transactions = import(ibkr.as_bytes(), BrokerFormat::InteractiveBrokers).unwrap();
+    assert!(matches!(transactions[0], Transaction::Sell { .. }));
+}
+
+#[test]
+fn commsec_dividends_are_dividends() {
+    let commsec = "Date,Reference,Details,Debit($),Credit($),Balance($)\n\
+                   02/01/2024,C123,B 10 BHP @ 45.00,459.95,,\n\
+                   28/03/2024,R456,Direct Credit 401579 BHP DIV AUI24/00812345,,12.40,\n\
+                   02/04/2024,R789,Direct Credit 062000 Transfer,,1000.00,\n";
+    let transactions = import(commsec.as_bytes(), BrokerFormat::CommSec).unwrap();
+    assert!(matches!(
+        &transactions[1],
+        Transaction::Dividend { symbol, amount, .. }
+            if &**symbol == "BHP.AX" && amount.to_string() == "12.40"
+    ));
+    assert!(matches!(transactions[2], Transaction::Deposit { .. }));
+}
+
+#[test]
+fn import_errors_say_where() {
+    let generic = "date,symbol\n2024-01-02,IBM\n";
+    let err = import(generic.as_bytes(), BrokerFormat::Generic).unwrap_err();
+    assert_eq!(err.to_string(), "there's no `type` column");
+
+    let path = std::env::temp_dir().join(format!("portfolio-missing-{}.csv", std::process::id()));
+    let err = import_file(&path, BrokerFormat::Generic).unwrap_err();
+    assert!(
+        err.to_string()
+            .starts_with(&format!("{}: ", path.display())),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn uk_tax_years_start_in_april() {
+    let uk = Jurisdiction::uk();
+    let report = GainsReport::for_year(vec![], 2024, uk.year_start()).unwrap();
+    assert_eq!(report.start_date.to_string(), "2023-04-06");
+    assert_eq!(report.end_date.to_string(), "2024-04-05");
+
+    let report = GainsReport::for_year(vec![], 2024, Jurisdiction::us().year_start()).unwrap();
+    assert_eq!(report.start_date.to_string(), "2024-01-01");
+    assert_eq!(report.end_date.to_string(), "2024-12-31");
+}
+
+#[test]
+fn tiers_are_parsed() {
+    assert!(matches!("free".parse(), Ok(ApiTier::Free)));
+    assert!(matches!(
+        "75".parse(),
+        Ok(ApiTier::Premium {
+            requests_per_minute: 75
+        })
+    ));
+    assert!("0".parse::<ApiTier>().is_err());
+    assert!("gold".parse::<ApiTier>().is_err());
+}